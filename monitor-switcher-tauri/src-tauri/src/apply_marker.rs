@@ -0,0 +1,123 @@
+//! Crash-safe marker for a profile apply in progress.
+//!
+//! Before a profile is applied, the current layout is saved next to a marker
+//! naming the profile; both are removed once applying returns, whether it
+//! worked or not. If the app or the GPU driver crashes in between, the marker
+//! is still there on the next start. It is then set aside as an interrupted
+//! apply, which the user can restore or dismiss.
+//! Single responsibility: persist, detect and restore the pre-apply layout.
+
+use crate::settings::get_apply_marker_path;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An apply that started but never finished.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyMarker {
+    /// Name of the profile being applied
+    pub profile: String,
+    /// Local time in RFC 3339 format
+    pub started_at: String,
+}
+
+/// Removes the marker when dropped, i.e. when applying returns.
+pub struct ApplyGuard {
+    marker: Option<PathBuf>,
+}
+
+impl Drop for ApplyGuard {
+    fn drop(&mut self) {
+        if let Some(marker) = &self.marker {
+            remove(marker);
+        }
+    }
+}
+
+/// Save the current layout and mark `profile` as being applied. Keep the
+/// returned guard alive until applying is done. Failing to write the marker
+/// doesn't stop the apply; it's only logged.
+pub fn begin(profile: &str) -> ApplyGuard {
+    match write_marker(profile) {
+        Ok(marker) => ApplyGuard { marker: Some(marker) },
+        Err(e) => {
+            log::warn!("Failed to save the layout before applying: {}", e);
+            ApplyGuard { marker: None }
+        }
+    }
+}
+
+fn write_marker(profile: &str) -> Result<PathBuf, String> {
+    let marker = get_apply_marker_path()?;
+    crate::profile::save_snapshot(&layout_path(&marker))?;
+
+    let json = serde_json::to_string_pretty(&ApplyMarker {
+        profile: profile.to_string(),
+        started_at: Local::now().to_rfc3339(),
+    })
+    .map_err(|e| format!("Failed to serialize apply marker: {}", e))?;
+
+    // Written last, so a marker always comes with a complete layout
+    fs::write(&marker, json)
+        .map_err(|e| format!("Failed to write apply marker: {}", e))?;
+    Ok(marker)
+}
+
+/// At startup: set a marker left by a crashed apply aside as interrupted, so
+/// the next apply can't overwrite the layout from before the crash.
+/// Returns the interrupted apply, if there is one.
+pub fn take_leftover() -> Option<ApplyMarker> {
+    let marker = get_apply_marker_path().ok()?;
+    if !marker.exists() {
+        return interrupted();
+    }
+
+    let set_aside = interrupted_path(&marker);
+    let moved = fs::rename(layout_path(&marker), layout_path(&set_aside))
+        .and_then(|_| fs::rename(&marker, &set_aside));
+    if let Err(e) = moved {
+        log::warn!("Failed to set the interrupted apply aside: {}", e);
+    }
+    interrupted()
+}
+
+/// The interrupted apply waiting for the user, if any.
+pub fn interrupted() -> Option<ApplyMarker> {
+    let path = interrupted_path(&get_apply_marker_path().ok()?);
+    let json = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Path of the layout saved with the interrupted apply.
+pub fn interrupted_layout() -> Result<PathBuf, String> {
+    Ok(layout_path(&interrupted_path(&get_apply_marker_path()?)))
+}
+
+/// Forget the interrupted apply.
+pub fn dismiss() -> Result<(), String> {
+    remove(&interrupted_path(&get_apply_marker_path()?));
+    Ok(())
+}
+
+/// Remove a marker and the layout saved with it.
+fn remove(marker: &Path) {
+    for path in [layout_path(marker), marker.to_path_buf()] {
+        if let Err(e) = fs::remove_file(&path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::warn!("Failed to remove {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+fn interrupted_path(marker: &Path) -> PathBuf {
+    marker.with_file_name("interrupted-apply.json")
+}
+
+/// The layout is stored in the profile format, next to its marker.
+fn layout_path(marker: &Path) -> PathBuf {
+    let stem = marker.file_stem().and_then(|s| s.to_str()).unwrap_or("apply");
+    marker.with_file_name(format!("{}-layout.json", stem))
+}
@@ -0,0 +1,93 @@
+//! Timings and details of recent profile applies.
+//!
+//! Switching can take a second on one dock and eight on another; the metrics
+//! show where the time went (waiting for monitors, the layout call itself),
+//! how the monitors were matched and whether the driver needed a retry. They
+//! are kept in memory only.
+//! Single responsibility: collect and keep the metrics of recent applies.
+
+use crate::display::MatchTier;
+use chrono::Local;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// How many applies are kept.
+const KEEP: usize = 20;
+
+static RECENT: Mutex<VecDeque<ApplyMetrics>> = Mutex::new(VecDeque::new());
+
+/// What one profile apply took.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyMetrics {
+    pub profile: String,
+    /// Local time in RFC 3339 format
+    pub started_at: String,
+    /// From the request to the result, waiting in the apply queue excluded
+    pub duration_ms: u64,
+    /// Spent waiting for the profile's monitors to be connected
+    pub wait_ms: u64,
+    /// Spent applying the layout; 0 if it was active already
+    pub layout_ms: u64,
+    /// Extra layout attempts: 1 after retrying with SDC_ALLOW_CHANGES, 2 through
+    /// the GDI fallback (Windows)
+    pub retries: u32,
+    /// How the profile's monitors were found among the connected ones
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_tier: Option<MatchTier>,
+    /// SetDisplayConfig flags of the accepted attempt (Windows)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flags: Option<u32>,
+    pub already_active: bool,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip)]
+    started: Option<Instant>,
+}
+
+impl ApplyMetrics {
+    /// Start measuring an apply of `profile`.
+    pub fn begin(profile: &str) -> Self {
+        Self {
+            profile: profile.to_string(),
+            started_at: Local::now().to_rfc3339(),
+            started: Some(Instant::now()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Run `f`, returning its result and how long it took in milliseconds.
+pub fn timed<T>(f: impl FnOnce() -> T) -> (T, u64) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed().as_millis() as u64)
+}
+
+/// Finish measuring an apply with its outcome and keep it.
+pub fn record(mut metrics: ApplyMetrics, result: &Result<(), String>) {
+    metrics.duration_ms = metrics.started.map_or(0, |start| start.elapsed().as_millis() as u64);
+    metrics.success = result.is_ok();
+    metrics.error = result.as_ref().err().cloned();
+    log::info!(
+        "Applying '{}' took {} ms (waiting {} ms, layout {} ms, {} retries, matched by {:?})",
+        metrics.profile,
+        metrics.duration_ms,
+        metrics.wait_ms,
+        metrics.layout_ms,
+        metrics.retries,
+        metrics.match_tier
+    );
+
+    let mut recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+    recent.push_front(metrics);
+    recent.truncate(KEEP);
+}
+
+/// The most recent applies, newest first.
+pub fn recent() -> Vec<ApplyMetrics> {
+    RECENT.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+}
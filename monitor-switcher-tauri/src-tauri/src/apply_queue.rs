@@ -0,0 +1,234 @@
+//! Serialized, debounced profile applies.
+//!
+//! The tray, hotkeys, the window and automation can all ask for a profile at
+//! the same time, and concurrent SetDisplayConfig/xrandr calls misbehave. Applies
+//! run one at a time, in the order they were asked for. A request for a profile
+//! that is already queued or running joins it instead of applying it again, and
+//! one that finished moments ago, with nothing else applied since, returns that
+//! result. Layout changes other
+//! than profile loads (templates, rotation, the battery saver, ...) don't
+//! coalesce but take their turn through `exclusive`.
+//! Single responsibility: decide when an apply runs and who shares its result.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Requests repeated within this time after an apply finished reuse its result.
+pub const DEBOUNCE: Duration = Duration::from_millis(1500);
+
+/// Held while anything changes the layout.
+static LAYOUT: Mutex<()> = Mutex::new(());
+
+/// Run `change` once no other layout change is running, queued profile loads
+/// included. Must not be nested.
+pub fn exclusive<R>(change: impl FnOnce() -> R) -> R {
+    let _layout = LAYOUT.lock().unwrap_or_else(|e| e.into_inner());
+    change()
+}
+
+/// Runs jobs one at a time, coalescing requests with the same key.
+pub struct ApplyQueue<T> {
+    state: Mutex<State<T>>,
+    changed: Condvar,
+    debounce: Duration,
+}
+
+struct State<T> {
+    /// Keys of the running job (first, if `busy`) and the waiting ones
+    queue: VecDeque<String>,
+    busy: bool,
+    /// How often each key finished, so joiners know when theirs is done
+    finished_count: HashMap<String, u64>,
+    /// Latest result per key and when it finished
+    results: HashMap<String, (Instant, Result<T, String>)>,
+    /// Key of the job that finished last
+    last_finished: Option<String>,
+}
+
+impl<T: Clone> ApplyQueue<T> {
+    pub fn new(debounce: Duration) -> Self {
+        Self {
+            state: Mutex::new(State {
+                queue: VecDeque::new(),
+                busy: false,
+                finished_count: HashMap::new(),
+                results: HashMap::new(),
+                last_finished: None,
+            }),
+            changed: Condvar::new(),
+            debounce,
+        }
+    }
+
+    /// Run `job` for `key` once every earlier job finished, or share the result
+    /// of a job for the same key that is queued, running or just finished.
+    pub fn run<F>(&self, key: &str, job: F) -> Result<T, String>
+    where
+        F: FnOnce() -> Result<T, String>,
+    {
+        let mut state = self.lock();
+
+        if state.queue.iter().any(|k| k == key) {
+            log::info!("'{}' is already being applied, waiting for it", key);
+            let seen = state.finished_count.get(key).copied().unwrap_or(0);
+            while state.finished_count.get(key).copied().unwrap_or(0) <= seen {
+                state = self.wait(state);
+            }
+            return Self::result_of(&state, key);
+        }
+
+        // Only while nothing else was applied since, so toggling A, B, A still ends on A
+        if state.last_finished.as_deref() == Some(key) {
+            if let Some((finished_at, result)) = state.results.get(key) {
+                if finished_at.elapsed() < self.debounce {
+                    log::info!("'{}' was applied moments ago, not applying it again", key);
+                    return result.clone();
+                }
+            }
+        }
+
+        state.queue.push_back(key.to_string());
+        while state.busy || state.queue.front().map(String::as_str) != Some(key) {
+            state = self.wait(state);
+        }
+        state.busy = true;
+        drop(state);
+
+        // Hands the turn on even if the job panics
+        let mut turn = Turn { queue: self, key, result: None };
+        let result = exclusive(job);
+        turn.result = Some(result.clone());
+        result
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn wait<'a>(&self, guard: MutexGuard<'a, State<T>>) -> MutexGuard<'a, State<T>> {
+        self.changed.wait(guard).unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn result_of(state: &State<T>, key: &str) -> Result<T, String> {
+        state
+            .results
+            .get(key)
+            .map(|(_, result)| result.clone())
+            .unwrap_or_else(|| Err(format!("'{}' finished without a result", key)))
+    }
+}
+
+/// The running job's turn. Dropping it finishes the job and wakes the others.
+struct Turn<'a, T: Clone> {
+    queue: &'a ApplyQueue<T>,
+    key: &'a str,
+    /// None if the job panicked
+    result: Option<Result<T, String>>,
+}
+
+impl<T: Clone> Drop for Turn<'_, T> {
+    fn drop(&mut self) {
+        let result = self
+            .result
+            .take()
+            .unwrap_or_else(|| Err(format!("Applying '{}' failed unexpectedly", self.key)));
+
+        let mut state = self.queue.lock();
+        state.queue.pop_front();
+        state.busy = false;
+        *state.finished_count.entry(self.key.to_string()).or_insert(0) += 1;
+        state.results.insert(self.key.to_string(), (Instant::now(), result));
+        state.last_finished = Some(self.key.to_string());
+        self.queue.changed.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::thread::{self, JoinHandle};
+
+    type Runs = Arc<Mutex<Vec<&'static str>>>;
+
+    fn spawn_run(queue: &Arc<ApplyQueue<String>>, runs: &Runs, key: &'static str) -> JoinHandle<Result<String, String>> {
+        let (queue, runs) = (queue.clone(), runs.clone());
+        thread::spawn(move || {
+            queue.run(key, || {
+                runs.lock().unwrap().push(key);
+                Ok(key.to_string())
+            })
+        })
+    }
+
+    #[test]
+    fn test_runs_one_at_a_time_and_coalesces() {
+        let queue = Arc::new(ApplyQueue::new(Duration::from_secs(3600)));
+        let runs: Runs = Arc::default();
+        let (started_tx, started) = mpsc::channel();
+        let (release, release_rx) = mpsc::channel::<()>();
+
+        let first = {
+            let (queue, runs) = (queue.clone(), runs.clone());
+            thread::spawn(move || {
+                queue.run("a", || {
+                    started_tx.send(()).unwrap();
+                    release_rx.recv().unwrap();
+                    runs.lock().unwrap().push("a");
+                    Ok("a".to_string())
+                })
+            })
+        };
+        started.recv().unwrap();
+
+        let others: Vec<_> = ["b", "a", "b"].into_iter().map(|key| spawn_run(&queue, &runs, key)).collect();
+        // "b" waits behind the running "a"
+        while queue.lock().queue.len() < 2 {
+            thread::yield_now();
+        }
+        assert!(runs.lock().unwrap().is_empty());
+        release.send(()).unwrap();
+
+        let results: Vec<_> = std::iter::once(first)
+            .chain(others)
+            .map(|h| h.join().unwrap().unwrap())
+            .collect();
+        assert_eq!(results, ["a", "b", "a", "b"]);
+        // Repeats either joined the queued job or reused its fresh result
+        assert_eq!(*runs.lock().unwrap(), ["a", "b"]);
+        assert_eq!(queue.run("b", || Ok("again".to_string())).unwrap(), "b");
+    }
+
+    #[test]
+    fn test_runs_again_after_another_key() {
+        let queue = ApplyQueue::new(Duration::from_secs(3600));
+        queue.run("a", || Ok("a".to_string())).unwrap();
+        queue.run("b", || Ok("b".to_string())).unwrap();
+        assert_eq!(queue.run("a", || Ok("a again".to_string())).unwrap(), "a again");
+        assert_eq!(queue.run("a", || Ok("a third".to_string())).unwrap(), "a again");
+    }
+
+    #[test]
+    fn test_runs_again_after_debounce() {
+        let queue = ApplyQueue::new(Duration::ZERO);
+        queue.run("a", || Ok("first".to_string())).unwrap();
+        assert_eq!(queue.run("a", || Ok("again".to_string())).unwrap(), "again");
+    }
+
+    #[test]
+    fn test_panicking_job_hands_on_turn() {
+        let queue = Arc::new(ApplyQueue::new(Duration::ZERO));
+        let runs: Runs = Arc::default();
+
+        let panicking = {
+            let queue = queue.clone();
+            thread::spawn(move || queue.run("a", || -> Result<String, String> { panic!("driver crashed") }))
+        };
+        assert!(panicking.join().is_err());
+
+        assert_eq!(spawn_run(&queue, &runs, "b").join().unwrap().unwrap(), "b");
+        assert_eq!(spawn_run(&queue, &runs, "a").join().unwrap().unwrap(), "a");
+    }
+}
@@ -0,0 +1,121 @@
+//! Per-monitor outcome of applying a profile.
+//!
+//! Applying succeeds as a whole even when a monitor wasn't connected or didn't
+//! take its settings; the report says which one, so the UI can point at it.
+//! Single responsibility: sort the monitors of a profile into applied, skipped
+//! and failed.
+
+use crate::i18n;
+use serde::Serialize;
+
+/// What happened to one monitor of the profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MonitorStatus {
+    /// The monitor took the profile's settings
+    Applied,
+    /// The monitor wasn't connected, so there was nothing to apply to
+    Skipped,
+    /// The monitor is connected but didn't take the settings
+    Failed,
+}
+
+/// Outcome for one monitor.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorResult {
+    /// Name as used in messages (friendly name, or output name on Linux)
+    pub monitor: String,
+    pub status: MonitorStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Settings the monitor ended up with other than requested, e.g. a
+    /// slightly different refresh rate. The monitor still counts as applied.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub discrepancies: Vec<String>,
+}
+
+/// Outcome of applying a profile.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyReport {
+    pub profile: String,
+    pub monitors: Vec<MonitorResult>,
+    /// The displays already had the profile's layout, so it wasn't applied again
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub already_active: bool,
+}
+
+impl ApplyReport {
+    /// Sort the profile's `monitors` by what the checks found. Names may
+    /// repeat (two identical monitors), so each finding is used up once.
+    /// `missing`: not connected. `dark`: not active after applying.
+    /// `replaced`: (monitor, reason) for settings the driver changed.
+    /// `discrepancies`: (monitor, differences) found when re-reading the
+    /// configuration after applying.
+    pub fn build(
+        profile: &str,
+        monitors: Vec<String>,
+        mut missing: Vec<String>,
+        mut dark: Vec<String>,
+        mut replaced: Vec<(String, String)>,
+        mut discrepancies: Vec<(String, Vec<String>)>,
+    ) -> Self {
+        let take = |list: &mut Vec<String>, name: &str| {
+            list.iter().position(|n| n == name).map(|i| list.remove(i)).is_some()
+        };
+
+        let monitors = monitors
+            .into_iter()
+            .map(|monitor| {
+                let (status, reason) = if take(&mut missing, &monitor) {
+                    // A monitor that isn't there isn't active either
+                    take(&mut dark, &monitor);
+                    (MonitorStatus::Skipped, Some(i18n::tr("report.notConnected")))
+                } else if take(&mut dark, &monitor) {
+                    (MonitorStatus::Failed, Some(i18n::tr("report.stayedDark")))
+                } else if let Some(i) = replaced.iter().position(|(n, _)| *n == monitor) {
+                    (MonitorStatus::Failed, Some(replaced.remove(i).1))
+                } else {
+                    (MonitorStatus::Applied, None)
+                };
+                let discrepancies = match discrepancies.iter().position(|(n, _)| *n == monitor) {
+                    Some(i) if status != MonitorStatus::Skipped => discrepancies.remove(i).1,
+                    _ => Vec::new(),
+                };
+                MonitorResult { monitor, status, reason, discrepancies }
+            })
+            .collect();
+
+        Self { profile: profile.to_string(), monitors, already_active: false }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_report() {
+        let names = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let report = ApplyReport::build(
+            "Desk",
+            names(&["DELL U2720Q", "DELL U2720Q", "LG TV", "Display 4"]),
+            names(&["DELL U2720Q"]),
+            names(&["LG TV"]),
+            vec![("Display 4".to_string(), "Refused 75 Hz".to_string())],
+            vec![("DELL U2720Q".to_string(), vec!["refresh rate is 59.95 Hz instead of 60.00 Hz".to_string()])],
+        );
+
+        let statuses: Vec<MonitorStatus> = report.monitors.iter().map(|m| m.status).collect();
+        assert_eq!(
+            statuses,
+            [MonitorStatus::Skipped, MonitorStatus::Applied, MonitorStatus::Failed, MonitorStatus::Failed]
+        );
+        assert_eq!(report.monitors[3].reason.as_deref(), Some("Refused 75 Hz"));
+        assert_eq!(report.monitors[1].reason, None);
+        // The first DELL wasn't connected, so the second one is off
+        assert!(report.monitors[0].discrepancies.is_empty());
+        assert_eq!(report.monitors[1].discrepancies.len(), 1);
+    }
+}
@@ -0,0 +1,105 @@
+//! Loop protection for automation.
+//!
+//! Applying a layout makes the system report a display change like any other,
+//! and a rule reacting to it could apply another profile, which changes the
+//! layout again. Changes reported while the app applies a layout, or shortly
+//! after, are the app's own and don't evaluate rules. A rule that applied a
+//! profile also rests for its cooldown before it applies one again.
+//! Single responsibility: tell triggers whether they may act right now.
+
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Display changes this long after an apply returned still count as its own.
+const OWN_CHANGE_GRACE: Duration = Duration::from_secs(5);
+
+/// Applies in progress, and when the last one returned.
+static OWN: Mutex<(u32, Option<Instant>)> = Mutex::new((0, None));
+
+static COOLDOWNS: Mutex<Cooldowns> = Mutex::new(Cooldowns { fired: Vec::new() });
+
+fn own() -> MutexGuard<'static, (u32, Option<Instant>)> {
+    OWN.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Marks the app's own layout change while alive.
+pub struct OwnApply;
+
+impl Drop for OwnApply {
+    fn drop(&mut self) {
+        let mut own = own();
+        own.0 = own.0.saturating_sub(1);
+        own.1 = Some(Instant::now());
+    }
+}
+
+/// Mark the layout changes from now until the returned guard is dropped, and
+/// for a grace period after, as the app's own.
+pub fn own_apply() -> OwnApply {
+    own().0 += 1;
+    OwnApply
+}
+
+/// Whether display changes reported now come from the app's own apply.
+pub fn is_own_change() -> bool {
+    let (applying, last) = *own();
+    applying > 0 || last.is_some_and(|returned| returned.elapsed() < OWN_CHANGE_GRACE)
+}
+
+/// Whether the request of the rule `key` may go ahead, i.e. the rule's last
+/// request that went ahead was `cooldown` or longer ago. Records it if so.
+pub fn try_fire(key: &str, cooldown: Duration) -> bool {
+    COOLDOWNS.lock().unwrap_or_else(|e| e.into_inner()).try_fire(key, cooldown, Instant::now())
+}
+
+/// When each rule last went ahead, by key.
+struct Cooldowns {
+    fired: Vec<(String, Instant)>,
+}
+
+impl Cooldowns {
+    fn try_fire(&mut self, key: &str, cooldown: Duration, now: Instant) -> bool {
+        match self.fired.iter_mut().find(|(k, _)| k == key) {
+            Some((_, last)) if now.saturating_duration_since(*last) < cooldown => false,
+            Some((_, last)) => {
+                *last = now;
+                true
+            }
+            None => {
+                self.fired.push((key.to_string(), now));
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cooldowns() {
+        let mut cooldowns = Cooldowns { fired: Vec::new() };
+        let start = Instant::now();
+        let cooldown = Duration::from_secs(30);
+
+        assert!(cooldowns.try_fire("network 'Office'", cooldown, start));
+        assert!(!cooldowns.try_fire("network 'Office'", cooldown, start + Duration::from_secs(10)));
+        // Each rule has its own cooldown
+        assert!(cooldowns.try_fire("steam started", cooldown, start + Duration::from_secs(10)));
+        assert!(cooldowns.try_fire("network 'Office'", cooldown, start + Duration::from_secs(30)));
+        assert!(!cooldowns.try_fire("network 'Office'", cooldown, start + Duration::from_secs(59)));
+
+        // Without a cooldown, every request goes ahead
+        assert!(cooldowns.try_fire("steam started", Duration::ZERO, start + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_own_apply() {
+        let guard = own_apply();
+        assert!(is_own_change());
+        drop(guard);
+        // Within the grace period the change is still the app's own
+        assert!(is_own_change());
+    }
+}
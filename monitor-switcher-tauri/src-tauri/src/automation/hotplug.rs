@@ -0,0 +1,80 @@
+//! Display layout change trigger.
+//!
+//! Single responsibility: report display layout changes (hotplug, other tools,
+//! the desktop environment) on the event bus, and pick the profile saved for
+//! newly connected hardware.
+
+use super::guard;
+use super::network;
+use super::settle;
+use super::trigger::{Event, EventBus, Trigger};
+use crate::display::{current_fingerprint, is_simulating, watch_display_changes};
+use crate::profile::{find_profile_by_fingerprint, profile_has_fingerprint};
+use crate::settings::load_settings;
+use log::debug;
+use std::mem;
+use std::sync::Mutex;
+
+/// Reports display layout changes.
+pub struct HotplugTrigger;
+
+impl Trigger for HotplugTrigger {
+    fn name(&self) -> &'static str {
+        "hotplug"
+    }
+
+    fn start(self: Box<Self>, bus: EventBus) -> Result<(), String> {
+        // Simulated displays never change behind our back
+        if is_simulating() {
+            return Ok(());
+        }
+
+        let last_fingerprint = Mutex::new(current_fingerprint().unwrap_or_default());
+        watch_display_changes(move || {
+            bus.publish(Event::DisplayChanged);
+            if let Some(profile) = profile_for_new_hardware(&last_fingerprint) {
+                bus.publish(Event::ApplyProfile {
+                    profile,
+                    reason: "hotplug".to_string(),
+                    cooldown_secs: None,
+                });
+            }
+        })
+    }
+}
+
+/// The profile to apply after a layout change, if the connected monitors
+/// changed and exactly one profile was saved with the new set.
+fn profile_for_new_hardware(last_fingerprint: &Mutex<String>) -> Option<String> {
+    let fingerprint = current_fingerprint().ok()?;
+
+    // Applying a profile changes the layout too; only new hardware counts
+    let mut last = last_fingerprint.lock().unwrap_or_else(|e| e.into_inner());
+    if mem::replace(&mut *last, fingerprint.clone()) == fingerprint || fingerprint.is_empty() {
+        return None;
+    }
+
+    // Applying a profile may bring monitors up or down, e.g. virtual displays
+    if guard::is_own_change() {
+        debug!("Hotplug: the app changed the layout itself, not picking a profile");
+        return None;
+    }
+
+    if !load_settings().map(|s| s.auto_select_profile).unwrap_or(false) {
+        return None;
+    }
+
+    // Pick the profile for the monitors that are connected once the dock is done
+    let fingerprint = settle::wait_for_displays().unwrap_or(fingerprint);
+    last.clone_from(&fingerprint);
+
+    // A network rule picks between profiles saved with the same monitors (office vs home dock)
+    let profile = network::profiles_for_current_networks()
+        .into_iter()
+        .find(|profile| profile_has_fingerprint(profile, &fingerprint))
+        .or_else(|| find_profile_by_fingerprint(&fingerprint).ok().flatten());
+    if profile.is_none() {
+        debug!("Hotplug: no unique profile saved for the connected monitors");
+    }
+    profile
+}
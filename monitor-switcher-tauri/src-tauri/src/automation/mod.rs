@@ -0,0 +1,96 @@
+//! Automation triggers that apply profiles without user interaction.
+//!
+//! Each trigger lives in its own submodule, implements `Trigger` and publishes
+//! events on a shared `EventBus`. The dispatcher here is the only subscriber
+//! that acts on them; profile requests go through `apply_triggered_profile`,
+//! which owns the shared safety checks. `guard` keeps rules from reacting to
+//! the app's own layout changes and rests them after they fired. Triggers read
+//! settings, rules and the script afresh whenever they act, so edits apply
+//! without a restart.
+
+mod guard;
+mod hotplug;
+mod network;
+mod power;
+mod process;
+mod resume;
+mod script;
+mod settle;
+mod trigger;
+
+use crate::settings::load_settings;
+use log::{error, info, warn};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Wry};
+use trigger::{Event, EventBus, Trigger};
+
+pub use guard::own_apply;
+
+/// All automation sources, in start order.
+fn triggers() -> Vec<Box<dyn Trigger>> {
+    vec![
+        Box::new(hotplug::HotplugTrigger),
+        Box::new(process::ProcessTrigger),
+        Box::new(network::NetworkTrigger),
+        Box::new(power::PowerTrigger),
+        Box::new(resume::ResumeTrigger),
+        Box::new(script::ScriptTrigger),
+    ]
+}
+
+/// Start the dispatcher and all automation triggers.
+pub fn start(app: &AppHandle<Wry>) {
+    let bus = EventBus::default();
+    spawn_dispatcher(app.clone(), &bus);
+
+    for trigger in triggers() {
+        let name = trigger.name();
+        if let Err(e) = trigger.start(bus.clone()) {
+            warn!("Automation: {} trigger unavailable: {}", name, e);
+        }
+    }
+}
+
+/// Spawn the thread that acts on bus events.
+fn spawn_dispatcher(app: AppHandle<Wry>, bus: &EventBus) {
+    let events = bus.subscribe();
+
+    thread::spawn(move || {
+        for event in events {
+            match event {
+                Event::ApplyProfile { profile, reason, cooldown_secs } => {
+                    apply_triggered_profile(&app, &profile, &reason, cooldown_secs)
+                }
+                Event::DisplayChanged => {
+                    let _ = crate::refresh_tray_menu(&app);
+                    crate::monitor_feed::publish(&app);
+                    let _ = app.emit("display-changed", ());
+                }
+                Event::PowerChanged { on_battery } => {
+                    info!("Automation: now on {}", if on_battery { "battery" } else { "AC power" });
+                }
+            }
+        }
+    });
+}
+
+/// Apply a profile on behalf of a trigger, unless the rule is cooling down.
+fn apply_triggered_profile(app: &AppHandle<Wry>, profile: &str, reason: &str, cooldown_secs: Option<u64>) {
+    // The layout in a remote session is virtual; never rearrange it automatically
+    if crate::display::is_remote_session() {
+        info!("Automation: skipping '{}' ({}) in a remote session", profile, reason);
+        return;
+    }
+
+    let cooldown = cooldown_secs.unwrap_or_else(|| load_settings().map(|s| s.automation_cooldown_secs).unwrap_or(0));
+    if !guard::try_fire(reason, Duration::from_secs(cooldown)) {
+        info!("Automation: skipping '{}' ({}), the rule fired less than {}s ago", profile, reason, cooldown);
+        return;
+    }
+
+    info!("Automation: applying '{}' ({})", profile, reason);
+    if let Err(e) = crate::do_load_profile(app, profile, reason) {
+        error!("Automation: failed to load profile '{}': {}", profile, e);
+    }
+}
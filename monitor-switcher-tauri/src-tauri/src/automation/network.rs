@@ -0,0 +1,208 @@
+//! Network-based trigger.
+//!
+//! Polls the connected networks (Wi-Fi SSIDs, and NetworkManager connection
+//! names on Linux) and applies a profile when a watched network connects. The
+//! hotplug trigger also asks here which profile the current location prefers,
+//! since office and home docks often look identical to the hardware fingerprint.
+//! Single responsibility: turn network changes into triggers.
+
+use super::trigger::{Event, EventBus, Trigger};
+use crate::settings::{load_settings, NetworkRule};
+use std::collections::HashSet;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+/// How often the connected networks are polled.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Applies profiles when configured networks connect.
+pub struct NetworkTrigger;
+
+impl Trigger for NetworkTrigger {
+    fn name(&self) -> &'static str {
+        "network"
+    }
+
+    fn start(self: Box<Self>, bus: EventBus) -> Result<(), String> {
+        spawn_watcher(bus);
+        Ok(())
+    }
+}
+
+/// Spawn the network watcher thread.
+fn spawn_watcher(bus: EventBus) {
+    thread::spawn(move || {
+        let mut previous: Option<HashSet<String>> = None;
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let rules = load_settings().map(|s| s.network_rules).unwrap_or_default();
+            if rules.is_empty() {
+                previous = None;
+                continue;
+            }
+
+            let connected = connected_networks();
+            if let Some(previous) = &previous {
+                for event in detect_connects(&rules, previous, &connected) {
+                    bus.publish(event);
+                }
+            }
+            previous = Some(connected);
+        }
+    });
+}
+
+/// Compare two network snapshots and return the profile requests to publish.
+fn detect_connects(
+    rules: &[NetworkRule],
+    previous: &HashSet<String>,
+    connected: &HashSet<String>,
+) -> Vec<Event> {
+    rules
+        .iter()
+        .filter(|rule| {
+            let network = normalize_network_name(&rule.network);
+            !network.is_empty() && !previous.contains(&network) && connected.contains(&network)
+        })
+        .map(|rule| Event::ApplyProfile {
+            profile: rule.profile.clone(),
+            reason: format!("network '{}'", rule.network),
+            cooldown_secs: rule.cooldown_secs,
+        })
+        .collect()
+}
+
+/// Profiles of the rules whose network is connected right now, in rule order.
+pub(super) fn profiles_for_current_networks() -> Vec<String> {
+    let rules = load_settings().map(|s| s.network_rules).unwrap_or_default();
+    if rules.is_empty() {
+        return Vec::new();
+    }
+
+    let connected = connected_networks();
+    rules
+        .into_iter()
+        .filter(|rule| connected.contains(&normalize_network_name(&rule.network)))
+        .map(|rule| rule.profile)
+        .collect()
+}
+
+/// Normalize a network name for comparison: trimmed, lowercase.
+fn normalize_network_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Get the normalized names of the connected Wi-Fi networks (Windows).
+#[cfg(windows)]
+fn connected_networks() -> HashSet<String> {
+    use std::os::windows::process::CommandExt;
+    // Polled in the background; don't flash a console window
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let Ok(output) = Command::new("netsh")
+        .args(["wlan", "show", "interfaces"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+    else {
+        return HashSet::new();
+    };
+    parse_netsh_ssids(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Get the normalized names of the active NetworkManager connections and
+/// Wi-Fi networks (Linux).
+#[cfg(target_os = "linux")]
+fn connected_networks() -> HashSet<String> {
+    let run = |args: &[&str]| {
+        Command::new("nmcli")
+            .args(args)
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+            .unwrap_or_default()
+    };
+
+    let connections = run(&["-t", "-f", "NAME", "connection", "show", "--active"]);
+    let wifi = run(&["-t", "-f", "ACTIVE,SSID", "device", "wifi"]);
+    parse_nmcli_networks(&connections, &wifi)
+}
+
+/// SSIDs from `netsh wlan show interfaces`: lines like "    SSID   : Office".
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_netsh_ssids(output: &str) -> HashSet<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(key, _)| key.trim() == "SSID")
+        .map(|(_, ssid)| normalize_network_name(ssid))
+        .filter(|ssid| !ssid.is_empty())
+        .collect()
+}
+
+/// Connection names from `nmcli -t -f NAME connection show --active`, plus the
+/// SSIDs marked active in `nmcli -t -f ACTIVE,SSID device wifi`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_nmcli_networks(connections: &str, wifi: &str) -> HashSet<String> {
+    // Terse output escapes ':' in values as '\:'
+    let unescape = |value: &str| value.replace(r"\:", ":");
+
+    let ssids = wifi
+        .lines()
+        .filter_map(|line| line.strip_prefix("yes:"))
+        .map(unescape);
+
+    connections
+        .lines()
+        .map(unescape)
+        .chain(ssids)
+        .map(|name| normalize_network_name(&name))
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_network_names() {
+        let netsh = "    Name                   : Wi-Fi\r\n    SSID                   : Office-5G\r\n    BSSID                  : aa:bb:cc:dd:ee:ff\r\n";
+        assert_eq!(parse_netsh_ssids(netsh), set(&["office-5g"]));
+
+        let connections = "Wired connection 1\nOffice\\: Guest\n";
+        let wifi = "no:Neighbour\nyes:Office\\: Guest\n";
+        assert_eq!(parse_nmcli_networks(connections, wifi), set(&["wired connection 1", "office: guest"]));
+    }
+
+    #[test]
+    fn test_detect_connects() {
+        let rules = vec![NetworkRule {
+            network: "Office-5G".to_string(),
+            profile: "Office".to_string(),
+            cooldown_secs: None,
+        }];
+
+        let connected = detect_connects(&rules, &set(&[]), &set(&["office-5g"]));
+        assert_eq!(
+            connected,
+            vec![Event::ApplyProfile {
+                profile: "Office".to_string(),
+                reason: "network 'Office-5G'".to_string(),
+                cooldown_secs: None,
+            }]
+        );
+
+        assert!(detect_connects(&rules, &set(&["office-5g"]), &set(&["office-5g"])).is_empty());
+        assert!(detect_connects(&rules, &set(&["office-5g"]), &set(&[])).is_empty());
+    }
+}
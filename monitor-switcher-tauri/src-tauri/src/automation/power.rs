@@ -0,0 +1,296 @@
+//! Power source trigger and battery saver.
+//!
+//! Polls the power source and reports switches between battery and AC. While on
+//! battery, the battery saver caps the refresh rate of all displays (and
+//! optionally lowers the laptop panel resolution). The original modes are
+//! restored when AC power returns. Single responsibility: turn power source
+//! changes into events and mode-only display changes.
+
+use super::trigger::{Event, EventBus, Trigger};
+use crate::display::{get_display_modes, is_remote_session, set_display_modes, DisplayMode};
+use crate::settings::{load_settings, BatterySaver};
+use log::{error, info};
+use std::thread;
+use std::time::Duration;
+
+/// How often the power source is polled.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Refresh rates within this margin of the cap (59.94 vs 60) are left alone.
+const REFRESH_TOLERANCE: f64 = 0.5;
+
+/// A mode changed by the battery saver: (original, limited).
+type LimitedMode = (DisplayMode, DisplayMode);
+
+/// Reports power source changes and runs the battery saver.
+pub struct PowerTrigger;
+
+impl Trigger for PowerTrigger {
+    fn name(&self) -> &'static str {
+        "power"
+    }
+
+    fn start(self: Box<Self>, bus: EventBus) -> Result<(), String> {
+        spawn_watcher(bus);
+        Ok(())
+    }
+}
+
+/// Spawn the power source watcher thread.
+fn spawn_watcher(bus: EventBus) {
+    thread::spawn(move || {
+        let mut was_on_battery: Option<bool> = None;
+        // Modes changed on battery, remembered until AC power returns
+        let mut limited: Vec<LimitedMode> = Vec::new();
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let battery = on_battery();
+            if let (Some(before), Some(now)) = (was_on_battery, battery) {
+                if before != now {
+                    bus.publish(Event::PowerChanged { on_battery: now });
+                }
+            }
+            was_on_battery = battery.or(was_on_battery);
+
+            let config = load_settings().map(|s| s.battery_saver).unwrap_or_default();
+            let should_limit = config.enabled && battery == Some(true);
+
+            if should_limit {
+                // The layout in a remote session is virtual; leave it alone
+                if is_remote_session() {
+                    continue;
+                }
+                // Checked on every poll, as displays plugged in or modes set
+                // on battery may exceed the limits too
+                merge_limited(&mut limited, apply_limits(&config));
+            } else if !limited.is_empty() {
+                restore(&std::mem::take(&mut limited));
+            }
+        }
+    });
+}
+
+/// Cap the current modes. Returns what was changed, empty if nothing was.
+fn apply_limits(config: &BatterySaver) -> Vec<LimitedMode> {
+    let current = match get_display_modes() {
+        Ok(modes) => modes,
+        Err(e) => {
+            error!("Battery saver: failed to read display modes: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let changes = limit_modes(&current, config);
+    if changes.is_empty() {
+        return changes;
+    }
+
+    info!("Battery saver: on battery, limiting {} display(s)", changes.len());
+    let modes: Vec<DisplayMode> = changes.iter().map(|(_, limited)| limited.clone()).collect();
+    if let Err(e) = crate::change_layout(|| set_display_modes(&modes)) {
+        error!("Battery saver: failed to apply limited modes: {}", e);
+        return Vec::new();
+    }
+
+    changes
+}
+
+/// Restore the original modes of displays that still use the limited mode.
+fn restore(limited: &[LimitedMode]) {
+    if limited.is_empty() {
+        return;
+    }
+
+    let current = match get_display_modes() {
+        Ok(modes) => modes,
+        Err(e) => {
+            error!("Battery saver: failed to read display modes: {}", e);
+            return;
+        }
+    };
+
+    let modes = modes_to_restore(limited, &current);
+    if modes.is_empty() {
+        return;
+    }
+
+    info!("Battery saver: on AC power, restoring {} display(s)", modes.len());
+    if let Err(e) = crate::change_layout(|| set_display_modes(&modes)) {
+        error!("Battery saver: failed to restore modes: {}", e);
+    }
+}
+
+/// Compute the (original, limited) mode of every display that exceeds the limits.
+fn limit_modes(current: &[DisplayMode], config: &BatterySaver) -> Vec<LimitedMode> {
+    let max_rate = config.max_refresh_rate as f64;
+
+    current
+        .iter()
+        .filter_map(|mode| {
+            let mut limited = mode.clone();
+
+            if mode.refresh_rate > max_rate + REFRESH_TOLERANCE {
+                limited.refresh_rate = max_rate;
+            }
+            if let (true, Some([width, height])) = (mode.internal, config.internal_resolution) {
+                limited.width = width;
+                limited.height = height;
+            }
+
+            (limited != *mode).then(|| (mode.clone(), limited))
+        })
+        .collect()
+}
+
+/// Add newly limited modes to the ones to restore. A display limited again
+/// after it changed since keeps the mode it changed to as its original.
+fn merge_limited(saved: &mut Vec<LimitedMode>, changes: Vec<LimitedMode>) {
+    for change in changes {
+        saved.retain(|(original, _)| original.key != change.0.key);
+        saved.push(change);
+    }
+}
+
+/// Original modes to restore. Displays that were unplugged, or that the user
+/// changed while on battery (e.g. by loading a profile), are left alone.
+fn modes_to_restore(limited: &[LimitedMode], current: &[DisplayMode]) -> Vec<DisplayMode> {
+    limited
+        .iter()
+        .filter(|(_, applied)| {
+            current.iter().any(|c| {
+                c.key == applied.key
+                    && (c.width, c.height) == (applied.width, applied.height)
+                    && (c.refresh_rate - applied.refresh_rate).abs() <= REFRESH_TOLERANCE
+            })
+        })
+        .map(|(original, _)| original.clone())
+        .collect()
+}
+
+/// Whether the machine runs on battery. None if unknown (e.g. a desktop).
+#[cfg(windows)]
+pub(super) fn on_battery() -> Option<bool> {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+
+    match status.ACLineStatus {
+        0 => Some(true),
+        1 => Some(false),
+        _ => None,
+    }
+}
+
+/// Remaining battery charge in percent. None without a battery.
+#[cfg(windows)]
+pub(super) fn battery_percent() -> Option<u32> {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+
+    // 255 means unknown
+    (status.BatteryLifePercent <= 100).then_some(status.BatteryLifePercent as u32)
+}
+
+/// Whether the machine runs on battery. None if unknown (e.g. a desktop).
+#[cfg(target_os = "linux")]
+pub(super) fn on_battery() -> Option<bool> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+
+    let mut has_battery = false;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let read = |name: &str| std::fs::read_to_string(path.join(name)).unwrap_or_default();
+
+        match read("type").trim() {
+            "Mains" | "USB" if read("online").trim() == "1" => return Some(false),
+            "Battery" => has_battery = true,
+            _ => {}
+        }
+    }
+
+    has_battery.then_some(true)
+}
+
+/// Remaining battery charge in percent. None without a battery.
+#[cfg(target_os = "linux")]
+pub(super) fn battery_percent() -> Option<u32> {
+    std::fs::read_dir("/sys/class/power_supply")
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| std::fs::read_to_string(path.join("type")).is_ok_and(|t| t.trim() == "Battery"))
+        .find_map(|path| std::fs::read_to_string(path.join("capacity")).ok()?.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(key: &str, width: u32, height: u32, refresh_rate: f64, internal: bool) -> DisplayMode {
+        DisplayMode {
+            key: key.to_string(),
+            width,
+            height,
+            refresh_rate,
+            internal,
+        }
+    }
+
+    #[test]
+    fn test_limit_modes_caps_refresh_and_panel_resolution() {
+        let config = BatterySaver {
+            enabled: true,
+            max_refresh_rate: 60,
+            internal_resolution: Some([1920, 1200]),
+        };
+        let current = [
+            mode("eDP-1", 2560, 1600, 165.0, true),
+            mode("DP-1", 2560, 1440, 144.0, false),
+            mode("HDMI-1", 1920, 1080, 59.94, false),
+        ];
+
+        let changes = limit_modes(&current, &config);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].1, mode("eDP-1", 1920, 1200, 60.0, true));
+        assert_eq!(changes[1].1, mode("DP-1", 2560, 1440, 60.0, false));
+    }
+
+    #[test]
+    fn test_modes_to_restore_skips_displays_changed_since() {
+        let limited = vec![
+            (mode("DP-1", 2560, 1440, 144.0, false), mode("DP-1", 2560, 1440, 60.0, false)),
+            (mode("DP-2", 1920, 1080, 120.0, false), mode("DP-2", 1920, 1080, 60.0, false)),
+            (mode("DP-3", 1920, 1080, 75.0, false), mode("DP-3", 1920, 1080, 60.0, false)),
+        ];
+        // DP-2 was switched by a profile load, DP-3 was unplugged
+        let current = [
+            mode("DP-1", 2560, 1440, 59.95, false),
+            mode("DP-2", 1920, 1080, 100.0, false),
+        ];
+
+        let restored = modes_to_restore(&limited, &current);
+        assert_eq!(restored, [mode("DP-1", 2560, 1440, 144.0, false)]);
+    }
+
+    #[test]
+    fn test_merge_limited_keeps_latest_original() {
+        let mut saved = vec![(mode("DP-1", 2560, 1440, 144.0, false), mode("DP-1", 2560, 1440, 60.0, false))];
+        // DP-2 was plugged in on battery, DP-1 set back to 120Hz by a profile
+        merge_limited(&mut saved, vec![
+            (mode("DP-1", 2560, 1440, 120.0, false), mode("DP-1", 2560, 1440, 60.0, false)),
+            (mode("DP-2", 1920, 1080, 144.0, false), mode("DP-2", 1920, 1080, 60.0, false)),
+        ]);
+
+        let originals: Vec<DisplayMode> = saved.into_iter().map(|(original, _)| original).collect();
+        assert_eq!(originals, [mode("DP-1", 2560, 1440, 120.0, false), mode("DP-2", 1920, 1080, 144.0, false)]);
+    }
+}
@@ -0,0 +1,201 @@
+//! Process-based triggers.
+//!
+//! Polls the running processes and applies a profile when a watched process
+//! starts or exits. Single responsibility: turn process start/exit into triggers.
+
+use super::trigger::{Event, EventBus, Trigger};
+use crate::settings::{load_settings, ProcessRule};
+use std::collections::HashSet;
+use std::thread;
+use std::time::Duration;
+
+/// How often the process list is polled.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Applies profiles when configured processes start or exit.
+pub struct ProcessTrigger;
+
+impl Trigger for ProcessTrigger {
+    fn name(&self) -> &'static str {
+        "process"
+    }
+
+    fn start(self: Box<Self>, bus: EventBus) -> Result<(), String> {
+        spawn_watcher(bus);
+        Ok(())
+    }
+}
+
+/// Spawn the process watcher thread.
+fn spawn_watcher(bus: EventBus) {
+    thread::spawn(move || {
+        let mut previous: Option<HashSet<String>> = None;
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let rules = load_settings().map(|s| s.process_rules).unwrap_or_default();
+            if rules.is_empty() {
+                previous = None;
+                continue;
+            }
+
+            let running = running_processes();
+            if let Some(previous) = &previous {
+                for event in detect_transitions(&rules, previous, &running) {
+                    bus.publish(event);
+                }
+            }
+            previous = Some(running);
+        }
+    });
+}
+
+/// Compare two process snapshots and return the profile requests to publish.
+fn detect_transitions(
+    rules: &[ProcessRule],
+    previous: &HashSet<String>,
+    running: &HashSet<String>,
+) -> Vec<Event> {
+    let mut triggers = Vec::new();
+
+    for rule in rules {
+        let name = normalize_process_name(&rule.process);
+        if name.is_empty() {
+            continue;
+        }
+
+        let was_running = previous.contains(&name);
+        let is_running = running.contains(&name);
+
+        let (profile, event) = match (was_running, is_running) {
+            (false, true) => (&rule.on_start, "started"),
+            (true, false) => (&rule.on_exit, "exited"),
+            _ => continue,
+        };
+
+        if let Some(profile) = profile {
+            triggers.push(Event::ApplyProfile {
+                profile: profile.clone(),
+                reason: format!("{} {}", rule.process, event),
+                cooldown_secs: rule.cooldown_secs,
+            });
+        }
+    }
+
+    triggers
+}
+
+/// Whether a process with this name is running right now.
+pub(super) fn is_running(name: &str) -> bool {
+    running_processes().contains(&normalize_process_name(name))
+}
+
+/// Normalize a process name or path for comparison: base name, lowercase, no `.exe`.
+fn normalize_process_name(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or(name).trim();
+    let lower = base.to_lowercase();
+    lower.strip_suffix(".exe").unwrap_or(&lower).to_string()
+}
+
+/// Get the normalized names of all running processes (Windows).
+#[cfg(windows)]
+fn running_processes() -> HashSet<String> {
+    use std::mem;
+    use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+    };
+
+    let mut names = HashSet::new();
+
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+            return names;
+        }
+
+        let mut entry: PROCESSENTRY32W = mem::zeroed();
+        entry.dwSize = mem::size_of::<PROCESSENTRY32W>() as u32;
+
+        let mut ok = Process32FirstW(snapshot, &mut entry);
+        while ok != 0 {
+            let end = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(entry.szExeFile.len());
+            names.insert(normalize_process_name(&String::from_utf16_lossy(&entry.szExeFile[..end])));
+            ok = Process32NextW(snapshot, &mut entry);
+        }
+
+        CloseHandle(snapshot);
+    }
+
+    names
+}
+
+/// Get the normalized names of all running processes (Linux).
+#[cfg(target_os = "linux")]
+fn running_processes() -> HashSet<String> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return HashSet::new();
+    };
+
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().chars().all(|c| c.is_ascii_digit()))
+        .filter_map(|entry| {
+            // argv[0] keeps the full name (comm is cut at 15 chars) and shows
+            // Wine/Proton games by their .exe name
+            let cmdline = std::fs::read(entry.path().join("cmdline")).ok()?;
+            let argv0 = cmdline.split(|&b| b == 0).next().filter(|a| !a.is_empty());
+            let name = match argv0 {
+                Some(argv0) => String::from_utf8_lossy(argv0).into_owned(),
+                None => std::fs::read_to_string(entry.path().join("comm")).ok()?,
+            };
+            Some(normalize_process_name(&name))
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_normalize_process_name() {
+        assert_eq!(normalize_process_name("iRacingSim64DX11.exe"), "iracingsim64dx11");
+        assert_eq!(normalize_process_name(r"Z:\games\iRacing\iRacingSim64DX11.exe"), "iracingsim64dx11");
+        assert_eq!(normalize_process_name("/usr/bin/steam"), "steam");
+        assert_eq!(normalize_process_name("obs\n"), "obs");
+    }
+
+    #[test]
+    fn test_detect_transitions() {
+        let rules = vec![ProcessRule {
+            process: "iRacingSim64DX11.exe".to_string(),
+            on_start: Some("Sim Racing".to_string()),
+            on_exit: Some("Desk".to_string()),
+            cooldown_secs: Some(60),
+        }];
+        let request = |profile: &str, reason: &str| Event::ApplyProfile {
+            profile: profile.to_string(),
+            reason: reason.to_string(),
+            cooldown_secs: Some(60),
+        };
+
+        let started = detect_transitions(&rules, &set(&["explorer"]), &set(&["explorer", "iracingsim64dx11"]));
+        assert_eq!(started, vec![request("Sim Racing", "iRacingSim64DX11.exe started")]);
+
+        let still_running = detect_transitions(&rules, &set(&["iracingsim64dx11"]), &set(&["iracingsim64dx11"]));
+        assert!(still_running.is_empty());
+
+        let exited = detect_transitions(&rules, &set(&["iracingsim64dx11"]), &set(&[]));
+        assert_eq!(exited, vec![request("Desk", "iRacingSim64DX11.exe exited")]);
+    }
+}
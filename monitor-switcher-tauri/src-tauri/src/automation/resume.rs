@@ -0,0 +1,125 @@
+//! Resume and unlock trigger.
+//!
+//! Monitors often come back in a different layout after sleep or while the
+//! session was locked (docks re-enumerate, TVs drop out). Polls for both and
+//! re-applies the last applied profile. Single responsibility: turn resume and
+//! unlock into profile requests.
+
+use super::settle;
+use super::trigger::{Event, EventBus, Trigger};
+use crate::settings::load_settings;
+use crate::state::last_applied;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// How often the session is polled.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A poll that took this much longer than `POLL_INTERVAL` means the machine slept.
+/// Threads don't run while suspended, but the wall clock keeps going.
+const SLEEP_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// Re-applies the last applied profile after resume and unlock.
+pub struct ResumeTrigger;
+
+impl Trigger for ResumeTrigger {
+    fn name(&self) -> &'static str {
+        "resume"
+    }
+
+    fn start(self: Box<Self>, bus: EventBus) -> Result<(), String> {
+        spawn_watcher(bus);
+        Ok(())
+    }
+}
+
+/// Spawn the resume/unlock watcher thread.
+fn spawn_watcher(bus: EventBus) {
+    thread::spawn(move || {
+        let mut was_locked = session_locked();
+
+        loop {
+            let before = SystemTime::now();
+            thread::sleep(POLL_INTERVAL);
+
+            let resumed = SystemTime::now()
+                .duration_since(before)
+                .is_ok_and(|slept| slept > POLL_INTERVAL + SLEEP_THRESHOLD);
+
+            let locked = session_locked();
+            let unlocked = was_locked == Some(true) && locked == Some(false);
+            was_locked = locked.or(was_locked);
+
+            let reason = match (resumed, unlocked) {
+                (true, _) => "resume",
+                (false, true) => "unlock",
+                (false, false) => continue,
+            };
+
+            if !load_settings().map(|s| s.reapply_on_resume).unwrap_or(false) {
+                continue;
+            }
+            let Some(last) = last_applied() else {
+                continue;
+            };
+            settle::wait_for_displays();
+            // By ID, in case the profile was renamed since
+            let profile = last.profile_id.unwrap_or(last.profile);
+            bus.publish(Event::ApplyProfile {
+                profile,
+                reason: reason.to_string(),
+                cooldown_secs: None,
+            });
+        }
+    });
+}
+
+/// Whether the session is locked. None if unknown.
+#[cfg(windows)]
+fn session_locked() -> Option<bool> {
+    use windows_sys::Win32::System::RemoteDesktop::{
+        WTSFreeMemory, WTSQuerySessionInformationW, WTSSessionInfoEx, WTSINFOEXW,
+        WTS_CURRENT_SERVER_HANDLE, WTS_CURRENT_SESSION, WTS_SESSIONSTATE_LOCK, WTS_SESSIONSTATE_UNKNOWN, WTS_SESSIONSTATE_UNLOCK,
+    };
+
+    let mut buffer: windows_sys::core::PWSTR = std::ptr::null_mut();
+    let mut size = 0u32;
+    let ok = unsafe {
+        WTSQuerySessionInformationW(WTS_CURRENT_SERVER_HANDLE, WTS_CURRENT_SESSION, WTSSessionInfoEx, &mut buffer, &mut size)
+    };
+    if ok == 0 || buffer.is_null() {
+        return None;
+    }
+
+    let info = unsafe { &*(buffer as *const WTSINFOEXW) };
+    // Read as on Windows 10 and later. Windows 7 reports the two flags swapped,
+    // but the app doesn't run there (WebView2 needs Windows 10)
+    let flags = if info.Level == 1 {
+        unsafe { info.Data.WTSInfoExLevel1.SessionFlags as u32 }
+    } else {
+        WTS_SESSIONSTATE_UNKNOWN
+    };
+    unsafe { WTSFreeMemory(buffer as *mut _) };
+
+    match flags {
+        WTS_SESSIONSTATE_LOCK => Some(true),
+        WTS_SESSIONSTATE_UNLOCK => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether the session is locked, from logind's LockedHint. None if unknown.
+#[cfg(target_os = "linux")]
+fn session_locked() -> Option<bool> {
+    let session = std::env::var("XDG_SESSION_ID").ok()?;
+    let output = std::process::Command::new("loginctl")
+        .args(["show-session", &session, "--property=LockedHint", "--value"])
+        .output()
+        .ok()?;
+
+    match String::from_utf8_lossy(&output.stdout).trim() {
+        "yes" => Some(true),
+        "no" => Some(false),
+        _ => None,
+    }
+}
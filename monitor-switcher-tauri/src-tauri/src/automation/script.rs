@@ -0,0 +1,298 @@
+//! Embedded automation scripts.
+//!
+//! Runs `automation.rhai` from the config directory, for logic the declarative
+//! rules can't express. The script may define any of these handlers:
+//!
+//! - `on_start()` - after the script is (re)loaded
+//! - `on_tick()` - once a minute
+//! - `on_display_change()` - after the display layout changed
+//! - `on_power_change()` - after switching between battery and AC power
+//!
+//! and can call `profiles()`, `monitors()`, `load_profile(name)`, `on_battery()`,
+//! `battery_percent()`, `process_running(name)`, `weekday()` (1 = Monday),
+//! `hour()` and `minute()`. `print()` goes to the log.
+//! Single responsibility: host the script and feed it events.
+
+use super::trigger::{Event, EventBus, Trigger};
+use super::{guard, power, process};
+use crate::profile::{current_monitors, list_profiles, resolve_host_variant, ProfileEntry};
+use crate::settings::get_script_path;
+use crate::state::{self, LastApplied};
+use chrono::{Datelike, Local, Timelike};
+use log::{error, info};
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Scope, AST};
+use std::cell::RefCell;
+use std::fs;
+use std::sync::mpsc::RecvTimeoutError;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// How often `on_tick` runs.
+const TICK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Operations a single handler may run before it is stopped, so a runaway
+/// loop can't hang the automation thread.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// How deeply script functions may call each other.
+const MAX_CALL_LEVELS: usize = 32;
+
+/// Runs the automation script, feeding it the events of the other triggers.
+pub struct ScriptTrigger;
+
+impl Trigger for ScriptTrigger {
+    fn name(&self) -> &'static str {
+        "script"
+    }
+
+    fn start(self: Box<Self>, bus: EventBus) -> Result<(), String> {
+        spawn_runner(bus);
+        Ok(())
+    }
+}
+
+/// Spawn the script runner thread.
+fn spawn_runner(bus: EventBus) {
+    let receiver = bus.subscribe();
+
+    thread::spawn(move || {
+        let last_request: RefCell<Option<Request>> = RefCell::default();
+        let engine = build_engine(move |profile| {
+            let applied_at = state::last_applied().map(|last| last.applied_at);
+            if !is_new_request(&mut last_request.borrow_mut(), profile, applied_at) || is_last_applied(profile) {
+                return;
+            }
+            bus.publish(Event::ApplyProfile {
+                profile: profile.to_string(),
+                reason: "script".to_string(),
+                cooldown_secs: None,
+            });
+        });
+        let mut script = LoadedScript::default();
+        let mut pending: Option<&str> = None;
+
+        loop {
+            if script.reload(&engine) {
+                script.call(&engine, "on_start");
+            } else if let Some(handler) = pending {
+                script.call(&engine, handler);
+            }
+
+            pending = match receiver.recv_timeout(TICK_INTERVAL) {
+                // Changes from the app's own applies would let the script loop
+                Ok(Event::DisplayChanged) if guard::is_own_change() => None,
+                Ok(Event::DisplayChanged) => Some("on_display_change"),
+                Ok(Event::PowerChanged { .. }) => Some("on_power_change"),
+                // Includes the script's own requests
+                Ok(Event::ApplyProfile { .. }) => None,
+                Err(RecvTimeoutError::Timeout) => Some("on_tick"),
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+        }
+    });
+}
+
+/// Create the engine with the automation API registered.
+fn build_engine<F: Fn(&str) + 'static>(on_load_profile: F) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+
+    engine.on_print(|text| info!("Script: {}", text));
+    engine.on_debug(|text, _, pos| info!("Script ({}): {}", pos, text));
+
+    engine.register_fn("profiles", || -> Array {
+        list_profiles().unwrap_or_default().into_iter().map(Dynamic::from).collect()
+    });
+    engine.register_fn("monitors", || -> Array {
+        current_monitors()
+            .ok()
+            .and_then(|monitors| rhai::serde::to_dynamic(monitors).ok())
+            .and_then(|monitors| monitors.try_cast::<Array>())
+            .unwrap_or_default()
+    });
+    engine.register_fn("on_battery", || power::on_battery() == Some(true));
+    engine.register_fn("battery_percent", || -> i64 {
+        power::battery_percent().map(i64::from).unwrap_or(-1)
+    });
+    engine.register_fn("process_running", |name: &str| process::is_running(name));
+    engine.register_fn("weekday", || Local::now().weekday().number_from_monday() as i64);
+    engine.register_fn("hour", || Local::now().hour() as i64);
+    engine.register_fn("minute", || Local::now().minute() as i64);
+
+    engine.register_fn("load_profile", move |name: &str| on_load_profile(name));
+
+    engine
+}
+
+/// The script's last `load_profile` request, with the latest apply at the time.
+struct Request {
+    profile: String,
+    applied_at: Option<String>,
+}
+
+/// Whether a `load_profile(profile)` call should be passed on. Handlers run
+/// every minute, so repeating the last request is dropped once anything
+/// applied a layout since: a manual switch holds until the script asks for
+/// another profile. A request nothing was applied for is retried.
+fn is_new_request(last: &mut Option<Request>, profile: &str, applied_at: Option<String>) -> bool {
+    match last {
+        Some(request) if request.profile == profile => request.applied_at == applied_at,
+        _ => {
+            *last = Some(Request { profile: profile.to_string(), applied_at });
+            true
+        }
+    }
+}
+
+/// Whether `name` refers to the profile applied last, by whatever applied it.
+fn is_last_applied(name: &str) -> bool {
+    let Some(last) = state::last_applied() else {
+        return false;
+    };
+    resolve_host_variant(name)
+        .ok()
+        .flatten()
+        .is_some_and(|entry| is_same_profile(&last, &entry))
+}
+
+/// Whether `last` is the profile `entry`. Loads resolve names to this machine's
+/// variant, so `entry` must be resolved the same way.
+fn is_same_profile(last: &LastApplied, entry: &ProfileEntry) -> bool {
+    match &last.profile_id {
+        Some(id) => *id == entry.id,
+        // State saved before profiles had IDs
+        None => last.profile == entry.name,
+    }
+}
+
+/// The compiled script and the modification time it was compiled from.
+#[derive(Default)]
+struct LoadedScript {
+    modified: Option<SystemTime>,
+    ast: Option<AST>,
+}
+
+impl LoadedScript {
+    /// Recompile the script if the file changed. Returns true if a new script was loaded.
+    fn reload(&mut self, engine: &Engine) -> bool {
+        let Ok(path) = get_script_path() else {
+            return false;
+        };
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        if modified == self.modified {
+            return false;
+        }
+
+        self.modified = modified;
+        self.ast = None;
+        if modified.is_none() {
+            info!("Script: {} removed", path.display());
+            return false;
+        }
+
+        match engine.compile_file(path.clone()) {
+            Ok(ast) => {
+                info!("Script: loaded {}", path.display());
+                if let Err(e) = engine.run_ast(&ast) {
+                    error!("Script: top-level code failed: {}", e);
+                }
+                self.ast = Some(ast);
+                true
+            }
+            Err(e) => {
+                error!("Script: failed to compile {}: {}", path.display(), e);
+                false
+            }
+        }
+    }
+
+    /// Call a handler if the script defines it.
+    fn call(&self, engine: &Engine, handler: &str) {
+        let Some(ast) = &self.ast else {
+            return;
+        };
+        if !ast.iter_functions().any(|f| f.name == handler && f.params.is_empty()) {
+            return;
+        }
+
+        // Top-level code already ran when the script was loaded
+        let options = CallFnOptions::new().eval_ast(false);
+        if let Err(e) = engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), ast, handler, ()) {
+            error!("Script: {} failed: {}", handler, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::rc::Rc;
+
+    #[test]
+    fn test_load_profile_passes_on_requests() {
+        let loaded: Rc<RefCell<Vec<String>>> = Rc::default();
+        let recorder = loaded.clone();
+        let engine = build_engine(move |name| recorder.borrow_mut().push(name.to_string()));
+
+        let ast = engine
+            .compile(r#"fn on_tick() { load_profile(if weekday() > 0 { "Desk" } else { "Couch" }); }"#)
+            .unwrap();
+        let script = LoadedScript { modified: None, ast: Some(ast) };
+
+        script.call(&engine, "on_tick");
+        script.call(&engine, "on_tick");
+        script.call(&engine, "on_display_change");
+
+        assert_eq!(*loaded.borrow(), ["Desk", "Desk"]);
+    }
+
+    #[test]
+    fn test_repeated_request_yields_to_later_applies() {
+        let mut last = None;
+        let applied = |at: &str| Some(at.to_string());
+
+        assert!(is_new_request(&mut last, "Desk", applied("1")));
+        // The apply failed, nothing changed: try again
+        assert!(is_new_request(&mut last, "Desk", applied("1")));
+        // Desk was applied, then the user switched to another profile
+        assert!(!is_new_request(&mut last, "Desk", applied("2")));
+        assert!(!is_new_request(&mut last, "Desk", applied("3")));
+        // The script changed its mind and back
+        assert!(is_new_request(&mut last, "Couch", applied("3")));
+        assert!(is_new_request(&mut last, "Desk", applied("3")));
+    }
+
+    #[test]
+    fn test_runaway_handler_is_stopped() {
+        let engine = build_engine(|_| {});
+        let ast = engine.compile("fn on_tick() { loop {} }").unwrap();
+        let script = LoadedScript { modified: None, ast: Some(ast) };
+
+        // Logs the error and returns instead of hanging
+        script.call(&engine, "on_tick");
+    }
+
+    #[test]
+    fn test_is_same_profile() {
+        let entry = |id: &str, name: &str| ProfileEntry {
+            id: id.to_string(),
+            name: name.to_string(),
+            path: Default::default(),
+        };
+        let last = |profile_id: Option<&str>| LastApplied {
+            profile: "Office".to_string(),
+            profile_id: profile_id.map(str::to_string),
+            applied_at: String::new(),
+        };
+
+        assert!(is_same_profile(&last(Some("1")), &entry("1", "Office")));
+        // Renamed since it was applied
+        assert!(is_same_profile(&last(Some("1")), &entry("1", "Work")));
+        // Another profile was applied since
+        assert!(!is_same_profile(&last(Some("2")), &entry("1", "Office")));
+        assert!(is_same_profile(&last(None), &entry("1", "Office")));
+        assert!(!is_same_profile(&last(None), &entry("1", "Home")));
+    }
+}
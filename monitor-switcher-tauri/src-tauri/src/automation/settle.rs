@@ -0,0 +1,66 @@
+//! Settling after resume and hotplug.
+//!
+//! Docks and TVs bring monitors up one by one, sometimes many seconds after the
+//! machine woke up. Applying a profile halfway through gets undone by the next
+//! monitor. Single responsibility: wait until the connected monitors are stable.
+
+use crate::display::current_fingerprint;
+use crate::settings::load_settings;
+use log::{debug, info};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the connected monitors are checked while waiting.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Wait for the configured settle delay, then until the connected monitors
+/// stop changing. Returns the fingerprint of the monitors connected at the end.
+pub fn wait_for_displays() -> Option<String> {
+    let settings = load_settings().unwrap_or_default();
+    let delay = Duration::from_secs(settings.settle_delay_secs);
+    let max_wait = Duration::from_secs(settings.settle_max_wait_secs);
+    if !delay.is_zero() || !max_wait.is_zero() {
+        info!("Automation: waiting for displays to settle");
+    }
+
+    wait_until_stable(delay, max_wait, POLL_INTERVAL, || current_fingerprint().ok())
+}
+
+/// Sleep for `delay`, then sample until two samples in a row agree or `max_wait`
+/// has passed. Returns the last sample.
+fn wait_until_stable<F>(delay: Duration, max_wait: Duration, poll: Duration, mut sample: F) -> Option<String>
+where
+    F: FnMut() -> Option<String>,
+{
+    thread::sleep(delay);
+
+    let deadline = Instant::now() + max_wait;
+    let mut last = sample();
+    while Instant::now() < deadline {
+        thread::sleep(poll);
+        let next = sample();
+        if next == last {
+            return next;
+        }
+        debug!("Automation: monitors still changing");
+        last = next;
+    }
+    last
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wait_until_stable() {
+        let mut samples = ["dock", "dock;tv", "dock;tv", "dock"].into_iter().map(|s| Some(s.to_string()));
+        let settled = wait_until_stable(Duration::ZERO, Duration::from_secs(5), Duration::ZERO, || samples.next().flatten());
+        assert_eq!(settled.as_deref(), Some("dock;tv"));
+
+        // Without a max wait, the first sample is taken as is
+        let mut samples = ["dock", "dock;tv"].into_iter().map(|s| Some(s.to_string()));
+        let settled = wait_until_stable(Duration::ZERO, Duration::ZERO, Duration::ZERO, || samples.next().flatten());
+        assert_eq!(settled.as_deref(), Some("dock"));
+    }
+}
@@ -0,0 +1,77 @@
+//! Trigger trait and the event bus triggers talk through.
+//!
+//! Single responsibility: decouple automation sources (triggers) from what
+//! happens in response to their events.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+/// Something that happened, as reported by a trigger.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// A trigger asks for a profile to be applied. The reason names the rule
+    /// and keys its cooldown; None uses the default cooldown.
+    ApplyProfile {
+        profile: String,
+        reason: String,
+        cooldown_secs: Option<u64>,
+    },
+    /// The display layout changed (hotplug, another tool, the desktop environment).
+    DisplayChanged,
+    /// The machine switched between battery and AC power.
+    PowerChanged { on_battery: bool },
+}
+
+/// A source of automation events.
+///
+/// To add one, implement this in its own module under `automation/` and list
+/// it in `automation::triggers()`.
+pub trait Trigger {
+    /// Short name for log messages.
+    fn name(&self) -> &'static str;
+
+    /// Start watching. Called once at startup; long-running work belongs on a
+    /// thread of the trigger's own. Returns an error if the source is unavailable.
+    fn start(self: Box<Self>, bus: EventBus) -> Result<(), String>;
+}
+
+/// Broadcasts events to every subscriber.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Vec<Sender<Event>>>>,
+}
+
+impl EventBus {
+    /// Send an event to all subscribers. Subscribers that went away are dropped.
+    pub fn publish(&self, event: Event) {
+        let mut subscribers = self.subscribers.lock().unwrap_or_else(|e| e.into_inner());
+        subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+
+    /// Receive all events published from now on.
+    pub fn subscribe(&self) -> Receiver<Event> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.lock().unwrap_or_else(|e| e.into_inner()).push(sender);
+        receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_reaches_every_subscriber() {
+        let bus = EventBus::default();
+        let first = bus.subscribe();
+        let second = bus.subscribe();
+        drop(second);
+
+        bus.publish(Event::DisplayChanged);
+        bus.publish(Event::PowerChanged { on_battery: true });
+
+        assert_eq!(first.try_recv(), Ok(Event::DisplayChanged));
+        assert_eq!(first.try_recv(), Ok(Event::PowerChanged { on_battery: true }));
+        assert_eq!(bus.subscribers.lock().unwrap().len(), 1);
+    }
+}
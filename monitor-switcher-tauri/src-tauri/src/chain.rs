@@ -0,0 +1,51 @@
+//! Profile chains.
+//!
+//! Single responsibility: run the steps of a profile chain in order, with the
+//! configured delays between them.
+
+use crate::i18n;
+use crate::settings::{load_settings, ChainStep};
+use log::info;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Wry};
+
+/// Run a chain, blocking until all steps finished. Stops at the first failing step.
+pub fn run_chain(app: &AppHandle<Wry>, name: &str) -> Result<(), String> {
+    let chain = load_settings()?
+        .chains
+        .into_iter()
+        .find(|chain| chain.name == name)
+        .ok_or_else(|| i18n::tr_with("error.chainNotFound", &[("name", name)]))?;
+
+    info!("Running chain '{}' ({} steps)", name, chain.steps.len());
+    let source = format!("chain '{}'", name);
+    for (i, step) in chain.steps.iter().enumerate() {
+        run_step(app, step, &source).map_err(|e| format!("Chain '{}' step {} failed: {}", name, i + 1, e))?;
+    }
+
+    Ok(())
+}
+
+fn run_step(app: &AppHandle<Wry>, step: &ChainStep, source: &str) -> Result<(), String> {
+    match step {
+        ChainStep::LoadProfile { profile } => crate::do_load_profile(app, profile, source).map(|_| ()),
+        ChainStep::Wait { ms } => {
+            thread::sleep(Duration::from_millis(*ms));
+            Ok(())
+        }
+        ChainStep::RunCommand { program, args } => {
+            let status = Command::new(program)
+                .args(args)
+                .status()
+                .map_err(|e| i18n::tr_with("error.runProgram", &[("program", program), ("error", &e.to_string())]))?;
+            if status.success() {
+                Ok(())
+            } else {
+                Err(i18n::tr_with("error.programExited", &[("program", program), ("status", &status.to_string())]))
+            }
+        }
+        ChainStep::TurnOffMonitors => crate::display::turn_off_monitors(),
+    }
+}
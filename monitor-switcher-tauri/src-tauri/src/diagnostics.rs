@@ -0,0 +1,154 @@
+//! Diagnostic dump of the raw display state.
+//!
+//! Single responsibility: collect everything needed to debug a profile that
+//! loads wrong into one JSON blob that users can attach to bug reports.
+
+use serde::Serialize;
+
+use crate::display::is_remote_session;
+use crate::profile::list_profiles;
+
+#[cfg(windows)]
+use crate::display::LUID;
+#[cfg(windows)]
+use crate::profile::{load_profile, profile_to_settings, capture_snapshot, replay_match, DisplayProfile};
+
+#[cfg(target_os = "linux")]
+use crate::display::{get_display_settings, OutputConfig};
+#[cfg(target_os = "linux")]
+use crate::profile::load_linux_profile;
+
+/// Everything we know about the display state at one point in time.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayStateDump {
+    pub app_version: &'static str,
+    pub platform: &'static str,
+    pub remote_session: bool,
+    /// Raw path/mode arrays, additional info and DPI data of the active configuration.
+    #[cfg(windows)]
+    pub current: DisplayProfile,
+    /// All outputs, including disconnected and disabled ones.
+    #[cfg(target_os = "linux")]
+    pub current: Vec<OutputConfig>,
+    /// How each saved profile would be matched against the current system.
+    pub profiles: Vec<ProfileMatchDump>,
+}
+
+/// Matching result for one saved profile.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileMatchDump {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[cfg(windows)]
+    pub paths: Vec<PathMatchDump>,
+    #[cfg(target_os = "linux")]
+    pub outputs: Vec<OutputMatchDump>,
+}
+
+/// Adapter IDs of one profile path before and after matching (Windows).
+#[cfg(windows)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathMatchDump {
+    pub source_id: u32,
+    pub target_id: u32,
+    pub saved_source_adapter: String,
+    pub matched_source_adapter: String,
+    pub saved_target_adapter: String,
+    pub matched_target_adapter: String,
+}
+
+/// Whether a profile output is currently connected (Linux).
+#[cfg(target_os = "linux")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputMatchDump {
+    pub name: String,
+    pub connected: bool,
+}
+
+/// Collect the display state dump (Windows).
+#[cfg(windows)]
+pub fn dump_display_state() -> Result<DisplayStateDump, String> {
+    let current = capture_snapshot()?;
+
+    let profiles = list_profiles()?
+        .into_iter()
+        .map(|name| match dump_profile_match(&name, &current) {
+            Ok(paths) => ProfileMatchDump { name, error: None, paths },
+            Err(e) => ProfileMatchDump { name, error: Some(e), paths: Vec::new() },
+        })
+        .collect();
+
+    Ok(DisplayStateDump {
+        app_version: env!("CARGO_PKG_VERSION"),
+        platform: "windows",
+        remote_session: is_remote_session(),
+        current,
+        profiles,
+    })
+}
+
+/// Run adapter matching for a profile against the snapshot without applying it (Windows).
+#[cfg(windows)]
+fn dump_profile_match(name: &str, current: &DisplayProfile) -> Result<Vec<PathMatchDump>, String> {
+    let profile = load_profile(name)?;
+    let (saved, _) = profile_to_settings(&profile);
+    let matched = replay_match(&profile, current);
+
+    Ok(saved
+        .path_info_array
+        .iter()
+        .zip(&matched.path_info_array)
+        .map(|(before, after)| PathMatchDump {
+            source_id: after.source_info.id,
+            target_id: after.target_info.id,
+            saved_source_adapter: luid_string(before.source_info.adapter_id),
+            matched_source_adapter: luid_string(after.source_info.adapter_id),
+            saved_target_adapter: luid_string(before.target_info.adapter_id),
+            matched_target_adapter: luid_string(after.target_info.adapter_id),
+        })
+        .collect())
+}
+
+/// Format an adapter LUID the way Windows tools print it.
+#[cfg(windows)]
+fn luid_string(luid: LUID) -> String {
+    format!("{:08X}:{:08X}", luid.high_part, luid.low_part)
+}
+
+/// Collect the display state dump (Linux).
+#[cfg(target_os = "linux")]
+pub fn dump_display_state() -> Result<DisplayStateDump, String> {
+    let current = get_display_settings(false)?.outputs;
+
+    let profiles = list_profiles()?
+        .into_iter()
+        .map(|name| match load_linux_profile(&name) {
+            Ok(settings) => ProfileMatchDump {
+                outputs: settings
+                    .outputs
+                    .iter()
+                    .map(|output| OutputMatchDump {
+                        name: output.name.clone(),
+                        connected: current.iter().any(|c| c.name == output.name),
+                    })
+                    .collect(),
+                name,
+                error: None,
+            },
+            Err(e) => ProfileMatchDump { name, error: Some(e), outputs: Vec::new() },
+        })
+        .collect();
+
+    Ok(DisplayStateDump {
+        app_version: env!("CARGO_PKG_VERSION"),
+        platform: "linux",
+        remote_session: is_remote_session(),
+        current,
+        profiles,
+    })
+}
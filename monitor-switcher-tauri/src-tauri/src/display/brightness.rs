@@ -0,0 +1,87 @@
+//! One brightness level for every monitor.
+//!
+//! External monitors are dimmed over DDC/CI, built-in panels through the
+//! platform's backlight control.
+//! Single responsibility: set the same brightness on all active monitors.
+
+use super::ddc::{get_vcp, set_vcp, VCP_BRIGHTNESS};
+use super::{brightness_targets, set_panel_brightness};
+
+/// An active monitor whose brightness can be set.
+#[derive(Debug, Clone)]
+pub struct BrightnessTarget {
+    /// As `MonitorDetails::monitor_id`
+    pub monitor_id: String,
+    /// Name for error messages
+    pub name: String,
+    /// Built-in panel, dimmed through the backlight instead of DDC/CI
+    pub internal: bool,
+}
+
+/// Set every active monitor to `percent` brightness. Monitors that can't be
+/// dimmed are logged and skipped; it only fails when none could be.
+pub fn set_all_brightness(percent: u8) -> Result<(), String> {
+    if percent > 100 {
+        return Err(format!("Brightness {}% is out of range (0-100)", percent));
+    }
+
+    let targets = brightness_targets()?;
+    if targets.is_empty() {
+        return Err("No active monitors".to_string());
+    }
+
+    let mut errors = Vec::new();
+    let mut attempted = 0;
+    let mut panel_done = false;
+    for target in &targets {
+        let result = if target.internal {
+            // The backlight control covers every built-in panel at once
+            if panel_done {
+                continue;
+            }
+            panel_done = true;
+            set_panel_brightness(percent)
+        } else {
+            set_ddc_brightness(&target.monitor_id, percent)
+        };
+        attempted += 1;
+
+        if let Err(e) = result {
+            log::warn!("Couldn't set the brightness of {}: {}", target.name, e);
+            errors.push(format!("{}: {}", target.name, e));
+        }
+    }
+
+    if errors.len() == attempted {
+        return Err(errors.join("; "));
+    }
+    Ok(())
+}
+
+/// Set the brightness of an external monitor, scaled to the maximum it reports.
+fn set_ddc_brightness(monitor_id: &str, percent: u8) -> Result<(), String> {
+    let current = get_vcp(monitor_id, VCP_BRIGHTNESS)?;
+    set_vcp(monitor_id, VCP_BRIGHTNESS, scale_to_maximum(percent, current.maximum))
+}
+
+/// A percentage as a VCP value; monitors reporting no maximum take the percentage.
+fn scale_to_maximum(percent: u8, maximum: u32) -> u32 {
+    if maximum == 0 {
+        return u32::from(percent);
+    }
+    (u32::from(percent) * maximum + 50) / 100
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_to_maximum() {
+        assert_eq!(scale_to_maximum(50, 100), 50);
+        assert_eq!(scale_to_maximum(50, 255), 128);
+        assert_eq!(scale_to_maximum(100, 255), 255);
+        assert_eq!(scale_to_maximum(0, 255), 0);
+        assert_eq!(scale_to_maximum(40, 0), 40);
+    }
+}
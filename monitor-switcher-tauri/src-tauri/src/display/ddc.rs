@@ -0,0 +1,246 @@
+//! DDC/CI monitor control (MCCS).
+//!
+//! Monitors describe what they support over DDC/CI in a capabilities string
+//! such as `(prot(monitor)type(lcd)model(U2720Q)vcp(10 12 60(0F 11 12))mccs_ver(2.1))`.
+//! The platform backends read it (and VCP values) from the monitor; this module
+//! turns it into something the UI and automation can check.
+//! Single responsibility: parse MCCS capabilities.
+
+use super::{read_capabilities_string, read_vcp, write_vcp};
+use serde::Serialize;
+
+/// VCP code of the luminance (brightness) control.
+pub const VCP_BRIGHTNESS: u8 = 0x10;
+/// VCP code of the contrast control.
+pub const VCP_CONTRAST: u8 = 0x12;
+/// VCP code of the input source selection.
+pub const VCP_INPUT_SOURCE: u8 = 0x60;
+
+/// Names of the VCP codes most monitors support.
+const VCP_NAMES: &[(u8, &str)] = &[
+    (0x02, "New control value"),
+    (0x04, "Restore factory defaults"),
+    (0x05, "Restore factory brightness/contrast"),
+    (0x08, "Restore factory color"),
+    (VCP_BRIGHTNESS, "Brightness"),
+    (VCP_CONTRAST, "Contrast"),
+    (0x14, "Color preset"),
+    (0x16, "Red gain"),
+    (0x18, "Green gain"),
+    (0x1A, "Blue gain"),
+    (0x52, "Active control"),
+    (VCP_INPUT_SOURCE, "Input source"),
+    (0x62, "Audio volume"),
+    (0x8D, "Audio mute"),
+    (0xAC, "Horizontal frequency"),
+    (0xAE, "Vertical frequency"),
+    (0xB2, "Subpixel layout"),
+    (0xB6, "Display technology"),
+    (0xC6, "Application enable key"),
+    (0xC8, "Display controller type"),
+    (0xC9, "Firmware level"),
+    (0xCA, "OSD"),
+    (0xCC, "OSD language"),
+    (0xD6, "Power mode"),
+    (0xDC, "Display mode"),
+    (0xDF, "VCP version"),
+];
+
+/// One VCP feature a monitor reports.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VcpFeature {
+    pub code: u8,
+    /// Human-readable name, for well-known codes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'static str>,
+    /// Allowed values of non-continuous features (e.g. the inputs of 0x60)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<u8>,
+}
+
+/// What a monitor supports over DDC/CI.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorCapabilities {
+    /// As `MonitorDetails::monitor_id`
+    pub monitor_id: String,
+    /// The capabilities string as reported
+    pub raw: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mccs_version: Option<String>,
+    pub features: Vec<VcpFeature>,
+    /// Brightness can be read and set
+    pub brightness: bool,
+    /// The input source can be switched
+    pub input_switching: bool,
+}
+
+/// The value of a VCP feature as the monitor reports it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VcpValue {
+    pub code: u8,
+    pub current: u32,
+    /// Maximum of continuous features; 0 when the monitor reports none
+    pub maximum: u32,
+}
+
+/// Read a raw VCP feature from a connected monitor.
+pub fn get_vcp(monitor_id: &str, code: u8) -> Result<VcpValue, String> {
+    let (current, maximum) = read_vcp(monitor_id, code)?;
+    Ok(VcpValue { code, current, maximum })
+}
+
+/// Write a raw VCP feature to a connected monitor. The value is sent as is;
+/// MCCS values are 16-bit.
+pub fn set_vcp(monitor_id: &str, code: u8, value: u32) -> Result<(), String> {
+    if value > u32::from(u16::MAX) {
+        return Err(format!("VCP value {} is out of range (0-65535)", value));
+    }
+    write_vcp(monitor_id, code, value)
+}
+
+/// Read and parse the capabilities of a connected monitor.
+pub fn get_monitor_capabilities(monitor_id: &str) -> Result<MonitorCapabilities, String> {
+    let raw = read_capabilities_string(monitor_id)?;
+    Ok(parse_capabilities(monitor_id, &raw))
+}
+
+/// Parse an MCCS capabilities string. Unknown sections are ignored.
+pub fn parse_capabilities(monitor_id: &str, raw: &str) -> MonitorCapabilities {
+    let sections = sections(raw.trim());
+    let section = |key: &str| {
+        sections
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.trim().to_string())
+    };
+
+    let features = section("vcp").map(|vcp| parse_vcp(&vcp)).unwrap_or_default();
+    let supports = |code: u8| features.iter().any(|f| f.code == code);
+
+    MonitorCapabilities {
+        monitor_id: monitor_id.to_string(),
+        raw: raw.trim().to_string(),
+        model: section("model").filter(|m| !m.is_empty()),
+        mccs_version: section("mccs_ver").filter(|v| !v.is_empty()),
+        brightness: supports(VCP_BRIGHTNESS),
+        input_switching: supports(VCP_INPUT_SOURCE),
+        features,
+    }
+}
+
+/// Split `key(value)key(value)...` at the top level, after removing the
+/// enclosing parentheses.
+fn sections(raw: &str) -> Vec<(String, String)> {
+    let inner = raw
+        .strip_prefix('(')
+        .and_then(|s| s.strip_suffix(')'))
+        .unwrap_or(raw);
+
+    let mut sections = Vec::new();
+    let mut key = String::new();
+    let mut value = String::new();
+    let mut depth = 0usize;
+
+    for c in inner.chars() {
+        match (c, depth) {
+            ('(', 0) => depth = 1,
+            ('(', _) => {
+                depth += 1;
+                value.push(c);
+            }
+            (')', 1) => {
+                depth = 0;
+                sections.push((key.trim().to_string(), std::mem::take(&mut value)));
+                key.clear();
+            }
+            (')', 0) => {}
+            (')', _) => {
+                depth -= 1;
+                value.push(c);
+            }
+            (_, 0) => key.push(c),
+            _ => value.push(c),
+        }
+    }
+    sections
+}
+
+/// Parse the `vcp(...)` section: hex codes, each optionally followed by a
+/// parenthesized list of allowed values.
+fn parse_vcp(vcp: &str) -> Vec<VcpFeature> {
+    let mut features: Vec<VcpFeature> = Vec::new();
+    let mut rest = vcp;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '(' {
+            let end = rest.find(')').unwrap_or(rest.len());
+            if let Some(feature) = features.last_mut() {
+                feature.values = hex_bytes(&rest[1..end]);
+            }
+            rest = rest.get(end + 1..).unwrap_or("");
+        } else if c.is_ascii_hexdigit() {
+            let end = rest.find(|c: char| !c.is_ascii_hexdigit()).unwrap_or(rest.len());
+            features.extend(hex_bytes(&rest[..end]).into_iter().map(|code| VcpFeature {
+                code,
+                name: vcp_name(code),
+                values: Vec::new(),
+            }));
+            rest = &rest[end..];
+        } else {
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    features
+}
+
+/// Hex bytes separated by spaces; runs without spaces ("0405") are read in pairs.
+fn hex_bytes(text: &str) -> Vec<u8> {
+    text.split_whitespace()
+        .flat_map(|run| {
+            run.as_bytes()
+                .chunks(2)
+                .filter_map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn vcp_name(code: u8) -> Option<&'static str> {
+    VCP_NAMES.iter().find(|(c, _)| *c == code).map(|(_, name)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_capabilities() {
+        let raw = "(prot(monitor)type(LCD)model(U2720Q)cmds(01 02 03 07 0C E3 F3)vcp(02 04 05 08 10 12 14(01 04 05 06 08 09 0B 0C) 16 18 1A 52 60(0F 11 1B) AA(01 02 04) D6(01 04 05) DF)mccs_ver(2.1)mswhql(1))";
+        let caps = parse_capabilities("DP-1", raw);
+
+        assert_eq!(caps.model.as_deref(), Some("U2720Q"));
+        assert_eq!(caps.mccs_version.as_deref(), Some("2.1"));
+        assert!(caps.brightness && caps.input_switching);
+
+        let input = caps.features.iter().find(|f| f.code == VCP_INPUT_SOURCE).unwrap();
+        assert_eq!(input.values, [0x0F, 0x11, 0x1B]);
+        assert_eq!(input.name, Some("Input source"));
+        assert_eq!(caps.features.len(), 15);
+        assert_eq!(caps.features.last().unwrap().code, 0xDF);
+    }
+
+    #[test]
+    fn test_parse_capabilities_without_spaces() {
+        let caps = parse_capabilities("x", "prot(monitor)vcp(021012D6(0104))");
+        let codes: Vec<u8> = caps.features.iter().map(|f| f.code).collect();
+        assert_eq!(codes, [0x02, 0x10, 0x12, 0xD6]);
+        assert_eq!(caps.features[3].values, [0x01, 0x04]);
+        assert!(caps.brightness && !caps.input_switching);
+        assert_eq!(caps.model, None);
+    }
+}
@@ -0,0 +1,224 @@
+//! autorandr profile conversion.
+//!
+//! autorandr keeps each profile in a directory (`~/.config/autorandr/<name>/`)
+//! with two files. `setup` lists the connected outputs with their EDID as hex,
+//! the fingerprint autorandr matches against (shell patterns such as `*` are
+//! allowed). `config` holds the xrandr settings of each output in blocks of
+//! `output <name>` followed by `off` or `mode`, `pos`, `rate`, `rotate`,
+//! `primary`, ... lines.
+//! Single responsibility: convert between output configurations and autorandr profiles.
+
+use super::types::OutputConfig;
+use super::Rotation;
+
+// ============================================================================
+// Export
+// ============================================================================
+
+/// Build the `config` file for `outputs`.
+pub fn build_config(outputs: &[OutputConfig]) -> String {
+    let mut config = String::new();
+
+    for output in outputs {
+        config.push_str(&format!("output {}\n", output.name));
+        if !output.enabled {
+            config.push_str("off\n");
+            continue;
+        }
+
+        config.push_str(&format!("mode {}x{}\n", output.width, output.height));
+        config.push_str(&format!("pos {}x{}\n", output.pos_x, output.pos_y));
+        if output.primary {
+            config.push_str("primary\n");
+        }
+        if output.refresh_rate > 0.0 {
+            config.push_str(&format!("rate {:.2}\n", output.refresh_rate));
+        }
+        config.push_str(&format!("rotate {}\n", output.rotation.to_xrandr_arg()));
+        if (output.scale - 1.0).abs() > 0.01 {
+            config.push_str(&format!("scale {}x{}\n", output.scale, output.scale));
+        }
+        if let Some(brightness) = output.brightness {
+            config.push_str(&format!("brightness {:.2}\n", brightness));
+        }
+        if let Some([r, g, b]) = output.gamma {
+            config.push_str(&format!("gamma {:.2}:{:.2}:{:.2}\n", r, g, b));
+        }
+    }
+
+    config
+}
+
+/// Build the `setup` file from output names and their EDIDs. Outputs without
+/// a readable EDID match any monitor.
+pub fn build_setup(fingerprints: &[(String, Option<Vec<u8>>)]) -> String {
+    fingerprints
+        .iter()
+        .map(|(name, edid)| match edid {
+            Some(edid) => format!("{} {}\n", name, to_hex(edid)),
+            None => format!("{} *\n", name),
+        })
+        .collect()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// ============================================================================
+// Import
+// ============================================================================
+
+/// Parse a `config` file. Settings this app doesn't model (crtc, panning,
+/// transform matrices, output properties) are ignored.
+pub fn parse_config(text: &str) -> Result<Vec<OutputConfig>, String> {
+    let mut outputs: Vec<OutputConfig> = Vec::new();
+
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+        let (key, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let value = value.trim();
+
+        if key == "output" {
+            outputs.push(OutputConfig {
+                name: value.to_string(),
+                enabled: true,
+                // autorandr leaves the refresh rate to xrandr when it isn't given
+                refresh_rate: 0.0,
+                ..Default::default()
+            });
+            continue;
+        }
+
+        let Some(output) = outputs.last_mut() else {
+            return Err(format!("Unexpected '{}' before the first output", key));
+        };
+        match key {
+            "off" => output.enabled = false,
+            "primary" => output.primary = true,
+            "mode" => {
+                let (width, height) = parse_pair(value, 'x').ok_or(format!("Invalid mode '{}'", value))?;
+                output.width = width as u32;
+                output.height = height as u32;
+            }
+            "pos" => {
+                let (x, y) = parse_pair(value, 'x').ok_or(format!("Invalid position '{}'", value))?;
+                output.pos_x = x as i32;
+                output.pos_y = y as i32;
+            }
+            "rate" => output.refresh_rate = value.parse().map_err(|_| format!("Invalid rate '{}'", value))?,
+            "rotate" => output.rotation = Rotation::from_xrandr(value),
+            "scale" => {
+                output.scale = parse_pair(value, 'x')
+                    .map(|(x, _)| x)
+                    .or_else(|| value.parse().ok())
+                    .ok_or(format!("Invalid scale '{}'", value))? as f32;
+            }
+            "brightness" => output.brightness = value.parse().ok(),
+            "gamma" => {
+                let parts: Vec<f32> = value.split(':').filter_map(|v| v.parse().ok()).collect();
+                if let [r, g, b] = parts[..] {
+                    output.gamma = Some([r, g, b]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if outputs.is_empty() {
+        return Err("The autorandr config has no outputs".to_string());
+    }
+    Ok(outputs)
+}
+
+/// Parse a `setup` file into output names and EDID fingerprints (hex or a pattern).
+pub fn parse_setup(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let (name, fingerprint) = line.trim().split_once(char::is_whitespace)?;
+            Some((name.to_string(), fingerprint.trim().to_lowercase()))
+        })
+        .collect()
+}
+
+/// Whether an EDID matches a fingerprint from a `setup` file.
+pub fn matches_fingerprint(fingerprint: &str, edid: &[u8]) -> bool {
+    fingerprint == "*" || fingerprint == to_hex(edid)
+}
+
+/// `<a><sep><b>` as two numbers.
+fn parse_pair(value: &str, sep: char) -> Option<(f64, f64)> {
+    let (a, b) = value.split_once(sep)?;
+    Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_round_trip() {
+        let outputs = vec![
+            OutputConfig {
+                name: "DP-1".to_string(),
+                enabled: true,
+                primary: true,
+                width: 2560,
+                height: 1440,
+                refresh_rate: 143.97,
+                rotation: Rotation::Left,
+                scale: 1.5,
+                gamma: Some([1.0, 0.9, 0.8]),
+                ..Default::default()
+            },
+            OutputConfig {
+                name: "HDMI-1".to_string(),
+                enabled: false,
+                ..Default::default()
+            },
+        ];
+
+        let config = build_config(&outputs);
+        assert_eq!(
+            config,
+            "output DP-1\nmode 2560x1440\npos 0x0\nprimary\nrate 143.97\nrotate left\nscale 1.5x1.5\ngamma 1.00:0.90:0.80\n\
+             output HDMI-1\noff\n"
+        );
+
+        let parsed = parse_config(&config).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!((parsed[0].width, parsed[0].height, parsed[0].refresh_rate), (2560, 1440, 143.97));
+        assert_eq!((parsed[0].rotation, parsed[0].scale, parsed[0].primary), (Rotation::Left, 1.5, true));
+        assert_eq!(parsed[0].gamma, Some([1.0, 0.9, 0.8]));
+        assert!(!parsed[1].enabled);
+    }
+
+    #[test]
+    fn test_parse_autorandr_written_config() {
+        let config = "output eDP-1\ncrtc 0\nmode 1920x1080\npos 0x0\nprimary\nrate 60.02\n\
+                      x-prop-broadcast_rgb Automatic\nx-prop-non_desktop 0\n\
+                      output DP-2\ncrtc 1\nmode 3840x2160\npos 1920x0\nscale 1x1\n";
+        let outputs = parse_config(config).unwrap();
+        assert_eq!(outputs[0].refresh_rate, 60.02);
+        assert_eq!((outputs[1].pos_x, outputs[1].scale, outputs[1].refresh_rate), (1920, 1.0, 0.0));
+
+        assert!(parse_config("mode 1920x1080\n").is_err());
+        assert!(parse_config("").is_err());
+    }
+
+    #[test]
+    fn test_setup() {
+        let edid = vec![0x00, 0xff, 0xab];
+        let setup = build_setup(&[("DP-1".to_string(), Some(edid.clone())), ("eDP-1".to_string(), None)]);
+        assert_eq!(setup, "DP-1 00ffab\neDP-1 *\n");
+
+        let parsed = parse_setup(&setup);
+        assert_eq!(parsed[0], ("DP-1".to_string(), "00ffab".to_string()));
+        assert!(matches_fingerprint(&parsed[0].1, &edid));
+        assert!(matches_fingerprint(&parsed[1].1, &[0x12]));
+        assert!(!matches_fingerprint(&parsed[0].1, &[0x12]));
+    }
+}
@@ -0,0 +1,67 @@
+//! Brightness of the built-in panel, through the kernel backlight interface.
+//!
+//! Single responsibility: list the outputs to dim and set the backlight level.
+
+use super::super::{simulate, BrightnessTarget};
+use super::{get_display_settings, INTERNAL_OUTPUT_PREFIXES};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const BACKLIGHT_DIR: &str = "/sys/class/backlight";
+
+/// The enabled outputs, with the built-in panels marked.
+pub fn brightness_targets() -> Result<Vec<BrightnessTarget>, String> {
+    Ok(get_display_settings(true)?
+        .outputs
+        .into_iter()
+        .filter(|output| !output.non_desktop)
+        .map(|output| BrightnessTarget {
+            internal: INTERNAL_OUTPUT_PREFIXES.iter().any(|p| output.name.starts_with(p)),
+            monitor_id: output.name.clone(),
+            name: output.name,
+        })
+        .collect())
+}
+
+/// Set the backlight of the built-in panel. The sysfs file is usually only
+/// writable by root, so brightnessctl (setuid or udev rules) is tried next.
+pub fn set_panel_brightness(percent: u8) -> Result<(), String> {
+    if simulate::is_simulating() {
+        return Err("Brightness can't be set on simulated displays".to_string());
+    }
+
+    let percent = percent.min(100);
+    let sysfs_error = match write_backlight(percent) {
+        Ok(()) => return Ok(()),
+        Err(e) => e,
+    };
+
+    match Command::new("brightnessctl").args(["--quiet", "set", &format!("{}%", percent)]).status() {
+        Ok(status) if status.success() => Ok(()),
+        _ => Err(format!("{} (and brightnessctl isn't available)", sysfs_error)),
+    }
+}
+
+/// Write the level to the first backlight device.
+fn write_backlight(percent: u8) -> Result<(), String> {
+    let device = fs::read_dir(BACKLIGHT_DIR)
+        .map_err(|e| format!("No backlight control: {}", e))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .min()
+        .ok_or("No backlight control")?;
+
+    let maximum: u32 = read_number(&device.join("max_brightness"))?;
+    let level = (u32::from(percent) * maximum + 50) / 100;
+    fs::write(device.join("brightness"), level.to_string())
+        .map_err(|e| format!("Failed to write {}: {}", device.display(), e))
+}
+
+fn read_number(path: &Path) -> Result<u32, String> {
+    fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        .trim()
+        .parse()
+        .map_err(|e| format!("Invalid number in {}: {}", path.display(), e))
+}
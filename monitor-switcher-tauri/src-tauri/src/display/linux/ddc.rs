@@ -0,0 +1,142 @@
+//! DDC/CI access through ddcutil.
+//!
+//! Single responsibility: find the I2C bus of an output and run ddcutil on it.
+
+use super::super::simulate;
+use std::process::Command;
+
+/// Read the MCCS capabilities string of a connected monitor.
+pub fn read_capabilities_string(output_name: &str) -> Result<String, String> {
+    let bus = find_bus(output_name)?;
+    let output = run_ddcutil(&["--bus", &bus.to_string(), "capabilities", "--verbose"])?;
+    parse_unparsed_capabilities(&output)
+        .ok_or_else(|| format!("{} didn't report its DDC/CI capabilities", output_name))
+}
+
+/// Read a VCP feature: (current value, maximum value).
+pub fn read_vcp(output_name: &str, code: u8) -> Result<(u32, u32), String> {
+    let bus = find_bus(output_name)?;
+    let output = run_ddcutil(&["--bus", &bus.to_string(), "getvcp", &format!("0x{:02x}", code), "--brief"])?;
+    parse_getvcp_brief(&output).ok_or_else(|| format!("Failed to read VCP feature 0x{:02X}", code))
+}
+
+/// Set a VCP feature.
+pub fn write_vcp(output_name: &str, code: u8, value: u32) -> Result<(), String> {
+    let bus = find_bus(output_name)?;
+    run_ddcutil(&["--bus", &bus.to_string(), "setvcp", &format!("0x{:02x}", code), &value.to_string()])?;
+    Ok(())
+}
+
+/// Run ddcutil and return its standard output.
+pub(super) fn run_ddcutil(args: &[&str]) -> Result<String, String> {
+    if simulate::is_simulating() {
+        return Err("DDC/CI isn't available for simulated displays".to_string());
+    }
+
+    let output = Command::new("ddcutil")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run ddcutil (is it installed?): {}", e))?;
+    if !output.status.success() {
+        return Err(format!("ddcutil failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// I2C bus number of the monitor on an output.
+pub(super) fn find_bus(output_name: &str) -> Result<u32, String> {
+    let detected = run_ddcutil(&["detect", "--terse"])?;
+    parse_detect_bus(&detected, output_name)
+        .ok_or_else(|| format!("No DDC/CI monitor found on {}", output_name))
+}
+
+/// Find the bus of an output in `ddcutil detect --terse` output:
+///
+/// ```text
+/// Display 1
+///    I2C bus:  /dev/i2c-5
+///    DRM connector:    card1-DP-1
+/// ```
+fn parse_detect_bus(detected: &str, output_name: &str) -> Option<u32> {
+    let wanted = normalize_connector(output_name);
+    let mut bus = None;
+
+    for line in detected.lines().map(str::trim) {
+        if let Some(device) = line.strip_prefix("I2C bus:") {
+            bus = device.trim().strip_prefix("/dev/i2c-").and_then(|n| n.parse().ok());
+        } else if let Some(connector) = line.strip_prefix("DRM connector:") {
+            if normalize_connector(connector.trim()) == wanted {
+                return bus;
+            }
+        }
+    }
+    None
+}
+
+/// Connector name in a form shared by DRM and xrandr: "card1-HDMI-A-1" and
+/// "HDMI-1" both become "hdmi1".
+fn normalize_connector(name: &str) -> String {
+    let name = name.to_lowercase();
+    let name = match name.split_once('-') {
+        Some((card, rest)) if card.starts_with("card") => rest.to_string(),
+        _ => name,
+    };
+    name.replace("hdmi-a-", "hdmi-").replace('-', "")
+}
+
+/// Parse `ddcutil getvcp --brief` output:
+///
+/// ```text
+/// VCP 10 C 50 100            (continuous: current, maximum)
+/// VCP 60 SNC x0f             (simple non-continuous)
+/// VCP 14 CNC x00 x0b x00 x05 (complex non-continuous: mh ml sh sl)
+/// ```
+fn parse_getvcp_brief(output: &str) -> Option<(u32, u32)> {
+    let line = output.lines().map(str::trim).find(|l| l.starts_with("VCP "))?;
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let hex = |field: &str| u32::from_str_radix(field.trim_start_matches('x'), 16).ok();
+
+    match *fields.get(2)? {
+        "C" => Some((fields.get(3)?.parse().ok()?, fields.get(4)?.parse().ok()?)),
+        "SNC" => Some((hex(fields.get(3)?)?, 0)),
+        "CNC" => {
+            let bytes = fields[3..].iter().map(|f| hex(f)).collect::<Option<Vec<_>>>()?;
+            match bytes[..] {
+                [mh, ml, sh, sl] => Some(((sh << 8) | sl, (mh << 8) | ml)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The raw string from `ddcutil capabilities --verbose`.
+fn parse_unparsed_capabilities(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Unparsed capabilities string:"))
+        .map(|caps| caps.trim().to_string())
+        .filter(|caps| !caps.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ddcutil_output() {
+        let detected = "Display 1\n   I2C bus:  /dev/i2c-4\n   DRM connector:    card1-eDP-1\n\nDisplay 2\n   I2C bus:  /dev/i2c-7\n   DRM connector:    card1-HDMI-A-1\n   Monitor:   DEL:DELL U2720Q:ABC\n";
+        assert_eq!(parse_detect_bus(detected, "HDMI-1"), Some(7));
+        assert_eq!(parse_detect_bus(detected, "HDMI-A-1"), Some(7));
+        assert_eq!(parse_detect_bus(detected, "eDP-1"), Some(4));
+        assert_eq!(parse_detect_bus(detected, "DP-2"), None);
+
+        let caps = "Model: U2720Q\n   Unparsed capabilities string: (prot(monitor)vcp(10 12))\n   VCP version: 2.1\n";
+        assert_eq!(parse_unparsed_capabilities(caps).as_deref(), Some("(prot(monitor)vcp(10 12))"));
+
+        assert_eq!(parse_getvcp_brief("VCP 10 C 50 100\n"), Some((50, 100)));
+        assert_eq!(parse_getvcp_brief("VCP 60 SNC x0f\n"), Some((0x0F, 0)));
+        assert_eq!(parse_getvcp_brief("VCP 14 CNC x00 x0b x00 x05\n"), Some((0x05, 0x0B)));
+        assert_eq!(parse_getvcp_brief("VCP 10 ERR\n"), None);
+    }
+}
@@ -20,6 +20,8 @@ pub struct EdidData {
     pub product_code: u16,
     /// Monitor name from EDID descriptor
     pub monitor_name: String,
+    /// Serial number descriptor, or the numeric serial as "0x%08x" without one
+    pub serial: String,
     /// Path to the DRM connector
     pub connector_path: String,
 }
@@ -31,6 +33,20 @@ pub struct EdidData {
 /// Read EDID data for a given output name.
 pub fn read_edid(output_name: &str) -> Result<EdidData, String> {
     let connector_path = find_drm_connector(output_name)?;
+    let edid_bytes = read_connector_edid(&connector_path)?;
+
+    let mut data = parse_edid_bytes(&edid_bytes);
+    data.connector_path = connector_path.to_string_lossy().to_string();
+
+    Ok(data)
+}
+
+/// Read the raw EDID of a given output name, including extension blocks.
+pub fn read_edid_bytes(output_name: &str) -> Result<Vec<u8>, String> {
+    read_connector_edid(&find_drm_connector(output_name)?)
+}
+
+fn read_connector_edid(connector_path: &std::path::Path) -> Result<Vec<u8>, String> {
     let edid_path = connector_path.join("edid");
 
     let edid_bytes = fs::read(&edid_path)
@@ -40,10 +56,7 @@ pub fn read_edid(output_name: &str) -> Result<EdidData, String> {
         return Err("EDID data too short".to_string());
     }
 
-    let mut data = parse_edid_bytes(&edid_bytes);
-    data.connector_path = connector_path.to_string_lossy().to_string();
-
-    Ok(data)
+    Ok(edid_bytes)
 }
 
 /// Find the DRM connector path for an output name.
@@ -94,6 +107,17 @@ pub fn find_drm_connector(output_name: &str) -> Result<PathBuf, String> {
     Err(format!("Could not find DRM connector for output: {}", output_name))
 }
 
+/// Whether two output names refer to the same connector, e.g. xrandr's
+/// "HDMI-1" and the DRM (Wayland) name "HDMI-A-1".
+pub fn same_connector(a: &str, b: &str) -> bool {
+    normalize_output_name(a) == normalize_output_name(b)
+}
+
+/// DRM name of an output, as Wayland compositors call it.
+pub fn drm_connector_name(output_name: &str) -> String {
+    convert_output_to_drm_name(output_name)
+}
+
 /// Convert xrandr output name to DRM connector name format.
 fn convert_output_to_drm_name(output_name: &str) -> String {
     // HDMI-1 -> HDMI-A-1 (DRM uses HDMI-A, HDMI-B, etc.)
@@ -159,23 +183,30 @@ fn parse_edid_bytes(bytes: &[u8]) -> EdidData {
     // Product code is at bytes 10-11 (little-endian)
     data.product_code = (bytes[10] as u16) | ((bytes[11] as u16) << 8);
 
-    // Monitor name is in the descriptor blocks (bytes 54-125)
+    // Monitor name and serial are in the descriptor blocks (bytes 54-125)
     // Each descriptor is 18 bytes, starting at byte 54
     for i in 0..4 {
         let offset = 54 + i * 18;
         if offset + 18 <= bytes.len() {
             let descriptor = &bytes[offset..offset + 18];
+            if descriptor[0] != 0 || descriptor[1] != 0 || descriptor[2] != 0 {
+                continue;
+            }
 
-            // Check if this is a monitor name descriptor (tag 0xFC)
-            if descriptor[0] == 0 && descriptor[1] == 0 && descriptor[2] == 0 && descriptor[3] == 0xFC {
-                // Name is in bytes 5-17
-                let name_bytes = &descriptor[5..18];
-                data.monitor_name = parse_edid_string(name_bytes);
-                break;
+            // Monitor name (tag 0xFC) and serial number (tag 0xFF) are in bytes 5-17
+            match descriptor[3] {
+                0xFC if data.monitor_name.is_empty() => data.monitor_name = parse_edid_string(&descriptor[5..18]),
+                0xFF if data.serial.is_empty() => data.serial = parse_edid_string(&descriptor[5..18]),
+                _ => {}
             }
         }
     }
 
+    // Without a serial descriptor, the numeric serial at bytes 12-15 (little-endian)
+    if data.serial.is_empty() {
+        data.serial = format!("0x{:08x}", u32::from_le_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]));
+    }
+
     data
 }
 
@@ -245,6 +276,17 @@ mod tests {
         assert_eq!(normalize_drm_name("card1-eDP-1"), "edp1");
     }
 
+    #[test]
+    fn test_parse_serial() {
+        let mut edid = [0u8; 128];
+        edid[12..16].copy_from_slice(&0x0102_0304u32.to_le_bytes());
+        assert_eq!(parse_edid_bytes(&edid).serial, "0x01020304");
+
+        edid[54 + 18..54 + 18 + 5].copy_from_slice(&[0, 0, 0, 0xFF, 0]);
+        edid[54 + 18 + 5..54 + 18 + 12].copy_from_slice(b"ABC123\n");
+        assert_eq!(parse_edid_bytes(&edid).serial, "ABC123");
+    }
+
     #[test]
     fn test_convert_output_to_drm_name() {
         assert_eq!(convert_output_to_drm_name("HDMI-1"), "HDMI-A-1");
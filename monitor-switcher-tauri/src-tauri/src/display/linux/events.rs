@@ -0,0 +1,78 @@
+//! RandR change notifications via x11rb.
+//!
+//! Single responsibility: tell the application when the X server's output layout
+//! changes, whether the change came from us, another tool or the desktop environment.
+
+use std::thread;
+use std::time::Duration;
+use x11rb::connection::Connection;
+use x11rb::protocol::randr::{ConnectionExt as _, NotifyMask};
+use x11rb::protocol::Event;
+
+/// Time to wait for a burst of RandR events to settle before notifying.
+/// A single layout change usually produces several screen/output/CRTC events.
+const SETTLE_DELAY: Duration = Duration::from_millis(250);
+
+// ============================================================================
+// Event Subscription
+// ============================================================================
+
+/// Subscribe to RandR screen and output change events.
+///
+/// Spawns a background thread that owns its own X connection and calls
+/// `on_change` once per burst of layout changes. Returns an error if the X server
+/// (or its RandR extension) is unavailable, e.g. on a pure Wayland session.
+pub fn watch_display_changes<F>(on_change: F) -> Result<(), String>
+where
+    F: Fn() + Send + 'static,
+{
+    let (conn, _) = x11rb::connect(None)
+        .map_err(|e| format!("Failed to connect to X server: {}", e))?;
+
+    // RandR requires the client to announce its version before selecting input
+    conn.randr_query_version(1, 2)
+        .map_err(|e| format!("Failed to query RandR version: {}", e))?
+        .reply()
+        .map_err(|e| format!("RandR extension not available: {}", e))?;
+
+    // Select on every screen's root window so multi-screen setups are covered too
+    let roots: Vec<_> = conn.setup().roots.iter().map(|screen| screen.root).collect();
+    let mask = NotifyMask::SCREEN_CHANGE | NotifyMask::OUTPUT_CHANGE | NotifyMask::CRTC_CHANGE;
+
+    for root in roots {
+        conn.randr_select_input(root, mask)
+            .map_err(|e| format!("Failed to subscribe to RandR events: {}", e))?;
+    }
+    conn.flush()
+        .map_err(|e| format!("Failed to flush X connection: {}", e))?;
+
+    thread::Builder::new()
+        .name("randr-events".to_string())
+        .spawn(move || loop {
+            let event = match conn.wait_for_event() {
+                Ok(event) => event,
+                Err(e) => {
+                    log::warn!("RandR event loop stopped: {}", e);
+                    return;
+                }
+            };
+
+            if !is_layout_event(&event) {
+                continue;
+            }
+
+            // Let the burst settle, then drain whatever else arrived
+            thread::sleep(SETTLE_DELAY);
+            while let Ok(Some(_)) = conn.poll_for_event() {}
+
+            on_change();
+        })
+        .map_err(|e| format!("Failed to spawn RandR event thread: {}", e))?;
+
+    Ok(())
+}
+
+/// Whether an X event signals a change to the display layout.
+fn is_layout_event(event: &Event) -> bool {
+    matches!(event, Event::RandrScreenChangeNotify(_) | Event::RandrNotify(_))
+}
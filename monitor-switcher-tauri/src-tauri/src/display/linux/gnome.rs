@@ -0,0 +1,347 @@
+//! GNOME `monitors.xml` conversion.
+//!
+//! GNOME (Mutter) keeps its layouts in `~/.config/monitors.xml`, one
+//! `<configuration>` per set of connected monitors; GDM reads the same format
+//! from its own home directory for the login screen. Monitors are identified
+//! by connector plus the EDID vendor, product and serial, and all four must
+//! match for Mutter to use a configuration. Positions are written in physical
+//! pixels, Mutter's default layout mode.
+//! Single responsibility: convert between output configurations and monitors.xml.
+
+use super::edid;
+use super::types::OutputConfig;
+use super::Rotation;
+
+/// How Mutter identifies a monitor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorSpec {
+    pub connector: String,
+    pub vendor: String,
+    pub product: String,
+    pub serial: String,
+}
+
+impl MonitorSpec {
+    /// Read the identity of a connected output from its EDID, the way Mutter
+    /// derives it. None if the EDID can't be read.
+    pub fn read(output_name: &str) -> Option<Self> {
+        let edid = edid::read_edid(output_name).ok()?;
+        Some(Self {
+            connector: edid::drm_connector_name(output_name),
+            vendor: edid.manufacturer,
+            product: match edid.monitor_name.is_empty() {
+                true => format!("0x{:04x}", edid.product_code),
+                false => edid.monitor_name,
+            },
+            serial: edid.serial,
+        })
+    }
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+/// Build a monitors.xml with one configuration for `outputs`. Outputs whose
+/// monitor can't be identified are left out, since Mutter couldn't match them.
+pub fn build_monitors_xml(outputs: &[(&OutputConfig, MonitorSpec)]) -> String {
+    let mut xml = String::from("<monitors version=\"2\">\n  <configuration>\n");
+
+    // Outputs at the same position with the same mode mirror each other
+    let enabled: Vec<&(&OutputConfig, MonitorSpec)> = outputs.iter().filter(|(o, _)| o.enabled).collect();
+    let mut groups: Vec<Vec<&(&OutputConfig, MonitorSpec)>> = Vec::new();
+    for entry in enabled {
+        let output = entry.0;
+        match groups.iter_mut().find(|g| {
+            let first = g[0].0;
+            (first.pos_x, first.pos_y, first.width, first.height) == (output.pos_x, output.pos_y, output.width, output.height)
+        }) {
+            Some(group) => group.push(entry),
+            None => groups.push(vec![entry]),
+        }
+    }
+
+    for group in groups {
+        let first = group[0].0;
+        xml.push_str("    <logicalmonitor>\n");
+        xml.push_str(&format!("      <x>{}</x>\n      <y>{}</y>\n", first.pos_x, first.pos_y));
+        xml.push_str(&format!("      <scale>{}</scale>\n", first.scale));
+        if group.iter().any(|(o, _)| o.primary) {
+            xml.push_str("      <primary>yes</primary>\n");
+        }
+        if first.rotation != Rotation::Normal {
+            xml.push_str(&format!(
+                "      <transform>\n        <rotation>{}</rotation>\n        <flipped>no</flipped>\n      </transform>\n",
+                rotation_name(first.rotation)
+            ));
+        }
+        for (output, spec) in group {
+            xml.push_str("      <monitor>\n");
+            push_monitor_spec(&mut xml, spec, "        ");
+            xml.push_str(&format!(
+                "        <mode>\n          <width>{}</width>\n          <height>{}</height>\n          <rate>{:.3}</rate>\n        </mode>\n",
+                output.width, output.height, output.refresh_rate
+            ));
+            xml.push_str("      </monitor>\n");
+        }
+        xml.push_str("    </logicalmonitor>\n");
+    }
+
+    let disabled: Vec<&MonitorSpec> = outputs.iter().filter(|(o, _)| !o.enabled).map(|(_, spec)| spec).collect();
+    if !disabled.is_empty() {
+        xml.push_str("    <disabled>\n");
+        for spec in disabled {
+            push_monitor_spec(&mut xml, spec, "      ");
+        }
+        xml.push_str("    </disabled>\n");
+    }
+
+    xml.push_str("  </configuration>\n</monitors>\n");
+    xml
+}
+
+fn push_monitor_spec(xml: &mut String, spec: &MonitorSpec, indent: &str) {
+    xml.push_str(&format!("{}<monitorspec>\n", indent));
+    for (tag, value) in [
+        ("connector", &spec.connector),
+        ("vendor", &spec.vendor),
+        ("product", &spec.product),
+        ("serial", &spec.serial),
+    ] {
+        xml.push_str(&format!("{}  <{}>{}</{}>\n", indent, tag, escape(value), tag));
+    }
+    xml.push_str(&format!("{}</monitorspec>\n", indent));
+}
+
+/// Mutter's name for a rotation. Like xrandr, "left" is counter-clockwise.
+fn rotation_name(rotation: Rotation) -> &'static str {
+    match rotation {
+        Rotation::Normal => "normal",
+        Rotation::Left => "left",
+        Rotation::Right => "right",
+        Rotation::Inverted => "upside_down",
+    }
+}
+
+// ============================================================================
+// Import
+// ============================================================================
+
+/// Parse the configurations of a monitors.xml. Outputs are named by the
+/// connectors in the file.
+pub fn parse_monitors_xml(xml: &str) -> Result<Vec<Vec<OutputConfig>>, String> {
+    let root = parse_xml(xml)?;
+    if root.name != "monitors" {
+        return Err("Not a GNOME monitors.xml file".to_string());
+    }
+
+    Ok(root
+        .children("configuration")
+        .map(|configuration| {
+            let mut outputs = Vec::new();
+            for logical in configuration.children("logicalmonitor") {
+                let number = |tag: &str| logical.child_text(tag).and_then(|v| v.parse::<f64>().ok());
+                let rotation = logical
+                    .child("transform")
+                    .and_then(|t| t.child_text("rotation"))
+                    .map(|r| match r {
+                        "left" => Rotation::Left,
+                        "right" => Rotation::Right,
+                        "upside_down" => Rotation::Inverted,
+                        _ => Rotation::Normal,
+                    })
+                    .unwrap_or_default();
+
+                for monitor in logical.children("monitor") {
+                    let Some(connector) = monitor.child("monitorspec").and_then(|s| s.child_text("connector")) else {
+                        continue;
+                    };
+                    let mode = monitor.child("mode");
+                    let mode_number = |tag: &str| mode.and_then(|m| m.child_text(tag)).and_then(|v| v.parse::<f64>().ok());
+
+                    outputs.push(OutputConfig {
+                        name: connector.to_string(),
+                        enabled: true,
+                        primary: logical.child_text("primary") == Some("yes"),
+                        width: mode_number("width").unwrap_or(0.0) as u32,
+                        height: mode_number("height").unwrap_or(0.0) as u32,
+                        refresh_rate: mode_number("rate").unwrap_or(60.0) as f32,
+                        pos_x: number("x").unwrap_or(0.0) as i32,
+                        pos_y: number("y").unwrap_or(0.0) as i32,
+                        rotation,
+                        scale: number("scale").unwrap_or(1.0) as f32,
+                        ..Default::default()
+                    });
+                }
+            }
+
+            for disabled in configuration.children("disabled") {
+                for spec in disabled.children("monitorspec") {
+                    if let Some(connector) = spec.child_text("connector") {
+                        outputs.push(OutputConfig {
+                            name: connector.to_string(),
+                            enabled: false,
+                            ..Default::default()
+                        });
+                    }
+                }
+            }
+            outputs
+        })
+        .filter(|outputs: &Vec<OutputConfig>| !outputs.is_empty())
+        .collect())
+}
+
+// ============================================================================
+// XML
+// ============================================================================
+
+/// An XML element; only what monitors.xml uses (no attributes, no mixed content).
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    text: String,
+    children: Vec<Element>,
+}
+
+impl Element {
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|c| c.text.trim())
+    }
+}
+
+/// Parse an XML document into its root element. Attributes, comments and the
+/// XML declaration are skipped.
+fn parse_xml(xml: &str) -> Result<Element, String> {
+    let mut stack: Vec<Element> = vec![Element::default()];
+    let mut rest = xml;
+
+    while let Some(start) = rest.find('<') {
+        stack.last_mut().unwrap().text.push_str(&unescape(&rest[..start]));
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let end = comment.find("-->").ok_or("Unterminated XML comment")?;
+            rest = &comment[end + 3..];
+            continue;
+        }
+
+        let end = rest.find('>').ok_or("Unterminated XML tag")?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            let element = stack.pop().filter(|_| !stack.is_empty()).ok_or("Unexpected closing XML tag")?;
+            if element.name != name.trim() || stack.is_empty() {
+                return Err(format!("Mismatched XML tag </{}>", name.trim()));
+            }
+            stack.last_mut().unwrap().children.push(element);
+            continue;
+        }
+
+        let self_closing = tag.ends_with('/');
+        let name = tag.trim_end_matches('/').split_whitespace().next().unwrap_or_default();
+        let element = Element { name: name.to_string(), ..Default::default() };
+        if self_closing {
+            stack.last_mut().unwrap().children.push(element);
+        } else {
+            stack.push(element);
+        }
+    }
+
+    if stack.len() != 1 {
+        return Err("Unterminated XML element".to_string());
+    }
+    stack.pop().unwrap().children.into_iter().next().ok_or_else(|| "Empty XML document".to_string())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(connector: &str) -> MonitorSpec {
+        MonitorSpec {
+            connector: connector.to_string(),
+            vendor: "DEL".to_string(),
+            product: "DELL U2720Q".to_string(),
+            serial: "A&B".to_string(),
+        }
+    }
+
+    fn output(name: &str, pos_x: i32) -> OutputConfig {
+        OutputConfig {
+            name: name.to_string(),
+            enabled: true,
+            width: 3840,
+            height: 2160,
+            refresh_rate: 59.997,
+            pos_x,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut left = output("DP-1", 0);
+        left.primary = true;
+        let mut right = output("HDMI-A-1", 3840);
+        right.rotation = Rotation::Inverted;
+        let mut off = output("eDP-1", 0);
+        off.enabled = false;
+
+        let xml = build_monitors_xml(&[(&left, spec("DP-1")), (&right, spec("HDMI-A-1")), (&off, spec("eDP-1"))]);
+        assert!(xml.contains("<serial>A&amp;B</serial>"));
+        assert!(xml.contains("<rotation>upside_down</rotation>"));
+
+        let configurations = parse_monitors_xml(&format!("<?xml version=\"1.0\"?>\n<!-- saved -->\n{}", xml)).unwrap();
+        assert_eq!(configurations.len(), 1);
+        let outputs = &configurations[0];
+
+        assert_eq!(outputs.len(), 3);
+        assert_eq!((outputs[0].name.as_str(), outputs[0].primary, outputs[0].refresh_rate), ("DP-1", true, 59.997));
+        assert_eq!((outputs[1].pos_x, outputs[1].rotation), (3840, Rotation::Inverted));
+        assert_eq!((outputs[2].name.as_str(), outputs[2].enabled), ("eDP-1", false));
+    }
+
+    #[test]
+    fn test_mirrored_outputs_share_a_logical_monitor() {
+        let a = output("DP-1", 0);
+        let b = output("DP-2", 0);
+        let xml = build_monitors_xml(&[(&a, spec("DP-1")), (&b, spec("DP-2"))]);
+        assert_eq!(xml.matches("<logicalmonitor>").count(), 1);
+        assert_eq!(xml.matches("<monitor>").count(), 2);
+    }
+
+    #[test]
+    fn test_rejects_other_xml() {
+        assert!(parse_monitors_xml("<profiles></profiles>").is_err());
+        assert!(parse_monitors_xml("<monitors><configuration>").is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! KDE kscreen configuration import.
+//!
+//! Plasma's kscreen daemon stores one JSON file per set of connected monitors
+//! in `~/.local/share/kscreen/`, named after a hash of their EDIDs. Each file
+//! is an array with one entry per output: connector name, mode, position,
+//! rotation and scale, and which output is primary (`primary` up to Plasma 5,
+//! `priority` 1 since Plasma 6). Per-output files in subdirectories like
+//! `outputs/` and `control/` hold extra settings and are not layouts.
+//! Single responsibility: convert kscreen configurations to output configurations.
+
+use super::types::OutputConfig;
+use super::Rotation;
+use serde::Deserialize;
+
+/// An output in a kscreen configuration file.
+#[derive(Debug, Deserialize)]
+struct KscreenOutput {
+    #[serde(default)]
+    enabled: bool,
+    metadata: Metadata,
+    mode: Option<Mode>,
+    pos: Option<Position>,
+    #[serde(default)]
+    primary: bool,
+    priority: Option<u32>,
+    /// KScreen rotation flag: 1 none, 2 left, 4 inverted, 8 right
+    rotation: Option<u32>,
+    scale: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Metadata {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Mode {
+    refresh: f32,
+    size: Size,
+}
+
+#[derive(Debug, Deserialize)]
+struct Size {
+    width: u32,
+    height: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct Position {
+    x: i32,
+    y: i32,
+}
+
+/// Parse a kscreen configuration file. Outputs are named by the connectors in
+/// the file.
+pub fn parse_config(json: &str) -> Result<Vec<OutputConfig>, String> {
+    let outputs: Vec<KscreenOutput> = serde_json::from_str(json)
+        .map_err(|e| format!("Not a kscreen configuration: {}", e))?;
+
+    Ok(outputs
+        .into_iter()
+        .map(|output| {
+            let mut config = OutputConfig {
+                name: output.metadata.name,
+                enabled: output.enabled,
+                primary: output.enabled && (output.primary || output.priority == Some(1)),
+                rotation: match output.rotation {
+                    Some(2) => Rotation::Left,
+                    Some(4) => Rotation::Inverted,
+                    Some(8) => Rotation::Right,
+                    _ => Rotation::Normal,
+                },
+                scale: output.scale.unwrap_or(1.0),
+                ..Default::default()
+            };
+            if let Some(mode) = output.mode.filter(|_| output.enabled) {
+                config.width = mode.size.width;
+                config.height = mode.size.height;
+                config.refresh_rate = mode.refresh;
+            }
+            if let Some(pos) = output.pos {
+                config.pos_x = pos.x;
+                config.pos_y = pos.y;
+            }
+            config
+        })
+        .collect())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let json = r#"[
+            {
+                "enabled": true, "id": "a1b2c3",
+                "metadata": {"fullname": "xrandr-Dell Inc.-DELL U2720Q-ABC123", "name": "DP-1"},
+                "mode": {"refresh": 59.997, "size": {"height": 2160, "width": 3840}},
+                "pos": {"x": 0, "y": 0}, "priority": 1, "rotation": 1, "scale": 1.5
+            },
+            {
+                "enabled": true, "id": "d4e5f6",
+                "metadata": {"fullname": "xrandr-Samsung-S24-XYZ", "name": "HDMI-A-1"},
+                "mode": {"refresh": 60, "size": {"height": 1080, "width": 1920}},
+                "pos": {"x": 2560, "y": 0}, "priority": 2, "rotation": 8, "scale": 1
+            },
+            {
+                "enabled": false, "id": "g7h8i9",
+                "metadata": {"fullname": "", "name": "eDP-1"},
+                "mode": {"refresh": 60, "size": {"height": 1200, "width": 1920}},
+                "pos": {"x": 0, "y": 0}, "primary": true, "rotation": 1
+            }
+        ]"#;
+
+        let outputs = parse_config(json).unwrap();
+        assert_eq!(outputs.len(), 3);
+
+        assert_eq!(outputs[0].name, "DP-1");
+        assert!(outputs[0].primary);
+        assert_eq!((outputs[0].width, outputs[0].height, outputs[0].refresh_rate), (3840, 2160, 59.997));
+        assert_eq!(outputs[0].scale, 1.5);
+
+        assert!(!outputs[1].primary);
+        assert_eq!((outputs[1].pos_x, outputs[1].rotation), (2560, Rotation::Right));
+
+        assert!(!outputs[2].enabled && !outputs[2].primary);
+        assert_eq!((outputs[2].width, outputs[2].height), (0, 0));
+    }
+
+    #[test]
+    fn test_rejects_other_json() {
+        assert!(parse_config(r#"{"outputs": []}"#).is_err());
+        assert!(parse_config(r#"[{"enabled": true}]"#).is_err());
+    }
+}
@@ -1,13 +1,31 @@
-//! Linux display management using XRandR.
+//! Linux display management using XRandR, or wlr-randr on wlroots compositors.
 //!
 //! This module is ONLY compiled on Linux.
 //! For Windows implementation, see `../windows/`.
 
+mod autorandr;
+mod brightness;
+mod ddc;
 mod edid;
+mod events;
+mod gnome;
+mod kscreen;
+mod nvidia;
+mod providers;
+mod templates;
 pub mod types;
+mod virtual_display;
+mod wlr_randr;
 mod xrandr;
 
+pub use brightness::{brightness_targets, set_panel_brightness};
+pub use ddc::{read_capabilities_string, read_vcp, write_vcp};
+pub use events::watch_display_changes;
+pub use providers::get_gpu_info;
 pub use types::{OutputConfig, Rotation};
+pub use virtual_display::{count_virtual_displays, set_virtual_display_count, set_virtual_displays, virtual_display_driver, write_evdi_device_count};
+
+use super::{describe_discrepancies, DisplayMode, DisplayState, MatchTier, RawEdid};
 
 // ============================================================================
 // Public Types
@@ -32,29 +50,380 @@ pub struct MonitorAdditionalInfo {
 
 /// Get the current display configuration.
 pub fn get_display_settings(active_only: bool) -> Result<DisplaySettings, String> {
-    let outputs = xrandr::query_outputs(active_only)?;
+    if let Some(mut settings) = super::simulate::current_settings() {
+        settings.outputs.retain(|o| o.enabled || !active_only);
+        return Ok(settings);
+    }
+
+    let outputs = if wlr_randr::is_available() {
+        wlr_randr::query_outputs(active_only)?
+    } else {
+        xrandr::query_outputs(active_only)?
+    };
     Ok(DisplaySettings { outputs })
 }
 
 /// Apply display settings.
+/// Falls back to an NVIDIA MetaMode when xrandr is rejected and nvidia-settings is available.
 pub fn set_display_settings(settings: &mut DisplaySettings) -> Result<(), String> {
-    xrandr::apply_configuration(&settings.outputs)
+    if super::simulate::apply(settings) {
+        return Ok(());
+    }
+    if wlr_randr::is_available() {
+        return wlr_randr::apply_configuration(&settings.outputs);
+    }
+
+    match xrandr::apply_configuration(&settings.outputs) {
+        Ok(()) => Ok(()),
+        Err(e) if nvidia::is_available() => {
+            log::warn!("xrandr apply failed ({}), retrying with nvidia-settings", e.trim());
+            nvidia::apply_metamode(&settings.outputs)
+                .map_err(|nv_err| format!("{}; NVIDIA fallback also failed: {}", e.trim(), nv_err))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Check whether xrandr (or the compositor) would accept the settings, without applying them.
+pub fn validate_display_settings(settings: &DisplaySettings) -> Result<(), String> {
+    if super::simulate::is_simulating() {
+        return Ok(());
+    }
+    if wlr_randr::is_available() {
+        return wlr_randr::validate_configuration(&settings.outputs);
+    }
+    xrandr::validate_configuration(&settings.outputs)
+}
+
+/// Build a standalone `apply.sh` that applies the settings with xrandr alone.
+pub fn export_xrandr_script(profile_name: &str, settings: &DisplaySettings) -> String {
+    xrandr::build_script(profile_name, &settings.outputs)
+}
+
+/// Build a GNOME `monitors.xml` with the settings as its only configuration.
+/// Monitors whose EDID can't be read are left out.
+pub fn export_gnome_monitors_xml(settings: &DisplaySettings) -> String {
+    let outputs: Vec<(&OutputConfig, gnome::MonitorSpec)> = settings
+        .outputs
+        .iter()
+        .filter_map(|o| gnome::MonitorSpec::read(&o.name).map(|spec| (o, spec)))
+        .collect();
+    gnome::build_monitors_xml(&outputs)
+}
+
+/// Read the configurations of a GNOME `monitors.xml`. Connectors are mapped to
+/// the names of the current outputs, so the result can be saved as profiles.
+pub fn import_gnome_monitors_xml(xml: &str) -> Result<Vec<DisplaySettings>, String> {
+    let current = get_display_settings(false)?;
+
+    Ok(gnome::parse_monitors_xml(xml)?
+        .into_iter()
+        .map(|outputs| DisplaySettings { outputs: with_current_names(outputs, &current.outputs) })
+        .collect())
+}
+
+/// Read a KDE kscreen configuration file, with connectors mapped to the names
+/// of the current outputs.
+pub fn import_kscreen_config(json: &str) -> Result<DisplaySettings, String> {
+    let current = get_display_settings(false)?;
+    let outputs = kscreen::parse_config(json)?;
+    Ok(DisplaySettings { outputs: with_current_names(outputs, &current.outputs) })
+}
+
+/// Build the `config` and `setup` files of an autorandr profile for the settings.
+pub fn export_autorandr_profile(settings: &DisplaySettings) -> (String, String) {
+    let fingerprints: Vec<(String, Option<Vec<u8>>)> = settings
+        .outputs
+        .iter()
+        .map(|o| (o.name.clone(), edid::read_edid_bytes(&o.name).ok()))
+        .collect();
+    (autorandr::build_config(&settings.outputs), autorandr::build_setup(&fingerprints))
+}
+
+/// Read an autorandr profile from its `config` and `setup` files. Outputs are
+/// renamed after the current output whose EDID matches their fingerprint, or
+/// else the one on the same connector.
+pub fn import_autorandr_profile(config: &str, setup: &str) -> Result<DisplaySettings, String> {
+    let current = get_display_settings(false)?;
+    let fingerprints = autorandr::parse_setup(setup);
+    let outputs = autorandr::parse_config(config)?;
+
+    let mut imported = Vec::new();
+    for output in outputs {
+        let fingerprint = fingerprints.iter().find(|(name, _)| *name == output.name).map(|(_, f)| f.as_str());
+        let known = fingerprint.filter(|f| *f != "*").and_then(|f| {
+            current.outputs.iter().find(|c| {
+                edid::read_edid_bytes(&c.name).is_ok_and(|edid| autorandr::matches_fingerprint(f, &edid))
+            })
+        });
+        match known {
+            Some(known) => imported.push(OutputConfig { name: known.name.clone(), ..output }),
+            None => imported.extend(with_current_names(vec![output], &current.outputs)),
+        }
+    }
+
+    Ok(DisplaySettings { outputs: imported })
+}
+
+/// Rename imported outputs after the current output on the same connector.
+/// Other desktops may call connectors by their DRM names.
+fn with_current_names(mut outputs: Vec<OutputConfig>, current: &[OutputConfig]) -> Vec<OutputConfig> {
+    for output in &mut outputs {
+        if let Some(known) = current.iter().find(|c| edid::same_connector(&c.name, &output.name)) {
+            output.name = known.name.clone();
+        }
+    }
+    outputs
 }
 
 /// Get additional monitor info for an output.
 pub fn get_monitor_additional_info(output_name: &str) -> MonitorAdditionalInfo {
     MonitorAdditionalInfo {
-        valid: edid::read_edid(output_name).is_ok(),
+        valid: super::simulate::is_simulating() || edid::read_edid(output_name).is_ok(),
     }
 }
 
+/// Check whether we are running inside a remote desktop (xrdp) session.
+/// The display topology there is virtual and unrelated to the physical monitors.
+pub fn is_remote_session() -> bool {
+    std::env::var_os("XRDP_SESSION").is_some()
+}
+
 /// Turn off all monitors using DPMS.
 pub fn turn_off_monitors() -> Result<(), String> {
     // Small delay to let user release mouse/keyboard
     std::thread::sleep(std::time::Duration::from_millis(500));
+    if super::simulate::is_simulating() {
+        return Ok(());
+    }
     xrandr::turn_off_displays()
 }
 
+/// Lock the session through logind.
+pub fn lock_session() -> Result<(), String> {
+    if super::simulate::is_simulating() {
+        return Ok(());
+    }
+
+    let status = std::process::Command::new("loginctl")
+        .arg("lock-session")
+        .status()
+        .map_err(|e| format!("Failed to run loginctl: {}", e))?;
+    if !status.success() {
+        return Err(format!("loginctl lock-session failed: {}", status));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Layout Templates
+// ============================================================================
+
+/// Switch to a template layout computed from the connected outputs.
+pub fn apply_template(template: super::Template) -> Result<(), String> {
+    let current = get_display_settings(false)?;
+    let mut settings = DisplaySettings {
+        outputs: templates::template_outputs(template, &current.outputs)?,
+    };
+    set_display_settings(&mut settings)
+}
+
+/// `settings` with only the primary output enabled. None when no other output
+/// is enabled.
+pub fn primary_only_layout(settings: &DisplaySettings) -> Option<DisplaySettings> {
+    Some(DisplaySettings {
+        outputs: templates::primary_only_outputs(&settings.outputs)?,
+    })
+}
+
+/// Whether the built-in panel is on. None on machines without one.
+pub fn get_internal_panel_state() -> Result<Option<bool>, String> {
+    let current = get_display_settings(false)?;
+    let mut internal = current
+        .outputs
+        .iter()
+        .filter(|o| o.enabled || o.preferred.is_some())
+        .filter(|o| INTERNAL_OUTPUT_PREFIXES.iter().any(|p| o.name.starts_with(p)))
+        .peekable();
+    if internal.peek().is_none() {
+        return Ok(None);
+    }
+    Ok(Some(internal.any(|o| o.enabled)))
+}
+
+// ============================================================================
+// Mode-Only Changes
+// ============================================================================
+
+/// Connector prefixes of built-in laptop panels.
+const INTERNAL_OUTPUT_PREFIXES: [&str; 3] = ["eDP", "LVDS", "DSI"];
+
+/// Get the resolution and refresh rate of every active output.
+pub fn get_display_modes() -> Result<Vec<DisplayMode>, String> {
+    Ok(display_modes(&get_display_settings(true)?))
+}
+
+/// Resolution and refresh rate of every enabled output of a configuration,
+/// keyed like `get_display_modes`.
+pub fn display_modes(settings: &DisplaySettings) -> Vec<DisplayMode> {
+    settings
+        .outputs
+        .iter()
+        .filter(|o| o.enabled && !o.non_desktop)
+        .map(|o| DisplayMode {
+            key: o.name.clone(),
+            width: o.width,
+            height: o.height,
+            refresh_rate: o.refresh_rate as f64,
+            internal: INTERNAL_OUTPUT_PREFIXES.iter().any(|p| o.name.starts_with(p)),
+        })
+        .collect()
+}
+
+/// Change resolution and refresh rate of the given outputs, leaving everything else as is.
+/// Outputs that are no longer active are ignored.
+pub fn set_display_modes(modes: &[DisplayMode]) -> Result<(), String> {
+    let mut settings = get_display_settings(false)?;
+    let mut changed = Vec::new();
+
+    for output in settings.outputs.iter_mut().filter(|o| o.enabled) {
+        if let Some(mode) = modes.iter().find(|m| m.key == output.name) {
+            output.width = mode.width;
+            output.height = mode.height;
+            output.refresh_rate = mode.refresh_rate as f32;
+            changed.push(output.clone());
+        }
+    }
+
+    if changed.is_empty() || super::simulate::apply(&settings) {
+        return Ok(());
+    }
+    if wlr_randr::is_available() {
+        return wlr_randr::apply_modes(&changed);
+    }
+
+    xrandr::apply_modes(&changed)
+}
+
+// ============================================================================
+// Position-Only Changes
+// ============================================================================
+
+/// Move the enabled outputs to where `settings` puts them, including which one
+/// is primary, keeping their current modes. Outputs the configuration doesn't
+/// have stay where they are.
+pub fn set_display_positions(settings: &DisplaySettings) -> Result<(), String> {
+    let mut current = get_display_settings(false)?;
+    let mut moved = Vec::new();
+
+    for output in current.outputs.iter_mut().filter(|o| o.enabled) {
+        if let Some(saved) = settings.outputs.iter().find(|s| s.name == output.name && s.enabled) {
+            output.pos_x = saved.pos_x;
+            output.pos_y = saved.pos_y;
+            output.primary = saved.primary;
+            moved.push(output.clone());
+        }
+    }
+
+    if moved.is_empty() {
+        return Err("None of the configuration's outputs are active".to_string());
+    }
+    if super::simulate::apply(&current) {
+        return Ok(());
+    }
+    if wlr_randr::is_available() {
+        return wlr_randr::apply_positions(&moved);
+    }
+    xrandr::apply_positions(&moved)
+}
+
+// ============================================================================
+// Rotation
+// ============================================================================
+
+/// Turn an output by 90° steps, clockwise for positive `quarter_turns`.
+/// The other outputs stay where they are.
+pub fn rotate_display(monitor_id: &str, quarter_turns: i32) -> Result<(), String> {
+    let mut settings = get_display_settings(false)?;
+    let output = settings
+        .outputs
+        .iter_mut()
+        .find(|o| o.name == monitor_id && o.enabled)
+        .ok_or_else(|| format!("Output {} is not active", monitor_id))?;
+
+    output.rotation = Rotation::from_u32(super::rotate_by(output.rotation.to_u32(), quarter_turns));
+    set_display_settings(&mut settings)
+}
+
+/// Name of the active output under the mouse pointer.
+pub fn monitor_at_cursor() -> Option<String> {
+    let (screen, x, y) = xrandr::pointer_position()?;
+    let settings = get_display_settings(true).ok()?;
+
+    settings
+        .outputs
+        .iter()
+        .filter(|o| o.screen == screen && !o.non_desktop)
+        .find(|o| {
+            // Modes are listed in panel orientation
+            let (width, height) = if o.rotation.is_sideways() { (o.height, o.width) } else { (o.width, o.height) };
+            (o.pos_x..o.pos_x + width as i32).contains(&x) && (o.pos_y..o.pos_y + height as i32).contains(&y)
+        })
+        .map(|o| o.name.clone())
+}
+
+/// Give rotation-locked outputs (by name) of a configuration the orientation
+/// they have right now. Locked outputs that are off keep the configuration's rotation.
+pub fn keep_rotations(settings: &mut DisplaySettings, locked: &[String]) {
+    if locked.is_empty() {
+        return;
+    }
+    let Ok(current) = get_display_settings(true) else {
+        return;
+    };
+
+    for output in settings.outputs.iter_mut().filter(|o| locked.contains(&o.name)) {
+        if let Some(rotation) = current.outputs.iter().find(|c| c.name == output.name).map(|c| c.rotation) {
+            output.rotation = rotation;
+        }
+    }
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+/// Find outputs whose resolution differs from the connected monitor's native mode.
+/// Returns one human-readable message per affected output.
+pub fn find_non_native_modes(settings: &DisplaySettings) -> Vec<String> {
+    let Ok(current) = get_display_settings(false) else {
+        return Vec::new();
+    };
+
+    settings
+        .outputs
+        .iter()
+        .filter(|output| output.enabled)
+        .filter_map(|output| {
+            let (native_width, native_height) = current
+                .outputs
+                .iter()
+                .find(|c| c.name == output.name)
+                .and_then(|c| c.preferred)?;
+
+            // Both come from xrandr mode lines, so they share the panel orientation
+            let (width, height) = (output.width, output.height);
+
+            ((width, height) != (native_width, native_height)).then(|| {
+                format!(
+                    "{}: {}x{} is not the native resolution ({}x{})",
+                    output.name, width, height, native_width, native_height
+                )
+            })
+        })
+        .collect()
+}
+
 // ============================================================================
 // Adapter Matching (Linux implementation)
 // ============================================================================
@@ -64,7 +433,7 @@ pub fn turn_off_monitors() -> Result<(), String> {
 pub fn match_adapter_ids(
     settings: &mut DisplaySettings,
     _additional_info: &[MonitorAdditionalInfo],
-) -> Result<(), String> {
+) -> Result<MatchTier, String> {
     let current = get_display_settings(true)?;
 
     // Match outputs by name
@@ -77,7 +446,162 @@ pub fn match_adapter_ids(
         }
     }
 
-    Ok(())
+    Ok(MatchTier::OutputName)
+}
+
+/// Names of the profile's enabled outputs that aren't connected right now.
+pub fn find_missing_monitors(
+    settings: &DisplaySettings,
+    _additional_info: &[MonitorAdditionalInfo],
+) -> Result<Vec<String>, String> {
+    // Disconnected outputs are listed too, but without any modes
+    let current = get_display_settings(false)?;
+
+    Ok(settings
+        .outputs
+        .iter()
+        .filter(|output| output.enabled)
+        .filter(|output| {
+            !current
+                .outputs
+                .iter()
+                .any(|c| c.name == output.name && (c.enabled || c.preferred.is_some()))
+        })
+        .map(|output| output.name.clone())
+        .collect())
+}
+
+/// Names of the outputs a configuration turns on.
+pub fn profile_monitor_names(settings: &DisplaySettings, _additional_info: &[MonitorAdditionalInfo]) -> Vec<String> {
+    settings
+        .outputs
+        .iter()
+        .filter(|output| output.enabled)
+        .map(|output| output.name.clone())
+        .collect()
+}
+
+/// Names of the enabled outputs of an applied configuration that aren't active.
+pub fn find_inactive_targets(
+    settings: &DisplaySettings,
+    _additional_info: &[MonitorAdditionalInfo],
+) -> Result<Vec<String>, String> {
+    let active = get_display_settings(true)?;
+
+    Ok(settings
+        .outputs
+        .iter()
+        .filter(|output| output.enabled)
+        .filter(|output| !active.outputs.iter().any(|a| a.name == output.name && a.enabled))
+        .map(|output| output.name.clone())
+        .collect())
+}
+
+/// Compare an applied configuration with the outputs as they are now, setting
+/// by setting; xrandr picks the closest mode when the exact one is missing.
+/// Returns the output name and what differs for each affected output; outputs
+/// that aren't active are left to `find_inactive_targets`.
+pub fn find_discrepancies(
+    settings: &DisplaySettings,
+    _additional_info: &[MonitorAdditionalInfo],
+) -> Result<Vec<(String, Vec<String>)>, String> {
+    let active = get_display_settings(true)?;
+
+    Ok(settings
+        .outputs
+        .iter()
+        .filter(|output| output.enabled)
+        .filter_map(|output| {
+            let current = active.outputs.iter().find(|a| a.name == output.name && a.enabled)?;
+            let found = describe_discrepancies(&display_state(output), &display_state(current));
+            (!found.is_empty()).then(|| (output.name.clone(), found))
+        })
+        .collect())
+}
+
+/// Whether the outputs are already set up as a configuration asks: the same
+/// outputs are enabled, each with the requested settings, with refresh rates
+/// as close as `find_discrepancies` allows, the same primary and scale, and
+/// the brightness and gamma the configuration sets, if any. Applying it again
+/// would only make the screens blank.
+pub fn is_already_applied(settings: &DisplaySettings) -> Result<bool, String> {
+    let active = get_display_settings(true)?;
+
+    let requested: Vec<&OutputConfig> = settings.outputs.iter().filter(|output| output.enabled).collect();
+    let enabled = active.outputs.iter().filter(|output| output.enabled).count();
+    if requested.is_empty() || requested.len() != enabled {
+        return Ok(false);
+    }
+
+    Ok(requested.iter().all(|output| {
+        active
+            .outputs
+            .iter()
+            .find(|a| a.name == output.name && a.enabled)
+            .is_some_and(|current| {
+                describe_discrepancies(&display_state(output), &display_state(current)).is_empty()
+                    && output.primary == current.primary
+                    && (output.scale - current.scale).abs() < 0.01
+                    && output.brightness.is_none_or(|b| current.brightness.is_some_and(|c| (b - c).abs() < 0.01))
+                    && output.gamma.is_none_or(|g| current.gamma == Some(g))
+            })
+    }))
+}
+
+/// An output's settings in desktop orientation; xrandr modes are in panel orientation.
+fn display_state(output: &OutputConfig) -> DisplayState {
+    let (width, height) = if output.rotation.is_sideways() {
+        (output.height, output.width)
+    } else {
+        (output.width, output.height)
+    };
+
+    DisplayState {
+        width,
+        height,
+        x: output.pos_x,
+        y: output.pos_y,
+        refresh_rate: output.refresh_rate as f64,
+        rotation: output.rotation.to_u32(),
+    }
+}
+
+/// IDs of the connected outputs, for hardware fingerprints. The output name
+/// plus the EDID vendor and product where available; non-desktop outputs are
+/// left out, like they are from profiles.
+pub fn connected_monitor_ids() -> Result<Vec<String>, String> {
+    let current = get_display_settings(false)?;
+
+    Ok(current
+        .outputs
+        .iter()
+        .filter(|output| (output.enabled || output.preferred.is_some()) && !output.non_desktop)
+        .map(|output| match edid::read_edid(&output.name) {
+            Ok(edid) => format!("{}:{}{:04X}", output.name, edid.manufacturer, edid.product_code),
+            Err(_) => output.name.clone(),
+        })
+        .collect())
+}
+
+/// Raw EDID of every connected output. Outputs without a readable EDID
+/// (virtual outputs, outputs of drivers that don't expose it) are left out.
+pub fn read_raw_edids() -> Result<Vec<RawEdid>, String> {
+    let current = get_display_settings(false)?;
+
+    Ok(current
+        .outputs
+        .iter()
+        .filter(|output| output.enabled || output.preferred.is_some())
+        .filter_map(|output| {
+            let edid = edid::read_edid_bytes(&output.name).ok()?;
+            let name = edid::read_edid(&output.name)
+                .map(|parsed| parsed.monitor_name)
+                .ok()
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| output.name.clone());
+            Some(RawEdid { monitor_id: output.name.clone(), name, edid, overridden: false })
+        })
+        .collect())
 }
 
 /// Get additional info for all outputs.
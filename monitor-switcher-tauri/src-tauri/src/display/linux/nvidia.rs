@@ -0,0 +1,153 @@
+//! NVIDIA proprietary driver fallback using nvidia-settings.
+//!
+//! Single responsibility: apply a configuration as an NVIDIA MetaMode when
+//! xrandr mode setting is rejected by the driver.
+
+use super::types::OutputConfig;
+use super::Rotation;
+use std::process::Command;
+
+// ============================================================================
+// Availability
+// ============================================================================
+
+/// Check whether nvidia-settings is installed and can talk to the driver.
+pub fn is_available() -> bool {
+    Command::new("nvidia-settings")
+        .args(["--query", "CurrentMetaMode", "--terse"])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+// ============================================================================
+// Apply Display Configuration
+// ============================================================================
+
+/// Apply display configuration by assigning a CurrentMetaMode.
+pub fn apply_metamode(outputs: &[OutputConfig]) -> Result<(), String> {
+    let metamode = build_metamode(outputs);
+    if metamode.is_empty() {
+        return Err("No enabled outputs to build a MetaMode from".to_string());
+    }
+
+    let output = Command::new("nvidia-settings")
+        .arg("--assign")
+        .arg(format!("CurrentMetaMode={}", metamode))
+        .output()
+        .map_err(|e| format!("Failed to execute nvidia-settings: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "nvidia-settings failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    // MetaModes have no notion of a primary output, so set it through xrandr
+    if let Some(primary) = outputs.iter().find(|o| o.enabled && o.primary) {
+        let _ = Command::new("xrandr")
+            .args(["--output", &primary.name, "--primary"])
+            .output();
+    }
+
+    Ok(())
+}
+
+/// Build a MetaMode string like "DP-0: 1920x1080_60 +0+0, HDMI-0: 2560x1440_144 +1920+0".
+/// Disabled outputs are omitted, which turns them off.
+fn build_metamode(outputs: &[OutputConfig]) -> String {
+    outputs
+        .iter()
+        .filter(|o| o.enabled)
+        .map(|o| {
+            let mut entry = format!(
+                "{}: {}x{}_{} +{}+{}",
+                o.name,
+                o.width,
+                o.height,
+                o.refresh_rate.round() as u32,
+                o.pos_x,
+                o.pos_y
+            );
+
+            let mut attributes = Vec::new();
+            if o.rotation != Rotation::Normal {
+                attributes.push(format!("Rotation={}", metamode_rotation(o.rotation)));
+            }
+            if (o.scale - 1.0).abs() > 0.01 {
+                let in_width = (o.width as f32 * o.scale).round() as u32;
+                let in_height = (o.height as f32 * o.scale).round() as u32;
+                attributes.push(format!("ViewPortIn={}x{}", in_width, in_height));
+            }
+            if !attributes.is_empty() {
+                entry.push_str(&format!(" {{{}}}", attributes.join(", ")));
+            }
+
+            entry
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Convert rotation to the MetaMode `Rotation` attribute value.
+fn metamode_rotation(rotation: Rotation) -> &'static str {
+    match rotation {
+        Rotation::Normal => "normal",
+        Rotation::Left => "left",
+        Rotation::Right => "right",
+        Rotation::Inverted => "invert",
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(name: &str, width: u32, height: u32, pos_x: i32) -> OutputConfig {
+        OutputConfig {
+            name: name.to_string(),
+            enabled: true,
+            width,
+            height,
+            pos_x,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_metamode() {
+        let mut right = output("HDMI-0", 2560, 1440, 1920);
+        right.refresh_rate = 143.98;
+        let outputs = vec![output("DP-0", 1920, 1080, 0), right];
+
+        assert_eq!(
+            build_metamode(&outputs),
+            "DP-0: 1920x1080_60 +0+0, HDMI-0: 2560x1440_144 +1920+0"
+        );
+    }
+
+    #[test]
+    fn test_build_metamode_attributes() {
+        let mut rotated = output("DP-2", 1440, 2560, 0);
+        rotated.rotation = Rotation::Inverted;
+        rotated.scale = 1.5;
+
+        assert_eq!(
+            build_metamode(&[rotated]),
+            "DP-2: 1440x2560_60 +0+0 {Rotation=invert, ViewPortIn=2160x3840}"
+        );
+    }
+
+    #[test]
+    fn test_build_metamode_skips_disabled() {
+        let mut off = output("DP-1", 1920, 1080, 0);
+        off.enabled = false;
+
+        assert_eq!(build_metamode(&[off]), "");
+    }
+}
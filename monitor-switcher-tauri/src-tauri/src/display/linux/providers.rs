@@ -0,0 +1,69 @@
+//! Display adapters via RandR providers.
+//!
+//! Single responsibility: list the GPUs known to the X server and the outputs
+//! connected to each of them.
+
+use super::super::GpuInfo;
+use std::fs;
+use x11rb::connection::Connection as _;
+use x11rb::protocol::randr::{Connection, ConnectionExt as _};
+
+/// Where the proprietary NVIDIA kernel module reports its version.
+const NVIDIA_VERSION_PATH: &str = "/sys/module/nvidia/version";
+
+/// List the display adapters (RandR providers) and their connected outputs.
+pub fn get_gpu_info() -> Result<Vec<GpuInfo>, String> {
+    let (conn, _) = x11rb::connect(None)
+        .map_err(|e| format!("Failed to connect to X server: {}", e))?;
+
+    // Providers were added in RandR 1.4
+    conn.randr_query_version(1, 4)
+        .map_err(|e| format!("Failed to query RandR version: {}", e))?
+        .reply()
+        .map_err(|e| format!("RandR extension not available: {}", e))?;
+
+    let roots: Vec<_> = conn.setup().roots.iter().map(|screen| screen.root).collect();
+    let mut gpus = Vec::new();
+
+    for root in roots {
+        let providers = conn
+            .randr_get_providers(root)
+            .map_err(|e| format!("Failed to query RandR providers: {}", e))?
+            .reply()
+            .map_err(|e| format!("Failed to query RandR providers: {}", e))?;
+
+        for provider in providers.providers {
+            let Ok(info) = conn.randr_get_provider_info(provider, providers.timestamp) else {
+                continue;
+            };
+            let Ok(info) = info.reply() else {
+                continue;
+            };
+
+            let monitors = info
+                .outputs
+                .iter()
+                .filter_map(|&output| conn.randr_get_output_info(output, providers.timestamp).ok()?.reply().ok())
+                .filter(|output| output.connection == Connection::CONNECTED)
+                .map(|output| String::from_utf8_lossy(&output.name).into_owned())
+                .collect();
+
+            let name = String::from_utf8_lossy(&info.name).into_owned();
+            let driver_version = if name.starts_with("NVIDIA") {
+                fs::read_to_string(NVIDIA_VERSION_PATH).ok().map(|v| v.trim().to_string())
+            } else {
+                None
+            };
+
+            gpus.push(GpuInfo {
+                name,
+                id: format!("0x{:x}", provider),
+                device_path: None,
+                driver_version,
+                monitors,
+            });
+        }
+    }
+
+    Ok(gpus)
+}
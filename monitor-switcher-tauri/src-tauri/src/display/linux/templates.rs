@@ -0,0 +1,207 @@
+//! Built-in layout templates.
+//!
+//! Single responsibility: compute the output configuration of a template layout
+//! from the connected outputs.
+
+use super::super::{best_common_resolution, primary_index, Template};
+use super::types::OutputConfig;
+use super::INTERNAL_OUTPUT_PREFIXES;
+
+/// Output configuration for a template, given all outputs as queried.
+/// Disconnected and non-desktop outputs are left out; outputs not part of the
+/// layout come back disabled.
+pub fn template_outputs(template: Template, outputs: &[OutputConfig]) -> Result<Vec<OutputConfig>, String> {
+    let mut outputs: Vec<OutputConfig> = outputs
+        .iter()
+        .filter(|o| !o.non_desktop && (o.enabled || o.preferred.is_some()))
+        .cloned()
+        .map(with_native_mode)
+        .collect();
+
+    // Keep the current left-to-right order, enabled outputs first
+    outputs.sort_by_key(|o| (!o.enabled, o.pos_x));
+    let anchor = outputs
+        .iter()
+        .position(is_internal)
+        .or_else(|| outputs.iter().position(|o| o.primary && o.enabled))
+        .ok_or_else(|| "No connected displays".to_string())?;
+    let pc = outputs.remove(anchor);
+    let externals = outputs;
+
+    let mut layout = match template {
+        Template::PcOnly => vec![pc.clone()],
+        Template::ExternalOnly if externals.is_empty() => {
+            return Err("No external displays connected".to_string());
+        }
+        Template::ExternalOnly => externals.clone(),
+        Template::ExtendRight => std::iter::once(pc.clone()).chain(externals.iter().cloned()).collect(),
+        Template::ExtendLeft => externals.iter().rev().cloned().chain(std::iter::once(pc.clone())).collect(),
+        Template::Duplicate => std::iter::once(pc.clone()).chain(externals.iter().cloned()).collect(),
+    };
+
+    let mut x = 0;
+    for output in &mut layout {
+        output.enabled = true;
+        output.primary = false;
+        output.pos_x = if template == Template::Duplicate { 0 } else { x };
+        output.pos_y = 0;
+        x += output.width as i32;
+    }
+    if template == Template::Duplicate {
+        use_common_resolution(&mut layout)?;
+    }
+    // The PC screen stays primary wherever it's part of the layout
+    match layout.iter_mut().find(|o| o.name == pc.name) {
+        Some(output) => output.primary = true,
+        None => layout[0].primary = true,
+    }
+
+    let left_out = std::iter::once(pc)
+        .chain(externals)
+        .filter(|o| !layout.iter().any(|l| l.name == o.name))
+        .map(|o| OutputConfig { enabled: false, primary: false, ..o });
+    layout.extend(left_out.collect::<Vec<_>>());
+    Ok(layout)
+}
+
+/// Give all duplicated outputs the largest resolution they all support, so they
+/// show the same picture. Outputs whose modes aren't known are left out of the choice.
+fn use_common_resolution(layout: &mut [OutputConfig]) -> Result<(), String> {
+    let supported: Vec<Vec<(u32, u32)>> = layout
+        .iter()
+        .map(|o| o.resolutions.clone())
+        .filter(|resolutions| !resolutions.is_empty())
+        .collect();
+    if supported.is_empty() {
+        return Ok(());
+    }
+    let (width, height) = best_common_resolution(&supported)
+        .ok_or_else(|| "The displays have no resolution in common".to_string())?;
+
+    for output in layout.iter_mut().filter(|o| (o.width, o.height) != (width, height)) {
+        output.width = width;
+        output.height = height;
+        output.refresh_rate = 60.0;
+        output.modeline = None;
+    }
+    Ok(())
+}
+
+fn is_internal(output: &OutputConfig) -> bool {
+    INTERNAL_OUTPUT_PREFIXES.iter().any(|p| output.name.starts_with(p))
+}
+
+/// Outputs that are off get their preferred mode; the refresh rate isn't known
+/// for it, so they get the 60 Hz every monitor supports.
+fn with_native_mode(output: OutputConfig) -> OutputConfig {
+    match output.preferred {
+        Some((width, height)) if !output.enabled => OutputConfig {
+            width,
+            height,
+            refresh_rate: 60.0,
+            ..output
+        },
+        _ => output,
+    }
+}
+
+/// `outputs` with every enabled output but the primary one disabled. Without a
+/// primary output, the one at the origin counts as it. None when no other
+/// output is enabled.
+pub fn primary_only_outputs(outputs: &[OutputConfig]) -> Option<Vec<OutputConfig>> {
+    let enabled: Vec<usize> = (0..outputs.len()).filter(|&i| outputs[i].enabled).collect();
+    if enabled.len() < 2 {
+        return None;
+    }
+
+    let positions: Vec<(i32, i32)> = enabled.iter().map(|&i| (outputs[i].pos_x, outputs[i].pos_y)).collect();
+    let primary = enabled
+        .iter()
+        .copied()
+        .find(|&i| outputs[i].primary)
+        .or_else(|| primary_index(&positions).map(|p| enabled[p]))?;
+
+    Some(
+        outputs
+            .iter()
+            .enumerate()
+            .map(|(i, output)| OutputConfig {
+                enabled: output.enabled && i == primary,
+                ..output.clone()
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(name: &str, enabled: bool, pos_x: i32, width: u32) -> OutputConfig {
+        OutputConfig {
+            name: name.to_string(),
+            enabled,
+            primary: name.starts_with("eDP"),
+            width: if enabled { width } else { 0 },
+            height: if enabled { 1080 } else { 0 },
+            pos_x,
+            preferred: Some((width, 1080)),
+            resolutions: vec![(width, 1080), (1920, 1080), (1280, 720)],
+            ..OutputConfig::default()
+        }
+    }
+
+    fn positions(layout: &[OutputConfig]) -> Vec<(&str, bool, i32)> {
+        layout.iter().map(|o| (o.name.as_str(), o.enabled, o.pos_x)).collect()
+    }
+
+    #[test]
+    fn test_template_outputs() {
+        let outputs = vec![
+            output("HDMI-1", true, 1920, 2560),
+            output("eDP-1", true, 0, 1920),
+            output("DP-1", false, 0, 3840),
+            OutputConfig { name: "DP-2".to_string(), ..OutputConfig::default() },
+        ];
+
+        let right = template_outputs(Template::ExtendRight, &outputs).unwrap();
+        assert_eq!(positions(&right), [("eDP-1", true, 0), ("HDMI-1", true, 1920), ("DP-1", true, 4480)]);
+        assert_eq!(right[2].width, 3840);
+
+        let left = template_outputs(Template::ExtendLeft, &outputs).unwrap();
+        assert_eq!(positions(&left), [("DP-1", true, 0), ("HDMI-1", true, 3840), ("eDP-1", true, 6400)]);
+        assert!(left[2].primary && !left[0].primary);
+
+        let pc = template_outputs(Template::PcOnly, &outputs).unwrap();
+        assert_eq!(positions(&pc), [("eDP-1", true, 0), ("HDMI-1", false, 1920), ("DP-1", false, 0)]);
+
+        let external = template_outputs(Template::ExternalOnly, &outputs).unwrap();
+        assert_eq!(positions(&external), [("HDMI-1", true, 0), ("DP-1", true, 2560), ("eDP-1", false, 0)]);
+        assert!(external[0].primary);
+
+        let duplicate = template_outputs(Template::Duplicate, &outputs).unwrap();
+        assert!(duplicate.iter().all(|o| o.enabled && o.pos_x == 0));
+        assert!(duplicate.iter().all(|o| (o.width, o.height) == (1920, 1080)));
+
+        let laptop_only = template_outputs(Template::ExternalOnly, &outputs[1..2]);
+        assert!(laptop_only.is_err());
+    }
+
+    #[test]
+    fn test_primary_only_outputs() {
+        let outputs = vec![
+            output("HDMI-1", true, 1920, 2560),
+            output("eDP-1", true, 0, 1920),
+            output("DP-1", false, 0, 3840),
+        ];
+        let focus = primary_only_outputs(&outputs).unwrap();
+        assert_eq!(positions(&focus), [("HDMI-1", false, 1920), ("eDP-1", true, 0), ("DP-1", false, 0)]);
+
+        // Without a primary output, the one at the origin stays on
+        let externals = vec![output("HDMI-1", true, 0, 2560), output("DP-1", true, 2560, 3840)];
+        let focus = primary_only_outputs(&externals).unwrap();
+        assert_eq!(positions(&focus), [("HDMI-1", true, 0), ("DP-1", false, 2560)]);
+
+        assert!(primary_only_outputs(&outputs[1..]).is_none());
+    }
+}
@@ -29,6 +29,21 @@ pub struct OutputConfig {
     pub rotation: Rotation,
     /// Scale factor (1.0 = 100%, 2.0 = 200%)
     pub scale: f32,
+    /// Software brightness multiplier (1.0 = unchanged). None if unknown.
+    pub brightness: Option<f32>,
+    /// Gamma correction as red, green, blue (the values passed to `--gamma`). None if unknown.
+    pub gamma: Option<[f32; 3]>,
+    /// X screen the output belongs to (always 0 unless running ZaphodHeads)
+    pub screen: u32,
+    /// Custom mode as an X modeline (`"name" clock hdisp hsyncstart ...`), for
+    /// modes that aren't named after their resolution. None for standard modes.
+    pub modeline: Option<String>,
+    /// RandR non-desktop output (VR headsets etc.), never part of a profile
+    pub non_desktop: bool,
+    /// Preferred (native) resolution reported by the monitor. Not stored in profiles.
+    pub preferred: Option<(u32, u32)>,
+    /// Resolutions the monitor supports, in the order xrandr lists them. Not stored in profiles.
+    pub resolutions: Vec<(u32, u32)>,
 }
 
 impl Default for OutputConfig {
@@ -44,6 +59,13 @@ impl Default for OutputConfig {
             pos_y: 0,
             rotation: Rotation::Normal,
             scale: 1.0,
+            brightness: None,
+            gamma: None,
+            screen: 0,
+            modeline: None,
+            non_desktop: false,
+            preferred: None,
+            resolutions: Vec::new(),
         }
     }
 }
@@ -60,7 +82,7 @@ pub enum Rotation {
 
 impl Rotation {
     /// Convert to xrandr rotation argument.
-    pub fn to_xrandr_arg(&self) -> &'static str {
+    pub fn to_xrandr_arg(self) -> &'static str {
         match self {
             Rotation::Normal => "normal",
             Rotation::Left => "left",
@@ -83,7 +105,7 @@ impl Rotation {
     /// This is used for the frontend MonitorDetails struct.
     /// 1 = Identity (0°), 2 = Rotate90 (90° CW / 270° CCW),
     /// 3 = Rotate180 (180°), 4 = Rotate270 (270° CW / 90° CCW)
-    pub fn to_u32(&self) -> u32 {
+    pub fn to_u32(self) -> u32 {
         match self {
             Rotation::Normal => 1,   // DISPLAYCONFIG_ROTATION_IDENTITY
             Rotation::Right => 2,    // DISPLAYCONFIG_ROTATION_ROTATE90 (90° clockwise)
@@ -91,5 +113,20 @@ impl Rotation {
             Rotation::Left => 4,     // DISPLAYCONFIG_ROTATION_ROTATE270 (90° counter-clockwise)
         }
     }
+
+    /// Inverse of `to_u32`. Unknown values are upright.
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            2 => Rotation::Right,
+            3 => Rotation::Inverted,
+            4 => Rotation::Left,
+            _ => Rotation::Normal,
+        }
+    }
+
+    /// Whether the output is turned on its side, swapping width and height on the desktop.
+    pub fn is_sideways(self) -> bool {
+        matches!(self, Rotation::Left | Rotation::Right)
+    }
 }
 
@@ -0,0 +1,94 @@
+//! Virtual displays through evdi.
+//!
+//! The evdi kernel module creates DRM devices on request through sysfs. Each
+//! one turns into an output once a client, such as the DisplayLink manager or
+//! a streaming host, attaches and hands it an EDID, which also decides the
+//! modes it offers. Adding and removing devices needs root, so it goes
+//! through the elevated worker.
+//! Single responsibility: make evdi provide a number of devices.
+
+use super::super::{simulate, VirtualDisplay};
+use crate::elevation::{run_privileged, PrivilegedOp};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Control files of the loaded evdi module.
+const EVDI_SYSFS: &str = "/sys/devices/evdi";
+
+const NO_DRIVER: &str = "The evdi kernel module isn't installed";
+
+/// Name of the installed virtual display driver, if any.
+pub fn virtual_display_driver() -> Option<&'static str> {
+    (Path::new(EVDI_SYSFS).exists() || module_available()).then_some("evdi")
+}
+
+/// Number of devices evdi provides.
+pub fn count_virtual_displays() -> Result<usize, String> {
+    if !Path::new(EVDI_SYSFS).exists() {
+        return if module_available() { Ok(0) } else { Err(NO_DRIVER.to_string()) };
+    }
+    read_device_count()
+}
+
+/// Make evdi provide one device per display. The modes come from the client
+/// attaching to each device; they're set by the layout that follows. Returns
+/// whether anything changed.
+pub fn set_virtual_displays(displays: &[VirtualDisplay]) -> Result<bool, String> {
+    set_virtual_display_count(displays.len())
+}
+
+/// Make evdi provide `count` devices. Returns whether anything changed.
+pub fn set_virtual_display_count(count: usize) -> Result<bool, String> {
+    if simulate::is_simulating() {
+        return Err("Virtual displays can't be created on simulated displays".to_string());
+    }
+    if count_virtual_displays()? == count {
+        return Ok(false);
+    }
+    run_privileged(&PrivilegedOp::SetEvdiDevices { count })?;
+    Ok(true)
+}
+
+/// Load evdi if needed and make it provide `count` devices. Needs root.
+pub fn write_evdi_device_count(count: usize) -> Result<(), String> {
+    let sysfs = Path::new(EVDI_SYSFS);
+    if !sysfs.exists() {
+        let status = Command::new("modprobe")
+            .arg("evdi")
+            .status()
+            .map_err(|e| format!("Failed to run modprobe: {}", e))?;
+        if !status.success() {
+            return Err("Failed to load the evdi kernel module".to_string());
+        }
+    }
+
+    let mut current = read_device_count()?;
+    // evdi can only remove all of its devices at once
+    if count < current {
+        fs::write(sysfs.join("remove_all"), "1")
+            .map_err(|e| format!("Failed to remove the evdi devices: {}", e))?;
+        current = 0;
+    }
+    if count > current {
+        fs::write(sysfs.join("add"), (count - current).to_string())
+            .map_err(|e| format!("Failed to add evdi devices: {}", e))?;
+    }
+    Ok(())
+}
+
+fn read_device_count() -> Result<usize, String> {
+    fs::read_to_string(Path::new(EVDI_SYSFS).join("count"))
+        .map_err(|e| format!("Failed to read the number of evdi devices: {}", e))?
+        .trim()
+        .parse()
+        .map_err(|_| "evdi reported an invalid number of devices".to_string())
+}
+
+/// Whether the evdi module is installed, loaded or not.
+fn module_available() -> bool {
+    Command::new("modinfo")
+        .arg("evdi")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
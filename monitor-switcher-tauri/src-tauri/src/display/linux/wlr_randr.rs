@@ -0,0 +1,325 @@
+//! wlroots fallback using the wlr-randr command-line tool.
+//!
+//! On wlroots compositors (Sway, Hyprland, river, ...) xrandr only sees the
+//! virtual XWayland outputs, so querying and applying goes through
+//! `wlr-randr` instead, which speaks the wlr-output-management protocol.
+//! Outputs are mapped to the same `OutputConfig` model xrandr uses. Wayland
+//! has no primary output, so the primary flag is left alone, and the scale
+//! is the compositor's HiDPI scale.
+//! Single responsibility: interact with the wlr-randr command-line tool.
+
+use super::types::OutputConfig;
+use super::Rotation;
+use serde::Deserialize;
+use std::process::Command;
+use std::sync::OnceLock;
+
+// ============================================================================
+// Availability
+// ============================================================================
+
+/// Whether this is a Wayland session whose compositor wlr-randr can configure.
+/// Checked once; the session doesn't change while the app runs.
+pub fn is_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        std::env::var_os("WAYLAND_DISPLAY").is_some()
+            && Command::new("wlr-randr")
+                .arg("--json")
+                .output()
+                .map(|output| output.status.success())
+                .unwrap_or(false)
+    })
+}
+
+// ============================================================================
+// Query Display Configuration
+// ============================================================================
+
+/// A head as listed by `wlr-randr --json`.
+#[derive(Debug, Deserialize)]
+struct Head {
+    name: String,
+    enabled: bool,
+    #[serde(default)]
+    modes: Vec<Mode>,
+    position: Option<Position>,
+    transform: Option<String>,
+    scale: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Mode {
+    width: u32,
+    height: u32,
+    /// In Hz
+    refresh: f32,
+    #[serde(default)]
+    preferred: bool,
+    #[serde(default)]
+    current: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Position {
+    x: i32,
+    y: i32,
+}
+
+/// Query the current outputs using wlr-randr.
+pub fn query_outputs(active_only: bool) -> Result<Vec<OutputConfig>, String> {
+    let output = Command::new("wlr-randr")
+        .arg("--json")
+        .output()
+        .map_err(|e| format!("Failed to execute wlr-randr: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "wlr-randr query failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let outputs = parse_heads(&String::from_utf8_lossy(&output.stdout))?;
+    if active_only {
+        Ok(outputs.into_iter().filter(|o| o.enabled).collect())
+    } else {
+        Ok(outputs)
+    }
+}
+
+/// Parse `wlr-randr --json` output into OutputConfig structs.
+fn parse_heads(json: &str) -> Result<Vec<OutputConfig>, String> {
+    let heads: Vec<Head> = serde_json::from_str(json)
+        .map_err(|e| format!("Failed to parse wlr-randr output: {}", e))?;
+
+    Ok(heads
+        .into_iter()
+        .map(|head| {
+            let mut config = OutputConfig {
+                name: head.name,
+                enabled: head.enabled,
+                rotation: head.transform.as_deref().map(rotation_from_transform).unwrap_or_default(),
+                scale: head.scale.unwrap_or(1.0),
+                preferred: head.modes.iter().find(|m| m.preferred).map(|m| (m.width, m.height)),
+                ..Default::default()
+            };
+            if let Some(position) = head.position {
+                config.pos_x = position.x;
+                config.pos_y = position.y;
+            }
+            for mode in &head.modes {
+                if !config.resolutions.contains(&(mode.width, mode.height)) {
+                    config.resolutions.push((mode.width, mode.height));
+                }
+            }
+            // Like xrandr, only enabled outputs have a current mode
+            if let Some(mode) = head.modes.iter().find(|m| m.current).filter(|_| head.enabled) {
+                config.width = mode.width;
+                config.height = mode.height;
+                config.refresh_rate = mode.refresh;
+            }
+            config
+        })
+        .collect())
+}
+
+/// Map a Wayland output transform to a rotation. Wayland turns
+/// counter-clockwise; flipped transforms keep only their rotation.
+fn rotation_from_transform(transform: &str) -> Rotation {
+    match transform.trim_start_matches("flipped").trim_start_matches('-') {
+        "90" => Rotation::Left,
+        "180" => Rotation::Inverted,
+        "270" => Rotation::Right,
+        _ => Rotation::Normal,
+    }
+}
+
+/// Inverse of `rotation_from_transform`.
+fn transform_arg(rotation: Rotation) -> &'static str {
+    match rotation {
+        Rotation::Normal => "normal",
+        Rotation::Left => "90",
+        Rotation::Inverted => "180",
+        Rotation::Right => "270",
+    }
+}
+
+// ============================================================================
+// Apply Display Configuration
+// ============================================================================
+
+/// Apply a configuration, turning off enabled outputs that aren't in it. The
+/// compositor applies all outputs at once or none of them.
+pub fn apply_configuration(outputs: &[OutputConfig]) -> Result<(), String> {
+    let current = query_outputs(true)?;
+    run(build_apply_args(outputs, &current))
+}
+
+/// Check whether the compositor would accept a configuration, without applying it.
+pub fn validate_configuration(outputs: &[OutputConfig]) -> Result<(), String> {
+    let current = query_outputs(true)?;
+    let mut args = build_apply_args(outputs, &current);
+    args.insert(0, "--dryrun".to_string());
+    run(args)
+}
+
+/// Change only mode and refresh rate of the given outputs.
+pub fn apply_modes(outputs: &[OutputConfig]) -> Result<(), String> {
+    run(outputs.iter().flat_map(|o| output_args(o, &[mode_arg(o)])).collect())
+}
+
+/// Change only the position of the given outputs.
+pub fn apply_positions(outputs: &[OutputConfig]) -> Result<(), String> {
+    run(outputs.iter().flat_map(|o| output_args(o, &[pos_arg(o)])).collect())
+}
+
+/// Build wlr-randr arguments that configure `outputs` and turn off any other
+/// enabled output in `current_outputs`.
+fn build_apply_args(outputs: &[OutputConfig], current_outputs: &[OutputConfig]) -> Vec<String> {
+    let mut args = Vec::new();
+
+    for current in current_outputs {
+        if current.enabled && !outputs.iter().any(|o| o.name == current.name) {
+            args.extend(output_args(current, &[vec!["--off".to_string()]]));
+        }
+    }
+
+    for output in outputs {
+        let settings = if output.enabled {
+            vec![
+                vec!["--on".to_string()],
+                mode_arg(output),
+                pos_arg(output),
+                vec!["--transform".to_string(), transform_arg(output.rotation).to_string()],
+                vec!["--scale".to_string(), format!("{}", output.scale)],
+            ]
+        } else {
+            vec![vec!["--off".to_string()]]
+        };
+        args.extend(output_args(output, &settings));
+    }
+
+    args
+}
+
+/// `--output <name>` followed by the given settings.
+fn output_args(output: &OutputConfig, settings: &[Vec<String>]) -> Vec<String> {
+    let mut args = vec!["--output".to_string(), output.name.clone()];
+    args.extend(settings.iter().flatten().cloned());
+    args
+}
+
+/// Without a refresh rate, the compositor picks one.
+fn mode_arg(output: &OutputConfig) -> Vec<String> {
+    let mode = if output.refresh_rate > 0.0 {
+        format!("{}x{}@{:.3}Hz", output.width, output.height, output.refresh_rate)
+    } else {
+        format!("{}x{}", output.width, output.height)
+    };
+    vec!["--mode".to_string(), mode]
+}
+
+fn pos_arg(output: &OutputConfig) -> Vec<String> {
+    vec!["--pos".to_string(), format!("{},{}", output.pos_x, output.pos_y)]
+}
+
+/// Run wlr-randr with `args`.
+fn run(args: Vec<String>) -> Result<(), String> {
+    if args.is_empty() {
+        return Ok(());
+    }
+
+    let output = Command::new("wlr-randr")
+        .args(&args)
+        .output()
+        .map_err(|e| format!("Failed to execute wlr-randr: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "wlr-randr failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"[
+        {
+            "name": "eDP-1", "description": "Sharp LQ140M1JW46", "make": "Sharp", "model": "LQ140M1JW46",
+            "enabled": true,
+            "modes": [
+                {"width": 1920, "height": 1080, "refresh": 144.003, "preferred": true, "current": true},
+                {"width": 1920, "height": 1080, "refresh": 60.0, "preferred": false, "current": false}
+            ],
+            "position": {"x": 0, "y": 0}, "transform": "normal", "scale": 1.25, "adaptive_sync": false
+        },
+        {
+            "name": "DP-2", "enabled": true,
+            "modes": [
+                {"width": 2560, "height": 1440, "refresh": 59.951, "preferred": true, "current": true}
+            ],
+            "position": {"x": 1536, "y": 0}, "transform": "90", "scale": 1.0
+        },
+        {
+            "name": "HDMI-A-1", "enabled": false,
+            "modes": [
+                {"width": 3840, "height": 2160, "refresh": 30.0, "preferred": true, "current": true}
+            ]
+        }
+    ]"#;
+
+    #[test]
+    fn test_parse_heads() {
+        let outputs = parse_heads(SAMPLE).unwrap();
+        assert_eq!(outputs.len(), 3);
+
+        let laptop = &outputs[0];
+        assert!(laptop.enabled);
+        assert_eq!((laptop.width, laptop.height, laptop.refresh_rate), (1920, 1080, 144.003));
+        assert_eq!(laptop.scale, 1.25);
+        assert_eq!(laptop.preferred, Some((1920, 1080)));
+        assert_eq!(laptop.resolutions, vec![(1920, 1080)]);
+
+        let rotated = &outputs[1];
+        assert_eq!((rotated.pos_x, rotated.pos_y), (1536, 0));
+        assert_eq!(rotated.rotation, Rotation::Left);
+
+        let off = &outputs[2];
+        assert!(!off.enabled);
+        assert_eq!((off.width, off.height), (0, 0));
+        assert_eq!(off.preferred, Some((3840, 2160)));
+    }
+
+    #[test]
+    fn test_transforms_round_trip() {
+        for rotation in [Rotation::Normal, Rotation::Left, Rotation::Inverted, Rotation::Right] {
+            assert_eq!(rotation_from_transform(transform_arg(rotation)), rotation);
+        }
+        assert_eq!(rotation_from_transform("flipped-270"), Rotation::Right);
+        assert_eq!(rotation_from_transform("flipped"), Rotation::Normal);
+    }
+
+    #[test]
+    fn test_build_apply_args() {
+        let current = parse_heads(SAMPLE).unwrap();
+        let mut external = current[1].clone();
+        external.rotation = Rotation::Normal;
+        external.pos_x = 0;
+
+        assert_eq!(
+            build_apply_args(&[external], &current).join(" "),
+            "--output eDP-1 --off \
+             --output DP-2 --on --mode 2560x1440@59.951Hz --pos 0,0 --transform normal --scale 1"
+        );
+    }
+}
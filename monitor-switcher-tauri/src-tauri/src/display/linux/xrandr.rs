@@ -4,34 +4,81 @@
 
 use super::types::OutputConfig;
 use super::Rotation;
+use crate::i18n;
 use std::process::Command;
+use x11rb::connection::Connection;
 
 // ============================================================================
 // Query Display Configuration
 // ============================================================================
 
-/// Query current display outputs using xrandr.
+/// Query current display outputs using xrandr, across all X screens.
 pub fn query_outputs(active_only: bool) -> Result<Vec<OutputConfig>, String> {
+    let mut outputs = Vec::new();
+    for screen in 0..screen_count() {
+        outputs.extend(query_screen_outputs(screen)?);
+    }
+
+    if active_only {
+        Ok(outputs.into_iter().filter(|o| o.enabled).collect())
+    } else {
+        Ok(outputs)
+    }
+}
+
+/// Number of X screens on the display.
+/// Only ZaphodHeads-style setups have more than one; falls back to 1 if the
+/// X server can't be reached directly.
+pub fn screen_count() -> u32 {
+    x11rb::connect(None)
+        .map(|(conn, _)| conn.setup().roots.len() as u32)
+        .unwrap_or(1)
+        .max(1)
+}
+
+/// Position of the mouse pointer on the default X screen, with that screen's number.
+pub fn pointer_position() -> Option<(u32, i32, i32)> {
+    use x11rb::protocol::xproto::ConnectionExt as _;
+
+    let (conn, screen) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots.get(screen)?.root;
+    let pointer = conn.query_pointer(root).ok()?.reply().ok()?;
+    Some((screen as u32, pointer.root_x as i32, pointer.root_y as i32))
+}
+
+/// Query the outputs of a single X screen.
+fn query_screen_outputs(screen: u32) -> Result<Vec<OutputConfig>, String> {
     let output = Command::new("xrandr")
-        .arg("--query")
+        .args(["--screen", &screen.to_string(), "--query"])
         .output()
-        .map_err(|e| format!("Failed to execute xrandr: {}", e))?;
+        .map_err(|e| i18n::tr_with("error.runXrandr", &[("error", &e.to_string())]))?;
 
     if !output.status.success() {
-        return Err(format!(
-            "xrandr query failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+        return Err(i18n::tr_with("error.xrandrQuery", &[("error", &String::from_utf8_lossy(&output.stderr))]));
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let outputs = parse_xrandr_output(&stdout);
+    let mut outputs = parse_xrandr_output(&stdout);
+    for output in &mut outputs {
+        output.screen = screen;
+    }
 
-    if active_only {
-        Ok(outputs.into_iter().filter(|o| o.enabled).collect())
-    } else {
-        Ok(outputs)
+    // Brightness, gamma and non-desktop are only reported in verbose mode
+    match query_verbose_properties(screen) {
+        Ok(properties) => {
+            for output in &mut outputs {
+                if let Some(props) = properties.iter().find(|p| p.name == output.name) {
+                    output.brightness = props.brightness;
+                    output.gamma = props.gamma;
+                    output.non_desktop = props.non_desktop;
+                    output.modeline = props.modeline.clone();
+                }
+            }
+        }
+        Err(e) => log::debug!("Could not read verbose output properties: {}", e),
     }
+
+    Ok(outputs)
 }
 
 /// Parse xrandr --query output into OutputConfig structs.
@@ -54,7 +101,7 @@ fn parse_xrandr_output(output: &str) -> Vec<OutputConfig> {
             }
 
             let name = parts[0].to_string();
-            let connected = parts.get(1).map_or(false, |s| *s == "connected");
+            let connected = parts.get(1).is_some_and(|s| *s == "connected");
 
             if !connected {
                 // Disconnected output - still record it but as disabled
@@ -108,6 +155,16 @@ fn parse_xrandr_output(output: &str) -> Vec<OutputConfig> {
         else if line.starts_with("   ") && current_output.is_some() {
             let line = line.trim();
             if let Some(output) = current_output.as_mut() {
+                // Remember the preferred (native) mode
+                if output.preferred.is_none() && is_preferred_mode_line(line) {
+                    output.preferred = parse_mode_line(line).map(|(width, height, _)| (width, height));
+                }
+                if let Some((width, height, _)) = parse_mode_line(line) {
+                    if !output.resolutions.contains(&(width, height)) {
+                        output.resolutions.push((width, height));
+                    }
+                }
+
                 // Only parse if this is the active mode (has *)
                 if line.contains('*') {
                     if let Some((width, height, refresh)) = parse_mode_line(line) {
@@ -129,6 +186,146 @@ fn parse_xrandr_output(output: &str) -> Vec<OutputConfig> {
     outputs
 }
 
+/// Properties of a single output that only `xrandr --verbose` reports.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct OutputProperties {
+    name: String,
+    brightness: Option<f32>,
+    gamma: Option<[f32; 3]>,
+    non_desktop: bool,
+    /// Modeline of the current mode, if it's a custom one
+    modeline: Option<String>,
+}
+
+/// Query per-output verbose properties of an X screen using xrandr --verbose.
+fn query_verbose_properties(screen: u32) -> Result<Vec<OutputProperties>, String> {
+    let output = Command::new("xrandr")
+        .args(["--screen", &screen.to_string(), "--verbose"])
+        .output()
+        .map_err(|e| i18n::tr_with("error.runXrandr", &[("error", &e.to_string())]))?;
+
+    if !output.status.success() {
+        return Err(i18n::tr_with("error.xrandrQuery", &[("error", &String::from_utf8_lossy(&output.stderr))]));
+    }
+
+    Ok(parse_verbose_properties(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse the Gamma, Brightness and non-desktop properties, and the timings of
+/// custom current modes, out of xrandr --verbose output.
+fn parse_verbose_properties(output: &str) -> Vec<OutputProperties> {
+    let mut properties: Vec<OutputProperties> = Vec::new();
+    // Name and clock of a custom current mode whose timing lines follow
+    let mut custom_mode: Option<(String, String, String)> = None;
+    let mut h_timings: Option<Vec<String>> = None;
+
+    for line in output.lines() {
+        if line.contains(" connected") || line.contains(" disconnected") {
+            if let Some(name) = line.split_whitespace().next() {
+                properties.push(OutputProperties {
+                    name: name.to_string(),
+                    ..Default::default()
+                });
+            }
+            continue;
+        }
+
+        let Some(current) = properties.last_mut() else {
+            continue;
+        };
+
+        // Mode lines: "  1920x1080_75.00 (0x4a8) 220.750MHz -HSync +VSync *current",
+        // followed by "h: width 1920 start 2064 end 2272 total 2624 ..." and a "v:" line
+        let line = line.trim();
+        if let Some((name, clock, flags)) = parse_verbose_mode_line(line) {
+            custom_mode = (line.contains("*current") && !is_standard_mode_name(&name)).then_some((name, clock, flags));
+            h_timings = None;
+        } else if let Some(value) = line.strip_prefix("h:") {
+            h_timings = custom_mode.as_ref().and_then(|_| parse_timings(value));
+        } else if let Some(value) = line.strip_prefix("v:") {
+            if let (Some((name, clock, flags)), Some(h), Some(v)) = (custom_mode.take(), h_timings.take(), parse_timings(value)) {
+                current.modeline = Some(format!("\"{}\" {} {} {} {}", name, clock, h.join(" "), v.join(" "), flags).trim_end().to_string());
+            }
+        } else if let Some(value) = line.strip_prefix("Gamma:") {
+            let channels: Vec<f32> = value
+                .trim()
+                .split(':')
+                .filter_map(|c| c.parse().ok())
+                .collect();
+            if let [r, g, b] = channels[..] {
+                current.gamma = Some([r, g, b]);
+            }
+        } else if let Some(value) = line.strip_prefix("Brightness:") {
+            current.brightness = value.trim().parse().ok();
+        } else if let Some(value) = line.strip_prefix("non-desktop:") {
+            // RandR 1.6 marks VR headsets and similar displays as non-desktop
+            current.non_desktop = value.split_whitespace().next() == Some("1");
+        }
+    }
+
+    properties
+}
+
+/// Parse a verbose mode line into (name, clock in MHz, modeline flags).
+fn parse_verbose_mode_line(line: &str) -> Option<(String, String, String)> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if !parts.get(1)?.starts_with("(0x") {
+        return None;
+    }
+    let clock = parts.get(2)?.strip_suffix("MHz")?;
+    let clock = clock.parse::<f64>().ok()?;
+    let flags: Vec<String> = parts[3..]
+        .iter()
+        .filter(|p| !p.starts_with('*') && !p.starts_with("+preferred"))
+        .map(|p| p.to_lowercase())
+        .collect();
+    Some((parts[0].to_string(), format!("{:.3}", clock), flags.join(" ")))
+}
+
+/// The display, sync start, sync end and total values of an "h:" or "v:" timing line.
+fn parse_timings(value: &str) -> Option<Vec<String>> {
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    let field = |key: &str| {
+        let index = parts.iter().position(|p| *p == key)?;
+        parts.get(index + 1).filter(|v| v.parse::<u32>().is_ok()).map(|v| v.to_string())
+    };
+    let display = field("width").or_else(|| field("height"))?;
+    Some(vec![display, field("start")?, field("end")?, field("total")?])
+}
+
+/// Whether a mode is named after its resolution ("1920x1080", "1920x1080i"),
+/// as the modes read from the EDID are.
+fn is_standard_mode_name(name: &str) -> bool {
+    let Some((width, height)) = name.split_once('x') else {
+        return false;
+    };
+    let height = height.strip_suffix('i').unwrap_or(height);
+    !width.is_empty()
+        && !height.is_empty()
+        && width.chars().all(|c| c.is_ascii_digit())
+        && height.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Split a modeline into the mode name and the timing arguments of `--newmode`.
+/// A leading "Modeline" keyword, as printed by `cvt` and `gtf`, is skipped.
+pub(super) fn split_modeline(modeline: &str) -> Option<(String, Vec<String>)> {
+    let modeline = modeline.trim();
+    let modeline = match modeline.get(..8) {
+        Some(keyword) if keyword.eq_ignore_ascii_case("modeline") => modeline[8..].trim_start(),
+        _ => modeline,
+    };
+
+    let (name, rest) = match modeline.strip_prefix('"') {
+        Some(quoted) => quoted.split_once('"')?,
+        None => modeline.split_once(char::is_whitespace)?,
+    };
+    let timings: Vec<String> = rest.split_whitespace().map(str::to_string).collect();
+    if name.is_empty() || timings.len() < 9 {
+        return None;
+    }
+    Some((name.to_string(), timings))
+}
+
 /// Parse geometry string like "1920x1080+0+0" into ((width, height), (x, y)).
 fn parse_geometry(geom: &str) -> Option<((u32, u32), (i32, i32))> {
     // Split by 'x' first to get width and the rest
@@ -205,6 +402,11 @@ fn parse_position(pos: &str) -> Option<(i32, i32)> {
     Some((x, y))
 }
 
+/// Check whether a mode line is the preferred mode (a refresh rate marked with +).
+fn is_preferred_mode_line(line: &str) -> bool {
+    line.split_whitespace().skip(1).any(|part| part.contains('+'))
+}
+
 /// Parse mode line like "1920x1080     60.00*+" into (width, height, refresh_rate).
 fn parse_mode_line(line: &str) -> Option<(u32, u32, f32)> {
     let parts: Vec<&str> = line.split_whitespace().collect();
@@ -246,16 +448,183 @@ fn parse_mode_line(line: &str) -> Option<(u32, u32, f32)> {
 
 /// Apply display configuration using xrandr.
 /// This will also turn off any connected outputs not in the provided list.
+/// Each X screen is configured with its own `xrandr --screen` call; screens
+/// without outputs in the list are left untouched.
 pub fn apply_configuration(outputs: &[OutputConfig]) -> Result<(), String> {
+    configure(outputs, false)
+}
+
+/// Check a configuration with `xrandr --dryrun`, without applying it.
+pub fn validate_configuration(outputs: &[OutputConfig]) -> Result<(), String> {
+    configure(outputs, true)
+}
+
+fn configure(outputs: &[OutputConfig], dry_run: bool) -> Result<(), String> {
     // Get current outputs to find ones we need to turn off
     let current_outputs = query_outputs(false)?;
+    let screens = screen_count();
+
+    // Refuse to guess where outputs of a missing screen should go
+    if let Some(output) = outputs.iter().find(|o| o.screen >= screens) {
+        return Err(i18n::tr_with("error.missingScreen", &[
+            ("output", &output.name),
+            ("screen", &output.screen.to_string()),
+            ("count", &screens.to_string()),
+        ]));
+    }
+
+    for screen in 0..screens {
+        let screen_outputs: Vec<&OutputConfig> = outputs.iter().filter(|o| o.screen == screen).collect();
+        if screen_outputs.is_empty() {
+            continue;
+        }
+        ensure_custom_modes(screen, &screen_outputs)?;
+
+        let current_screen_outputs: Vec<&OutputConfig> = current_outputs
+            .iter()
+            .filter(|o| o.screen == screen)
+            .collect();
+
+        let mut args = build_apply_args(&screen_outputs, &current_screen_outputs);
+        if dry_run {
+            args.insert(0, "--dryrun".to_string());
+        }
+        run_on_screen(screen, args)?;
+    }
+
+    Ok(())
+}
+
+/// Change only mode and refresh rate of the given outputs.
+/// Positions, rotation and all other outputs are left as they are.
+pub fn apply_modes(outputs: &[OutputConfig]) -> Result<(), String> {
+    for screen in 0..screen_count() {
+        let screen_outputs: Vec<&OutputConfig> = outputs.iter().filter(|o| o.screen == screen).collect();
+        if !screen_outputs.is_empty() {
+            ensure_custom_modes(screen, &screen_outputs)?;
+            run_on_screen(screen, build_mode_args(&screen_outputs))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Change only position and primary flag of the given outputs.
+/// Modes, rotation and all other outputs are left as they are.
+pub fn apply_positions(outputs: &[OutputConfig]) -> Result<(), String> {
+    for screen in 0..screen_count() {
+        let screen_outputs: Vec<&OutputConfig> = outputs.iter().filter(|o| o.screen == screen).collect();
+        if !screen_outputs.is_empty() {
+            run_on_screen(screen, build_position_args(&screen_outputs))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Create the custom modes of `outputs` that the X server doesn't have yet
+/// (`--newmode`) and add them to their outputs (`--addmode`). Custom modes
+/// are lost when the X session ends, so a fresh session needs them again.
+fn ensure_custom_modes(screen: u32, outputs: &[&OutputConfig]) -> Result<(), String> {
+    let custom: Vec<(&OutputConfig, String, Vec<String>)> = outputs
+        .iter()
+        .filter(|o| o.enabled)
+        .filter_map(|o| {
+            let (name, timings) = split_modeline(o.modeline.as_deref()?)?;
+            Some((*o, name, timings))
+        })
+        .collect();
+    if custom.is_empty() {
+        return Ok(());
+    }
+
+    let query = Command::new("xrandr")
+        .args(["--screen", &screen.to_string(), "--query"])
+        .output()
+        .map_err(|e| i18n::tr_with("error.runXrandr", &[("error", &e.to_string())]))?;
+    let known = parse_output_mode_names(&String::from_utf8_lossy(&query.stdout));
+
+    for (output, name, timings) in custom {
+        let has_mode = known
+            .iter()
+            .any(|(o, modes)| *o == output.name && modes.contains(&name));
+        if has_mode {
+            continue;
+        }
+
+        // Fails if the mode exists without being on any output; --addmode tells
+        let mut newmode = vec!["--newmode".to_string(), name.clone()];
+        newmode.extend(timings);
+        if let Err(e) = run_on_screen(screen, newmode) {
+            log::debug!("xrandr --newmode {}: {}", name, e.trim());
+        }
+        run_on_screen(screen, vec!["--addmode".to_string(), output.name.clone(), name.clone()])
+            .map_err(|e| i18n::tr_with("error.addMode", &[("mode", &name), ("output", &output.name), ("error", e.trim())]))?;
+    }
+
+    Ok(())
+}
+
+/// The mode names listed under each output of xrandr --query output.
+fn parse_output_mode_names(output: &str) -> Vec<(String, Vec<String>)> {
+    let mut outputs: Vec<(String, Vec<String>)> = Vec::new();
+    for line in output.lines() {
+        if line.contains(" connected") || line.contains(" disconnected") {
+            if let Some(name) = line.split_whitespace().next() {
+                outputs.push((name.to_string(), Vec::new()));
+            }
+        } else if line.starts_with("   ") {
+            if let (Some((_, modes)), Some(mode)) = (outputs.last_mut(), line.split_whitespace().next()) {
+                modes.push(mode.to_string());
+            }
+        }
+    }
+    outputs
+}
+
+/// The `--mode` (and `--rate`) arguments for an output: custom modes are
+/// selected by name, since their refresh rate identifies nothing. Without a
+/// refresh rate, xrandr picks the preferred one.
+fn mode_args(output: &OutputConfig) -> Vec<String> {
+    if let Some((name, _)) = output.modeline.as_deref().and_then(split_modeline) {
+        return vec!["--mode".to_string(), name];
+    }
+    let mut args = vec!["--mode".to_string(), format!("{}x{}", output.width, output.height)];
+    if output.refresh_rate > 0.0 {
+        args.extend(["--rate".to_string(), format!("{:.2}", output.refresh_rate)]);
+    }
+    args
+}
+
+/// Run xrandr with `args` against one X screen.
+fn run_on_screen(screen: u32, args: Vec<String>) -> Result<(), String> {
+    let output = Command::new("xrandr")
+        .args(["--screen".to_string(), screen.to_string()])
+        .args(&args)
+        .output()
+        .map_err(|e| i18n::tr_with("error.runXrandr", &[("error", &e.to_string())]))?;
+
+    if !output.status.success() {
+        return Err(i18n::tr_with("error.xrandrScreen", &[
+            ("screen", &screen.to_string()),
+            ("error", &String::from_utf8_lossy(&output.stderr)),
+        ]));
+    }
+
+    Ok(())
+}
+
+/// Build xrandr arguments that configure `outputs` and turn off any other
+/// enabled output in `current_outputs`.
+fn build_apply_args(outputs: &[&OutputConfig], current_outputs: &[&OutputConfig]) -> Vec<String> {
     let profile_output_names: Vec<&str> = outputs.iter().map(|o| o.name.as_str()).collect();
 
     let mut args = Vec::new();
 
     // First, turn off any connected outputs not in the profile
-    for current in &current_outputs {
-        if current.enabled && !profile_output_names.contains(&current.name.as_str()) {
+    // (non-desktop outputs such as VR headsets are managed by their own runtime)
+    for current in current_outputs {
+        if current.enabled && !current.non_desktop && !profile_output_names.contains(&current.name.as_str()) {
             args.push("--output".to_string());
             args.push(current.name.clone());
             args.push("--off".to_string());
@@ -268,13 +637,8 @@ pub fn apply_configuration(outputs: &[OutputConfig]) -> Result<(), String> {
         args.push(output.name.clone());
 
         if output.enabled {
-            // Mode
-            args.push("--mode".to_string());
-            args.push(format!("{}x{}", output.width, output.height));
-
-            // Refresh rate
-            args.push("--rate".to_string());
-            args.push(format!("{:.2}", output.refresh_rate));
+            // Mode and refresh rate
+            args.extend(mode_args(output));
 
             // Position
             args.push("--pos".to_string());
@@ -294,24 +658,121 @@ pub fn apply_configuration(outputs: &[OutputConfig]) -> Result<(), String> {
                 args.push("--scale".to_string());
                 args.push(format!("{}x{}", output.scale, output.scale));
             }
+
+            // Brightness and gamma (only if captured in the profile)
+            if let Some(brightness) = output.brightness {
+                args.push("--brightness".to_string());
+                args.push(format!("{:.2}", brightness));
+            }
+            if let Some([r, g, b]) = output.gamma {
+                args.push("--gamma".to_string());
+                args.push(format!("{:.2}:{:.2}:{:.2}", r, g, b));
+            }
         } else {
             args.push("--off".to_string());
         }
     }
 
-    let output = Command::new("xrandr")
-        .args(&args)
-        .output()
-        .map_err(|e| format!("Failed to execute xrandr: {}", e))?;
+    args
+}
 
-    if !output.status.success() {
-        return Err(format!(
-            "xrandr failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+/// Build xrandr arguments that set mode and refresh rate of `outputs` only.
+fn build_mode_args(outputs: &[&OutputConfig]) -> Vec<String> {
+    outputs
+        .iter()
+        .flat_map(|output| {
+            let mut args = vec!["--output".to_string(), output.name.clone()];
+            args.extend(mode_args(output));
+            args
+        })
+        .collect()
+}
+
+/// Build xrandr arguments that set position and primary flag of `outputs` only.
+fn build_position_args(outputs: &[&OutputConfig]) -> Vec<String> {
+    outputs
+        .iter()
+        .flat_map(|output| {
+            let mut args = vec![
+                "--output".to_string(),
+                output.name.clone(),
+                "--pos".to_string(),
+                format!("{}x{}", output.pos_x, output.pos_y),
+            ];
+            if output.primary {
+                args.push("--primary".to_string());
+            }
+            args
+        })
+        .collect()
+}
+
+// ============================================================================
+// Script Export
+// ============================================================================
+
+/// Build a standalone shell script that applies `outputs` with xrandr alone,
+/// one invocation per X screen. Outputs the profile doesn't list are left as
+/// they are, since the outputs connected where the script runs aren't known.
+pub fn build_script(profile_name: &str, outputs: &[OutputConfig]) -> String {
+    let mut script = String::from("#!/bin/sh\n");
+    script.push_str(&format!(
+        "# Applies the monitor profile \"{}\".\n",
+        profile_name.replace(['\r', '\n'], " ")
+    ));
+    script.push_str("# Exported by Monitor Switcher; needs nothing but xrandr.\n");
+    script.push_str("set -e\n");
+
+    let mut screens: Vec<u32> = outputs.iter().map(|o| o.screen).collect();
+    screens.sort();
+    screens.dedup();
+
+    for screen in screens {
+        let screen_outputs: Vec<&OutputConfig> = outputs.iter().filter(|o| o.screen == screen).collect();
+
+        // Custom modes may already exist, so failures to create them are fine
+        for output in screen_outputs.iter().filter(|o| o.enabled) {
+            if let Some((name, timings)) = output.modeline.as_deref().and_then(split_modeline) {
+                let name = shell_quote(&name);
+                script.push_str(&format!(
+                    "\nxrandr --screen {} --newmode {} {} 2>/dev/null || true\n",
+                    screen,
+                    name,
+                    timings.join(" ")
+                ));
+                script.push_str(&format!(
+                    "xrandr --screen {} --addmode {} {} 2>/dev/null || true\n",
+                    screen,
+                    shell_quote(&output.name),
+                    name
+                ));
+            }
+        }
+
+        script.push_str(&format!("\nxrandr --screen {}", screen));
+        for arg in build_apply_args(&screen_outputs, &[]) {
+            // One output per line
+            if arg == "--output" {
+                script.push_str(" \\\n   ");
+            }
+            script.push(' ');
+            script.push_str(&shell_quote(&arg));
+        }
+        script.push('\n');
     }
 
-    Ok(())
+    script
+}
+
+/// Quote an argument for a POSIX shell, if it needs quoting.
+fn shell_quote(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_.:+/=,".contains(c));
+    if is_plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
 }
 
 // ============================================================================
@@ -330,7 +791,7 @@ pub fn turn_off_displays() -> Result<(), String> {
         _ => {
             // Fallback: try xrandr to set all outputs to off temporarily
             // This is less ideal but works in more environments
-            Err("Failed to turn off monitors using DPMS. Try running: xset dpms force off".to_string())
+            Err(i18n::tr("error.dpms"))
         }
     }
 }
@@ -371,6 +832,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_is_preferred_mode_line() {
+        assert!(is_preferred_mode_line("3840x2160     60.00 +  30.00"));
+        assert!(is_preferred_mode_line("1920x1080     60.00*+"));
+        assert!(!is_preferred_mode_line("1920x1080     60.00*   50.00"));
+    }
+
     #[test]
     fn test_parse_position() {
         assert_eq!(parse_position("+0+0"), Some((0, 0)));
@@ -378,4 +846,184 @@ mod tests {
         assert_eq!(parse_position("-100+200"), Some((-100, 200)));
     }
 
+    #[test]
+    fn test_parse_verbose_properties() {
+        let output = "\
+Screen 0: minimum 8 x 8, current 3840 x 1080, maximum 32767 x 32767
+DP-1 connected primary 1920x1080+0+0 (0x46) normal (normal left inverted right x axis y axis) 527mm x 296mm
+\tIdentifier: 0x42
+\tGamma:      1.0:0.90:0.80
+\tBrightness: 0.80
+HDMI-1 disconnected (normal left inverted right x axis y axis)
+\tIdentifier: 0x43
+DP-2 connected 2880x1600+1920+0 (0x50) normal (normal left inverted right x axis y axis) 0mm x 0mm
+\tnon-desktop: 1 
+\t\tsupported: 0, 1
+";
+        let properties = parse_verbose_properties(output);
+        assert_eq!(properties.len(), 3);
+        assert_eq!(properties[0].name, "DP-1");
+        assert_eq!(properties[0].brightness, Some(0.8));
+        assert_eq!(properties[0].gamma, Some([1.0, 0.9, 0.8]));
+        assert!(!properties[0].non_desktop);
+        assert_eq!(properties[1].brightness, None);
+        assert_eq!(properties[1].gamma, None);
+        assert!(properties[2].non_desktop);
+    }
+
+    #[test]
+    fn test_build_apply_args_turns_off_unlisted_outputs() {
+        let profile = OutputConfig {
+            name: "DP-1".to_string(),
+            enabled: true,
+            primary: true,
+            width: 1920,
+            height: 1080,
+            ..Default::default()
+        };
+        let stale = OutputConfig {
+            name: "HDMI-1".to_string(),
+            enabled: true,
+            ..Default::default()
+        };
+
+        let args = build_apply_args(&[&profile], &[&profile, &stale]);
+        assert_eq!(
+            args,
+            [
+                "--output", "HDMI-1", "--off",
+                "--output", "DP-1", "--mode", "1920x1080", "--rate", "60.00",
+                "--pos", "0x0", "--rotate", "normal", "--primary",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_mode_args_leaves_layout_alone() {
+        let panel = OutputConfig {
+            name: "eDP-1".to_string(),
+            enabled: true,
+            width: 1920,
+            height: 1200,
+            refresh_rate: 60.0,
+            pos_x: 2560,
+            ..Default::default()
+        };
+
+        let args = build_mode_args(&[&panel]);
+        assert_eq!(args, ["--output", "eDP-1", "--mode", "1920x1200", "--rate", "60.00"]);
+
+        // No refresh rate, e.g. imported from autorandr
+        let args = build_mode_args(&[&OutputConfig { refresh_rate: 0.0, ..panel }]);
+        assert_eq!(args, ["--output", "eDP-1", "--mode", "1920x1200"]);
+    }
+
+    #[test]
+    fn test_build_position_args_leaves_modes_alone() {
+        let panel = OutputConfig {
+            name: "eDP-1".to_string(),
+            enabled: true,
+            width: 1920,
+            height: 1200,
+            pos_x: 2560,
+            pos_y: 240,
+            ..Default::default()
+        };
+        let monitor = OutputConfig {
+            name: "DP-1".to_string(),
+            enabled: true,
+            primary: true,
+            ..Default::default()
+        };
+
+        let args = build_position_args(&[&panel, &monitor]);
+        assert_eq!(
+            args,
+            ["--output", "eDP-1", "--pos", "2560x240", "--output", "DP-1", "--pos", "0x0", "--primary"]
+        );
+    }
+
+    #[test]
+    fn test_build_script() {
+        let desk = OutputConfig {
+            name: "DP-1".to_string(),
+            enabled: true,
+            primary: true,
+            width: 2560,
+            height: 1440,
+            ..Default::default()
+        };
+        let panel = OutputConfig {
+            name: "eDP-1".to_string(),
+            ..Default::default()
+        };
+        let tv = OutputConfig {
+            name: "HDMI-1".to_string(),
+            enabled: true,
+            width: 1920,
+            height: 1080,
+            screen: 1,
+            ..Default::default()
+        };
+
+        let script = build_script("Bob's desk", &[desk, panel, tv]);
+        assert_eq!(
+            script,
+            "#!/bin/sh\n\
+             # Applies the monitor profile \"Bob's desk\".\n\
+             # Exported by Monitor Switcher; needs nothing but xrandr.\n\
+             set -e\n\
+             \n\
+             xrandr --screen 0 \\\n    \
+             --output DP-1 --mode 2560x1440 --rate 60.00 --pos 0x0 --rotate normal --primary \\\n    \
+             --output eDP-1 --off\n\
+             \n\
+             xrandr --screen 1 \\\n    \
+             --output HDMI-1 --mode 1920x1080 --rate 60.00 --pos 0x0 --rotate normal\n"
+        );
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn test_custom_modes() {
+        let output = "\
+DP-1 connected primary 1920x1080+0+0 (0x4a8) normal (normal left inverted right x axis y axis) 527mm x 296mm
+  1920x1080_75.00 (0x4a8) 220.750MHz -HSync +VSync *current
+        h: width  1920 start 2064 end 2272 total 2624 skew    0 clock  84.13KHz
+        v: height 1080 start 1083 end 1088 total 1124           clock  74.85Hz
+  1920x1080 (0x46) 148.500MHz +HSync +VSync +preferred
+        h: width  1920 start 2008 end 2052 total 2200 skew    0 clock  67.50KHz
+        v: height 1080 start 1084 end 1089 total 1125           clock  60.00Hz
+HDMI-1 connected 1920x1080+1920+0 (0x46) normal (normal left inverted right x axis y axis) 527mm x 296mm
+  1920x1080 (0x46) 148.500MHz +HSync +VSync *current +preferred
+        h: width  1920 start 2008 end 2052 total 2200 skew    0 clock  67.50KHz
+        v: height 1080 start 1084 end 1089 total 1125           clock  60.00Hz
+";
+        let properties = parse_verbose_properties(output);
+        let modeline = properties[0].modeline.clone().unwrap();
+        assert_eq!(modeline, "\"1920x1080_75.00\" 220.750 1920 2064 2272 2624 1080 1083 1088 1124 -hsync +vsync");
+        assert_eq!(properties[1].modeline, None);
+
+        let (name, timings) = split_modeline(&modeline).unwrap();
+        assert_eq!(name, "1920x1080_75.00");
+        assert_eq!(timings.len(), 11);
+        let cvt = r#"Modeline "2560x1080_60.00"  230.00  2560 2720 2992 3424  1080 1083 1093 1120 -hsync +vsync"#;
+        assert_eq!(split_modeline(cvt).unwrap().0, "2560x1080_60.00");
+        assert_eq!(split_modeline("broken 1 2"), None);
+
+        let custom = OutputConfig {
+            name: "DP-1".to_string(),
+            enabled: true,
+            modeline: Some(modeline),
+            ..Default::default()
+        };
+        assert_eq!(build_mode_args(&[&custom]), ["--output", "DP-1", "--mode", "1920x1080_75.00"]);
+
+        let query = "DP-1 connected 1920x1080+0+0 (normal left inverted right x axis y axis) 527mm x 296mm\n   1920x1080     60.00 +\n   1920x1080_75.00  74.85*\n";
+        assert_eq!(
+            parse_output_mode_names(query),
+            [("DP-1".to_string(), vec!["1920x1080".to_string(), "1920x1080_75.00".to_string()])]
+        );
+    }
+
 }
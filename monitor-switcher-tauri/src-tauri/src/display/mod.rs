@@ -6,6 +6,15 @@
 //! - `windows/` - Windows CCD API implementation
 //! - `linux/` - Linux XRandR implementation
 //!
+//! `ddc.rs` parses what monitors report over DDC/CI; the backends do the I/O.
+//! `brightness.rs` sets one brightness on all monitors on top of it.
+//!
+//! `vdd.rs` reads and edits the settings file of the Virtual Display Driver,
+//! which the Windows backend drives to create virtual displays.
+//!
+//! `simulate.rs` holds an in-memory fake that both platform backends defer to when
+//! the app is started with `--simulate <fixture.json>`.
+//!
 //! ## Architecture
 //!
 //! Each platform module exports the same public API, allowing the rest of the application
@@ -31,9 +40,359 @@ mod linux;
 #[cfg(target_os = "linux")]
 pub use linux::*;
 
+mod simulate;
+pub use simulate::{enable_simulation, is_simulating};
+
+mod ddc;
+pub use ddc::{get_monitor_capabilities, get_vcp, set_vcp, MonitorCapabilities, VcpValue};
+
+mod brightness;
+pub use brightness::{set_all_brightness, BrightnessTarget};
+
+#[cfg_attr(not(windows), allow(dead_code))]
+mod vdd;
+
+// ============================================================================
+// Shared Types
+// ============================================================================
+
+/// Resolution and refresh rate of one active display, for mode-only changes
+/// that leave positions and topology untouched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayMode {
+    /// Platform identity of the display (output name on Linux, adapter+target on Windows)
+    pub key: String,
+    pub width: u32,
+    pub height: u32,
+    /// Refresh rate in Hz
+    pub refresh_rate: f64,
+    /// Built-in laptop panel
+    pub internal: bool,
+}
+
+/// Desktop bounds of one monitor in a layout from another tool, to be mapped
+/// onto the connected monitors.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub struct MonitorGeometry {
+    /// Hardware ID as the tool recorded it, e.g. `MONITOR\DEL40F4\...`
+    pub hardware_id: String,
+    /// Monitor name, if the tool recorded one
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    /// Desktop size, after rotation
+    pub width: u32,
+    pub height: u32,
+    /// Refresh rate in Hz. None keeps the current one.
+    pub refresh_rate: Option<f64>,
+    /// Clockwise quarter turns
+    pub quarter_turns: u32,
+    pub primary: bool,
+}
+
+/// A display a virtual display driver creates, e.g. for streaming to a
+/// Moonlight client with no monitor attached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualDisplay {
+    pub width: u32,
+    pub height: u32,
+    /// Refresh rate in Hz
+    pub refresh_rate: u32,
+}
+
+/// EDID of one connected monitor, as the system uses it.
+#[derive(Debug, Clone)]
+pub struct RawEdid {
+    /// `MonitorDetails::monitor_id` of the monitor
+    pub monitor_id: String,
+    /// Name reported by the monitor, or the monitor ID without one
+    pub name: String,
+    /// The base block followed by any extension blocks
+    pub edid: Vec<u8>,
+    /// An EDID override replaces what the monitor sent
+    pub overridden: bool,
+}
+
+/// A display adapter (GPU) and the monitors connected to it.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpuInfo {
+    /// Human-readable name, e.g. "NVIDIA GeForce RTX 4080"
+    pub name: String,
+    /// Adapter identity: the LUID as "HIGH:LOW" on Windows, the RandR provider id on Linux
+    pub id: String,
+    /// Adapter device path (Windows only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_path: Option<String>,
+    /// Driver version, where the platform exposes it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver_version: Option<String>,
+    /// Connected monitors, as `MonitorDetails::monitor_id`
+    pub monitors: Vec<String>,
+}
+
+// ============================================================================
+// Layout Templates
+// ============================================================================
+
+/// Built-in layout computed from the connected monitors rather than loaded from
+/// a saved profile. "PC screen" is the built-in panel, or the primary display
+/// on machines without one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Template {
+    /// Only the PC screen
+    PcOnly,
+    /// Only the external monitors
+    ExternalOnly,
+    /// All monitors, external ones left of the PC screen
+    ExtendLeft,
+    /// All monitors, external ones right of the PC screen
+    ExtendRight,
+    /// All monitors showing the same picture
+    Duplicate,
+}
+
+impl Template {
+    pub const ALL: [Template; 5] = [
+        Template::PcOnly,
+        Template::ExternalOnly,
+        Template::ExtendLeft,
+        Template::ExtendRight,
+        Template::Duplicate,
+    ];
+
+    /// Stable identifier, used in tray menu ids.
+    pub fn id(self) -> &'static str {
+        match self {
+            Template::PcOnly => "pcOnly",
+            Template::ExternalOnly => "externalOnly",
+            Template::ExtendLeft => "extendLeft",
+            Template::ExtendRight => "extendRight",
+            Template::Duplicate => "duplicate",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Template> {
+        Template::ALL.into_iter().find(|t| t.id() == id)
+    }
+}
+
+/// Largest resolution every display supports, given each display's supported
+/// resolutions. Duplicating displays with different native resolutions needs
+/// one they all share. None if there is none.
+pub fn best_common_resolution(supported: &[Vec<(u32, u32)>]) -> Option<(u32, u32)> {
+    let (first, rest) = supported.split_first()?;
+    first
+        .iter()
+        .copied()
+        .filter(|resolution| rest.iter().all(|modes| modes.contains(resolution)))
+        .max_by_key(|&(width, height)| (width as u64 * height as u64, width))
+}
+
+// ============================================================================
+// Rotation
+// ============================================================================
+
+/// Rotation after turning a display by `quarter_turns` of 90°, clockwise for
+/// positive values. Rotations use the DISPLAYCONFIG_ROTATION numbering of
+/// `MonitorDetails::rotation`: 1 upright, 2 = 90°, 3 = 180°, 4 = 270° clockwise.
+pub fn rotate_by(rotation: u32, quarter_turns: i32) -> u32 {
+    let steps = rotation.clamp(1, 4) as i32 - 1;
+    (steps + quarter_turns).rem_euclid(4) as u32 + 1
+}
+
+// ============================================================================
+// Monitor Matching
+// ============================================================================
+
+/// How the monitors of a profile were found among the connected ones, from
+/// the most to the least reliable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchTier {
+    /// Same source and target IDs as when saved
+    #[cfg(windows)]
+    IdPairs,
+    /// Same GDI device name (`\\.\DISPLAYn`)
+    #[cfg(windows)]
+    GdiName,
+    /// Same EDID friendly name
+    #[cfg(windows)]
+    FriendlyName,
+    /// The saved adapter IDs swapped for the current ones wholesale
+    #[cfg(windows)]
+    BulkReplacement,
+    /// Nothing matched; the saved IDs are used as they are
+    #[cfg(windows)]
+    Unmatched,
+    /// Outputs are found by their name
+    #[cfg(target_os = "linux")]
+    OutputName,
+}
+
+// ============================================================================
+// Post-Apply Verification
+// ============================================================================
+
+/// Settings of one display in desktop orientation, for comparing what was
+/// requested with what the driver applied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplayState {
+    pub width: u32,
+    pub height: u32,
+    pub x: i32,
+    pub y: i32,
+    /// Refresh rate in Hz; 0 when requesting whatever the driver picks.
+    pub refresh_rate: f64,
+    /// DISPLAYCONFIG_ROTATION numbering, as in `MonitorDetails::rotation`.
+    pub rotation: u32,
+}
+
+/// Refresh rates closer than this are the same rate, reported with rounding.
+const REFRESH_RATE_TOLERANCE: f64 = 0.01;
+
+/// What differs between the requested and the applied state of a display, as
+/// one human-readable message per setting.
+pub fn describe_discrepancies(requested: &DisplayState, applied: &DisplayState) -> Vec<String> {
+    let mut found = Vec::new();
+
+    if (requested.width, requested.height) != (applied.width, applied.height) {
+        found.push(format!(
+            "resolution is {}x{} instead of {}x{}",
+            applied.width, applied.height, requested.width, requested.height
+        ));
+    }
+    if requested.refresh_rate > 0.0 && (requested.refresh_rate - applied.refresh_rate).abs() >= REFRESH_RATE_TOLERANCE {
+        found.push(format!(
+            "refresh rate is {:.2} Hz instead of {:.2} Hz",
+            applied.refresh_rate, requested.refresh_rate
+        ));
+    }
+    if (requested.x, requested.y) != (applied.x, applied.y) {
+        found.push(format!(
+            "position is {}, {} instead of {}, {}",
+            applied.x, applied.y, requested.x, requested.y
+        ));
+    }
+    if requested.rotation != applied.rotation {
+        let degrees = |rotation: u32| (rotation.clamp(1, 4) - 1) * 90;
+        found.push(format!(
+            "rotation is {}° instead of {}°",
+            degrees(applied.rotation),
+            degrees(requested.rotation)
+        ));
+    }
+
+    found
+}
+
+// ============================================================================
+// Primary Display
+// ============================================================================
+
+/// Index of the primary display, given the desktop position of each display's
+/// source. Windows keeps the primary source at the desktop origin; in a layout
+/// that isn't normalized the source closest to the origin is taken instead.
+/// Clones share their source's position, so only the first of them is primary.
+pub fn primary_index(positions: &[(i32, i32)]) -> Option<usize> {
+    positions.iter().position(|&p| p == (0, 0)).or_else(|| {
+        positions
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, &(x, y))| (x as i64).abs() + (y as i64).abs())
+            .map(|(i, _)| i)
+    })
+}
+
+// ============================================================================
+// Hardware Fingerprint
+// ============================================================================
+
+/// Normalized identity of a set of monitors: their IDs lower-cased, sorted and
+/// joined, so the result doesn't depend on enumeration order.
+pub fn monitor_fingerprint<S: AsRef<str>>(ids: &[S]) -> String {
+    let mut ids: Vec<String> = ids
+        .iter()
+        .map(|id| id.as_ref().trim().to_lowercase())
+        .filter(|id| !id.is_empty())
+        .collect();
+    ids.sort();
+    ids.dedup();
+    ids.join(";")
+}
+
+/// Fingerprint of the monitors connected right now.
+pub fn current_fingerprint() -> Result<String, String> {
+    Ok(monitor_fingerprint(&connected_monitor_ids()?))
+}
+
 // ============================================================================
 // Compile-time check for unsupported platforms
 // ============================================================================
 
 #[cfg(not(any(windows, target_os = "linux")))]
 compile_error!("Unsupported platform. Only Windows and Linux are supported.");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_discrepancies() {
+        let requested = DisplayState { width: 2560, height: 1440, x: 0, y: 0, refresh_rate: 60.0, rotation: 1 };
+        assert!(describe_discrepancies(&requested, &DisplayState { refresh_rate: 60.004, ..requested }).is_empty());
+
+        let applied = DisplayState { refresh_rate: 59.951, rotation: 2, ..requested };
+        assert_eq!(
+            describe_discrepancies(&requested, &applied),
+            ["refresh rate is 59.95 Hz instead of 60.00 Hz", "rotation is 90° instead of 0°"]
+        );
+
+        // Without a requested rate any rate will do
+        let any_rate = DisplayState { refresh_rate: 0.0, ..requested };
+        assert!(describe_discrepancies(&any_rate, &applied)[0].starts_with("rotation"));
+    }
+
+    #[test]
+    fn test_primary_index() {
+        assert_eq!(primary_index(&[(-1920, 0), (0, 0), (1920, 0)]), Some(1));
+        // Duplicated displays share the primary source's origin
+        assert_eq!(primary_index(&[(0, 0), (0, 0)]), Some(0));
+        assert_eq!(primary_index(&[(1920, 0), (-100, 40)]), Some(1));
+        assert_eq!(primary_index(&[]), None);
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_order_and_case() {
+        let docked = monitor_fingerprint(&[r"\\?\DISPLAY#DEL4093#UID4353", "eDP-1:BOE0A1C"]);
+        let reordered = monitor_fingerprint(&["edp-1:boe0a1c", r"\\?\display#del4093#uid4353"]);
+
+        assert_eq!(docked, reordered);
+        assert_ne!(docked, monitor_fingerprint(&["eDP-1:BOE0A1C"]));
+        assert_eq!(monitor_fingerprint::<&str>(&[]), "");
+    }
+
+    #[test]
+    fn test_rotate_by() {
+        assert_eq!(rotate_by(1, 1), 2);
+        assert_eq!(rotate_by(4, 1), 1);
+        assert_eq!(rotate_by(1, -1), 4);
+        assert_eq!(rotate_by(2, 6), 4);
+        assert_eq!(rotate_by(0, 0), 1);
+    }
+
+    #[test]
+    fn test_best_common_resolution() {
+        let laptop = vec![(2560, 1600), (1920, 1200), (1920, 1080), (1280, 720)];
+        let tv = vec![(3840, 2160), (1920, 1080), (1280, 720)];
+        let projector = vec![(1280, 720), (1024, 768)];
+
+        assert_eq!(best_common_resolution(&[laptop.clone(), tv.clone()]), Some((1920, 1080)));
+        assert_eq!(best_common_resolution(&[laptop, tv, projector.clone()]), Some((1280, 720)));
+        assert_eq!(best_common_resolution(&[projector, vec![(1920, 1080)]]), None);
+        assert_eq!(best_common_resolution(&[]), None);
+    }
+}
@@ -0,0 +1,60 @@
+//! Simulated display backend for development without the target hardware.
+//!
+//! When enabled (via `--simulate <fixture.json>`), the platform backends read and
+//! write this in-memory state instead of touching the real displays.
+//! Single responsibility: hold the simulated display state.
+
+use super::{DisplaySettings, MonitorAdditionalInfo};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+struct SimulatedState {
+    /// Settings and monitor info as loaded from the fixture.
+    fixture: DisplaySettings,
+    additional_info: Vec<MonitorAdditionalInfo>,
+    /// Settings as last "applied".
+    current: DisplaySettings,
+}
+
+static STATE: OnceLock<Mutex<SimulatedState>> = OnceLock::new();
+
+fn lock_state() -> Option<MutexGuard<'static, SimulatedState>> {
+    STATE
+        .get()
+        .map(|state| state.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Switch the display backend to simulation. Can only be enabled once, at startup.
+pub fn enable_simulation(settings: DisplaySettings, additional_info: Vec<MonitorAdditionalInfo>) {
+    let _ = STATE.set(Mutex::new(SimulatedState {
+        fixture: settings.clone(),
+        additional_info,
+        current: settings,
+    }));
+}
+
+/// Whether the display backend is simulated.
+pub fn is_simulating() -> bool {
+    STATE.get().is_some()
+}
+
+/// Currently "applied" settings. None when not simulating.
+pub(super) fn current_settings() -> Option<DisplaySettings> {
+    lock_state().map(|state| state.current.clone())
+}
+
+/// Fixture settings together with their monitor info. None when not simulating.
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+pub(super) fn fixture() -> Option<(DisplaySettings, Vec<MonitorAdditionalInfo>)> {
+    lock_state().map(|state| (state.fixture.clone(), state.additional_info.clone()))
+}
+
+/// Record applied settings. Returns false when not simulating.
+pub(super) fn apply(settings: &DisplaySettings) -> bool {
+    match lock_state() {
+        Some(mut state) => {
+            state.current = settings.clone();
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,141 @@
+//! Settings file of the Virtual Display Driver (`vdd_settings.xml`).
+//!
+//! The driver shows as many monitors as `<monitors><count>` says and offers
+//! each of them every `<resolution>` listed under `<resolutions>`. The file is
+//! edited as text, so comments and settings this app doesn't know about stay
+//! as they are.
+//! Single responsibility: read and change the monitor count and offered modes.
+
+use super::VirtualDisplay;
+use std::ops::Range;
+
+/// Number of monitors the driver is set to show.
+pub fn monitor_count(xml: &str) -> Option<usize> {
+    let range = count_range(xml)?;
+    xml[range].trim().parse().ok()
+}
+
+/// `xml` with the monitor count set to `count`.
+pub fn with_monitor_count(xml: &str, count: usize) -> Result<String, String> {
+    let range = count_range(xml).ok_or("The settings file has no monitor count")?;
+    Ok(format!("{}{}{}", &xml[..range.start], count, &xml[range.end..]))
+}
+
+/// `xml` with a `<resolution>` entry for each mode of `displays` the driver
+/// doesn't offer yet.
+pub fn with_resolutions(xml: &str, displays: &[VirtualDisplay]) -> Result<String, String> {
+    let section = inner(xml, "resolutions", 0).ok_or("The settings file has no <resolutions> section")?;
+    let offered = offered_resolutions(&xml[section.clone()]);
+
+    let mut missing: Vec<&VirtualDisplay> = Vec::new();
+    for display in displays {
+        if !offered.contains(display) && !missing.contains(&display) {
+            missing.push(display);
+        }
+    }
+    if missing.is_empty() {
+        return Ok(xml.to_string());
+    }
+
+    // Insert before the line of the closing tag, indented one level deeper
+    let line_start = xml[..section.end].rfind('\n').map_or(0, |i| i + 1);
+    let (at, indent, lead) = if xml[line_start..section.end].trim().is_empty() {
+        (line_start, &xml[line_start..section.end], "")
+    } else {
+        (section.end, "", "\n")
+    };
+
+    let mut entries = lead.to_string();
+    for display in missing {
+        entries.push_str(&format!(
+            "{i}    <resolution>\n{i}        <width>{}</width>\n{i}        <height>{}</height>\n{i}        <refresh_rate>{}</refresh_rate>\n{i}    </resolution>\n",
+            display.width,
+            display.height,
+            display.refresh_rate,
+            i = indent
+        ));
+    }
+
+    Ok(format!("{}{}{}", &xml[..at], entries, &xml[at..]))
+}
+
+/// Modes listed in the body of `<resolutions>`.
+fn offered_resolutions(section: &str) -> Vec<VirtualDisplay> {
+    let mut offered = Vec::new();
+    let mut from = 0;
+
+    while let Some(entry) = inner(section, "resolution", from) {
+        let body = &section[entry.clone()];
+        let number = |tag: &str| inner(body, tag, 0).and_then(|range| body[range].trim().parse::<u32>().ok());
+        if let (Some(width), Some(height), Some(refresh_rate)) = (number("width"), number("height"), number("refresh_rate")) {
+            offered.push(VirtualDisplay { width, height, refresh_rate });
+        }
+        from = entry.end;
+    }
+
+    offered
+}
+
+/// Range of the count inside `<monitors>`.
+fn count_range(xml: &str) -> Option<Range<usize>> {
+    let monitors = inner(xml, "monitors", 0)?;
+    inner(xml, "count", monitors.start).filter(|count| count.end <= monitors.end)
+}
+
+/// Range of the body of the first `<tag>` element at or after `from`.
+fn inner(xml: &str, tag: &str, from: usize) -> Option<Range<usize>> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = xml[from..].find(&open)? + from + open.len();
+    let end = xml[start..].find(&close)? + start;
+    Some(start..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: &str = "<?xml version='1.0' encoding='utf-8'?>
+<vdd_settings>
+    <monitors>
+        <count>1</count>
+    </monitors>
+    <gpu>
+        <friendlyname>default</friendlyname>
+    </gpu>
+    <resolutions>
+        <resolution>
+            <width>1920</width>
+            <height>1080</height>
+            <refresh_rate>60</refresh_rate>
+        </resolution>
+    </resolutions>
+</vdd_settings>
+";
+
+    #[test]
+    fn test_monitor_count() {
+        assert_eq!(monitor_count(SETTINGS), Some(1));
+
+        let updated = with_monitor_count(SETTINGS, 2).unwrap();
+        assert_eq!(monitor_count(&updated), Some(2));
+        assert!(updated.contains("<friendlyname>default</friendlyname>"));
+
+        assert!(with_monitor_count("<vdd_settings></vdd_settings>", 1).is_err());
+    }
+
+    #[test]
+    fn test_with_resolutions() {
+        let fhd = VirtualDisplay { width: 1920, height: 1080, refresh_rate: 60 };
+        let uhd = VirtualDisplay { width: 3840, height: 2160, refresh_rate: 120 };
+
+        // Offered already
+        assert_eq!(with_resolutions(SETTINGS, &[fhd]).unwrap(), SETTINGS);
+
+        let updated = with_resolutions(SETTINGS, &[fhd, uhd, uhd]).unwrap();
+        let section = inner(&updated, "resolutions", 0).unwrap();
+        assert_eq!(offered_resolutions(&updated[section]), vec![fhd, uhd]);
+        assert!(updated.contains("\n        <resolution>\n            <width>3840</width>\n"));
+        assert!(updated.contains("</resolution>\n    </resolutions>"));
+    }
+}
@@ -0,0 +1,252 @@
+//! Display adapter (GPU) information.
+//!
+//! Single responsibility: list the adapters driving the connected monitors,
+//! with the names and driver versions Windows keeps for them, and tell
+//! virtual adapters from physical ones.
+
+use super::super::GpuInfo;
+use super::api::{get_adapter_name, get_display_settings, get_monitor_additional_info, get_source_gdi_device_name};
+use super::types::LUID;
+use std::collections::HashMap;
+use std::mem;
+use std::ptr;
+
+use windows_sys::Win32::Graphics::Gdi::{EnumDisplayDevicesW, DISPLAY_DEVICEW, DISPLAY_DEVICE_ACTIVE};
+use windows_sys::Win32::System::Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ};
+use windows_sys::Win32::UI::WindowsAndMessaging::EDD_GET_DEVICE_INTERFACE_NAME;
+
+/// Prefix of the kernel registry path in `DISPLAY_DEVICEW::DeviceKey`.
+const MACHINE_KEY_PREFIX: &str = r"\Registry\Machine\";
+
+/// GDI view of one adapter output.
+struct GdiDevice {
+    /// Adapter description, e.g. "NVIDIA GeForce RTX 4080"
+    description: String,
+    /// Driver registry key, relative to HKEY_LOCAL_MACHINE
+    driver_key: String,
+}
+
+/// List the display adapters and the monitors connected to each of them.
+pub fn get_gpu_info() -> Result<Vec<GpuInfo>, String> {
+    // All paths, so adapters without an active monitor are listed too
+    let settings = get_display_settings(false)?;
+    let gdi_devices = enum_gdi_devices();
+
+    let mut adapters: Vec<(LUID, GpuInfo)> = Vec::new();
+
+    for path in &settings.path_info_array {
+        let adapter_id = path.target_info.adapter_id;
+        let index = match adapters.iter().position(|(id, _)| *id == adapter_id) {
+            Some(index) => index,
+            None => {
+                adapters.push((adapter_id, GpuInfo {
+                    name: String::new(),
+                    id: format!("{:08X}:{:08X}", adapter_id.high_part, adapter_id.low_part),
+                    device_path: get_adapter_name(adapter_id),
+                    driver_version: None,
+                    monitors: Vec::new(),
+                }));
+                adapters.len() - 1
+            }
+        };
+        let gpu = &mut adapters[index].1;
+
+        // Every source of the adapter maps to a GDI device carrying its description
+        if gpu.name.is_empty() {
+            let device = get_source_gdi_device_name(path.source_info.adapter_id, path.source_info.id)
+                .and_then(|name| gdi_devices.get(&name));
+            if let Some(device) = device {
+                gpu.name = device.description.clone();
+                gpu.driver_version = read_driver_version(&device.driver_key);
+            }
+        }
+
+        // The all-paths query lists each target once per source it could be driven by
+        let info = get_monitor_additional_info(adapter_id, path.target_info.id);
+        if info.valid && !info.monitor_device_path.is_empty() && !gpu.monitors.contains(&info.monitor_device_path) {
+            gpu.monitors.push(info.monitor_device_path);
+        }
+    }
+
+    Ok(adapters
+        .into_iter()
+        .map(|(_, mut gpu)| {
+            if gpu.name.is_empty() {
+                gpu.name = gpu.device_path.clone().unwrap_or_else(|| format!("Adapter {}", gpu.id));
+            }
+            gpu
+        })
+        .collect())
+}
+
+/// Device path fragments of virtual and remote-streaming adapters.
+const VIRTUAL_ADAPTER_MARKERS: &[&str] = &[
+    "PARSEC",
+    "SPACEDESK",
+    "IDDSAMPLEDRIVER",
+    "MTTVDD",
+    "VIRTUALDISPLAY",
+    "VIRTUAL_DISPLAY",
+    "USBMMIDD",
+    "AMYUNI",
+];
+
+/// DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INDIRECT_VIRTUAL, reported by IddCx
+/// drivers with no physical connector. DisplayLink docks report
+/// INDIRECT_WIRED instead and drive real monitors.
+const OUTPUT_TECHNOLOGY_INDIRECT_VIRTUAL: u32 = 17;
+
+/// Whether an adapter is a virtual or remote-streaming one (Parsec, spacedesk,
+/// IddCx drivers), from its device path and, if known, the output technology
+/// of one of its targets. Software-enumerated adapters (`ROOT#...`) count as
+/// virtual, except the Microsoft Basic Display Adapter.
+pub fn is_virtual_adapter(adapter_name: &str, output_technology: Option<u32>) -> bool {
+    if output_technology == Some(OUTPUT_TECHNOLOGY_INDIRECT_VIRTUAL) {
+        return true;
+    }
+
+    // Device paths look like \\?\ROOT#DISPLAY#0000#{...} or \\?\PCI#VEN_10DE&...
+    let path = adapter_name.to_uppercase();
+    let path = path.strip_prefix(r"\\?\").unwrap_or(&path);
+    (path.starts_with("ROOT#") && !path.starts_with("ROOT#BASICDISPLAY"))
+        || VIRTUAL_ADAPTER_MARKERS.iter().any(|marker| path.contains(marker))
+}
+
+/// Enumerate GDI display devices, keyed by device name (e.g. `\\.\DISPLAY1`).
+fn enum_gdi_devices() -> HashMap<String, GdiDevice> {
+    let mut devices = HashMap::new();
+
+    for index in 0.. {
+        let mut device: DISPLAY_DEVICEW = unsafe { mem::zeroed() };
+        device.cb = mem::size_of::<DISPLAY_DEVICEW>() as u32;
+
+        if unsafe { EnumDisplayDevicesW(ptr::null(), index, &mut device, 0) } == 0 {
+            break;
+        }
+
+        let key = wide_to_string(&device.DeviceKey);
+        let driver_key = key
+            .get(..MACHINE_KEY_PREFIX.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(MACHINE_KEY_PREFIX))
+            .map(|_| key[MACHINE_KEY_PREFIX.len()..].to_string())
+            .unwrap_or_default();
+
+        devices.insert(wide_to_string(&device.DeviceName), GdiDevice {
+            description: wide_to_string(&device.DeviceString),
+            driver_key,
+        });
+    }
+
+    devices
+}
+
+/// Get the display number Windows Settings shows for a monitor, from its
+/// device path. The `\\.\DISPLAYn` suffix of the GDI name is not that
+/// number; Settings counts the active monitors in enumeration order.
+pub fn settings_display_number(monitor_device_path: &str) -> Option<u32> {
+    position_in(monitor_device_path, &enum_active_monitors())
+}
+
+/// 1-based position of a monitor device path in an enumerated list.
+fn position_in(monitor_device_path: &str, monitors: &[String]) -> Option<u32> {
+    if monitor_device_path.is_empty() {
+        return None;
+    }
+    monitors
+        .iter()
+        .position(|path| path.eq_ignore_ascii_case(monitor_device_path))
+        .map(|index| index as u32 + 1)
+}
+
+/// Device interface paths of the active monitors, adapter outputs first and
+/// then the monitors on each, as EnumDisplayDevices lists them.
+fn enum_active_monitors() -> Vec<String> {
+    let mut monitors = Vec::new();
+
+    for index in 0.. {
+        let mut device: DISPLAY_DEVICEW = unsafe { mem::zeroed() };
+        device.cb = mem::size_of::<DISPLAY_DEVICEW>() as u32;
+
+        if unsafe { EnumDisplayDevicesW(ptr::null(), index, &mut device, 0) } == 0 {
+            break;
+        }
+
+        for monitor_index in 0.. {
+            let mut monitor: DISPLAY_DEVICEW = unsafe { mem::zeroed() };
+            monitor.cb = mem::size_of::<DISPLAY_DEVICEW>() as u32;
+
+            let found = unsafe {
+                EnumDisplayDevicesW(device.DeviceName.as_ptr(), monitor_index, &mut monitor, EDD_GET_DEVICE_INTERFACE_NAME)
+            };
+            if found == 0 {
+                break;
+            }
+            if monitor.StateFlags & DISPLAY_DEVICE_ACTIVE != 0 {
+                monitors.push(wide_to_string(&monitor.DeviceID));
+            }
+        }
+    }
+
+    monitors
+}
+
+/// Read `DriverVersion` from an adapter's driver registry key.
+fn read_driver_version(driver_key: &str) -> Option<String> {
+    if driver_key.is_empty() {
+        return None;
+    }
+
+    let sub_key: Vec<u16> = driver_key.encode_utf16().chain(Some(0)).collect();
+    let value: Vec<u16> = "DriverVersion".encode_utf16().chain(Some(0)).collect();
+    let mut buffer = [0u16; 64];
+    let mut size = mem::size_of_val(&buffer) as u32;
+
+    let result = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            sub_key.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_SZ,
+            ptr::null_mut(),
+            buffer.as_mut_ptr() as *mut _,
+            &mut size,
+        )
+    };
+
+    (result == 0).then(|| wide_to_string(&buffer)).filter(|v| !v.is_empty())
+}
+
+/// Convert a NUL-terminated UTF-16 buffer to a string.
+pub(super) fn wide_to_string(buffer: &[u16]) -> String {
+    let end = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    String::from_utf16_lossy(&buffer[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELL: &str = r"\\?\DISPLAY#DELA0F3#5&1a2b3c4d&0&UID4352#{e6f07b5f-ee97-4a90-b076-33f57bf4eaa7}";
+    const LG: &str = r"\\?\DISPLAY#GSM5B7F#5&1a2b3c4d&0&UID4353#{e6f07b5f-ee97-4a90-b076-33f57bf4eaa7}";
+
+    #[test]
+    fn test_display_number_follows_enumeration_order() {
+        // The LG drives \\.\DISPLAY2 but is listed first, so Settings calls it 1
+        let monitors = vec![LG.to_string(), DELL.to_string()];
+        assert_eq!(position_in(LG, &monitors), Some(1));
+        assert_eq!(position_in(DELL, &monitors), Some(2));
+    }
+
+    #[test]
+    fn test_display_number_ignores_case() {
+        let monitors = vec![DELL.to_lowercase()];
+        assert_eq!(position_in(DELL, &monitors), Some(1));
+    }
+
+    #[test]
+    fn test_display_number_unknown_monitor() {
+        let monitors = vec![DELL.to_string()];
+        assert_eq!(position_in(LG, &monitors), None);
+        assert_eq!(position_in("", &monitors), None);
+    }
+}
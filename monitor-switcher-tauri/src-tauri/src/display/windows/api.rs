@@ -3,7 +3,13 @@
 //! This module provides raw Windows API calls for display configuration.
 //! Single responsibility: interact with Windows CCD API.
 
+use super::adapters::is_virtual_adapter;
+use super::edid::{apply_edid_override, read_serial_number};
+use super::gdi;
+use super::super::simulate;
 use super::types::*;
+use crate::i18n;
+use log::warn;
 use std::mem;
 
 use windows_sys::Win32::Devices::Display::{
@@ -11,12 +17,17 @@ use windows_sys::Win32::Devices::Display::{
     GetDisplayConfigBufferSizes, QueryDisplayConfig, SetDisplayConfig,
     QDC_ONLY_ACTIVE_PATHS, QDC_ALL_PATHS,
     SDC_APPLY, SDC_USE_SUPPLIED_DISPLAY_CONFIG, SDC_SAVE_TO_DATABASE,
-    SDC_NO_OPTIMIZATION, SDC_ALLOW_CHANGES,
-    DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME,
+    SDC_NO_OPTIMIZATION, SDC_ALLOW_CHANGES, SDC_ALLOW_PATH_ORDER_CHANGES,
+    SDC_VALIDATE,
+    DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME, DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME,
+    DISPLAYCONFIG_DEVICE_INFO_GET_MONITOR_SPECIALIZATION, DISPLAYCONFIG_DEVICE_INFO_GET_ADAPTER_NAME,
+    DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_PREFERRED_MODE,
 };
 
+use windows_sys::Win32::System::Shutdown::LockWorkStation;
+
 use windows_sys::Win32::UI::WindowsAndMessaging::{
-    PostMessageW, HWND_BROADCAST, WM_SYSCOMMAND,
+    GetSystemMetrics, PostMessageW, HWND_BROADCAST, SM_REMOTESESSION, WM_SYSCOMMAND,
 };
 
 // ============================================================================
@@ -38,6 +49,31 @@ pub struct MonitorAdditionalInfo {
     pub valid: bool,
     pub monitor_device_path: String,
     pub monitor_friendly_device: String,
+    /// Target is a specialized display (e.g. a VR headset), not part of the desktop.
+    pub specialized: bool,
+    /// GDI device name (e.g. `\\.\DISPLAY1`) of the source driving this target.
+    pub gdi_device_name: String,
+    /// Device path of the adapter (GPU) driving this target.
+    pub adapter_name: String,
+    /// Tells apart connectors of the same type on one adapter. Monitors
+    /// daisy-chained over DisplayPort (MST) share their connector.
+    pub connector_instance: u32,
+    /// EDID serial number, empty if the monitor has none. Tells identical
+    /// monitors apart, like the ones of a daisy chain.
+    pub serial_number: String,
+    /// Target is on a virtual or remote-streaming adapter (Parsec, spacedesk, IddCx drivers).
+    pub virtual_adapter: bool,
+    /// Identity from the monitor's own EDID when a registry EDID override
+    /// replaces it. The IDs and name above are then the overridden ones.
+    pub edid_override: Option<EdidIdentity>,
+}
+
+/// EDID identity of a monitor, with the IDs in the byte order CCD reports them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EdidIdentity {
+    pub manufacture_id: u16,
+    pub product_code_id: u16,
+    pub friendly_name: String,
 }
 
 // ============================================================================
@@ -46,6 +82,10 @@ pub struct MonitorAdditionalInfo {
 
 /// Get the current display configuration.
 pub fn get_display_settings(active_only: bool) -> Result<DisplaySettings, String> {
+    if let Some(settings) = simulate::current_settings() {
+        return Ok(settings);
+    }
+
     let flags = if active_only {
         QDC_ONLY_ACTIVE_PATHS
     } else {
@@ -61,7 +101,7 @@ pub fn get_display_settings(active_only: bool) -> Result<DisplaySettings, String
     };
 
     if result != 0 {
-        return Err(format!("GetDisplayConfigBufferSizes failed with error: {}", result));
+        return Err(i18n::tr_with("error.queryDisplays", &[("api", "GetDisplayConfigBufferSizes"), ("code", &result.to_string())]));
     }
 
     if num_paths == 0 || num_modes == 0 {
@@ -85,7 +125,7 @@ pub fn get_display_settings(active_only: bool) -> Result<DisplaySettings, String
     };
 
     if result != 0 {
-        return Err(format!("QueryDisplayConfig failed with error: {}", result));
+        return Err(i18n::tr_with("error.queryDisplays", &[("api", "QueryDisplayConfig"), ("code", &result.to_string())]));
     }
 
     // Trim to actual size
@@ -104,7 +144,30 @@ pub fn get_display_settings(active_only: bool) -> Result<DisplaySettings, String
 
 /// Apply display settings.
 pub fn set_display_settings(settings: &mut DisplaySettings) -> Result<(), String> {
-    let flags = SDC_APPLY | SDC_USE_SUPPLIED_DISPLAY_CONFIG | SDC_SAVE_TO_DATABASE | SDC_NO_OPTIMIZATION;
+    set_display_settings_with_flags(settings, ApplyFlags::default())
+}
+
+/// Apply display settings using the given SetDisplayConfig options.
+pub fn set_display_settings_with_flags(settings: &mut DisplaySettings, apply_flags: ApplyFlags) -> Result<(), String> {
+    apply_display_settings(settings, apply_flags).map(|_| ())
+}
+
+/// How the settings got accepted by `apply_display_settings`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApplyAttempts {
+    /// 1 if the exact configuration was accepted, 2 after retrying with
+    /// SDC_ALLOW_CHANGES, 3 through the GDI fallback.
+    pub attempts: u32,
+    /// SetDisplayConfig flags of the last attempt.
+    pub flags: u32,
+}
+
+/// `set_display_settings_with_flags`, reporting how many attempts it took.
+pub fn apply_display_settings(settings: &mut DisplaySettings, apply_flags: ApplyFlags) -> Result<ApplyAttempts, String> {
+    let flags = to_set_display_config_flags(apply_flags);
+    if simulate::apply(settings) {
+        return Ok(ApplyAttempts { attempts: 1, flags });
+    }
 
     // First attempt without ALLOW_CHANGES
     let result = unsafe {
@@ -118,26 +181,133 @@ pub fn set_display_settings(settings: &mut DisplaySettings) -> Result<(), String
     };
 
     if result == 0 {
-        return Ok(());
+        return Ok(ApplyAttempts { attempts: 1, flags });
     }
 
-    // Second attempt with ALLOW_CHANGES
+    // Second attempt with ALLOW_CHANGES; the driver may swap timings it doesn't list
+    let strict_result = result;
     let flags_with_changes = flags | SDC_ALLOW_CHANGES;
+    let result = if apply_flags.retry_with_allow_changes {
+        unsafe {
+            SetDisplayConfig(
+                settings.path_info_array.len() as u32,
+                settings.path_info_array.as_mut_ptr() as *mut _,
+                settings.mode_info_array.len() as u32,
+                settings.mode_info_array.as_mut_ptr() as *mut _,
+                flags_with_changes,
+            )
+        }
+    } else {
+        result
+    };
+
+    if result == 0 {
+        warn!(
+            "SetDisplayConfig only accepted the settings with SDC_ALLOW_CHANGES (first attempt: {})",
+            describe_set_display_config_error(strict_result)
+        );
+        return Ok(ApplyAttempts { attempts: 2, flags: flags_with_changes });
+    }
+    let last_flags = if apply_flags.retry_with_allow_changes { flags_with_changes } else { flags };
+
+    // Last resort: some older drivers reject CCD but accept the legacy GDI API
+    warn!("SetDisplayConfig failed with error {}, falling back to ChangeDisplaySettingsEx", result);
+    gdi::apply_settings(settings)
+        .map(|_| ApplyAttempts { attempts: 3, flags: last_flags })
+        .map_err(|e| {
+            format!(
+                "SetDisplayConfig failed with error: {}; GDI fallback also failed: {}",
+                describe_set_display_config_error(result),
+                e
+            )
+        })
+}
+
+/// Re-apply the current configuration without touching the Windows display
+/// database, dropping settings Windows restored from it. With `rewrite`, the
+/// configuration is then saved over the database entry for the connected
+/// monitors, so Windows stops bringing the stale settings back.
+pub fn refresh_display_database(rewrite: bool) -> Result<(), String> {
+    if simulate::is_simulating() {
+        return Ok(());
+    }
+
+    let mut settings = get_display_settings(true)?;
+    set_display_settings_with_flags(
+        &mut settings,
+        ApplyFlags {
+            save_to_database: false,
+            no_optimization: true,
+            ..ApplyFlags::default()
+        },
+    )?;
+
+    if rewrite {
+        let mut settings = get_display_settings(true)?;
+        set_display_settings_with_flags(
+            &mut settings,
+            ApplyFlags {
+                save_to_database: true,
+                no_optimization: true,
+                ..ApplyFlags::default()
+            },
+        )?;
+    }
+    Ok(())
+}
+
+/// Ask SetDisplayConfig whether it would accept the settings, without applying them.
+/// With `allow_changes`, Windows may adjust modes the way a retry with SDC_ALLOW_CHANGES would.
+pub fn validate_display_settings(settings: &mut DisplaySettings, allow_changes: bool) -> Result<(), String> {
+    if simulate::is_simulating() {
+        return Ok(());
+    }
+
+    let mut flags = SDC_VALIDATE | SDC_USE_SUPPLIED_DISPLAY_CONFIG;
+    if allow_changes {
+        flags |= SDC_ALLOW_CHANGES;
+    }
+
     let result = unsafe {
         SetDisplayConfig(
             settings.path_info_array.len() as u32,
             settings.path_info_array.as_mut_ptr() as *mut _,
             settings.mode_info_array.len() as u32,
             settings.mode_info_array.as_mut_ptr() as *mut _,
-            flags_with_changes,
+            flags,
         )
     };
 
     if result == 0 {
         Ok(())
     } else {
-        Err(format!("SetDisplayConfig failed with error: {}", result))
+        Err(describe_set_display_config_error(result))
+    }
+}
+
+/// Explain a SetDisplayConfig error code, e.g. "87 (invalid parameter: ...)".
+pub(super) fn describe_set_display_config_error(code: i32) -> String {
+    match code {
+        5 | 31 | 50 | 87 | 1610 => format!("{} ({})", code, i18n::tr(&format!("displayError.{}", code))),
+        _ => code.to_string(),
+    }
+}
+
+/// Build the SetDisplayConfig flag set for the given options.
+fn to_set_display_config_flags(apply_flags: ApplyFlags) -> u32 {
+    let mut flags = SDC_APPLY | SDC_USE_SUPPLIED_DISPLAY_CONFIG;
+
+    if apply_flags.save_to_database {
+        flags |= SDC_SAVE_TO_DATABASE;
+    }
+    if apply_flags.no_optimization {
+        flags |= SDC_NO_OPTIMIZATION;
+    }
+    if apply_flags.allow_path_order_changes {
+        flags |= SDC_ALLOW_PATH_ORDER_CHANGES;
     }
+
+    flags
 }
 
 // ============================================================================
@@ -146,6 +316,16 @@ pub fn set_display_settings(settings: &mut DisplaySettings) -> Result<(), String
 
 /// Get additional monitor info (EDID data, friendly name) for a target.
 pub fn get_monitor_additional_info(adapter_id: LUID, target_id: u32) -> MonitorAdditionalInfo {
+    if let Some((fixture, additional_info)) = simulate::fixture() {
+        return fixture
+            .mode_info_array
+            .iter()
+            .zip(additional_info)
+            .find(|(m, _)| m.info_type == MODE_INFO_TYPE_TARGET && m.adapter_id == adapter_id && m.id == target_id)
+            .map(|(_, info)| info)
+            .unwrap_or_default();
+    }
+
     let mut device_name = DisplayConfigTargetDeviceName::default();
     device_name.header.info_type = DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME as u32;
     device_name.header.size = mem::size_of::<DisplayConfigTargetDeviceName>() as u32;
@@ -158,13 +338,23 @@ pub fn get_monitor_additional_info(adapter_id: LUID, target_id: u32) -> MonitorA
     };
 
     if result == 0 {
-        MonitorAdditionalInfo {
+        let adapter_name = get_adapter_name(adapter_id).unwrap_or_default();
+        let monitor_device_path = device_name.get_device_path();
+        let mut info = MonitorAdditionalInfo {
             manufacture_id: device_name.edid_manufacture_id,
             product_code_id: device_name.edid_product_code_id,
             valid: true,
-            monitor_device_path: device_name.get_device_path(),
+            serial_number: read_serial_number(&monitor_device_path),
+            monitor_device_path,
             monitor_friendly_device: device_name.get_friendly_name(),
-        }
+            specialized: is_specialized_target(adapter_id, target_id),
+            virtual_adapter: is_virtual_adapter(&adapter_name, Some(device_name.output_technology)),
+            adapter_name,
+            connector_instance: device_name.connector_instance,
+            ..Default::default()
+        };
+        apply_edid_override(&mut info);
+        info
     } else {
         MonitorAdditionalInfo {
             valid: false,
@@ -173,6 +363,127 @@ pub fn get_monitor_additional_info(adapter_id: LUID, target_id: u32) -> MonitorA
     }
 }
 
+/// Check whether a target is a specialized display such as a VR headset.
+/// Returns false on systems that don't support monitor specialization.
+pub fn is_specialized_target(adapter_id: LUID, target_id: u32) -> bool {
+    let mut request = DisplayConfigGetMonitorSpecialization {
+        header: DisplayConfigDeviceInfoHeader::new::<DisplayConfigGetMonitorSpecialization>(
+            DISPLAYCONFIG_DEVICE_INFO_GET_MONITOR_SPECIALIZATION,
+            adapter_id,
+            target_id,
+        ),
+        ..Default::default()
+    };
+
+    let result = unsafe {
+        DisplayConfigGetDeviceInfo(&mut request as *mut _ as *mut _)
+    };
+
+    result == 0 && request.is_specialization_enabled()
+}
+
+/// Get the preferred (native) resolution of a target as (width, height).
+pub fn get_preferred_mode(adapter_id: LUID, target_id: u32) -> Option<(u32, u32)> {
+    let mut request = DisplayConfigTargetPreferredMode {
+        header: DisplayConfigDeviceInfoHeader::new::<DisplayConfigTargetPreferredMode>(
+            DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_PREFERRED_MODE,
+            adapter_id,
+            target_id,
+        ),
+        ..Default::default()
+    };
+
+    let result = unsafe {
+        DisplayConfigGetDeviceInfo(&mut request as *mut _ as *mut _)
+    };
+
+    if result != 0 || request.width == 0 || request.height == 0 {
+        return None;
+    }
+
+    Some((request.width, request.height))
+}
+
+/// Get the GDI device name (e.g. `\\.\DISPLAY1`) for a source.
+pub fn get_source_gdi_device_name(adapter_id: LUID, source_id: u32) -> Option<String> {
+    let mut request = DisplayConfigSourceDeviceName {
+        header: DisplayConfigDeviceInfoHeader::new::<DisplayConfigSourceDeviceName>(
+            DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME,
+            adapter_id,
+            source_id,
+        ),
+        ..Default::default()
+    };
+
+    let result = unsafe {
+        DisplayConfigGetDeviceInfo(&mut request as *mut _ as *mut _)
+    };
+
+    if result != 0 {
+        return None;
+    }
+
+    Some(request.get_gdi_device_name())
+}
+
+/// Short connector label, e.g. "DP-1" or "HDMI-2", from a target's output
+/// technology (DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY) and connector instance.
+pub fn connector_label(output_technology: u32, connector_instance: u32) -> String {
+    let kind = match output_technology {
+        0 => "VGA",
+        1 => "S-Video",
+        2 => "Composite",
+        3 => "Component",
+        4 => "DVI",
+        5 => "HDMI",
+        6 => "LVDS",
+        8 => "D-Jpn",
+        9 => "SDI",
+        10 => "DP",
+        11 => "eDP",
+        12 => "UDI",
+        13 => "eUDI",
+        14 => "SDTV",
+        15 => "Miracast",
+        16 | 17 => "Indirect",
+        0x8000_0000 => return "Internal".to_string(),
+        _ => "Other",
+    };
+    format!("{}-{}", kind, connector_instance)
+}
+
+/// Get the device path of a display adapter (GPU), e.g. `\\?\PCI#VEN_10DE&DEV_2484...`.
+pub fn get_adapter_name(adapter_id: LUID) -> Option<String> {
+    let mut request = DisplayConfigAdapterName {
+        header: DisplayConfigDeviceInfoHeader::new::<DisplayConfigAdapterName>(
+            DISPLAYCONFIG_DEVICE_INFO_GET_ADAPTER_NAME,
+            adapter_id,
+            0,
+        ),
+        ..Default::default()
+    };
+
+    let result = unsafe {
+        DisplayConfigGetDeviceInfo(&mut request as *mut _ as *mut _)
+    };
+
+    if result != 0 {
+        return None;
+    }
+
+    Some(request.get_adapter_device_path())
+}
+
+// ============================================================================
+// Session Information
+// ============================================================================
+
+/// Check whether we are running inside a Remote Desktop (terminal services) session.
+/// The display topology there is virtual and unrelated to the physical monitors.
+pub fn is_remote_session() -> bool {
+    unsafe { GetSystemMetrics(SM_REMOTESESSION) != 0 }
+}
+
 // ============================================================================
 // Monitor Power Control
 // ============================================================================
@@ -185,6 +496,10 @@ pub fn turn_off_monitors() -> Result<(), String> {
     // Small delay to let user release mouse/keyboard
     std::thread::sleep(std::time::Duration::from_millis(500));
 
+    if simulate::is_simulating() {
+        return Ok(());
+    }
+
     let result = unsafe {
         PostMessageW(
             HWND_BROADCAST,
@@ -197,7 +512,20 @@ pub fn turn_off_monitors() -> Result<(), String> {
     if result != 0 {
         Ok(())
     } else {
-        Err("Failed to send monitor power off message".to_string())
+        Err(i18n::tr("error.turnOffMonitors"))
+    }
+}
+
+/// Lock the workstation, as Win+L does.
+pub fn lock_session() -> Result<(), String> {
+    if simulate::is_simulating() {
+        return Ok(());
+    }
+
+    if unsafe { LockWorkStation() } != 0 {
+        Ok(())
+    } else {
+        Err(i18n::tr("error.lockSession"))
     }
 }
 
@@ -229,13 +557,17 @@ pub fn get_dpi_scaling_info(adapter_id: LUID, source_id: u32) -> Option<DpiScali
 
 /// Set DPI scaling for a display source.
 pub fn set_dpi_scaling(adapter_id: LUID, source_id: u32, dpi_percent: u32) -> Result<(), String> {
+    if simulate::is_simulating() {
+        return Ok(());
+    }
+
     let info = get_dpi_scaling_info(adapter_id, source_id)
         .ok_or("Failed to get current DPI scaling info")?;
 
     let dpi_to_set = dpi_percent.clamp(info.minimum, info.maximum);
 
     let target_idx = dpi_to_index(dpi_to_set)
-        .ok_or_else(|| format!("Invalid DPI value: {}%", dpi_percent))?;
+        .ok_or_else(|| i18n::tr_with("error.invalidDpi", &[("percent", &dpi_percent.to_string())]))?;
     let recommended_idx = dpi_to_index(info.recommended)
         .ok_or("Failed to find recommended DPI index")?;
 
@@ -255,6 +587,6 @@ pub fn set_dpi_scaling(adapter_id: LUID, source_id: u32, dpi_percent: u32) -> Re
     if result == 0 {
         Ok(())
     } else {
-        Err(format!("DisplayConfigSetDeviceInfo failed with error: {}", result))
+        Err(i18n::tr_with("error.setDpi", &[("code", &result.to_string())]))
     }
 }
@@ -0,0 +1,61 @@
+//! Brightness of the built-in panel, through WMI.
+//!
+//! Single responsibility: list the monitors to dim and set the backlight level.
+
+use super::super::{simulate, BrightnessTarget};
+use super::api::{get_display_settings, get_monitor_additional_info};
+use super::modes::INTERNAL_OUTPUT_TECHNOLOGIES;
+use super::types::DISPLAYCONFIG_PATH_ACTIVE;
+use std::os::windows::process::CommandExt;
+use std::process::Command;
+
+/// The active monitors, with the built-in panels marked.
+pub fn brightness_targets() -> Result<Vec<BrightnessTarget>, String> {
+    let settings = get_display_settings(true)?;
+
+    Ok(settings
+        .path_info_array
+        .iter()
+        .filter(|path| path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0)
+        .map(|path| {
+            let info = get_monitor_additional_info(path.target_info.adapter_id, path.target_info.id);
+            BrightnessTarget {
+                name: if info.monitor_friendly_device.is_empty() {
+                    info.monitor_device_path.clone()
+                } else {
+                    info.monitor_friendly_device.clone()
+                },
+                monitor_id: info.monitor_device_path,
+                internal: INTERNAL_OUTPUT_TECHNOLOGIES.contains(&path.target_info.output_technology),
+            }
+        })
+        .collect())
+}
+
+/// Set the backlight of the built-in panels (WmiMonitorBrightnessMethods).
+pub fn set_panel_brightness(percent: u8) -> Result<(), String> {
+    // Don't flash a console window
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    if simulate::is_simulating() {
+        return Err("Brightness can't be set on simulated displays".to_string());
+    }
+
+    let script = format!(
+        "Get-CimInstance -Namespace root/WMI -ClassName WmiMonitorBrightnessMethods | \
+         Invoke-CimMethod -MethodName WmiSetBrightness -Arguments @{{Timeout=1; Brightness={}}} | Out-Null",
+        percent.min(100)
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run PowerShell: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to set the panel brightness: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,146 @@
+//! Windows color filters (Settings > Accessibility > Color filters).
+//!
+//! The state lives under `HKCU\Software\Microsoft\ColorFiltering`. Writing the
+//! registry alone only takes effect at the next sign-in, so turning the filter
+//! on or off presses the Win+Ctrl+C shortcut, enabling it for the moment if the
+//! user has it disabled. Single responsibility: read and change the color filter.
+
+use super::super::simulate;
+use serde::{Deserialize, Serialize};
+use std::mem;
+use std::ptr;
+use std::thread;
+use std::time::Duration;
+use windows_sys::Win32::System::Registry::{
+    RegGetValueW, RegSetKeyValueW, HKEY_CURRENT_USER, REG_DWORD, RRF_RT_REG_DWORD,
+};
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+    SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, VK_CONTROL, VK_LWIN,
+};
+
+const COLOR_FILTERING_KEY: &str = r"Software\Microsoft\ColorFiltering";
+
+/// Virtual key of the "C" in Win+Ctrl+C.
+const VK_C: u16 = 0x43;
+
+/// Time for Windows to react to the shortcut before it's disabled again.
+const SHORTCUT_DELAY: Duration = Duration::from_millis(300);
+
+/// Color filter state, as stored in a profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ColorFilter {
+    pub active: bool,
+    /// 0 grayscale, 1 inverted, 2 grayscale inverted, 3 deuteranopia,
+    /// 4 protanopia, 5 tritanopia
+    pub filter_type: u32,
+}
+
+/// Get the current color filter state. None if it was never configured.
+pub fn get_color_filter() -> Option<ColorFilter> {
+    Some(ColorFilter {
+        active: read_dword("Active")? != 0,
+        filter_type: read_dword("FilterType").unwrap_or(0),
+    })
+}
+
+/// Switch the color filter to the given state.
+pub fn set_color_filter(filter: ColorFilter) -> Result<(), String> {
+    if simulate::is_simulating() {
+        return Ok(());
+    }
+
+    let current = get_color_filter().unwrap_or(ColorFilter { active: false, filter_type: 0 });
+    if current == filter {
+        return Ok(());
+    }
+
+    write_dword("FilterType", filter.filter_type)?;
+    // The shortcut applies the stored filter type; a different type while
+    // active needs the filter switched off and on again
+    let presses = match (current.active, filter.active) {
+        (false, true) | (true, false) => 1,
+        (true, true) => 2,
+        (false, false) => 0,
+    };
+    if presses == 0 {
+        return Ok(());
+    }
+
+    let hotkey_enabled = read_dword("HotkeyEnabled").unwrap_or(0);
+    if hotkey_enabled == 0 {
+        write_dword("HotkeyEnabled", 1)?;
+    }
+    for _ in 0..presses {
+        press_shortcut()?;
+        thread::sleep(SHORTCUT_DELAY);
+    }
+    if hotkey_enabled == 0 {
+        write_dword("HotkeyEnabled", 0)?;
+    }
+    Ok(())
+}
+
+/// Press and release Win+Ctrl+C.
+fn press_shortcut() -> Result<(), String> {
+    let key = |vk: u16, flags: u32| INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT { wVk: vk, wScan: 0, dwFlags: flags, time: 0, dwExtraInfo: 0 },
+        },
+    };
+    let inputs = [
+        key(VK_LWIN, 0),
+        key(VK_CONTROL, 0),
+        key(VK_C, 0),
+        key(VK_C, KEYEVENTF_KEYUP),
+        key(VK_CONTROL, KEYEVENTF_KEYUP),
+        key(VK_LWIN, KEYEVENTF_KEYUP),
+    ];
+
+    let sent = unsafe { SendInput(inputs.len() as u32, inputs.as_ptr(), mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        return Err("Failed to send the color filter shortcut".to_string());
+    }
+    Ok(())
+}
+
+fn read_dword(name: &str) -> Option<u32> {
+    let sub_key: Vec<u16> = COLOR_FILTERING_KEY.encode_utf16().chain(Some(0)).collect();
+    let value: Vec<u16> = name.encode_utf16().chain(Some(0)).collect();
+    let mut data = 0u32;
+    let mut size = mem::size_of::<u32>() as u32;
+
+    let result = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            sub_key.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_DWORD,
+            ptr::null_mut(),
+            &mut data as *mut u32 as *mut _,
+            &mut size,
+        )
+    };
+    (result == 0).then_some(data)
+}
+
+fn write_dword(name: &str, data: u32) -> Result<(), String> {
+    let sub_key: Vec<u16> = COLOR_FILTERING_KEY.encode_utf16().chain(Some(0)).collect();
+    let value: Vec<u16> = name.encode_utf16().chain(Some(0)).collect();
+
+    let result = unsafe {
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            sub_key.as_ptr(),
+            value.as_ptr(),
+            REG_DWORD,
+            &data as *const u32 as *const _,
+            mem::size_of::<u32>() as u32,
+        )
+    };
+    if result != 0 {
+        return Err(format!("Failed to write color filter setting {}: error {}", name, result));
+    }
+    Ok(())
+}
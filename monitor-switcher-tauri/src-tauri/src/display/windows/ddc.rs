@@ -0,0 +1,138 @@
+//! DDC/CI access through the Windows monitor configuration API (dxva2).
+//!
+//! Single responsibility: find the physical monitor handle for a monitor ID
+//! and talk DDC/CI to it.
+
+use super::super::simulate;
+use super::api::{get_display_settings, get_monitor_additional_info, get_source_gdi_device_name};
+use super::types::DISPLAYCONFIG_PATH_ACTIVE;
+use std::mem;
+use std::ptr;
+use windows_sys::Win32::Devices::Display::{
+    CapabilitiesRequestAndCapabilitiesReply, DestroyPhysicalMonitors, GetCapabilitiesStringLength,
+    GetNumberOfPhysicalMonitorsFromHMONITOR, GetPhysicalMonitorsFromHMONITOR, GetVCPFeatureAndVCPFeatureReply,
+    SetVCPFeature, MC_VCP_CODE_TYPE, PHYSICAL_MONITOR,
+};
+use windows_sys::Win32::Foundation::{BOOL, HANDLE, LPARAM, RECT};
+use windows_sys::Win32::Graphics::Gdi::{EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFO, MONITORINFOEXW};
+
+/// Read the MCCS capabilities string of a connected monitor.
+pub fn read_capabilities_string(monitor_id: &str) -> Result<String, String> {
+    with_physical_monitor(monitor_id, |handle| {
+        let mut length = 0u32;
+        if unsafe { GetCapabilitiesStringLength(handle, &mut length) } == 0 || length == 0 {
+            return Err("The monitor didn't report its DDC/CI capabilities".to_string());
+        }
+
+        let mut buffer = vec![0u8; length as usize];
+        if unsafe { CapabilitiesRequestAndCapabilitiesReply(handle, buffer.as_mut_ptr(), length) } == 0 {
+            return Err("Failed to read the DDC/CI capabilities".to_string());
+        }
+        let end = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+        Ok(String::from_utf8_lossy(&buffer[..end]).into_owned())
+    })
+}
+
+/// Read a VCP feature: (current value, maximum value).
+pub fn read_vcp(monitor_id: &str, code: u8) -> Result<(u32, u32), String> {
+    with_physical_monitor(monitor_id, |handle| {
+        let mut code_type: MC_VCP_CODE_TYPE = 0;
+        let mut current = 0u32;
+        let mut maximum = 0u32;
+        if unsafe { GetVCPFeatureAndVCPFeatureReply(handle, code, &mut code_type, &mut current, &mut maximum) } == 0 {
+            return Err(format!("Failed to read VCP feature 0x{:02X}", code));
+        }
+        Ok((current, maximum))
+    })
+}
+
+/// Set a VCP feature.
+pub fn write_vcp(monitor_id: &str, code: u8, value: u32) -> Result<(), String> {
+    with_physical_monitor(monitor_id, |handle| {
+        if unsafe { SetVCPFeature(handle, code, value) } == 0 {
+            return Err(format!("Failed to set VCP feature 0x{:02X}", code));
+        }
+        Ok(())
+    })
+}
+
+/// Run `f` with the physical monitor handle of the given monitor.
+pub(super) fn with_physical_monitor<T, F>(monitor_id: &str, f: F) -> Result<T, String>
+where
+    F: FnOnce(HANDLE) -> Result<T, String>,
+{
+    if simulate::is_simulating() {
+        return Err("DDC/CI isn't available for simulated displays".to_string());
+    }
+
+    let (gdi_device_name, clone_index) = find_gdi_device(monitor_id)?;
+    let hmonitor = find_hmonitor(&gdi_device_name)
+        .ok_or_else(|| format!("No desktop monitor for {}", gdi_device_name))?;
+
+    let mut count = 0u32;
+    if unsafe { GetNumberOfPhysicalMonitorsFromHMONITOR(hmonitor, &mut count) } == 0 || count == 0 {
+        return Err(format!("No physical monitor for {}", gdi_device_name));
+    }
+    let mut monitors: Vec<PHYSICAL_MONITOR> = vec![unsafe { mem::zeroed() }; count as usize];
+    if unsafe { GetPhysicalMonitorsFromHMONITOR(hmonitor, count, monitors.as_mut_ptr()) } == 0 {
+        return Err(format!("Failed to open the physical monitor for {}", gdi_device_name));
+    }
+
+    let handle = monitors.get(clone_index).unwrap_or(&monitors[0]).hPhysicalMonitor;
+    let result = f(handle);
+    unsafe { DestroyPhysicalMonitors(count, monitors.as_ptr()) };
+    result
+}
+
+/// GDI device name (`\\.\DISPLAY1`) of the source showing a monitor, and the
+/// monitor's position among the monitors cloned on that source.
+fn find_gdi_device(monitor_id: &str) -> Result<(String, usize), String> {
+    let settings = get_display_settings(true)?;
+    let active: Vec<_> = settings
+        .path_info_array
+        .iter()
+        .filter(|path| path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0)
+        .collect();
+
+    let path = active
+        .iter()
+        .find(|path| {
+            get_monitor_additional_info(path.target_info.adapter_id, path.target_info.id)
+                .monitor_device_path
+                .eq_ignore_ascii_case(monitor_id)
+        })
+        .ok_or_else(|| format!("Monitor {} is not active", monitor_id))?;
+
+    let gdi_device_name = get_source_gdi_device_name(path.source_info.adapter_id, path.source_info.id)
+        .ok_or_else(|| format!("No GDI device for monitor {}", monitor_id))?;
+    let clone_index = active
+        .iter()
+        .filter(|p| p.source_info.adapter_id == path.source_info.adapter_id && p.source_info.id == path.source_info.id)
+        .position(|p| p.target_info.id == path.target_info.id)
+        .unwrap_or(0);
+    Ok((gdi_device_name, clone_index))
+}
+
+/// The HMONITOR of a GDI device.
+fn find_hmonitor(gdi_device_name: &str) -> Option<HMONITOR> {
+    unsafe extern "system" fn collect(hmonitor: HMONITOR, _hdc: HDC, _rect: *mut RECT, data: LPARAM) -> BOOL {
+        let monitors = &mut *(data as *mut Vec<HMONITOR>);
+        monitors.push(hmonitor);
+        1
+    }
+
+    let mut monitors: Vec<HMONITOR> = Vec::new();
+    unsafe {
+        EnumDisplayMonitors(ptr::null_mut(), ptr::null(), Some(collect), &mut monitors as *mut _ as LPARAM);
+    }
+
+    monitors.into_iter().find(|&hmonitor| {
+        let mut info: MONITORINFOEXW = unsafe { mem::zeroed() };
+        info.monitorInfo.cbSize = mem::size_of::<MONITORINFOEXW>() as u32;
+        if unsafe { GetMonitorInfoW(hmonitor, &mut info as *mut _ as *mut MONITORINFO) } == 0 {
+            return false;
+        }
+        let end = info.szDevice.iter().position(|&c| c == 0).unwrap_or(info.szDevice.len());
+        String::from_utf16_lossy(&info.szDevice[..end]).eq_ignore_ascii_case(gdi_device_name)
+    })
+}
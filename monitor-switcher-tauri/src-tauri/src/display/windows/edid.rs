@@ -0,0 +1,261 @@
+//! Raw EDID of the connected monitors.
+//!
+//! Windows keeps the EDID each monitor sent under the monitor's device key,
+//! `HKLM\SYSTEM\CurrentControlSet\Enum\DISPLAY\<model>\<instance>\Device Parameters`.
+//! An `EDID_OVERRIDE` subkey there replaces blocks of it, one value per block
+//! (`0`, `1`, ...), and Windows then reports the overridden identity.
+//! The EDID serial number tells identical monitors apart, such as the ones of
+//! a DisplayPort daisy chain (MST), whose target IDs aren't stable.
+//! Single responsibility: read the EDID bytes of the connected monitors.
+
+use super::super::RawEdid;
+use super::api::{get_display_settings, get_monitor_additional_info, EdidIdentity, MonitorAdditionalInfo};
+use std::ptr;
+use windows_sys::Win32::System::Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_BINARY};
+
+/// Largest EDID Windows stores: the base block and 255 extension blocks.
+const MAX_EDID_SIZE: usize = 256 * 128;
+
+const EDID_BLOCK_SIZE: usize = 128;
+
+const EDID_HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+
+/// Raw EDID of every connected monitor, active or not. Monitors Windows keeps
+/// no EDID for are left out.
+pub fn read_raw_edids() -> Result<Vec<RawEdid>, String> {
+    let current = get_display_settings(false)?;
+
+    // The all-paths query lists each target once per source it could be driven by
+    let mut edids: Vec<RawEdid> = Vec::new();
+    for path in &current.path_info_array {
+        let info = get_monitor_additional_info(path.target_info.adapter_id, path.target_info.id);
+        if !info.valid || info.monitor_device_path.is_empty() || edids.iter().any(|e| e.monitor_id == info.monitor_device_path) {
+            continue;
+        }
+
+        let Some(stored) = read_registry_edid(&info.monitor_device_path) else {
+            log::debug!("No EDID stored for {}", info.monitor_device_path);
+            continue;
+        };
+        let overridden = read_edid_override(&info.monitor_device_path).map(|blocks| with_override(&stored, &blocks));
+
+        edids.push(RawEdid {
+            name: if info.monitor_friendly_device.is_empty() {
+                info.monitor_device_path.clone()
+            } else {
+                info.monitor_friendly_device
+            },
+            monitor_id: info.monitor_device_path,
+            overridden: overridden.is_some(),
+            edid: overridden.unwrap_or(stored),
+        });
+    }
+
+    Ok(edids)
+}
+
+// ============================================================================
+// Overrides
+// ============================================================================
+
+/// Give `info` the identity of the effective EDID when an override changes
+/// it, and keep the monitor's own identity in `edid_override`, so profiles
+/// saved before the override was added still find the monitor.
+pub fn apply_edid_override(info: &mut MonitorAdditionalInfo) {
+    let Some(blocks) = read_edid_override(&info.monitor_device_path) else {
+        return;
+    };
+    let Some(stored) = read_registry_edid(&info.monitor_device_path) else {
+        return;
+    };
+    let (Some(original), Some(effective)) = (parse_identity(&stored), parse_identity(&with_override(&stored, &blocks))) else {
+        return;
+    };
+    if original == effective {
+        return;
+    }
+
+    info.manufacture_id = effective.manufacture_id;
+    info.product_code_id = effective.product_code_id;
+    if !effective.friendly_name.is_empty() {
+        info.monitor_friendly_device = effective.friendly_name.clone();
+    }
+    info.edid_override = Some(original);
+}
+
+/// Serial number of a monitor from its effective EDID. Empty when the EDID
+/// carries none.
+pub fn read_serial_number(device_path: &str) -> String {
+    let Some(stored) = read_registry_edid(device_path) else {
+        return String::new();
+    };
+    match read_edid_override(device_path) {
+        Some(blocks) => parse_serial_number(&with_override(&stored, &blocks)),
+        None => parse_serial_number(&stored),
+    }
+}
+
+/// Override blocks of a monitor, in order. None without an override.
+fn read_edid_override(device_path: &str) -> Option<Vec<Vec<u8>>> {
+    let key = format!(r"{}\EDID_OVERRIDE", device_parameters_key(device_path)?);
+    let blocks: Vec<Vec<u8>> = (0..)
+        .map_while(|index: u32| read_binary(&key, &index.to_string()))
+        .filter(|block| block.len() == EDID_BLOCK_SIZE)
+        .collect();
+    (!blocks.is_empty()).then_some(blocks)
+}
+
+/// `stored` with its first blocks replaced by the override's.
+fn with_override(stored: &[u8], blocks: &[Vec<u8>]) -> Vec<u8> {
+    let mut edid = stored.to_vec();
+    for (index, block) in blocks.iter().enumerate() {
+        let start = index * EDID_BLOCK_SIZE;
+        if edid.len() < start + EDID_BLOCK_SIZE {
+            edid.resize(start + EDID_BLOCK_SIZE, 0);
+        }
+        edid[start..start + EDID_BLOCK_SIZE].copy_from_slice(block);
+    }
+    edid
+}
+
+/// Identity in an EDID base block, with the IDs in the byte order CCD uses.
+fn parse_identity(edid: &[u8]) -> Option<EdidIdentity> {
+    if edid.len() < EDID_BLOCK_SIZE || edid[..8] != EDID_HEADER {
+        return None;
+    }
+
+    Some(EdidIdentity {
+        manufacture_id: u16::from_le_bytes([edid[8], edid[9]]),
+        product_code_id: u16::from_le_bytes([edid[10], edid[11]]),
+        friendly_name: descriptor_text(edid, 0xFC).unwrap_or_default(),
+    })
+}
+
+/// Serial number in an EDID base block: the serial descriptor, else the
+/// numeric serial. Many monitors leave the numeric one 0 or at the 0x01010101
+/// placeholder, which tell nothing apart.
+fn parse_serial_number(edid: &[u8]) -> String {
+    if edid.len() < EDID_BLOCK_SIZE || edid[..8] != EDID_HEADER {
+        return String::new();
+    }
+    if let Some(serial) = descriptor_text(edid, 0xFF).filter(|serial| !serial.is_empty()) {
+        return serial;
+    }
+    match u32::from_le_bytes([edid[12], edid[13], edid[14], edid[15]]) {
+        0 | 0x0101_0101 => String::new(),
+        serial => format!("{:08X}", serial),
+    }
+}
+
+/// Text of the display descriptor with the given tag (0xFC name, 0xFF
+/// serial), ended by a newline.
+fn descriptor_text(edid: &[u8], tag: u8) -> Option<String> {
+    [54, 72, 90, 108]
+        .iter()
+        .map(|&at| &edid[at..at + 18])
+        .find(|descriptor| descriptor[..3] == [0, 0, 0] && descriptor[3] == tag)
+        .map(|descriptor| {
+            let text = &descriptor[5..];
+            let end = text.iter().position(|&b| b == b'\n').unwrap_or(text.len());
+            String::from_utf8_lossy(&text[..end]).trim().to_string()
+        })
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+/// Registry key holding the EDID of a monitor, from its device path, e.g.
+/// `\\?\DISPLAY#DEL40F4#5&1a2b3c&0&UID4352#{e6f07b5f-...}`.
+fn device_parameters_key(device_path: &str) -> Option<String> {
+    let mut parts = device_path.strip_prefix(r"\\?\")?.split('#');
+    let (class, model, instance) = (parts.next()?, parts.next()?, parts.next()?);
+    Some(format!(r"SYSTEM\CurrentControlSet\Enum\{}\{}\{}\Device Parameters", class, model, instance))
+}
+
+/// EDID the monitor sent, as Windows stored it.
+fn read_registry_edid(device_path: &str) -> Option<Vec<u8>> {
+    read_binary(&device_parameters_key(device_path)?, "EDID").filter(|edid| edid.len() >= EDID_BLOCK_SIZE)
+}
+
+/// Binary value under `HKLM\<key>`.
+fn read_binary(key: &str, value: &str) -> Option<Vec<u8>> {
+    let sub_key: Vec<u16> = key.encode_utf16().chain(Some(0)).collect();
+    let value: Vec<u16> = value.encode_utf16().chain(Some(0)).collect();
+    let mut buffer = vec![0u8; MAX_EDID_SIZE];
+    let mut size = buffer.len() as u32;
+
+    let result = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            sub_key.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_BINARY,
+            ptr::null_mut(),
+            buffer.as_mut_ptr() as *mut _,
+            &mut size,
+        )
+    };
+    if result != 0 {
+        return None;
+    }
+    buffer.truncate(size as usize);
+    Some(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_block(manufacturer: [u8; 2], product: u16, name: &str) -> Vec<u8> {
+        let mut edid = vec![0u8; EDID_BLOCK_SIZE];
+        edid[..8].copy_from_slice(&EDID_HEADER);
+        edid[8..10].copy_from_slice(&manufacturer);
+        edid[10..12].copy_from_slice(&product.to_le_bytes());
+        edid[72 + 3] = 0xFC;
+        let text = format!("{}\n", name);
+        edid[72 + 5..72 + 5 + text.len()].copy_from_slice(text.as_bytes());
+        edid
+    }
+
+    #[test]
+    fn test_parse_identity() {
+        // "DEL" is 0x10AC in the EDID, which CCD reports as 0xAC10
+        let identity = parse_identity(&base_block([0x10, 0xAC], 0x40F4, "DELL U2720Q")).unwrap();
+        assert_eq!(identity.manufacture_id, 0xAC10);
+        assert_eq!(identity.product_code_id, 0x40F4);
+        assert_eq!(identity.friendly_name, "DELL U2720Q");
+
+        assert!(parse_identity(&[0u8; EDID_BLOCK_SIZE]).is_none());
+    }
+
+    #[test]
+    fn test_parse_serial_number() {
+        let mut edid = base_block([0x10, 0xAC], 0x40F4, "DELL U2720Q");
+        assert_eq!(parse_serial_number(&edid), "");
+
+        edid[12..16].copy_from_slice(&0x0101_0101u32.to_le_bytes());
+        assert_eq!(parse_serial_number(&edid), "");
+
+        edid[12..16].copy_from_slice(&0x4C4Au32.to_le_bytes());
+        assert_eq!(parse_serial_number(&edid), "00004C4A");
+
+        // The serial descriptor wins over the numeric serial
+        edid[90 + 3] = 0xFF;
+        edid[90 + 5..90 + 5 + 9].copy_from_slice(b"7RM2Q53\n ");
+        assert_eq!(parse_serial_number(&edid), "7RM2Q53");
+    }
+
+    #[test]
+    fn test_with_override() {
+        let mut stored = base_block([0x10, 0xAC], 0x40F4, "DELL U2720Q");
+        stored.extend(vec![0x02; EDID_BLOCK_SIZE]);
+        let replacement = base_block([0x1E, 0x6D], 0x5B09, "LG HDR 4K");
+
+        let edid = with_override(&stored, std::slice::from_ref(&replacement));
+        assert_eq!(edid.len(), 2 * EDID_BLOCK_SIZE);
+        assert_eq!(parse_identity(&edid).unwrap().friendly_name, "LG HDR 4K");
+        // Blocks the override doesn't list stay as the monitor sent them
+        assert_eq!(edid[EDID_BLOCK_SIZE..], stored[EDID_BLOCK_SIZE..]);
+    }
+}
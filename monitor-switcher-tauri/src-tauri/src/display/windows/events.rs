@@ -0,0 +1,135 @@
+//! Display change notifications via a hidden window.
+//!
+//! Single responsibility: tell the application when the display layout changes,
+//! whether the change came from us, Windows Settings or a hotplug.
+
+use std::cell::RefCell;
+use std::io;
+use std::mem;
+use std::ptr;
+use std::sync::mpsc;
+use std::thread;
+
+use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, KillTimer, RegisterClassW, SetTimer,
+    DBT_DEVNODES_CHANGED, MSG, WM_DEVICECHANGE, WM_DISPLAYCHANGE, WM_TIMER, WNDCLASSW,
+};
+
+/// Time to wait for a burst of change messages to settle before notifying.
+/// Docking a laptop produces several device and display changes in a row.
+const SETTLE_DELAY_MS: u32 = 250;
+
+/// Timer that fires once the burst has settled.
+const SETTLE_TIMER: usize = 1;
+
+thread_local! {
+    /// Callback of the window thread, reached from the window procedure.
+    static ON_CHANGE: RefCell<Option<Box<dyn Fn()>>> = RefCell::new(None);
+}
+
+// ============================================================================
+// Event Subscription
+// ============================================================================
+
+/// Subscribe to display and device change messages.
+///
+/// Spawns a background thread that owns a hidden window and calls `on_change`
+/// once per burst of changes. Returns an error if the window can't be created.
+pub fn watch_display_changes<F>(on_change: F) -> Result<(), String>
+where
+    F: Fn() + Send + 'static,
+{
+    let (ready_sender, ready) = mpsc::channel();
+
+    thread::Builder::new()
+        .name("display-events".to_string())
+        .spawn(move || {
+            ON_CHANGE.with(|callback| *callback.borrow_mut() = Some(Box::new(on_change)));
+
+            // The window belongs to the thread that created it, so create it here
+            if let Err(e) = create_window() {
+                let _ = ready_sender.send(Err(e));
+                return;
+            }
+            let _ = ready_sender.send(Ok(()));
+
+            // GetMessageW returns 0 on WM_QUIT and -1 on errors
+            let mut msg: MSG = unsafe { mem::zeroed() };
+            while unsafe { GetMessageW(&mut msg, ptr::null_mut(), 0, 0) } > 0 {
+                unsafe { DispatchMessageW(&msg) };
+            }
+            log::warn!("Display event loop stopped");
+        })
+        .map_err(|e| format!("Failed to spawn display event thread: {}", e))?;
+
+    ready
+        .recv()
+        .map_err(|_| "Display event thread exited".to_string())?
+}
+
+/// Create the hidden window receiving the change messages.
+fn create_window() -> Result<HWND, String> {
+    let class_name: Vec<u16> = "MonitorSwitcherDisplayEvents".encode_utf16().chain(Some(0)).collect();
+    let instance = unsafe { GetModuleHandleW(ptr::null()) };
+
+    let class = WNDCLASSW {
+        lpfnWndProc: Some(window_proc),
+        hInstance: instance,
+        lpszClassName: class_name.as_ptr(),
+        ..unsafe { mem::zeroed() }
+    };
+    if unsafe { RegisterClassW(&class) } == 0 {
+        return Err(format!("Failed to register window class: {}", io::Error::last_os_error()));
+    }
+
+    // A top-level window that is never shown: message-only windows don't
+    // receive broadcasts such as WM_DISPLAYCHANGE
+    let hwnd = unsafe {
+        CreateWindowExW(
+            0,
+            class_name.as_ptr(),
+            class_name.as_ptr(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            instance,
+            ptr::null(),
+        )
+    };
+    if hwnd.is_null() {
+        return Err(format!("Failed to create window: {}", io::Error::last_os_error()));
+    }
+
+    Ok(hwnd)
+}
+
+/// Window procedure of the hidden window.
+unsafe extern "system" fn window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    match msg {
+        WM_DISPLAYCHANGE => restart_settle_timer(hwnd),
+        WM_DEVICECHANGE if wparam == DBT_DEVNODES_CHANGED as WPARAM => restart_settle_timer(hwnd),
+        WM_TIMER if wparam == SETTLE_TIMER => {
+            KillTimer(hwnd, SETTLE_TIMER);
+            ON_CHANGE.with(|callback| {
+                if let Some(on_change) = callback.borrow().as_ref() {
+                    on_change();
+                }
+            });
+            return 0;
+        }
+        _ => {}
+    }
+
+    DefWindowProcW(hwnd, msg, wparam, lparam)
+}
+
+/// (Re)start the settle timer, so a burst of messages results in one notification.
+unsafe fn restart_settle_timer(hwnd: HWND) {
+    SetTimer(hwnd, SETTLE_TIMER, SETTLE_DELAY_MS, None);
+}
@@ -0,0 +1,173 @@
+//! Legacy GDI display configuration fallback.
+//!
+//! Some older drivers reject SetDisplayConfig calls that ChangeDisplaySettingsEx accepts.
+//! Single responsibility: apply DisplaySettings through the legacy GDI API.
+
+use super::adapters::wide_to_string;
+use super::api::{DisplaySettings, get_source_gdi_device_name};
+use super::types::*;
+use std::mem;
+use std::ptr;
+
+use windows_sys::Win32::Foundation::POINTL;
+use windows_sys::Win32::Graphics::Gdi::{
+    ChangeDisplaySettingsExW, EnumDisplayDevicesW, EnumDisplaySettingsW, DEVMODEW, DEVMODEW_0_1, DISPLAY_DEVICEW,
+    CDS_NORESET, CDS_SET_PRIMARY, CDS_TYPE, CDS_UPDATEREGISTRY, DISPLAY_DEVICE_ATTACHED_TO_DESKTOP,
+    DISP_CHANGE_SUCCESSFUL, DM_DISPLAYFREQUENCY, DM_DISPLAYORIENTATION, DM_PELSHEIGHT, DM_PELSWIDTH, DM_POSITION,
+    DMDO_180, DMDO_270, DMDO_90, DMDO_DEFAULT, ENUM_CURRENT_SETTINGS,
+};
+
+/// Apply display settings with ChangeDisplaySettingsEx.
+///
+/// Each source is staged in the registry with CDS_NORESET, displays active now
+/// but not in the settings are staged detached, then all changes are committed
+/// with a single final call so the layout switches in one step. If staging or
+/// committing fails, the staged devices get their current modes back.
+pub fn apply_settings(settings: &DisplaySettings) -> Result<(), String> {
+    let mut staged = Vec::new();
+    let result = stage_settings(settings, &mut staged).and_then(|_| commit());
+    if result.is_err() {
+        // Left staged, the modes would be applied with the next mode change
+        restage_current_modes(&staged);
+    }
+    result
+}
+
+/// Stage every source of `settings` and detach the other displays. Records the
+/// device names it staged in `staged`, including one that failed.
+fn stage_settings(settings: &DisplaySettings, staged: &mut Vec<String>) -> Result<(), String> {
+    for path in &settings.path_info_array {
+        let Some(mode) = settings
+            .mode_info_array
+            .get(path.source_info.mode_info_idx as usize)
+            .filter(|m| m.info_type == MODE_INFO_TYPE_SOURCE)
+        else {
+            continue;
+        };
+        let source = mode.get_source_mode();
+
+        let device_name = get_source_gdi_device_name(path.source_info.adapter_id, path.source_info.id)
+            .ok_or_else(|| format!("Failed to get GDI device name for source {}", path.source_info.id))?;
+
+        let mut devmode = empty_devmode();
+        devmode.dmFields = DM_PELSWIDTH | DM_PELSHEIGHT | DM_POSITION | DM_DISPLAYORIENTATION;
+        devmode.dmPelsWidth = source.width;
+        devmode.dmPelsHeight = source.height;
+        devmode.Anonymous1.Anonymous2 = DEVMODEW_0_1 {
+            dmPosition: POINTL { x: source.position.x, y: source.position.y },
+            dmDisplayOrientation: rotation_to_orientation(path.target_info.rotation),
+            dmDisplayFixedOutput: 0,
+        };
+
+        let refresh = path.target_info.refresh_rate;
+        if refresh.denominator > 0 {
+            devmode.dmFields |= DM_DISPLAYFREQUENCY;
+            devmode.dmDisplayFrequency =
+                (refresh.numerator as f64 / refresh.denominator as f64).round() as u32;
+        }
+
+        let mut flags = CDS_UPDATEREGISTRY | CDS_NORESET;
+        if source.position.x == 0 && source.position.y == 0 {
+            flags |= CDS_SET_PRIMARY;
+        }
+
+        staged.push(device_name.clone());
+        stage(&device_name, &devmode, flags)?;
+    }
+
+    if staged.is_empty() {
+        return Err("No source modes to apply".to_string());
+    }
+
+    // Displays active now but left out of the settings are detached
+    let extras: Vec<String> = attached_devices().into_iter().filter(|d| !staged.contains(d)).collect();
+    for device_name in extras {
+        staged.push(device_name.clone());
+        stage(&device_name, &detached_devmode(), CDS_UPDATEREGISTRY | CDS_NORESET)?;
+    }
+    Ok(())
+}
+
+/// Stage a mode for one display device in the registry.
+fn stage(device_name: &str, devmode: &DEVMODEW, flags: CDS_TYPE) -> Result<(), String> {
+    let wide_name: Vec<u16> = device_name.encode_utf16().chain(std::iter::once(0)).collect();
+    let result = unsafe {
+        ChangeDisplaySettingsExW(wide_name.as_ptr(), devmode, ptr::null_mut(), flags, ptr::null())
+    };
+
+    if result != DISP_CHANGE_SUCCESSFUL {
+        return Err(format!("ChangeDisplaySettingsEx failed for {} with error: {}", device_name, result));
+    }
+    Ok(())
+}
+
+/// Apply everything staged at once.
+fn commit() -> Result<(), String> {
+    let result = unsafe {
+        ChangeDisplaySettingsExW(ptr::null(), ptr::null(), ptr::null_mut(), 0, ptr::null())
+    };
+
+    if result == DISP_CHANGE_SUCCESSFUL {
+        Ok(())
+    } else {
+        Err(format!("ChangeDisplaySettingsEx commit failed with error: {}", result))
+    }
+}
+
+/// Stage the modes the devices have now over what was staged for them, so
+/// nothing changes when the registry modes are next applied. Devices without
+/// a current mode are off and stay detached.
+fn restage_current_modes(devices: &[String]) {
+    for device_name in devices {
+        let wide_name: Vec<u16> = device_name.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut devmode = empty_devmode();
+        if unsafe { EnumDisplaySettingsW(wide_name.as_ptr(), ENUM_CURRENT_SETTINGS, &mut devmode) } == 0 {
+            devmode = detached_devmode();
+        }
+        if let Err(e) = stage(device_name, &devmode, CDS_UPDATEREGISTRY | CDS_NORESET) {
+            log::warn!("Couldn't undo the staged mode: {}", e);
+        }
+    }
+}
+
+/// GDI device names (e.g. `\\.\DISPLAY1`) of the displays attached to the desktop.
+fn attached_devices() -> Vec<String> {
+    let mut devices = Vec::new();
+
+    for index in 0.. {
+        let mut device: DISPLAY_DEVICEW = unsafe { mem::zeroed() };
+        device.cb = mem::size_of::<DISPLAY_DEVICEW>() as u32;
+
+        if unsafe { EnumDisplayDevicesW(ptr::null(), index, &mut device, 0) } == 0 {
+            break;
+        }
+        if device.StateFlags & DISPLAY_DEVICE_ATTACHED_TO_DESKTOP != 0 {
+            devices.push(wide_to_string(&device.DeviceName));
+        }
+    }
+
+    devices
+}
+
+fn empty_devmode() -> DEVMODEW {
+    let mut devmode: DEVMODEW = unsafe { mem::zeroed() };
+    devmode.dmSize = mem::size_of::<DEVMODEW>() as u16;
+    devmode
+}
+
+/// A zero-size mode with a position, which detaches a display from the desktop.
+fn detached_devmode() -> DEVMODEW {
+    let mut devmode = empty_devmode();
+    devmode.dmFields = DM_PELSWIDTH | DM_PELSHEIGHT | DM_POSITION;
+    devmode
+}
+
+/// Convert a DISPLAYCONFIG_ROTATION value to a DEVMODE display orientation.
+fn rotation_to_orientation(rotation: u32) -> u32 {
+    match rotation {
+        2 => DMDO_90,
+        3 => DMDO_180,
+        4 => DMDO_270,
+        _ => DMDO_DEFAULT,
+    }
+}
@@ -0,0 +1,105 @@
+//! Layouts described by plain monitor geometry.
+//!
+//! Single responsibility: turn desktop bounds from another tool's layout into
+//! a configuration of the active displays, matching each monitor by hardware
+//! ID or name, and the rest by their left-to-right order.
+
+use super::super::MonitorGeometry;
+use super::api::{get_display_settings, DisplaySettings, MonitorAdditionalInfo};
+use super::matcher::get_additional_info_for_modes;
+use super::types::*;
+
+/// The current configuration with the active displays arranged as `monitors`
+/// describe. Active displays none of them match keep their place; monitors
+/// that match no active display are left out.
+pub fn settings_from_geometry(monitors: &[MonitorGeometry]) -> Result<DisplaySettings, String> {
+    let mut settings = get_display_settings(true)?;
+    let additional_info = get_additional_info_for_modes(&settings.mode_info_array);
+
+    let paths: Vec<usize> = (0..settings.path_info_array.len())
+        .filter(|&i| settings.path_info_array[i].flags & DISPLAYCONFIG_PATH_ACTIVE != 0)
+        .collect();
+    let info_of = |path: usize| -> Option<&MonitorAdditionalInfo> {
+        additional_info.get(settings.path_info_array[path].target_info.mode_info_idx as usize)
+    };
+
+    // First by hardware ID or name
+    let mut assigned: Vec<(usize, &MonitorGeometry)> = Vec::new();
+    for monitor in monitors {
+        if let Some(&path) = paths
+            .iter()
+            .filter(|p| !assigned.iter().any(|(a, _)| a == *p))
+            .find(|&&p| info_of(p).is_some_and(|info| identifies(monitor, info)))
+        {
+            assigned.push((path, monitor));
+        }
+    }
+
+    // Then the rest left to right
+    let mut rest_monitors: Vec<&MonitorGeometry> = monitors
+        .iter()
+        .filter(|m| !assigned.iter().any(|(_, a)| std::ptr::eq(*a, *m)))
+        .collect();
+    rest_monitors.sort_by_key(|m| (m.x, m.y));
+    let mut rest_paths: Vec<usize> = paths
+        .iter()
+        .copied()
+        .filter(|p| !assigned.iter().any(|(a, _)| a == p))
+        .collect();
+    rest_paths.sort_by_key(|&p| source_at(&settings, p).map(|s| (s.position.x, s.position.y)).unwrap_or_default());
+    for (monitor, path) in rest_monitors.iter().zip(&rest_paths) {
+        assigned.push((*path, monitor));
+    }
+    if rest_monitors.len() > rest_paths.len() {
+        log::warn!("{} monitor(s) of the layout aren't connected and are left out", rest_monitors.len() - rest_paths.len());
+    }
+    if assigned.is_empty() {
+        return Err("None of the layout's monitors are active".to_string());
+    }
+
+    for (idx, monitor) in assigned {
+        let path = &mut settings.path_info_array[idx];
+        path.target_info.rotation = monitor.quarter_turns % 4 + 1;
+
+        if let Some(refresh_rate) = monitor.refresh_rate {
+            let rate = path.target_info.refresh_rate;
+            let current = if rate.denominator == 0 { 0.0 } else { rate.numerator as f64 / rate.denominator as f64 };
+            if (current - refresh_rate).abs() > 0.5 {
+                path.target_info.refresh_rate = DisplayConfigRational {
+                    numerator: (refresh_rate * 1000.0).round() as u32,
+                    denominator: 1000,
+                };
+                // Let Windows pick target timings matching the new rate
+                path.target_info.mode_info_idx = DISPLAYCONFIG_PATH_MODE_IDX_INVALID;
+            }
+        }
+
+        let source_idx = path.source_info.mode_info_idx as usize;
+        if let Some(mut source) = source_at(&settings, idx) {
+            source.position = PointL { x: monitor.x, y: monitor.y };
+            source.width = monitor.width;
+            source.height = monitor.height;
+            settings.mode_info_array[source_idx].set_source_mode(&source);
+        }
+    }
+
+    Ok(settings)
+}
+
+/// Whether a monitor of the layout is the display described by `info`: its
+/// hardware ID names the same model (e.g. `DEL40F4`), or it has the same name.
+fn identifies(monitor: &MonitorGeometry, info: &MonitorAdditionalInfo) -> bool {
+    // Device paths look like \\?\DISPLAY#DEL40F4#5&1a2b3c&0&UID4352#{...}
+    let model = info.monitor_device_path.split('#').nth(1).unwrap_or_default().to_uppercase();
+    let by_model = !model.is_empty() && monitor.hardware_id.to_uppercase().contains(&model);
+    let by_name = !monitor.name.is_empty() && monitor.name.eq_ignore_ascii_case(&info.monitor_friendly_device);
+    by_model || by_name
+}
+
+fn source_at(settings: &DisplaySettings, path: usize) -> Option<DisplayConfigSourceMode> {
+    settings
+        .mode_info_array
+        .get(settings.path_info_array[path].source_info.mode_info_idx as usize)
+        .filter(|m| m.info_type == MODE_INFO_TYPE_SOURCE)
+        .map(|m| *m.get_source_mode())
+}
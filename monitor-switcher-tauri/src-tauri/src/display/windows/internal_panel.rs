@@ -0,0 +1,44 @@
+//! On/off state of the built-in (laptop) panel.
+//!
+//! Windows likes to turn the panel back on, e.g. when a profile leaves it out
+//! but the topology database remembers it. Profiles record whether it was on,
+//! and a profile saved with it off switches it off again after applying.
+//! Single responsibility: read the panel state and turn the panel off.
+
+use super::api::{get_display_settings, set_display_settings};
+use super::matcher::drop_unused_modes;
+use super::modes::INTERNAL_OUTPUT_TECHNOLOGIES;
+use super::types::*;
+
+/// Whether the built-in panel is on. None on machines without one.
+pub fn get_internal_panel_state() -> Result<Option<bool>, String> {
+    let all = get_display_settings(false)?;
+    let mut internal = all.path_info_array.iter().filter(|path| is_internal(path)).peekable();
+    if internal.peek().is_none() {
+        return Ok(None);
+    }
+    Ok(Some(internal.any(|path| path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0)))
+}
+
+/// Turn the built-in panel off, leaving the other displays as they are.
+/// Returns whether it was on.
+pub fn turn_off_internal_panel() -> Result<bool, String> {
+    let mut settings = get_display_settings(true)?;
+    let before = settings.path_info_array.len();
+    settings.path_info_array.retain(|path| !is_internal(path));
+
+    if settings.path_info_array.len() == before {
+        return Ok(false);
+    }
+    if settings.path_info_array.is_empty() {
+        return Err("The built-in display is the only active display; leaving it on".to_string());
+    }
+
+    drop_unused_modes(&mut settings, &mut Vec::new());
+    set_display_settings(&mut settings)?;
+    Ok(true)
+}
+
+fn is_internal(path: &DisplayConfigPathInfo) -> bool {
+    INTERNAL_OUTPUT_TECHNOLOGIES.contains(&path.target_info.output_technology)
+}
@@ -1,56 +1,101 @@
 //! Adapter ID matching logic for display profiles.
 //!
 //! Adapter IDs (LUIDs) change on system restart, so we need to match profiles
-//! to current system state using multiple fallback strategies.
+//! to current system state using multiple fallback strategies. Monitors saved
+//! on physical adapters are never matched to targets of virtual adapters
+//! (Parsec, spacedesk, IddCx drivers) unless the user says otherwise.
+//! Monitors daisy-chained over DisplayPort (MST) may swap target IDs after a
+//! power cycle; identical units are then told apart by EDID serial number and
+//! connector.
 //!
 //! Single responsibility: match saved adapter IDs to current system state.
 
+use super::super::MatchTier;
 use super::types::*;
-use super::api::{DisplaySettings, MonitorAdditionalInfo, get_display_settings, get_monitor_additional_info};
+use super::api::{DisplaySettings, MonitorAdditionalInfo, get_display_settings, get_monitor_additional_info, get_source_gdi_device_name, is_specialized_target};
 use log::{debug, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Match monitors to virtual adapters like to physical ones (the user's override).
+static MATCH_VIRTUAL_ADAPTERS: AtomicBool = AtomicBool::new(false);
 
 /// Match profile adapter IDs to current system adapter IDs.
-/// Uses a 3-tier fallback strategy:
+/// Uses a 4-tier fallback strategy:
 /// 1. Match by source/target ID pairs
-/// 2. Match by monitor friendly name (EDID)
-/// 3. Bulk adapter ID replacement
+/// 2. Match by GDI device name (\\.\DISPLAYn)
+/// 3. Match by monitor friendly name (EDID)
+/// 4. Bulk adapter ID replacement
+///
+/// Tiers 1 and 2 are skipped when the saved target IDs now lead to other
+/// units of the same model, as they would swap those monitors.
 pub fn match_adapter_ids(
     settings: &mut DisplaySettings,
     additional_info: &[MonitorAdditionalInfo],
-) -> Result<(), String> {
+) -> Result<MatchTier, String> {
     // Get current display settings
     let current = get_display_settings(true)?;
-    let current_additional_info = get_additional_info_for_modes(&current.mode_info_array);
+    let mut current_additional_info = get_additional_info_for_modes(&current.mode_info_array);
+    fill_gdi_device_names(&current, &mut current_additional_info);
+
+    Ok(match_adapter_ids_against(settings, additional_info, &current, &current_additional_info))
+}
+
+/// Match profile adapter IDs against a given system state instead of querying it.
+/// Lets recorded snapshots be replayed without the original hardware.
+pub fn match_adapter_ids_against(
+    settings: &mut DisplaySettings,
+    additional_info: &[MonitorAdditionalInfo],
+    current: &DisplaySettings,
+    current_additional_info: &[MonitorAdditionalInfo],
+) -> MatchTier {
+    let reshuffled = is_reshuffled(settings, additional_info, current, current_additional_info);
+    if reshuffled {
+        debug!("Adapter matching: identical monitors changed target IDs, skipping tiers 1 and 2");
+    }
 
     // Try tier 1: Match by source/target ID pairs
-    if try_match_by_ids(settings, &current) {
+    if !reshuffled && try_match_by_ids(settings, additional_info, current, current_additional_info) {
         debug!("Adapter matching: Tier 1 (ID pairs) succeeded");
-        return Ok(());
+        return MatchTier::IdPairs;
+    }
+
+    // Try tier 2: Match by GDI device name
+    if !reshuffled && try_match_by_gdi_name(settings, additional_info, current, current_additional_info) {
+        debug!("Adapter matching: Tier 2 (GDI device name) succeeded");
+        return MatchTier::GdiName;
     }
 
-    // Try tier 2: Match by monitor friendly name
-    if try_match_by_friendly_name(settings, additional_info, &current, &current_additional_info) {
-        debug!("Adapter matching: Tier 2 (friendly name) succeeded");
-        return Ok(());
+    // Try tier 3: Match by monitor friendly name
+    if try_match_by_friendly_name(settings, additional_info, current, current_additional_info) {
+        debug!("Adapter matching: Tier 3 (friendly name) succeeded");
+        return MatchTier::FriendlyName;
     }
 
-    // Try tier 3: Bulk replacement
-    if try_bulk_replacement(settings, &current) {
-        debug!("Adapter matching: Tier 3 (bulk replacement) succeeded");
-        return Ok(());
+    // Try tier 4: Bulk replacement
+    if try_bulk_replacement(settings, additional_info, current, current_additional_info) {
+        debug!("Adapter matching: Tier 4 (bulk replacement) succeeded");
+        return MatchTier::BulkReplacement;
     }
 
     warn!("Adapter matching: All tiers failed, using original IDs");
-    Ok(())
+    MatchTier::Unmatched
 }
 
 /// Tier 1: Match by source and target ID pairs.
-fn try_match_by_ids(settings: &mut DisplaySettings, current: &DisplaySettings) -> bool {
+fn try_match_by_ids(
+    settings: &mut DisplaySettings,
+    additional_info: &[MonitorAdditionalInfo],
+    current: &DisplaySettings,
+    current_additional_info: &[MonitorAdditionalInfo],
+) -> bool {
     let mut matched_any = false;
 
     // Match paths by source/target IDs
     for path in &mut settings.path_info_array {
         for current_path in &current.path_info_array {
+            if is_virtual_mismatch(path, additional_info, current_path, current_additional_info) {
+                continue;
+            }
             if path.source_info.id == current_path.source_info.id
                 && path.target_info.id == current_path.target_info.id
             {
@@ -98,7 +143,65 @@ fn try_match_by_ids(settings: &mut DisplaySettings, current: &DisplaySettings) -
     true
 }
 
-/// Tier 2: Match by monitor friendly device name.
+/// Tier 2: Match by the GDI device name of each path's source.
+///
+/// Every active path has to find its GDI name again, or nothing is changed:
+/// a partial match would leave the other paths on stale IDs.
+fn try_match_by_gdi_name(
+    settings: &mut DisplaySettings,
+    additional_info: &[MonitorAdditionalInfo],
+    current: &DisplaySettings,
+    current_additional_info: &[MonitorAdditionalInfo],
+) -> bool {
+    let mut matched = settings.clone();
+    let mut matched_any = false;
+    // Current paths already given to a saved path
+    let mut taken: Vec<usize> = Vec::new();
+
+    for path in &mut matched.path_info_array {
+        if path.flags & DISPLAYCONFIG_PATH_ACTIVE == 0 {
+            continue;
+        }
+        let Some(saved_info) = additional_info
+            .get(path.target_info.mode_info_idx as usize)
+            .filter(|info| !info.gdi_device_name.is_empty())
+        else {
+            return false;
+        };
+
+        let found = current.path_info_array.iter().enumerate().find(|(j, current_path)| {
+            !taken.contains(j)
+                && current_additional_info
+                    .get(current_path.target_info.mode_info_idx as usize)
+                    .is_some_and(|info| {
+                        !info.specialized
+                            && may_match(saved_info, info)
+                            && info.gdi_device_name == saved_info.gdi_device_name
+                    })
+        });
+        let Some((j, current_path)) = found else {
+            return false;
+        };
+
+        taken.push(j);
+        path.source_info.adapter_id = current_path.source_info.adapter_id;
+        path.source_info.id = current_path.source_info.id;
+        path.target_info.adapter_id = current_path.target_info.adapter_id;
+        path.target_info.id = current_path.target_info.id;
+        matched_any = true;
+    }
+
+    if !matched_any {
+        return false;
+    }
+
+    // Update modes based on matched paths
+    update_mode_adapter_ids_from_paths(&mut matched);
+    *settings = matched;
+    true
+}
+
+/// Tier 3: Match by monitor friendly device name.
 fn try_match_by_friendly_name(
     settings: &mut DisplaySettings,
     additional_info: &[MonitorAdditionalInfo],
@@ -106,6 +209,8 @@ fn try_match_by_friendly_name(
     current_additional_info: &[MonitorAdditionalInfo],
 ) -> bool {
     let mut matched_any = false;
+    // Current targets already given to a saved monitor
+    let mut taken: Vec<usize> = Vec::new();
 
     for (i, mode) in settings.mode_info_array.iter_mut().enumerate() {
         if mode.info_type != MODE_INFO_TYPE_TARGET {
@@ -119,38 +224,79 @@ fn try_match_by_friendly_name(
             continue;
         }
 
-        // Find matching current monitor by friendly name
-        for (j, current_mode) in current.mode_info_array.iter().enumerate() {
-            if current_mode.info_type != MODE_INFO_TYPE_TARGET {
-                continue;
-            }
+        // Find matching current monitors by friendly name
+        let candidates: Vec<usize> = current
+            .mode_info_array
+            .iter()
+            .enumerate()
+            .filter(|(j, current_mode)| {
+                current_mode.info_type == MODE_INFO_TYPE_TARGET
+                    && !taken.contains(j)
+                    // Never move a desktop monitor onto a VR headset or other specialized display
+                    && current_additional_info.get(*j).is_some_and(|info| {
+                        info.valid
+                            && !info.specialized
+                            && may_match(saved_info, info)
+                            && same_name(saved_info, info)
+                    })
+            })
+            .map(|(j, _)| j)
+            .collect();
 
-            let Some(current_info) = current_additional_info.get(j).filter(|info| info.valid) else {
-                continue;
-            };
+        // With identical monitors, prefer the same unit, then the same connector
+        // (the daisy chain), then the same adapter
+        let best = candidates
+            .iter()
+            .find(|&&j| same_serial(saved_info, &current_additional_info[j]))
+            .or_else(|| candidates.iter().find(|&&j| same_connector(saved_info, &current_additional_info[j])))
+            .or_else(|| {
+                candidates.iter().find(|&&j| {
+                    !saved_info.adapter_name.is_empty()
+                        && current_additional_info[j].adapter_name == saved_info.adapter_name
+                })
+            })
+            .or(candidates.first());
 
-            if current_info.monitor_friendly_device == saved_info.monitor_friendly_device {
-                mode.adapter_id = current_mode.adapter_id;
-                mode.id = current_mode.id;
-                matched_any = true;
-                break;
-            }
+        if let Some(&j) = best {
+            mode.adapter_id = current.mode_info_array[j].adapter_id;
+            mode.id = current.mode_info_array[j].id;
+            taken.push(j);
+            matched_any = true;
         }
     }
 
     if matched_any {
+        // Paths follow their target modes, which may have moved to other target IDs
+        for path in &mut settings.path_info_array {
+            if let Some(mode) = settings
+                .mode_info_array
+                .get(path.target_info.mode_info_idx as usize)
+                .filter(|mode| mode.info_type == MODE_INFO_TYPE_TARGET)
+            {
+                path.target_info.id = mode.id;
+            }
+        }
+
         // Update paths based on matched modes
-        update_path_adapter_ids_from_modes(settings, current);
+        update_path_adapter_ids_from_modes(settings, additional_info, current, current_additional_info);
     }
 
     matched_any
 }
 
-/// Tier 3: Bulk replacement of old adapter IDs with new ones.
-fn try_bulk_replacement(settings: &mut DisplaySettings, current: &DisplaySettings) -> bool {
+/// Tier 4: Bulk replacement of old adapter IDs with new ones.
+fn try_bulk_replacement(
+    settings: &mut DisplaySettings,
+    additional_info: &[MonitorAdditionalInfo],
+    current: &DisplaySettings,
+    current_additional_info: &[MonitorAdditionalInfo],
+) -> bool {
     // Find one matching path to get the old->new adapter ID mapping
     for path in &settings.path_info_array {
         for current_path in &current.path_info_array {
+            if is_virtual_mismatch(path, additional_info, current_path, current_additional_info) {
+                continue;
+            }
             // Try to find any matching criteria
             if path.source_info.id == current_path.source_info.id {
                 let old_id = path.source_info.adapter_id;
@@ -186,10 +332,18 @@ fn replace_all_adapter_ids(settings: &mut DisplaySettings, old_id: LUID, new_id:
 }
 
 /// Update path adapter IDs based on matched mode adapter IDs.
-fn update_path_adapter_ids_from_modes(settings: &mut DisplaySettings, current: &DisplaySettings) {
+fn update_path_adapter_ids_from_modes(
+    settings: &mut DisplaySettings,
+    additional_info: &[MonitorAdditionalInfo],
+    current: &DisplaySettings,
+    current_additional_info: &[MonitorAdditionalInfo],
+) {
     for path in &mut settings.path_info_array {
         // Find current path with same source/target IDs if possible
         for current_path in &current.path_info_array {
+            if is_virtual_mismatch(path, additional_info, current_path, current_additional_info) {
+                continue;
+            }
             if path.source_info.id == current_path.source_info.id {
                 path.source_info.adapter_id = current_path.source_info.adapter_id;
             }
@@ -200,6 +354,263 @@ fn update_path_adapter_ids_from_modes(settings: &mut DisplaySettings, current: &
     }
 }
 
+/// Update mode adapter IDs based on the paths that reference them.
+fn update_mode_adapter_ids_from_paths(settings: &mut DisplaySettings) {
+    for path in &settings.path_info_array {
+        if let Some(mode) = settings.mode_info_array.get_mut(path.source_info.mode_info_idx as usize) {
+            if mode.info_type == MODE_INFO_TYPE_SOURCE {
+                mode.adapter_id = path.source_info.adapter_id;
+                mode.id = path.source_info.id;
+            }
+        }
+        if let Some(mode) = settings.mode_info_array.get_mut(path.target_info.mode_info_idx as usize) {
+            if mode.info_type == MODE_INFO_TYPE_TARGET {
+                mode.adapter_id = path.target_info.adapter_id;
+                mode.id = path.target_info.id;
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Virtual Adapters
+// ============================================================================
+
+/// Let monitors be matched to targets of virtual adapters like to physical
+/// ones, for setups that stream from a virtual display on purpose.
+pub fn set_match_virtual_adapters(on: bool) {
+    MATCH_VIRTUAL_ADAPTERS.store(on, Ordering::Relaxed);
+}
+
+/// Whether the monitor saved as `saved` may be matched to the current target
+/// described by `current`: a monitor saved on a physical adapter never lands
+/// on a virtual one, unless overridden.
+fn may_match(saved: &MonitorAdditionalInfo, current: &MonitorAdditionalInfo) -> bool {
+    let allowed = !current.virtual_adapter || saved.virtual_adapter || MATCH_VIRTUAL_ADAPTERS.load(Ordering::Relaxed);
+    if !allowed {
+        debug!("Adapter matching: passed over '{}' on virtual adapter {}", current.monitor_friendly_device, current.adapter_name);
+    }
+    allowed
+}
+
+/// `may_match` for a saved path and a current path, looked up by their target
+/// modes. Paths without info are left to the other checks.
+fn is_virtual_mismatch(
+    path: &DisplayConfigPathInfo,
+    additional_info: &[MonitorAdditionalInfo],
+    current_path: &DisplayConfigPathInfo,
+    current_additional_info: &[MonitorAdditionalInfo],
+) -> bool {
+    let default = MonitorAdditionalInfo::default();
+    let saved = additional_info.get(path.target_info.mode_info_idx as usize).unwrap_or(&default);
+    current_additional_info
+        .get(current_path.target_info.mode_info_idx as usize)
+        .is_some_and(|current| !may_match(saved, current))
+}
+
+// ============================================================================
+// EDID Overrides
+// ============================================================================
+
+/// Whether the current target shows the saved monitor's name, either its
+/// effective one or, under an EDID override, the one the monitor sent.
+fn same_name(saved: &MonitorAdditionalInfo, current: &MonitorAdditionalInfo) -> bool {
+    current.monitor_friendly_device == saved.monitor_friendly_device
+        || current
+            .edid_override
+            .as_ref()
+            .is_some_and(|original| original.friendly_name == saved.monitor_friendly_device)
+}
+
+/// Whether the current target is the saved monitor by EDID identity, either
+/// its effective one or, under an EDID override, the one the monitor sent.
+fn same_identity(saved: &MonitorAdditionalInfo, current: &MonitorAdditionalInfo) -> bool {
+    let effective = current.manufacture_id == saved.manufacture_id
+        && current.product_code_id == saved.product_code_id
+        && current.monitor_friendly_device == saved.monitor_friendly_device;
+    effective
+        || current.edid_override.as_ref().is_some_and(|original| {
+            original.manufacture_id == saved.manufacture_id
+                && original.product_code_id == saved.product_code_id
+                && original.friendly_name == saved.monitor_friendly_device
+        })
+}
+
+// ============================================================================
+// Daisy Chains (MST)
+// ============================================================================
+
+/// Whether the current target is the very unit saved, by EDID serial number.
+fn same_serial(saved: &MonitorAdditionalInfo, current: &MonitorAdditionalInfo) -> bool {
+    !saved.serial_number.is_empty() && current.serial_number == saved.serial_number
+}
+
+/// Whether the current target is behind the connector the saved monitor was
+/// on. The monitors of a daisy chain share their connector.
+fn same_connector(saved: &MonitorAdditionalInfo, current: &MonitorAdditionalInfo) -> bool {
+    !saved.adapter_name.is_empty()
+        && current.adapter_name == saved.adapter_name
+        && current.connector_instance == saved.connector_instance
+}
+
+/// Whether a saved target ID now leads to another unit of the same model,
+/// told by its serial number. Daisy-chained monitors do this after a power
+/// cycle.
+fn is_reshuffled(
+    settings: &DisplaySettings,
+    additional_info: &[MonitorAdditionalInfo],
+    current: &DisplaySettings,
+    current_additional_info: &[MonitorAdditionalInfo],
+) -> bool {
+    settings.path_info_array.iter().any(|path| {
+        let Some(saved) = additional_info
+            .get(path.target_info.mode_info_idx as usize)
+            .filter(|info| info.valid && !info.serial_number.is_empty())
+        else {
+            return false;
+        };
+        current
+            .path_info_array
+            .iter()
+            .filter(|current_path| current_path.target_info.id == path.target_info.id)
+            .filter_map(|current_path| current_additional_info.get(current_path.target_info.mode_info_idx as usize))
+            .any(|info| {
+                info.valid && !info.serial_number.is_empty() && same_identity(saved, info) && !same_serial(saved, info)
+            })
+    })
+}
+
+// ============================================================================
+// Connection Check
+// ============================================================================
+
+/// Names of the profile's monitors that aren't connected right now.
+/// Monitors are recognised by their EDID identity, so with identical models
+/// each connected unit satisfies one of them.
+pub fn find_missing_monitors(
+    settings: &DisplaySettings,
+    additional_info: &[MonitorAdditionalInfo],
+) -> Result<Vec<String>, String> {
+    let mut connected = connected_monitor_info()?;
+
+    let mut missing = Vec::new();
+    for (i, path) in settings.path_info_array.iter().enumerate() {
+        // Without EDID data there is nothing to wait for
+        let Some(wanted) = additional_info
+            .get(path.target_info.mode_info_idx as usize)
+            .filter(|info| info.valid)
+        else {
+            continue;
+        };
+
+        let found = connected.iter().position(|c| same_identity(wanted, c));
+        match found {
+            Some(index) => {
+                connected.swap_remove(index);
+            }
+            None if wanted.monitor_friendly_device.is_empty() => missing.push(format!("Display {}", i + 1)),
+            None => missing.push(wanted.monitor_friendly_device.clone()),
+        }
+    }
+
+    Ok(missing)
+}
+
+/// IDs of the connected monitors, for hardware fingerprints.
+/// Specialized displays are left out, like they are from profiles.
+pub fn connected_monitor_ids() -> Result<Vec<String>, String> {
+    Ok(connected_monitor_info()?
+        .into_iter()
+        .filter(|info| !info.specialized)
+        .map(|info| {
+            if info.monitor_device_path.is_empty() {
+                format!("{:04X}{:04X}", info.manufacture_id, info.product_code_id)
+            } else {
+                info.monitor_device_path
+            }
+        })
+        .collect())
+}
+
+/// EDID info of every connected monitor, whether it's active or not.
+fn connected_monitor_info() -> Result<Vec<MonitorAdditionalInfo>, String> {
+    let current = get_display_settings(false)?;
+
+    // The all-paths query lists each target once per source it could be driven by
+    let mut connected: Vec<MonitorAdditionalInfo> = Vec::new();
+    for path in &current.path_info_array {
+        let info = get_monitor_additional_info(path.target_info.adapter_id, path.target_info.id);
+        let listed = connected.iter().any(|c| {
+            !info.monitor_device_path.is_empty() && c.monitor_device_path == info.monitor_device_path
+        });
+        if info.valid && !listed {
+            connected.push(info);
+        }
+    }
+
+    Ok(connected)
+}
+
+// ============================================================================
+// Specialized Displays
+// ============================================================================
+
+/// Remove paths whose target is a specialized display (VR headset etc.) so they
+/// don't end up in saved profiles. Modes only used by removed paths are dropped
+/// and `additional_info` is kept aligned with the remaining modes.
+/// Returns the number of removed paths.
+pub fn exclude_specialized_targets(
+    settings: &mut DisplaySettings,
+    additional_info: &mut Vec<MonitorAdditionalInfo>,
+) -> usize {
+    let before = settings.path_info_array.len();
+    settings
+        .path_info_array
+        .retain(|p| !is_specialized_target(p.target_info.adapter_id, p.target_info.id));
+
+    let removed = before - settings.path_info_array.len();
+    if removed > 0 {
+        drop_unused_modes(settings, additional_info);
+    }
+    removed
+}
+
+/// Drop the modes no path refers to after paths were removed, and point the
+/// paths at the new mode indices. `additional_info` is kept aligned with the
+/// modes.
+pub(super) fn drop_unused_modes(settings: &mut DisplaySettings, additional_info: &mut Vec<MonitorAdditionalInfo>) {
+    // Find the modes still referenced by the remaining paths
+    let mut used = vec![false; settings.mode_info_array.len()];
+    for path in &settings.path_info_array {
+        for idx in [path.source_info.mode_info_idx, path.target_info.mode_info_idx] {
+            if let Some(flag) = used.get_mut(idx as usize) {
+                *flag = true;
+            }
+        }
+    }
+
+    // Compact modes and additional info, remembering where each mode moved
+    let mut remap = vec![DISPLAYCONFIG_PATH_MODE_IDX_INVALID; settings.mode_info_array.len()];
+    let mut modes = Vec::new();
+    let mut infos = Vec::new();
+    for (i, mode) in settings.mode_info_array.iter().enumerate() {
+        if used[i] {
+            remap[i] = modes.len() as u32;
+            modes.push(*mode);
+            infos.push(additional_info.get(i).cloned().unwrap_or_default());
+        }
+    }
+
+    for path in &mut settings.path_info_array {
+        for idx in [&mut path.source_info.mode_info_idx, &mut path.target_info.mode_info_idx] {
+            *idx = remap.get(*idx as usize).copied().unwrap_or(DISPLAYCONFIG_PATH_MODE_IDX_INVALID);
+        }
+    }
+
+    settings.mode_info_array = modes;
+    *additional_info = infos;
+}
+
 /// Get additional info for all target modes in the array.
 pub fn get_additional_info_for_modes(mode_info_array: &[DisplayConfigModeInfo]) -> Vec<MonitorAdditionalInfo> {
     mode_info_array
@@ -213,3 +624,18 @@ pub fn get_additional_info_for_modes(mode_info_array: &[DisplayConfigModeInfo])
         })
         .collect()
 }
+
+/// Record the GDI device name of each path's source on both of its mode entries.
+pub fn fill_gdi_device_names(settings: &DisplaySettings, additional_info: &mut [MonitorAdditionalInfo]) {
+    for path in &settings.path_info_array {
+        let Some(name) = get_source_gdi_device_name(path.source_info.adapter_id, path.source_info.id) else {
+            continue;
+        };
+
+        for idx in [path.source_info.mode_info_idx, path.target_info.mode_info_idx] {
+            if let Some(info) = additional_info.get_mut(idx as usize) {
+                info.gdi_device_name = name.clone();
+            }
+        }
+    }
+}
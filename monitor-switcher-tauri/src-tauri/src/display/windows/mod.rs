@@ -8,26 +8,94 @@
 //! - `api.rs` - Raw Windows CCD API calls
 //! - `types.rs` - Windows-specific type definitions (LUID, DisplayConfig*, etc.)
 //! - `matcher.rs` - Adapter ID matching logic for profile restoration
+//! - `gdi.rs` - Legacy ChangeDisplaySettingsEx fallback for drivers that reject CCD
+//! - `validate.rs` - Checks of a profile against the connected monitors
+//! - `modes.rs` - Resolution/refresh rate changes that keep the current layout
+//! - `positions.rs` - Position changes that keep the current modes
+//! - `geometry.rs` - Layouts from other tools described by monitor bounds
+//! - `edid.rs` - Raw EDID of the connected monitors, from the registry
+//! - `adapters.rs` - Display adapter (GPU) names, driver versions and monitors
+//! - `templates.rs` - Built-in layouts based on the Windows topology presets
+//! - `color_filter.rs` - Accessibility color filters (grayscale, inverted, ...)
+//! - `night_light.rs` - Night Light on/off
+//! - `ddc.rs` - DDC/CI through the monitor configuration API
+//! - `brightness.rs` - Built-in panel brightness (WMI)
+//! - `timings.rs` - Detection of custom target timings the driver refused
+//! - `internal_panel.rs` - Built-in panel on/off state
+//! - `rotation.rs` - Rotating single displays and rotation locks
+//! - `virtual_display.rs` - Virtual displays through an installed driver
+//! - `wireless.rs` - Wireless (Miracast) display targets
 
+mod adapters;
 mod api;
+mod brightness;
+mod color_filter;
+mod ddc;
+mod edid;
+mod events;
+mod gdi;
+mod geometry;
+mod internal_panel;
 mod matcher;
+mod modes;
+mod night_light;
+mod positions;
+mod rotation;
+mod templates;
+mod timings;
 mod types;
+mod validate;
+mod virtual_display;
+mod wireless;
 
 // Re-export public API
 pub use api::{
-    get_display_settings, set_display_settings,
-    get_monitor_additional_info, turn_off_monitors,
-    get_dpi_scaling_info, set_dpi_scaling,
+    get_display_settings, set_display_settings, apply_display_settings, validate_display_settings, refresh_display_database, turn_off_monitors, lock_session,
+    get_dpi_scaling_info, set_dpi_scaling, get_source_gdi_device_name, connector_label, get_adapter_name, get_preferred_mode, is_remote_session,
     DisplaySettings, MonitorAdditionalInfo,
 };
 
-pub use matcher::{match_adapter_ids, get_additional_info_for_modes};
+pub use validate::{find_non_native_modes, find_inactive_targets, find_discrepancies, is_already_applied, profile_monitor_names};
+
+pub use timings::find_replaced_timings;
+
+pub use internal_panel::{get_internal_panel_state, turn_off_internal_panel};
+
+pub use modes::{display_modes, get_display_modes, set_display_modes};
+
+pub use positions::set_display_positions;
+
+pub use geometry::settings_from_geometry;
+
+pub use rotation::{keep_rotations, monitor_at_cursor, rotate_display};
+
+pub use adapters::{get_gpu_info, is_virtual_adapter, settings_display_number};
+
+pub use edid::read_raw_edids;
+
+pub use virtual_display::{count_virtual_displays, set_virtual_display_count, set_virtual_displays, virtual_display_driver};
+
+pub use wireless::{exclude_absent_wireless_targets, open_wireless_connect, wireless_display_names};
+
+pub use templates::{apply_template, primary_only_layout};
+
+pub use color_filter::{get_color_filter, set_color_filter, ColorFilter};
+
+pub use night_light::{get_night_light, set_night_light};
+
+pub use ddc::{read_capabilities_string, read_vcp, write_vcp};
+
+pub use brightness::{brightness_targets, set_panel_brightness};
+
+pub use events::watch_display_changes;
+
+pub use matcher::{match_adapter_ids, match_adapter_ids_against, set_match_virtual_adapters, find_missing_monitors, connected_monitor_ids, get_additional_info_for_modes, fill_gdi_device_names, exclude_specialized_targets};
 
 pub use types::{
     LUID, DisplayConfigPathInfo, DisplayConfigModeInfo,
     DisplayConfigTargetMode, DisplayConfigSourceMode,
     DisplayConfigRational, DisplayConfig2DRegion, PointL,
     DisplayConfigPathSourceInfo, DisplayConfigPathTargetInfo,
-    DisplayConfigVideoSignalInfo, DpiScalingInfo,
-    MODE_INFO_TYPE_SOURCE, MODE_INFO_TYPE_TARGET,
+    DisplayConfigVideoSignalInfo, ApplyFlags,
+    MODE_INFO_TYPE_SOURCE, MODE_INFO_TYPE_TARGET, DPI_VALUES,
 };
@@ -0,0 +1,103 @@
+//! Mode-only changes on the active paths.
+//!
+//! Single responsibility: read and change resolution/refresh rate of the current
+//! configuration without touching positions or topology.
+
+use super::super::DisplayMode;
+use super::api::{DisplaySettings, get_display_settings, set_display_settings_with_flags};
+use super::types::*;
+
+/// Output technologies (DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY) of built-in panels.
+pub(super) const INTERNAL_OUTPUT_TECHNOLOGIES: [u32; 4] = [
+    0x8000_0000, // INTERNAL
+    6,           // LVDS
+    11,          // DISPLAYPORT_EMBEDDED
+    13,          // UDI_EMBEDDED
+];
+
+/// Get the resolution and refresh rate of every active display.
+pub fn get_display_modes() -> Result<Vec<DisplayMode>, String> {
+    Ok(display_modes(&get_display_settings(true)?))
+}
+
+/// Resolution and refresh rate of every active path of a configuration, keyed
+/// like `get_display_modes`. Expects adapter IDs to already be matched to the
+/// current system.
+pub fn display_modes(settings: &DisplaySettings) -> Vec<DisplayMode> {
+    settings
+        .path_info_array
+        .iter()
+        .filter(|path| path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0)
+        .filter_map(|path| {
+            let source = source_mode(settings, path)?;
+            let rate = path.target_info.refresh_rate;
+            let refresh_rate = if rate.denominator == 0 {
+                0.0
+            } else {
+                rate.numerator as f64 / rate.denominator as f64
+            };
+
+            Some(DisplayMode {
+                key: mode_key(path),
+                width: source.width,
+                height: source.height,
+                refresh_rate,
+                internal: INTERNAL_OUTPUT_TECHNOLOGIES.contains(&path.target_info.output_technology),
+            })
+        })
+        .collect()
+}
+
+/// Change resolution and refresh rate of the given displays, leaving everything else as is.
+/// Displays that are no longer active are ignored.
+pub fn set_display_modes(modes: &[DisplayMode]) -> Result<(), String> {
+    let mut settings = get_display_settings(true)?;
+
+    for i in 0..settings.path_info_array.len() {
+        let path = settings.path_info_array[i];
+        let Some(mode) = modes.iter().find(|m| m.key == mode_key(&path)) else {
+            continue;
+        };
+
+        let path = &mut settings.path_info_array[i];
+        path.target_info.refresh_rate = DisplayConfigRational {
+            numerator: (mode.refresh_rate * 1000.0).round() as u32,
+            denominator: 1000,
+        };
+        // Let Windows pick target timings matching the new rate
+        path.target_info.mode_info_idx = DISPLAYCONFIG_PATH_MODE_IDX_INVALID;
+
+        let source_idx = path.source_info.mode_info_idx as usize;
+        if let Some(source_info) = settings
+            .mode_info_array
+            .get_mut(source_idx)
+            .filter(|m| m.info_type == MODE_INFO_TYPE_SOURCE)
+        {
+            let mut source = *source_info.get_source_mode();
+            source.width = mode.width;
+            source.height = mode.height;
+            source_info.set_source_mode(&source);
+        }
+    }
+
+    // Temporary change: keep the Windows display database as the user left it
+    let flags = ApplyFlags {
+        save_to_database: false,
+        ..ApplyFlags::default()
+    };
+    set_display_settings_with_flags(&mut settings, flags)
+}
+
+/// Identity of a path's target, stable while the adapter stays in place.
+fn mode_key(path: &DisplayConfigPathInfo) -> String {
+    let adapter = path.target_info.adapter_id;
+    format!("{:08X}:{:08X}:{}", adapter.high_part, adapter.low_part, path.target_info.id)
+}
+
+fn source_mode<'a>(settings: &'a DisplaySettings, path: &DisplayConfigPathInfo) -> Option<&'a DisplayConfigSourceMode> {
+    settings
+        .mode_info_array
+        .get(path.source_info.mode_info_idx as usize)
+        .filter(|m| m.info_type == MODE_INFO_TYPE_SOURCE)
+        .map(|m| m.get_source_mode())
+}
@@ -0,0 +1,146 @@
+//! Windows Night Light.
+//!
+//! Windows keeps the Night Light state in an undocumented CloudStore blob and
+//! watches it for changes. The layout is known from community tooling: byte 18
+//! is 0x15 when on and 0x13 when off, and the "on" form carries two more bytes
+//! (10 00) at offset 23. A timestamp at bytes 10..15 has to move forward for the
+//! change to be picked up. Single responsibility: read and switch Night Light.
+
+use super::super::simulate;
+use std::mem;
+use std::ptr;
+use windows_sys::Win32::System::Registry::{
+    RegGetValueW, RegSetKeyValueW, HKEY_CURRENT_USER, REG_BINARY, RRF_RT_REG_BINARY,
+};
+
+const STATE_KEY: &str = concat!(
+    r"Software\Microsoft\Windows\CurrentVersion\CloudStore\Store\DefaultAccount\Current\",
+    r"default$windows.data.bluelightreduction.bluelightreductionstate\",
+    r"windows.data.bluelightreduction.bluelightreductionstate",
+);
+
+const STATE_OFFSET: usize = 18;
+const STATE_ON: u8 = 0x15;
+const STATE_OFF: u8 = 0x13;
+/// Where the "on" form has two extra bytes.
+const EXTRA_OFFSET: usize = 23;
+const EXTRA_BYTES: [u8; 2] = [0x10, 0x00];
+/// Bytes of the change timestamp.
+const TIMESTAMP: std::ops::Range<usize> = 10..15;
+
+/// Whether Night Light is on. None if its state was never stored.
+pub fn get_night_light() -> Option<bool> {
+    read_state().and_then(|data| is_on(&data))
+}
+
+/// Turn Night Light on or off.
+pub fn set_night_light(on: bool) -> Result<(), String> {
+    if simulate::is_simulating() {
+        return Ok(());
+    }
+
+    let data = read_state().ok_or("Night Light state not found")?;
+    if is_on(&data) == Some(on) {
+        return Ok(());
+    }
+    let data = switch_state(&data, on).ok_or("Unrecognized Night Light state")?;
+    write_state(&data)
+}
+
+fn is_on(data: &[u8]) -> Option<bool> {
+    match *data.get(STATE_OFFSET)? {
+        STATE_ON => Some(true),
+        STATE_OFF => Some(false),
+        _ => None,
+    }
+}
+
+/// The state blob switched to `on`, with the timestamp moved forward.
+fn switch_state(data: &[u8], on: bool) -> Option<Vec<u8>> {
+    if data.len() < EXTRA_OFFSET + EXTRA_BYTES.len() || is_on(data)? == on {
+        return None;
+    }
+
+    let mut switched = data[..EXTRA_OFFSET].to_vec();
+    if on {
+        switched.extend_from_slice(&EXTRA_BYTES);
+        switched.extend_from_slice(&data[EXTRA_OFFSET..]);
+    } else {
+        switched.extend_from_slice(&data[EXTRA_OFFSET + EXTRA_BYTES.len()..]);
+    }
+    switched[STATE_OFFSET] = if on { STATE_ON } else { STATE_OFF };
+
+    if let Some(byte) = switched[TIMESTAMP].iter_mut().find(|b| **b != 0xFF) {
+        *byte += 1;
+    }
+    Some(switched)
+}
+
+fn read_state() -> Option<Vec<u8>> {
+    let sub_key: Vec<u16> = STATE_KEY.encode_utf16().chain(Some(0)).collect();
+    let value: Vec<u16> = "Data".encode_utf16().chain(Some(0)).collect();
+    let mut buffer = [0u8; 256];
+    let mut size = mem::size_of_val(&buffer) as u32;
+
+    let result = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            sub_key.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_BINARY,
+            ptr::null_mut(),
+            buffer.as_mut_ptr() as *mut _,
+            &mut size,
+        )
+    };
+    (result == 0).then(|| buffer[..size as usize].to_vec())
+}
+
+fn write_state(data: &[u8]) -> Result<(), String> {
+    let sub_key: Vec<u16> = STATE_KEY.encode_utf16().chain(Some(0)).collect();
+    let value: Vec<u16> = "Data".encode_utf16().chain(Some(0)).collect();
+
+    let result = unsafe {
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            sub_key.as_ptr(),
+            value.as_ptr(),
+            REG_BINARY,
+            data.as_ptr() as *const _,
+            data.len() as u32,
+        )
+    };
+    if result != 0 {
+        return Err(format!("Failed to write the Night Light state: error {}", result));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_switch_state() {
+        let off: Vec<u8> = vec![
+            0x43, 0x42, 0x01, 0x00, 0x0A, 0x02, 0x01, 0x00, 0x2A, 0x06, 0xFF, 0x8E, 0xB1, 0xC0, 0x06, 0x2A,
+            0x2B, 0x0E, 0x13, 0x43, 0x42, 0x01, 0x00, 0xD0, 0x0A, 0x02, 0xC6, 0x14, 0xB8, 0x8E, 0x9D, 0xD0,
+            0xB4, 0xC0, 0xAE, 0xE9, 0x01, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let on = switch_state(&off, true).unwrap();
+        assert_eq!(on.len(), off.len() + 2);
+        assert_eq!(is_on(&on), Some(true));
+        assert_eq!(&on[EXTRA_OFFSET..EXTRA_OFFSET + 2], &EXTRA_BYTES);
+        // The first timestamp byte that isn't 0xFF moves forward
+        assert_eq!(on[10..12], [0xFF, 0x8F]);
+
+        let off_again = switch_state(&on, false).unwrap();
+        assert_eq!(off_again.len(), off.len());
+        assert_eq!(is_on(&off_again), Some(false));
+        assert_eq!(off_again[EXTRA_OFFSET..], off[EXTRA_OFFSET..]);
+
+        assert!(switch_state(&off, false).is_none());
+        assert!(switch_state(&off[..20], true).is_none());
+    }
+}
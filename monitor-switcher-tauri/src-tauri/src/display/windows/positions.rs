@@ -0,0 +1,53 @@
+//! Position-only changes on the active paths.
+//!
+//! Single responsibility: move the active displays to the positions of another
+//! configuration while keeping their current modes and topology.
+
+use super::api::{get_display_settings, set_display_settings_with_flags, DisplaySettings};
+use super::types::*;
+
+/// Move the active displays to where `settings` puts them. The primary display
+/// is the one at the origin, so it follows along. Displays the configuration
+/// doesn't have stay where they are.
+/// Expects adapter IDs to already be matched to the current system.
+pub fn set_display_positions(settings: &DisplaySettings) -> Result<(), String> {
+    let mut current = get_display_settings(true)?;
+    let mut moved = 0;
+
+    for i in 0..current.path_info_array.len() {
+        let path = current.path_info_array[i];
+        if path.flags & DISPLAYCONFIG_PATH_ACTIVE == 0 {
+            continue;
+        }
+        let Some(position) = settings
+            .path_info_array
+            .iter()
+            .filter(|saved| saved.flags & DISPLAYCONFIG_PATH_ACTIVE != 0)
+            .find(|saved| saved.target_info.adapter_id == path.target_info.adapter_id && saved.target_info.id == path.target_info.id)
+            .and_then(|saved| source_at(settings, saved.source_info.mode_info_idx))
+            .map(|source| source.position)
+        else {
+            continue;
+        };
+
+        let idx = path.source_info.mode_info_idx as usize;
+        if let Some(mut source) = source_at(&current, path.source_info.mode_info_idx) {
+            source.position = position;
+            current.mode_info_array[idx].set_source_mode(&source);
+            moved += 1;
+        }
+    }
+
+    if moved == 0 {
+        return Err("None of the configuration's displays are active".to_string());
+    }
+    set_display_settings_with_flags(&mut current, ApplyFlags::default())
+}
+
+fn source_at(settings: &DisplaySettings, idx: u32) -> Option<DisplayConfigSourceMode> {
+    settings
+        .mode_info_array
+        .get(idx as usize)
+        .filter(|m| m.info_type == MODE_INFO_TYPE_SOURCE)
+        .map(|m| *m.get_source_mode())
+}
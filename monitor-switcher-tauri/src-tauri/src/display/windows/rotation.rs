@@ -0,0 +1,116 @@
+//! Rotation of single displays.
+//!
+//! Single responsibility: turn one display by 90° steps, find the display under
+//! the mouse pointer, and keep rotation-locked displays upright while a profile
+//! is applied.
+
+use super::super::rotate_by;
+use super::api::{get_display_settings, get_monitor_additional_info, set_display_settings_with_flags, DisplaySettings};
+use super::matcher::get_additional_info_for_modes;
+use super::types::*;
+use windows_sys::Win32::Foundation::POINT;
+use windows_sys::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+/// Turn a display (by monitor device path) by 90° steps, clockwise for
+/// positive `quarter_turns`. Its neighbours stay where they are.
+pub fn rotate_display(monitor_id: &str, quarter_turns: i32) -> Result<(), String> {
+    let mut settings = get_display_settings(true)?;
+    let additional_info = get_additional_info_for_modes(&settings.mode_info_array);
+
+    let idx = settings
+        .path_info_array
+        .iter()
+        .position(|path| {
+            path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0
+                && additional_info
+                    .get(path.target_info.mode_info_idx as usize)
+                    .is_some_and(|info| info.monitor_device_path == monitor_id)
+        })
+        .ok_or_else(|| format!("Display {} is not active", monitor_id))?;
+
+    let rotation = rotate_by(settings.path_info_array[idx].target_info.rotation, quarter_turns);
+    set_rotation(&mut settings, idx, rotation);
+    set_display_settings_with_flags(&mut settings, ApplyFlags::default())
+}
+
+/// Monitor device path of the active display under the mouse pointer.
+pub fn monitor_at_cursor() -> Option<String> {
+    let mut point = POINT { x: 0, y: 0 };
+    if unsafe { GetCursorPos(&mut point) } == 0 {
+        return None;
+    }
+
+    let settings = get_display_settings(true).ok()?;
+    let additional_info = get_additional_info_for_modes(&settings.mode_info_array);
+
+    settings
+        .path_info_array
+        .iter()
+        .filter(|path| path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0)
+        .find(|path| {
+            settings
+                .mode_info_array
+                .get(path.source_info.mode_info_idx as usize)
+                .filter(|m| m.info_type == MODE_INFO_TYPE_SOURCE)
+                .map(|m| m.get_source_mode())
+                .is_some_and(|source| {
+                    (source.position.x..source.position.x + source.width as i32).contains(&point.x)
+                        && (source.position.y..source.position.y + source.height as i32).contains(&point.y)
+                })
+        })
+        .and_then(|path| additional_info.get(path.target_info.mode_info_idx as usize))
+        .map(|info| info.monitor_device_path.clone())
+        .filter(|path| !path.is_empty())
+}
+
+/// Give rotation-locked displays (by monitor device path) of a configuration
+/// the orientation they have right now. Expects adapter IDs to already be
+/// matched to the current system; locked displays that are off keep the
+/// configuration's rotation.
+pub fn keep_rotations(settings: &mut DisplaySettings, locked: &[String]) {
+    if locked.is_empty() {
+        return;
+    }
+    let Ok(current) = get_display_settings(true) else {
+        return;
+    };
+
+    for idx in 0..settings.path_info_array.len() {
+        let target = settings.path_info_array[idx].target_info;
+        let info = get_monitor_additional_info(target.adapter_id, target.id);
+        if !locked.contains(&info.monitor_device_path) {
+            continue;
+        }
+        let Some(rotation) = current
+            .path_info_array
+            .iter()
+            .find(|path| path.target_info.adapter_id == target.adapter_id && path.target_info.id == target.id)
+            .map(|path| path.target_info.rotation)
+        else {
+            continue;
+        };
+        if rotation != target.rotation {
+            log::info!("Keeping {} at its locked rotation", info.monitor_friendly_device);
+            set_rotation(settings, idx, rotation);
+        }
+    }
+}
+
+/// Set a path's rotation. Turning it sideways swaps the desktop width and height.
+fn set_rotation(settings: &mut DisplaySettings, idx: usize, rotation: u32) {
+    let path = &mut settings.path_info_array[idx];
+    let sideways = |rotation: u32| rotation == 2 || rotation == 4;
+    let swap = sideways(path.target_info.rotation) != sideways(rotation);
+    path.target_info.rotation = rotation;
+
+    let source_idx = path.source_info.mode_info_idx as usize;
+    if let Some(mode) = settings
+        .mode_info_array
+        .get_mut(source_idx)
+        .filter(|m| swap && m.info_type == MODE_INFO_TYPE_SOURCE)
+    {
+        let mut source = *mode.get_source_mode();
+        std::mem::swap(&mut source.width, &mut source.height);
+        mode.set_source_mode(&source);
+    }
+}
@@ -0,0 +1,290 @@
+//! Built-in layout templates.
+//!
+//! Single responsibility: switch to a template layout using the Windows topology
+//! presets (the same ones as Win+P), then arrange extended displays around the
+//! PC screen, or give duplicated displays a resolution they all support.
+
+use super::super::{best_common_resolution, primary_index, Template};
+use super::api::{
+    describe_set_display_config_error, get_display_settings, get_source_gdi_device_name,
+    set_display_settings_with_flags, DisplaySettings, MonitorAdditionalInfo,
+};
+use super::matcher::drop_unused_modes;
+use super::modes::INTERNAL_OUTPUT_TECHNOLOGIES;
+use super::types::*;
+use std::mem;
+use windows_sys::Win32::Devices::Display::{
+    SetDisplayConfig, SDC_APPLY, SDC_TOPOLOGY_CLONE, SDC_TOPOLOGY_EXTEND, SDC_TOPOLOGY_EXTERNAL,
+    SDC_TOPOLOGY_INTERNAL,
+};
+use windows_sys::Win32::Graphics::Gdi::{EnumDisplaySettingsW, DEVMODEW};
+
+/// Switch to a template layout.
+pub fn apply_template(template: Template) -> Result<(), String> {
+    if super::super::is_simulating() {
+        return Err("Templates can't be applied to simulated displays".to_string());
+    }
+
+    let topology = match template {
+        Template::PcOnly => SDC_TOPOLOGY_INTERNAL,
+        Template::ExternalOnly => SDC_TOPOLOGY_EXTERNAL,
+        Template::ExtendLeft | Template::ExtendRight => SDC_TOPOLOGY_EXTEND,
+        Template::Duplicate => return duplicate_at_best_common_mode(),
+    };
+    apply_topology(topology)?;
+
+    match template {
+        Template::ExtendLeft => arrange_extended(true),
+        Template::ExtendRight => arrange_extended(false),
+        _ => Ok(()),
+    }
+}
+
+/// `settings` with only the primary display active: the paths of the source at
+/// the desktop origin, so displays cloning it stay on. None when no other
+/// display is active.
+pub fn primary_only_layout(settings: &DisplaySettings) -> Option<DisplaySettings> {
+    let mut layout = settings.clone();
+    layout.path_info_array.retain(|path| path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0);
+
+    let positions: Vec<(i32, i32)> = layout
+        .path_info_array
+        .iter()
+        .map(|path| {
+            layout
+                .mode_info_array
+                .get(path.source_info.mode_info_idx as usize)
+                .filter(|m| m.info_type == MODE_INFO_TYPE_SOURCE)
+                .map_or((i32::MAX, i32::MAX), |m| {
+                    let position = m.get_source_mode().position;
+                    (position.x, position.y)
+                })
+        })
+        .collect();
+    let primary = layout.path_info_array[primary_index(&positions)?].source_info;
+
+    let before = layout.path_info_array.len();
+    layout.path_info_array.retain(|path| {
+        path.source_info.adapter_id == primary.adapter_id && path.source_info.id == primary.id
+    });
+    if layout.path_info_array.len() == before {
+        return None;
+    }
+
+    let mut additional_info = vec![MonitorAdditionalInfo::default(); layout.mode_info_array.len()];
+    drop_unused_modes(&mut layout, &mut additional_info);
+    Some(layout)
+}
+
+/// Duplicate all displays at the largest resolution they all support. Left to
+/// itself, Windows picks something like 1024x768 when the native resolutions differ.
+fn duplicate_at_best_common_mode() -> Result<(), String> {
+    // Before cloning, each active display has its own source listing its own modes
+    let mut supported = source_resolutions(&get_display_settings(true)?);
+    apply_topology(SDC_TOPOLOGY_CLONE)?;
+
+    let mut settings = get_display_settings(true)?;
+    supported.extend(source_resolutions(&settings));
+    let Some((width, height)) = best_common_resolution(&supported) else {
+        log::warn!("The duplicated displays have no resolution in common, keeping the one Windows picked");
+        return Ok(());
+    };
+
+    for i in 0..settings.path_info_array.len() {
+        let path = &mut settings.path_info_array[i];
+        if path.flags & DISPLAYCONFIG_PATH_ACTIVE == 0 {
+            continue;
+        }
+        // Let Windows pick target timings for the new resolution
+        path.target_info.mode_info_idx = DISPLAYCONFIG_PATH_MODE_IDX_INVALID;
+
+        let idx = path.source_info.mode_info_idx as usize;
+        if settings.mode_info_array.get(idx).is_some_and(|m| m.info_type == MODE_INFO_TYPE_SOURCE) {
+            let mut source = source_at(&settings, idx);
+            source.width = width;
+            source.height = height;
+            settings.mode_info_array[idx].set_source_mode(&source);
+        }
+    }
+
+    set_display_settings_with_flags(&mut settings, ApplyFlags::default())
+}
+
+/// Resolutions each active source supports, one list per source.
+fn source_resolutions(settings: &DisplaySettings) -> Vec<Vec<(u32, u32)>> {
+    let mut devices: Vec<String> = settings
+        .path_info_array
+        .iter()
+        .filter(|path| path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0)
+        .filter_map(|path| get_source_gdi_device_name(path.source_info.adapter_id, path.source_info.id))
+        .collect();
+    // Cloned targets share a source
+    devices.sort();
+    devices.dedup();
+
+    devices
+        .iter()
+        .map(|device| supported_resolutions(device))
+        .filter(|resolutions| !resolutions.is_empty())
+        .collect()
+}
+
+/// Resolutions a GDI display device (e.g. "\\.\DISPLAY1") supports.
+fn supported_resolutions(device: &str) -> Vec<(u32, u32)> {
+    let wide_name: Vec<u16> = device.encode_utf16().chain(std::iter::once(0)).collect();
+    let mut resolutions = Vec::new();
+
+    for mode in 0.. {
+        let mut devmode: DEVMODEW = unsafe { mem::zeroed() };
+        devmode.dmSize = mem::size_of::<DEVMODEW>() as u16;
+        if unsafe { EnumDisplaySettingsW(wide_name.as_ptr(), mode, &mut devmode) } == 0 {
+            break;
+        }
+        let resolution = (devmode.dmPelsWidth, devmode.dmPelsHeight);
+        if !resolutions.contains(&resolution) {
+            resolutions.push(resolution);
+        }
+    }
+    resolutions
+}
+
+/// Apply one of the topologies stored in the Windows display database.
+fn apply_topology(topology: u32) -> Result<(), String> {
+    let result = unsafe {
+        SetDisplayConfig(0, std::ptr::null_mut(), 0, std::ptr::null_mut(), SDC_APPLY | topology)
+    };
+    if result != 0 {
+        return Err(format!(
+            "SetDisplayConfig failed with error: {}",
+            describe_set_display_config_error(result)
+        ));
+    }
+    Ok(())
+}
+
+/// Place the PC screen at the origin and line up the other displays beside it,
+/// top-aligned, to its left or right.
+fn arrange_extended(externals_left: bool) -> Result<(), String> {
+    let mut settings = get_display_settings(true)?;
+
+    let mut sources: Vec<(usize, bool)> = settings
+        .path_info_array
+        .iter()
+        .filter(|path| path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0)
+        .filter_map(|path| {
+            let idx = path.source_info.mode_info_idx as usize;
+            let mode = settings.mode_info_array.get(idx)?;
+            let internal = INTERNAL_OUTPUT_TECHNOLOGIES.contains(&path.target_info.output_technology);
+            (mode.info_type == MODE_INFO_TYPE_SOURCE).then_some((idx, internal))
+        })
+        .collect();
+    // Cloned targets share a source; move it only once
+    sources.sort_by_key(|&(idx, internal)| (idx, !internal));
+    sources.dedup_by_key(|(idx, _)| *idx);
+    if sources.len() < 2 {
+        return Ok(());
+    }
+
+    // Keep the current left-to-right order, with the PC screen first
+    let anchor = sources
+        .iter()
+        .position(|&(_, internal)| internal)
+        .or_else(|| {
+            sources.iter().position(|&(idx, _)| {
+                let position = source_at(&settings, idx).position;
+                position.x == 0 && position.y == 0
+            })
+        })
+        .unwrap_or(0);
+    let (anchor_idx, _) = sources.remove(anchor);
+    sources.sort_by_key(|&(idx, _)| source_at(&settings, idx).position.x);
+    if externals_left {
+        sources.reverse();
+    }
+
+    set_position(&mut settings, anchor_idx, 0);
+    let mut left = 0;
+    let mut right = source_at(&settings, anchor_idx).width as i32;
+    for (idx, _) in sources {
+        let width = source_at(&settings, idx).width as i32;
+        if externals_left {
+            left -= width;
+            set_position(&mut settings, idx, left);
+        } else {
+            set_position(&mut settings, idx, right);
+            right += width;
+        }
+    }
+
+    set_display_settings_with_flags(&mut settings, ApplyFlags::default())
+}
+
+fn source_at(settings: &DisplaySettings, idx: usize) -> DisplayConfigSourceMode {
+    *settings.mode_info_array[idx].get_source_mode()
+}
+
+fn set_position(settings: &mut DisplaySettings, idx: usize, x: i32) {
+    let mut source = source_at(settings, idx);
+    source.position = PointL { x, y: 0 };
+    settings.mode_info_array[idx].set_source_mode(&source);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source_mode(id: u32, x: i32, y: i32) -> DisplayConfigModeInfo {
+        let mut mode = DisplayConfigModeInfo { info_type: MODE_INFO_TYPE_SOURCE, id, ..Default::default() };
+        mode.set_source_mode(&DisplayConfigSourceMode {
+            width: 1920,
+            height: 1080,
+            pixel_format: 0,
+            position: PointL { x, y },
+        });
+        mode
+    }
+
+    fn target_mode(id: u32) -> DisplayConfigModeInfo {
+        DisplayConfigModeInfo { info_type: MODE_INFO_TYPE_TARGET, id, ..Default::default() }
+    }
+
+    /// Path from source `source_id` to target `target_id`, with their mode indices.
+    fn path(source_id: u32, source_idx: u32, target_id: u32, target_idx: u32, flags: u32) -> DisplayConfigPathInfo {
+        let mut path = DisplayConfigPathInfo { flags, ..Default::default() };
+        (path.source_info.id, path.source_info.mode_info_idx) = (source_id, source_idx);
+        (path.target_info.id, path.target_info.mode_info_idx) = (target_id, target_idx);
+        path
+    }
+
+    #[test]
+    fn test_primary_only_layout() {
+        let invalid = DISPLAYCONFIG_PATH_MODE_IDX_INVALID;
+        let settings = DisplaySettings {
+            path_info_array: vec![
+                // A display to the right of the primary one
+                path(1, 0, 12, 1, DISPLAYCONFIG_PATH_ACTIVE),
+                // The primary display and a projector cloning it
+                path(0, 2, 10, 3, DISPLAYCONFIG_PATH_ACTIVE),
+                path(0, 2, 11, 4, DISPLAYCONFIG_PATH_ACTIVE),
+                // Connected but off
+                path(2, invalid, 13, invalid, 0),
+            ],
+            mode_info_array: vec![source_mode(1, 1920, 0), target_mode(12), source_mode(0, 0, 0), target_mode(10), target_mode(11)],
+        };
+
+        let layout = primary_only_layout(&settings).unwrap();
+        let targets: Vec<u32> = layout.path_info_array.iter().map(|p| p.target_info.id).collect();
+        assert_eq!(targets, [10, 11]);
+
+        // Modes of the display switched off are dropped and the paths follow theirs
+        let mode_ids: Vec<u32> = layout.mode_info_array.iter().map(|m| m.id).collect();
+        assert_eq!(mode_ids, [0, 10, 11]);
+        for path in &layout.path_info_array {
+            assert_eq!(layout.mode_info_array[path.source_info.mode_info_idx as usize].id, 0);
+            assert_eq!(layout.mode_info_array[path.target_info.mode_info_idx as usize].id, path.target_info.id);
+        }
+
+        // Nothing left to switch off
+        assert!(primary_only_layout(&layout).is_none());
+    }
+}
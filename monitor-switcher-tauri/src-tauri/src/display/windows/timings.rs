@@ -0,0 +1,117 @@
+//! Custom target timings (CRU-style resolutions).
+//!
+//! Profiles store the full target video signal, and it's handed to
+//! SetDisplayConfig as is. Drivers that don't list a timing may still refuse
+//! it: SetDisplayConfig then fails, or swaps in a listed timing when retried
+//! with SDC_ALLOW_CHANGES.
+//! Single responsibility: spot targets whose saved timing wasn't applied.
+
+use super::api::{get_display_settings, DisplaySettings, MonitorAdditionalInfo};
+use super::types::*;
+
+/// Find active targets of an applied configuration whose timing differs from
+/// the one requested. Expects adapter IDs to already be matched to the current
+/// system. Returns the monitor name and a human-readable message for each.
+pub fn find_replaced_timings(
+    settings: &DisplaySettings,
+    additional_info: &[MonitorAdditionalInfo],
+) -> Result<Vec<(String, String)>, String> {
+    let applied = get_display_settings(true)?;
+
+    Ok(settings
+        .path_info_array
+        .iter()
+        .enumerate()
+        .filter(|(_, path)| path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0)
+        .filter_map(|(i, path)| {
+            let requested = target_signal(settings, path)?;
+            let current_path = applied.path_info_array.iter().find(|a| {
+                a.target_info.adapter_id == path.target_info.adapter_id && a.target_info.id == path.target_info.id
+            })?;
+            let current = target_signal(&applied, current_path)?;
+            if timings_match(requested, current) {
+                return None;
+            }
+
+            let name = additional_info
+                .get(path.target_info.mode_info_idx as usize)
+                .map(|info| info.monitor_friendly_device.clone())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| format!("Display {}", i + 1));
+            let message = format!(
+                "The driver refused the saved timing {} and used {}",
+                describe_timing(requested),
+                describe_timing(current)
+            );
+            Some((name, message))
+        })
+        .collect())
+}
+
+/// The target video signal of a path, if it has a target mode.
+fn target_signal<'a>(settings: &'a DisplaySettings, path: &DisplayConfigPathInfo) -> Option<&'a DisplayConfigVideoSignalInfo> {
+    settings
+        .mode_info_array
+        .get(path.target_info.mode_info_idx as usize)
+        .filter(|m| m.info_type == MODE_INFO_TYPE_TARGET)
+        .map(|m| &m.get_target_mode().target_video_signal_info)
+}
+
+/// Whether two timings describe the same signal. Rationals are compared by
+/// value; drivers report them with different denominators.
+fn timings_match(a: &DisplayConfigVideoSignalInfo, b: &DisplayConfigVideoSignalInfo) -> bool {
+    a.active_size.cx == b.active_size.cx
+        && a.active_size.cy == b.active_size.cy
+        && a.total_size.cx == b.total_size.cx
+        && a.total_size.cy == b.total_size.cy
+        && a.pixel_rate == b.pixel_rate
+        && a.scan_line_ordering == b.scan_line_ordering
+        && same_rate(a.v_sync_freq, b.v_sync_freq)
+}
+
+fn same_rate(a: DisplayConfigRational, b: DisplayConfigRational) -> bool {
+    u64::from(a.numerator) * u64::from(b.denominator) == u64::from(b.numerator) * u64::from(a.denominator)
+}
+
+/// A timing as "2560x1080 @ 75.00 Hz (2720x1111 total, 226.50 MHz)".
+fn describe_timing(signal: &DisplayConfigVideoSignalInfo) -> String {
+    let refresh = match signal.v_sync_freq.denominator {
+        0 => 0.0,
+        d => f64::from(signal.v_sync_freq.numerator) / f64::from(d),
+    };
+    format!(
+        "{}x{} @ {:.2} Hz ({}x{} total, {:.2} MHz)",
+        signal.active_size.cx,
+        signal.active_size.cy,
+        refresh,
+        signal.total_size.cx,
+        signal.total_size.cy,
+        signal.pixel_rate as f64 / 1_000_000.0
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(total_cx: u32, pixel_rate: u64, v_sync: (u32, u32)) -> DisplayConfigVideoSignalInfo {
+        DisplayConfigVideoSignalInfo {
+            pixel_rate,
+            v_sync_freq: DisplayConfigRational { numerator: v_sync.0, denominator: v_sync.1 },
+            active_size: DisplayConfig2DRegion { cx: 2560, cy: 1080 },
+            total_size: DisplayConfig2DRegion { cx: total_cx, cy: 1111 },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_timings_match() {
+        let custom = signal(2720, 226_500_000, (75_000, 1_000));
+        assert!(timings_match(&custom, &signal(2720, 226_500_000, (75, 1))));
+        assert!(!timings_match(&custom, &signal(3424, 230_000_000, (60, 1))));
+        assert_eq!(
+            describe_timing(&custom),
+            "2560x1080 @ 75.00 Hz (2720x1111 total, 226.50 MHz)"
+        );
+    }
+}
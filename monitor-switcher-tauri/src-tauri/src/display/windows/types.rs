@@ -3,9 +3,12 @@
 //! These types must match the exact memory layout expected by Windows API.
 //! Single responsibility: define Windows-specific data structures.
 
+use serde::{Deserialize, Serialize};
+
 /// Locally Unique Identifier for display adapters.
 /// Note: Adapter IDs change on system restart, so matching must be done by other fields.
 #[repr(C)]
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct LUID {
     pub low_part: u32,
@@ -234,6 +237,111 @@ impl DisplayConfigTargetDeviceName {
     }
 }
 
+/// GDI device name for a source (e.g. `\\.\DISPLAY1`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DisplayConfigSourceDeviceName {
+    pub header: DisplayConfigDeviceInfoHeader,
+    pub view_gdi_device_name: [u16; 32],
+}
+
+impl DisplayConfigSourceDeviceName {
+    /// Get the GDI device name as a Rust string.
+    pub fn get_gdi_device_name(&self) -> String {
+        let end = self.view_gdi_device_name
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(32);
+        String::from_utf16_lossy(&self.view_gdi_device_name[..end])
+    }
+}
+
+/// Device path of a display adapter (GPU).
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayConfigAdapterName {
+    pub header: DisplayConfigDeviceInfoHeader,
+    pub adapter_device_path: [u16; 128],
+}
+
+impl Default for DisplayConfigAdapterName {
+    fn default() -> Self {
+        Self {
+            header: DisplayConfigDeviceInfoHeader::default(),
+            adapter_device_path: [0u16; 128],
+        }
+    }
+}
+
+impl DisplayConfigAdapterName {
+    /// Get the adapter device path as a Rust string.
+    pub fn get_adapter_device_path(&self) -> String {
+        let end = self.adapter_device_path
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(128);
+        String::from_utf16_lossy(&self.adapter_device_path[..end])
+    }
+}
+
+/// Preferred (native) mode of a target.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DisplayConfigTargetPreferredMode {
+    pub header: DisplayConfigDeviceInfoHeader,
+    pub width: u32,
+    pub height: u32,
+    pub target_mode: DisplayConfigTargetMode,
+}
+
+/// Monitor specialization state for a target (VR headsets and other specialized displays).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DisplayConfigGetMonitorSpecialization {
+    pub header: DisplayConfigDeviceInfoHeader,
+    /// Bitfield: bit 0 = specialization enabled, bit 1 = available for monitor,
+    /// bit 2 = available for system.
+    pub value: u32,
+}
+
+impl DisplayConfigGetMonitorSpecialization {
+    /// Whether the target is currently used as a specialized display.
+    pub fn is_specialization_enabled(&self) -> bool {
+        self.value & 0x1 != 0
+    }
+}
+
+// ============================================================================
+// Apply Flags
+// ============================================================================
+
+/// Options controlling which SetDisplayConfig flags are used when applying,
+/// stored per profile or as the global default. Defaults, also for missing
+/// fields, reproduce the historical behaviour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct ApplyFlags {
+    /// Persist the configuration to the Windows display database (SDC_SAVE_TO_DATABASE).
+    pub save_to_database: bool,
+    /// Apply exactly as supplied, even if it is already active (SDC_NO_OPTIMIZATION).
+    pub no_optimization: bool,
+    /// Let Windows reorder paths when needed (SDC_ALLOW_PATH_ORDER_CHANGES).
+    pub allow_path_order_changes: bool,
+    /// Retry with SDC_ALLOW_CHANGES if the exact configuration is rejected.
+    pub retry_with_allow_changes: bool,
+}
+
+impl Default for ApplyFlags {
+    fn default() -> Self {
+        Self {
+            save_to_database: true,
+            no_optimization: true,
+            allow_path_order_changes: false,
+            retry_with_allow_changes: true,
+        }
+    }
+}
+
 // ============================================================================
 // Constants
 // ============================================================================
@@ -244,6 +352,12 @@ pub const MODE_INFO_TYPE_SOURCE: u32 = 1;
 /// Mode info type for target modes.
 pub const MODE_INFO_TYPE_TARGET: u32 = 2;
 
+/// Mode index value meaning "no mode".
+pub const DISPLAYCONFIG_PATH_MODE_IDX_INVALID: u32 = 0xFFFF_FFFF;
+
+/// Path flag: the path is part of the active configuration.
+pub const DISPLAYCONFIG_PATH_ACTIVE: u32 = 0x1;
+
 // Undocumented device info types for DPI scaling
 // These values are used by Windows Settings app but not publicly documented
 pub const DISPLAYCONFIG_DEVICE_INFO_GET_DPI_SCALE: i32 = -3;
@@ -287,7 +401,7 @@ pub struct DisplayConfigSourceDpiScaleGet {
 
 impl DisplayConfigSourceDpiScaleGet {
     /// Convert the relative scale values to absolute DPI percentages.
-    pub fn to_dpi_info(&self) -> Option<DpiScalingInfo> {
+    pub fn to_dpi_info(self) -> Option<DpiScalingInfo> {
         // Validate: current should be between min and max
         if self.cur_scale_rel < self.min_scale_rel || self.cur_scale_rel > self.max_scale_rel {
             return None;
@@ -0,0 +1,195 @@
+//! Profile validation against the connected hardware.
+//!
+//! Single responsibility: point out settings that will work but look wrong,
+//! such as a non-native resolution on an LCD panel, displays that stayed
+//! dark after applying, and settings the driver applied differently.
+
+use super::super::{describe_discrepancies, DisplayState};
+use super::api::{DisplaySettings, MonitorAdditionalInfo, get_display_settings, get_monitor_additional_info, get_preferred_mode};
+use super::types::*;
+
+/// Rotation values (DISPLAYCONFIG_ROTATION) that swap width and height.
+const ROTATE90: u32 = 2;
+const ROTATE270: u32 = 4;
+
+/// Find paths whose resolution differs from the monitor's native mode.
+/// Expects adapter IDs to already be matched to the current system.
+/// Returns one human-readable message per affected monitor.
+pub fn find_non_native_modes(settings: &DisplaySettings) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    for path in &settings.path_info_array {
+        let Some(source) = settings
+            .mode_info_array
+            .get(path.source_info.mode_info_idx as usize)
+            .filter(|m| m.info_type == MODE_INFO_TYPE_SOURCE)
+            .map(|m| m.get_source_mode())
+        else {
+            continue;
+        };
+
+        let Some((native_width, native_height)) =
+            get_preferred_mode(path.target_info.adapter_id, path.target_info.id)
+        else {
+            continue;
+        };
+
+        // The preferred mode is reported in panel orientation
+        let (width, height) = match path.target_info.rotation {
+            ROTATE90 | ROTATE270 => (source.height, source.width),
+            _ => (source.width, source.height),
+        };
+
+        if (width, height) != (native_width, native_height) {
+            let info = get_monitor_additional_info(path.target_info.adapter_id, path.target_info.id);
+            let name = if info.monitor_friendly_device.is_empty() {
+                format!("Display {}", path.target_info.id)
+            } else {
+                info.monitor_friendly_device
+            };
+
+            warnings.push(format!(
+                "{}: {}x{} is not the native resolution ({}x{})",
+                name, width, height, native_width, native_height
+            ));
+        }
+    }
+
+    warnings
+}
+
+/// Names of the displays a configuration turns on, as the checks here and in
+/// `find_missing_monitors` report them.
+pub fn profile_monitor_names(settings: &DisplaySettings, additional_info: &[MonitorAdditionalInfo]) -> Vec<String> {
+    settings
+        .path_info_array
+        .iter()
+        .enumerate()
+        .filter(|(_, path)| path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0)
+        .map(|(i, path)| {
+            additional_info
+                .get(path.target_info.mode_info_idx as usize)
+                .map(|info| info.monitor_friendly_device.clone())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| format!("Display {}", i + 1))
+        })
+        .collect()
+}
+
+/// Compare an applied configuration with the displays as they are now, setting
+/// by setting. Drivers may quietly pick a close match, e.g. 59.94 Hz for 60 Hz.
+/// Expects adapter IDs to already be matched to the current system.
+/// Returns the monitor name and what differs for each affected display;
+/// displays that aren't active are left to `find_inactive_targets`.
+pub fn find_discrepancies(
+    settings: &DisplaySettings,
+    additional_info: &[MonitorAdditionalInfo],
+) -> Result<Vec<(String, Vec<String>)>, String> {
+    let applied = get_display_settings(true)?;
+
+    Ok(settings
+        .path_info_array
+        .iter()
+        .enumerate()
+        .filter(|(_, path)| path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0)
+        .filter_map(|(i, path)| {
+            let requested = display_state(settings, path)?;
+            let current = applied.path_info_array.iter().find(|a| {
+                a.target_info.adapter_id == path.target_info.adapter_id && a.target_info.id == path.target_info.id
+            })?;
+            let found = describe_discrepancies(&requested, &display_state(&applied, current)?);
+            if found.is_empty() {
+                return None;
+            }
+
+            let name = additional_info
+                .get(path.target_info.mode_info_idx as usize)
+                .map(|info| info.monitor_friendly_device.clone())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| format!("Display {}", i + 1));
+            Some((name, found))
+        })
+        .collect())
+}
+
+/// Whether the displays are already set up as a configuration asks: the same
+/// displays are active, each with the requested settings, with refresh rates
+/// as close as `find_discrepancies` allows. Applying it again would only make
+/// the screens blank. Expects adapter IDs to already be matched to the
+/// current system.
+pub fn is_already_applied(settings: &DisplaySettings) -> Result<bool, String> {
+    let current = get_display_settings(true)?;
+
+    let requested: Vec<&DisplayConfigPathInfo> = settings
+        .path_info_array
+        .iter()
+        .filter(|path| path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0)
+        .collect();
+    if requested.is_empty() || requested.len() != current.path_info_array.len() {
+        return Ok(false);
+    }
+
+    Ok(requested.iter().all(|path| {
+        let Some(active) = current.path_info_array.iter().find(|a| {
+            a.target_info.adapter_id == path.target_info.adapter_id && a.target_info.id == path.target_info.id
+        }) else {
+            return false;
+        };
+        match (display_state(settings, path), display_state(&current, active)) {
+            (Some(requested), Some(active)) => describe_discrepancies(&requested, &active).is_empty(),
+            _ => false,
+        }
+    }))
+}
+
+/// Desktop size and position of a path's source, with its target's refresh
+/// rate and rotation. None without a source mode.
+fn display_state(settings: &DisplaySettings, path: &DisplayConfigPathInfo) -> Option<DisplayState> {
+    let source = settings
+        .mode_info_array
+        .get(path.source_info.mode_info_idx as usize)
+        .filter(|m| m.info_type == MODE_INFO_TYPE_SOURCE)
+        .map(|m| m.get_source_mode())?;
+    let rate = path.target_info.refresh_rate;
+
+    Some(DisplayState {
+        width: source.width,
+        height: source.height,
+        x: source.position.x,
+        y: source.position.y,
+        refresh_rate: match rate.denominator {
+            0 => 0.0,
+            d => f64::from(rate.numerator) / f64::from(d),
+        },
+        rotation: path.target_info.rotation,
+    })
+}
+
+/// Find displays of an applied configuration that aren't active.
+/// Expects adapter IDs to already be matched to the current system.
+/// Returns the names of the dark displays.
+pub fn find_inactive_targets(
+    settings: &DisplaySettings,
+    additional_info: &[MonitorAdditionalInfo],
+) -> Result<Vec<String>, String> {
+    let active = get_display_settings(true)?;
+
+    Ok(settings
+        .path_info_array
+        .iter()
+        .enumerate()
+        .filter(|(_, path)| path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0)
+        .filter(|(_, path)| {
+            !active.path_info_array.iter().any(|a| {
+                a.target_info.adapter_id == path.target_info.adapter_id && a.target_info.id == path.target_info.id
+            })
+        })
+        .map(|(i, path)| {
+            additional_info
+                .get(path.target_info.mode_info_idx as usize)
+                .map(|info| info.monitor_friendly_device.clone())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| format!("Display {}", i + 1))
+        })
+        .collect())
+}
@@ -0,0 +1,166 @@
+//! Virtual displays through an installed virtual display driver.
+//!
+//! Two drivers are supported. The Virtual Display Driver shows as many
+//! monitors as its settings file asks for, with the modes listed there, and
+//! picks up changes when told to reload over its named pipe. usbmmidd adds or
+//! removes one display per `deviceinstaller64 enableidd 1|0` and can't be
+//! asked how many it shows, so they're counted on its adapter.
+//! Single responsibility: make the installed driver show a number of displays.
+
+use super::super::{simulate, vdd, VirtualDisplay};
+use super::adapters::get_gpu_info;
+use std::fs;
+use std::io::Write;
+use std::os::windows::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Where the Virtual Display Driver installer puts its settings file.
+const VDD_SETTINGS: &str = r"C:\VirtualDisplayDriver\vdd_settings.xml";
+
+/// Named pipe the Virtual Display Driver listens to for commands.
+const VDD_PIPE: &str = r"\\.\pipe\MTTVirtualDisplayPipe";
+
+/// Where the usbmmidd archive is usually extracted.
+const USBMMIDD_DIRS: &[&str] = &[r"C:\usbmmidd_v2", r"C:\Program Files\usbmmidd_v2"];
+
+/// Description of the usbmmidd adapter.
+const USBMMIDD_ADAPTER: &str = "USB Mobile Monitor";
+
+enum Driver {
+    /// Virtual Display Driver, with the path of its settings file
+    Vdd(PathBuf),
+    /// usbmmidd, with the path of `deviceinstaller64.exe`
+    Usbmmidd(PathBuf),
+}
+
+/// Name of the installed virtual display driver, if any.
+pub fn virtual_display_driver() -> Option<&'static str> {
+    find_driver().map(|driver| match driver {
+        Driver::Vdd(_) => "Virtual Display Driver",
+        Driver::Usbmmidd(_) => "usbmmidd",
+    })
+}
+
+/// Number of displays the virtual display driver shows.
+pub fn count_virtual_displays() -> Result<usize, String> {
+    match find_driver().ok_or(NO_DRIVER)? {
+        Driver::Vdd(settings) => vdd::monitor_count(&read_settings(&settings)?)
+            .ok_or_else(|| "The Virtual Display Driver settings have no monitor count".to_string()),
+        Driver::Usbmmidd(_) => count_usbmmidd_displays(),
+    }
+}
+
+/// Make the virtual display driver show exactly `displays`. usbmmidd picks
+/// the modes itself; they're set by the layout that follows. Returns whether
+/// anything changed.
+pub fn set_virtual_displays(displays: &[VirtualDisplay]) -> Result<bool, String> {
+    if simulate::is_simulating() {
+        return Err("Virtual displays can't be created on simulated displays".to_string());
+    }
+
+    match find_driver().ok_or(NO_DRIVER)? {
+        Driver::Vdd(settings) => {
+            let xml = read_settings(&settings)?;
+            let updated = vdd::with_monitor_count(&vdd::with_resolutions(&xml, displays)?, displays.len())?;
+            if updated == xml {
+                return Ok(false);
+            }
+            fs::write(&settings, updated)
+                .map_err(|e| format!("Failed to write the Virtual Display Driver settings: {}", e))?;
+            reload_vdd()?;
+            Ok(true)
+        }
+        Driver::Usbmmidd(tool) => {
+            let current = count_usbmmidd_displays()?;
+            for _ in current..displays.len() {
+                run_device_installer(&tool, "1")?;
+            }
+            for _ in displays.len()..current {
+                run_device_installer(&tool, "0")?;
+            }
+            Ok(current != displays.len())
+        }
+    }
+}
+
+/// Make the virtual display driver show `count` displays in the modes it offers.
+pub fn set_virtual_display_count(count: usize) -> Result<bool, String> {
+    match find_driver().ok_or(NO_DRIVER)? {
+        // Only the count changes; the mode list is left alone
+        Driver::Vdd(settings) => {
+            let xml = read_settings(&settings)?;
+            let updated = vdd::with_monitor_count(&xml, count)?;
+            if updated == xml {
+                return Ok(false);
+            }
+            fs::write(&settings, updated)
+                .map_err(|e| format!("Failed to write the Virtual Display Driver settings: {}", e))?;
+            reload_vdd()?;
+            Ok(true)
+        }
+        // usbmmidd ignores the modes
+        Driver::Usbmmidd(_) => {
+            let mode = VirtualDisplay { width: 1920, height: 1080, refresh_rate: 60 };
+            set_virtual_displays(&vec![mode; count])
+        }
+    }
+}
+
+const NO_DRIVER: &str = "No virtual display driver is installed (Virtual Display Driver or usbmmidd)";
+
+fn find_driver() -> Option<Driver> {
+    let settings = Path::new(VDD_SETTINGS);
+    if settings.is_file() {
+        return Some(Driver::Vdd(settings.to_path_buf()));
+    }
+
+    USBMMIDD_DIRS
+        .iter()
+        .map(|dir| Path::new(dir).join("deviceinstaller64.exe"))
+        .find(|tool| tool.is_file())
+        .map(Driver::Usbmmidd)
+}
+
+fn read_settings(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Failed to read the Virtual Display Driver settings: {}", e))
+}
+
+/// Tell the Virtual Display Driver to read its settings again.
+fn reload_vdd() -> Result<(), String> {
+    let command: Vec<u8> = "RELOAD_DRIVER".encode_utf16().flat_map(u16::to_le_bytes).collect();
+    fs::OpenOptions::new()
+        .write(true)
+        .open(VDD_PIPE)
+        .and_then(|mut pipe| pipe.write_all(&command))
+        .map_err(|e| format!("Failed to reach the Virtual Display Driver; restart it to apply the change ({})", e))
+}
+
+/// Displays connected to the usbmmidd adapter.
+fn count_usbmmidd_displays() -> Result<usize, String> {
+    Ok(get_gpu_info()?
+        .iter()
+        .filter(|gpu| gpu.name.contains(USBMMIDD_ADAPTER))
+        .map(|gpu| gpu.monitors.len())
+        .sum())
+}
+
+/// Add (`"1"`) or remove (`"0"`) one usbmmidd display.
+fn run_device_installer(tool: &Path, enable: &str) -> Result<(), String> {
+    // Don't flash a console window
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let output = Command::new(tool)
+        .args(["enableidd", enable])
+        .current_dir(tool.parent().unwrap_or(Path::new(".")))
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run deviceinstaller64: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "deviceinstaller64 failed: {}",
+            String::from_utf8_lossy(&output.stdout).trim()
+        ));
+    }
+    Ok(())
+}
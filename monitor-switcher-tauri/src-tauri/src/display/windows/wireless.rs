@@ -0,0 +1,100 @@
+//! Wireless (Miracast) display targets.
+//!
+//! A wireless display only shows up as a target while Windows is connected
+//! to it, and there's no API for connecting to one by name. A profile can
+//! open the system "Connect to a wireless display" flyout instead, so the
+//! user only has to pick the display. Wireless targets that still aren't
+//! there are left out of the layout rather than failing the apply.
+//! Single responsibility: find wireless targets and ask for a connection.
+
+use super::api::{get_display_settings, DisplaySettings, MonitorAdditionalInfo};
+use super::matcher::drop_unused_modes;
+use super::types::*;
+use std::ptr;
+use windows_sys::Win32::UI::Shell::ShellExecuteW;
+use windows_sys::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+/// DISPLAYCONFIG_OUTPUT_TECHNOLOGY_MIRACAST
+const OUTPUT_TECHNOLOGY_MIRACAST: u32 = 15;
+
+/// Settings page listing the wireless displays in range.
+const CONNECT_URI: &str = "ms-settings-connectabledevices:devicediscovery";
+
+/// Names of the wireless displays a configuration turns on, as
+/// `find_missing_monitors` reports them.
+pub fn wireless_display_names(settings: &DisplaySettings, additional_info: &[MonitorAdditionalInfo]) -> Vec<String> {
+    settings
+        .path_info_array
+        .iter()
+        .enumerate()
+        .filter(|(_, path)| path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0 && is_wireless(path))
+        .map(|(i, path)| {
+            additional_info
+                .get(path.target_info.mode_info_idx as usize)
+                .map(|info| info.monitor_friendly_device.clone())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| format!("Display {}", i + 1))
+        })
+        .collect()
+}
+
+/// Open the flyout for connecting to a wireless display.
+pub fn open_wireless_connect() -> Result<(), String> {
+    let verb: Vec<u16> = "open".encode_utf16().chain(Some(0)).collect();
+    let uri: Vec<u16> = CONNECT_URI.encode_utf16().chain(Some(0)).collect();
+
+    let result = unsafe {
+        ShellExecuteW(ptr::null_mut(), verb.as_ptr(), uri.as_ptr(), ptr::null(), ptr::null(), SW_SHOWNORMAL)
+    };
+    // Values up to 32 are errors
+    if result as isize <= 32 {
+        return Err(format!("Failed to open the wireless display settings (error {})", result as isize));
+    }
+    Ok(())
+}
+
+/// Remove the paths of wireless displays that aren't connected right now, so
+/// the rest of the layout can still be applied. Expects adapter IDs to
+/// already be matched to the current system. `additional_info` is kept
+/// aligned with the modes. Returns the names of the removed displays.
+pub fn exclude_absent_wireless_targets(
+    settings: &mut DisplaySettings,
+    additional_info: &mut Vec<MonitorAdditionalInfo>,
+) -> Result<Vec<String>, String> {
+    if !settings.path_info_array.iter().any(is_wireless) {
+        return Ok(Vec::new());
+    }
+
+    let current = get_display_settings(false)?;
+    let is_present = |path: &DisplayConfigPathInfo| {
+        current.path_info_array.iter().any(|c| {
+            is_wireless(c)
+                && c.target_info.adapter_id == path.target_info.adapter_id
+                && c.target_info.id == path.target_info.id
+        })
+    };
+
+    let mut removed = Vec::new();
+    for (i, path) in settings.path_info_array.iter().enumerate() {
+        if is_wireless(path) && !is_present(path) {
+            removed.push(
+                additional_info
+                    .get(path.target_info.mode_info_idx as usize)
+                    .map(|info| info.monitor_friendly_device.clone())
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| format!("Display {}", i + 1)),
+            );
+        }
+    }
+    if removed.is_empty() {
+        return Ok(removed);
+    }
+
+    settings.path_info_array.retain(|path| !is_wireless(path) || is_present(path));
+    drop_unused_modes(settings, additional_info);
+    Ok(removed)
+}
+
+fn is_wireless(path: &DisplayConfigPathInfo) -> bool {
+    path.target_info.output_technology == OUTPUT_TECHNOLOGY_MIRACAST
+}
@@ -0,0 +1,265 @@
+//! Elevation on demand for privileged operations.
+//!
+//! The app itself never runs elevated. An operation that needs admin rights is
+//! handed to a short-lived copy of the app started elevated (UAC on Windows,
+//! pkexec on Linux) with `--elevated-worker`, which runs that one operation and
+//! exits. The operation travels on the worker's command line, so it's fixed by
+//! the time the user consents; the outcome comes back through a result file the
+//! app creates and the worker fills in.
+//! Single responsibility: run one whitelisted operation with admin rights.
+
+use crate::profile::get_machine_profiles_dir;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Command-line flag that turns the app into the elevated worker.
+pub const WORKER_ARG: &str = "--elevated-worker";
+
+/// Operations the elevated worker performs. Nothing else runs elevated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "op")]
+pub enum PrivilegedOp {
+    /// Copy a profile file into the machine-wide profiles directory.
+    #[serde(rename_all = "camelCase")]
+    InstallProfile { source: PathBuf, file_name: String },
+    /// Remove a profile file from the machine-wide profiles directory.
+    #[serde(rename_all = "camelCase")]
+    RemoveMachineProfile { file_name: String },
+    /// Make the evdi kernel module provide a number of virtual display devices.
+    #[serde(rename_all = "camelCase")]
+    SetEvdiDevices { count: usize },
+}
+
+/// Run `op` with admin rights: right here when the app already has them,
+/// otherwise in an elevated worker. Fails if the user declines.
+pub fn run_privileged(op: &PrivilegedOp) -> Result<(), String> {
+    if is_elevated() {
+        return execute(op);
+    }
+
+    let request = serde_json::to_string(op)
+        .map_err(|e| format!("Failed to serialize the request: {}", e))?;
+    let result_path = std::env::temp_dir().join(format!("monitor-switcher-elevated-{}.json", Uuid::new_v4()));
+    // Created here, so it belongs to the user even when the worker runs as root
+    fs::write(&result_path, "")
+        .map_err(|e| format!("Failed to create the result file: {}", e))?;
+
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Failed to locate the app: {}", e))?;
+    let launched = platform::launch_elevated(&exe, &[WORKER_ARG, &to_hex(&request), &result_path.to_string_lossy()]);
+
+    let outcome = fs::read_to_string(&result_path);
+    let _ = fs::remove_file(&result_path);
+    launched?;
+
+    let outcome = outcome.map_err(|e| format!("Failed to read the result file: {}", e))?;
+    serde_json::from_str::<Result<(), String>>(&outcome)
+        .map_err(|_| "The elevated worker exited without a result".to_string())?
+}
+
+/// Entry point of the elevated worker: `args` are the ones following
+/// `WORKER_ARG`. Returns the process exit code.
+pub fn run_worker(args: &[String]) -> i32 {
+    let [request, result_path] = args else {
+        return 2;
+    };
+
+    let result = from_hex(request)
+        .and_then(|json| serde_json::from_str::<PrivilegedOp>(&json).map_err(|e| format!("Invalid request: {}", e)))
+        .and_then(|op| execute(&op));
+    let code = if result.is_ok() { 0 } else { 1 };
+
+    // The app created the file; opening it without O_CREAT keeps root allowed
+    // to write it in sticky directories like /tmp
+    let written = fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(result_path)
+        .and_then(|mut file| file.write_all(serde_json::to_string(&result).unwrap_or_default().as_bytes()));
+    if written.is_err() {
+        return 3;
+    }
+    code
+}
+
+/// Perform an operation, assuming admin rights.
+fn execute(op: &PrivilegedOp) -> Result<(), String> {
+    match op {
+        PrivilegedOp::InstallProfile { source, file_name } => {
+            let dir = get_machine_profiles_dir()?;
+            let target = machine_profile_path(&dir, file_name)?;
+            fs::create_dir_all(&dir)
+                .map_err(|e| format!("Failed to create the machine-wide profiles directory: {}", e))?;
+            fs::copy(source, target)
+                .map(|_| ())
+                .map_err(|e| format!("Failed to install the profile: {}", e))
+        }
+        PrivilegedOp::RemoveMachineProfile { file_name } => {
+            let dir = get_machine_profiles_dir()?;
+            fs::remove_file(machine_profile_path(&dir, file_name)?)
+                .map_err(|e| format!("Failed to remove the machine-wide profile: {}", e))
+        }
+        PrivilegedOp::SetEvdiDevices { count } => set_evdi_devices(*count),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn set_evdi_devices(count: usize) -> Result<(), String> {
+    crate::display::write_evdi_device_count(count)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_evdi_devices(_count: usize) -> Result<(), String> {
+    Err("evdi is only available on Linux".to_string())
+}
+
+/// Path of a file in the machine-wide profiles directory. Only plain `.json`
+/// file names are accepted, so a request can't reach outside the directory.
+fn machine_profile_path(dir: &Path, file_name: &str) -> Result<PathBuf, String> {
+    let plain = !file_name.is_empty()
+        && file_name.ends_with(".json")
+        && !file_name.starts_with('.')
+        && !file_name.contains(['/', '\\', ':']);
+    if !plain {
+        return Err(format!("Invalid profile file name '{}'", file_name));
+    }
+    Ok(dir.join(file_name))
+}
+
+/// Hex-encode the request, so it survives command-line quoting on every platform.
+fn to_hex(text: &str) -> String {
+    text.bytes().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Result<String, String> {
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or("Invalid request encoding")?;
+    String::from_utf8(bytes).map_err(|_| "Invalid request encoding".to_string())
+}
+
+// ============================================================================
+// Platform
+// ============================================================================
+
+/// Whether the app is running with admin rights.
+fn is_elevated() -> bool {
+    platform::is_elevated()
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::mem;
+    use std::path::Path;
+    use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, ERROR_CANCELLED};
+    use windows_sys::Win32::System::Threading::{GetExitCodeProcess, WaitForSingleObject, INFINITE};
+    use windows_sys::Win32::UI::Shell::{IsUserAnAdmin, ShellExecuteExW, SEE_MASK_NOCLOSEPROCESS, SHELLEXECUTEINFOW};
+    use windows_sys::Win32::UI::WindowsAndMessaging::SW_HIDE;
+
+    pub fn is_elevated() -> bool {
+        unsafe { IsUserAnAdmin() != 0 }
+    }
+
+    /// Start `exe` through UAC and wait for it to exit.
+    pub fn launch_elevated(exe: &Path, args: &[&str]) -> Result<(), String> {
+        let wide = |s: &str| -> Vec<u16> { s.encode_utf16().chain(Some(0)).collect() };
+        // None of the arguments contain quotes; paths may contain spaces
+        let parameters: Vec<String> = args.iter().map(|arg| format!("\"{}\"", arg)).collect();
+
+        let verb = wide("runas");
+        let file = wide(&exe.to_string_lossy());
+        let parameters = wide(&parameters.join(" "));
+
+        let mut info: SHELLEXECUTEINFOW = unsafe { mem::zeroed() };
+        info.cbSize = mem::size_of::<SHELLEXECUTEINFOW>() as u32;
+        info.fMask = SEE_MASK_NOCLOSEPROCESS;
+        info.lpVerb = verb.as_ptr();
+        info.lpFile = file.as_ptr();
+        info.lpParameters = parameters.as_ptr();
+        info.nShow = SW_HIDE;
+
+        if unsafe { ShellExecuteExW(&mut info) } == 0 {
+            return Err(match unsafe { GetLastError() } {
+                ERROR_CANCELLED => "Administrator rights were declined".to_string(),
+                code => format!("Failed to start the elevated worker (error {})", code),
+            });
+        }
+        if info.hProcess.is_null() {
+            return Err("Failed to start the elevated worker".to_string());
+        }
+
+        let mut code = 0u32;
+        unsafe {
+            WaitForSingleObject(info.hProcess, INFINITE);
+            GetExitCodeProcess(info.hProcess, &mut code);
+            CloseHandle(info.hProcess);
+        }
+        match code {
+            0 | 1 => Ok(()),
+            code => Err(format!("The elevated worker failed (exit code {})", code)),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Whether the effective user is root, from the `Uid:` line of
+    /// /proc/self/status (real, effective, saved, filesystem).
+    pub fn is_elevated() -> bool {
+        fs::read_to_string("/proc/self/status")
+            .ok()
+            .and_then(|status| {
+                let line = status.lines().find(|line| line.starts_with("Uid:"))?;
+                line.split_whitespace().nth(2).map(|euid| euid == "0")
+            })
+            .unwrap_or(false)
+    }
+
+    /// Start `exe` through pkexec and wait for it to exit.
+    pub fn launch_elevated(exe: &Path, args: &[&str]) -> Result<(), String> {
+        let status = Command::new("pkexec")
+            .arg(exe)
+            .args(args)
+            .status()
+            .map_err(|e| format!("Failed to run pkexec: {}", e))?;
+
+        // pkexec exits with 126 when the dialog is dismissed, 127 when not authorized
+        match status.code() {
+            Some(0) | Some(1) => Ok(()),
+            Some(126) | Some(127) => Err("Administrator rights were declined".to_string()),
+            code => Err(format!("The elevated worker failed (exit code {:?})", code)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_machine_profile_path() {
+        let dir = Path::new("machine");
+        assert_eq!(machine_profile_path(dir, "a1b2.json").unwrap(), dir.join("a1b2.json"));
+        for name in ["", "../settings.json", "..\\x.json", "C:x.json", ".json", "profile.txt"] {
+            assert!(machine_profile_path(dir, name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let op = PrivilegedOp::RemoveMachineProfile { file_name: "Büro \"1\".json".to_string() };
+        let json = serde_json::to_string(&op).unwrap();
+        assert_eq!(from_hex(&to_hex(&json)).unwrap(), json);
+        assert!(from_hex("4").is_err());
+        assert!(from_hex("zz").is_err());
+    }
+}
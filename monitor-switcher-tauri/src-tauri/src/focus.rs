@@ -0,0 +1,59 @@
+//! Focus mode: only the primary display stays on.
+//!
+//! Turning it on remembers the active layout and switches every other display
+//! off; turning it off applies the remembered layout again. Any other layout
+//! change in between ends focus mode without restoring anything. The
+//! layout is kept in memory only, so focus mode doesn't outlive the app.
+//! Single responsibility: switch focus mode and remember what to restore.
+
+use crate::display::{self, get_display_settings, set_display_settings, DisplaySettings};
+use std::sync::{Mutex, MutexGuard};
+
+/// Layout from before focus mode was turned on; None while it's off.
+static SAVED: Mutex<Option<DisplaySettings>> = Mutex::new(None);
+
+fn saved() -> MutexGuard<'static, Option<DisplaySettings>> {
+    SAVED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Whether focus mode is on.
+pub fn is_enabled() -> bool {
+    saved().is_some()
+}
+
+/// Switch every display but the primary one off. Returns false if there was
+/// nothing to switch off or focus mode was on already.
+pub fn enable() -> Result<bool, String> {
+    let mut saved = saved();
+    if saved.is_some() {
+        return Ok(false);
+    }
+
+    let current = get_display_settings(true)?;
+    let Some(mut layout) = display::primary_only_layout(&current) else {
+        return Ok(false);
+    };
+    set_display_settings(&mut layout)?;
+    *saved = Some(current);
+    Ok(true)
+}
+
+/// Apply the layout from before focus mode again. Returns false if focus mode
+/// was off. When applying fails focus mode stays on, so it can be retried.
+pub fn disable() -> Result<bool, String> {
+    let mut saved = saved();
+    let Some(previous) = saved.as_ref() else {
+        return Ok(false);
+    };
+
+    set_display_settings(&mut previous.clone())?;
+    *saved = None;
+    Ok(true)
+}
+
+/// End focus mode without restoring, because another layout was applied.
+pub fn forget() {
+    if saved().take().is_some() {
+        log::debug!("Focus mode ended by applying another layout");
+    }
+}
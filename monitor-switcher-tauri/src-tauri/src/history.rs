@@ -0,0 +1,95 @@
+//! History of applied configurations.
+//!
+//! Every profile load is appended to `history.jsonl` (one JSON entry per line)
+//! with what asked for it, so unexpected layout changes can be traced back.
+//! Single responsibility: append to and read the history log.
+
+use crate::settings::get_history_path;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+/// One profile load.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    /// Local time in RFC 3339 format
+    pub timestamp: String,
+    pub profile: String,
+    /// What asked for the load: "window", "tray", "chain 'Movie'", "hotplug", ...
+    pub source: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Append the outcome of a profile load to the history.
+pub fn record(profile: &str, source: &str, result: &Result<(), String>) -> Result<(), String> {
+    let entry = HistoryEntry {
+        timestamp: Local::now().to_rfc3339(),
+        profile: profile.to_string(),
+        source: source.to_string(),
+        success: result.is_ok(),
+        error: result.as_ref().err().cloned(),
+    };
+    let line = serde_json::to_string(&entry)
+        .map_err(|e| format!("Failed to serialize history entry: {}", e))?;
+
+    let path = get_history_path()?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open history file: {}", e))?;
+
+    writeln!(file, "{}", line)
+        .map_err(|e| format!("Failed to write history file: {}", e))
+}
+
+/// Read the most recent entries, newest first. All of them if `limit` is None.
+pub fn read(limit: Option<usize>) -> Result<Vec<HistoryEntry>, String> {
+    let path = get_history_path()?;
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let text = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read history file: {}", e))?;
+
+    let mut entries = parse_history(&text);
+    entries.reverse();
+    entries.truncate(limit.unwrap_or(usize::MAX));
+    Ok(entries)
+}
+
+/// Parse the history log. Lines that can't be parsed (e.g. cut off by a crash) are skipped.
+fn parse_history(text: &str) -> Vec<HistoryEntry> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_history_skips_broken_lines() {
+        let text = concat!(
+            r#"{"timestamp":"2024-05-01T02:13:00+02:00","profile":"TV","source":"hotplug","success":true}"#,
+            "\n\n",
+            r#"{"timestamp":"2024-05-01T02:14:00+02:00","profile":"Desk","source":"tray","success":false,"error":"Monitor not connected"}"#,
+            "\n",
+            r#"{"timestamp":"2024-05-01T02:15"#,
+        );
+
+        let entries = parse_history(text);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].source, "hotplug");
+        assert_eq!(entries[1].error.as_deref(), Some("Monitor not connected"));
+    }
+}
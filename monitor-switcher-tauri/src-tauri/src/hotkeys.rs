@@ -0,0 +1,33 @@
+//! Global keyboard shortcuts.
+//!
+//! Each binding in the settings maps a shortcut like "Ctrl+Alt+M" to one of the
+//! tray quick actions, so turning off the monitors doesn't need the mouse.
+//! Single responsibility: keep the registered shortcuts in sync with the settings.
+
+use crate::settings::load_settings;
+use log::{info, warn};
+use tauri::{AppHandle, Wry};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+/// Register the shortcuts from the settings, replacing any registered before.
+/// Shortcuts that are invalid or taken by another app are skipped with a warning.
+pub fn register(app: &AppHandle<Wry>) {
+    let shortcuts = app.global_shortcut();
+    if let Err(e) = shortcuts.unregister_all() {
+        warn!("Failed to unregister hotkeys: {}", e);
+    }
+
+    for binding in load_settings().unwrap_or_default().hotkeys {
+        let action = binding.action.clone();
+        let result = shortcuts.on_shortcut(binding.shortcut.as_str(), move |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                crate::run_tray_action(app, &action);
+            }
+        });
+
+        match result {
+            Ok(()) => info!("Hotkey {} registered", binding.shortcut),
+            Err(e) => warn!("Failed to register hotkey {}: {}", binding.shortcut, e),
+        }
+    }
+}
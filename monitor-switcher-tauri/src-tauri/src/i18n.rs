@@ -0,0 +1,168 @@
+//! Localized strings for the tray and backend messages.
+//!
+//! Language files map string keys to text, with `{name}` placeholders. English
+//! and German are built in; JSON files in the `Languages` folder next to the
+//! profiles directory add languages or override built-in strings. The language
+//! is picked once at startup from the settings or the system locale, and
+//! anything missing falls back to English.
+//! Single responsibility: look up the text for a string key.
+
+use crate::settings::get_languages_dir;
+use log::{info, warn};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::OnceLock;
+
+/// Built-in language files as (code, JSON).
+const BUILT_IN: &[(&str, &str)] = &[
+    ("en", include_str!("../lang/en.json")),
+    ("de", include_str!("../lang/de.json")),
+];
+
+type Strings = HashMap<String, String>;
+
+/// Strings of the language picked at startup, and the English fallback.
+struct Catalog {
+    strings: Strings,
+    fallback: Strings,
+}
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+/// English strings for lookups before `init`, e.g. a settings error while
+/// logging is set up. Kept apart so `init` still picks the language.
+static EARLY_FALLBACK: OnceLock<Strings> = OnceLock::new();
+
+/// Load the strings for `language` (e.g. "de" or "pt-BR"), or for the system
+/// locale if None. Call once at startup; later calls have no effect.
+pub fn init(language: Option<&str>) {
+    let locale = language.map(str::to_string).or_else(system_locale).unwrap_or_default();
+
+    CATALOG.get_or_init(|| {
+        let mut strings = Strings::new();
+        // Less specific first, so "pt-BR" overrides "pt"
+        for code in locale_candidates(&locale).iter().rev() {
+            strings.extend(load_language(code));
+        }
+        info!("Language: {}", if strings.is_empty() { "en" } else { locale.as_str() });
+        Catalog { strings, fallback: load_language("en") }
+    });
+}
+
+/// Text for a string key. Unknown keys come back as the key itself.
+pub fn tr(key: &str) -> String {
+    tr_with(key, &[])
+}
+
+/// Text for a string key, with `{name}` placeholders replaced by `args`.
+pub fn tr_with(key: &str, args: &[(&str, &str)]) -> String {
+    let text = match CATALOG.get() {
+        Some(catalog) => catalog.strings.get(key).or_else(|| catalog.fallback.get(key)),
+        None => EARLY_FALLBACK.get_or_init(|| load_language("en")).get(key),
+    }
+    .map(String::as_str)
+    .unwrap_or(key);
+    format_placeholders(text, args)
+}
+
+/// Strings of one language: the built-in file, overridden by a user file.
+fn load_language(code: &str) -> Strings {
+    let mut strings = Strings::new();
+
+    if let Some((_, json)) = BUILT_IN.iter().find(|(c, _)| c.eq_ignore_ascii_case(code)) {
+        match serde_json::from_str::<Strings>(json) {
+            Ok(built_in) => strings.extend(built_in),
+            Err(e) => warn!("Built-in language '{}' is invalid: {}", code, e),
+        }
+    }
+
+    let Ok(path) = get_languages_dir().map(|dir| dir.join(format!("{}.json", code))) else {
+        return strings;
+    };
+    if let Ok(json) = fs::read_to_string(&path) {
+        match serde_json::from_str::<Strings>(&json) {
+            Ok(user) => strings.extend(user),
+            Err(e) => warn!("Failed to parse language file {}: {}", path.display(), e),
+        }
+    }
+
+    strings
+}
+
+/// Language codes to try for a locale, most specific first:
+/// "de_DE.UTF-8" gives ["de-DE", "de"].
+fn locale_candidates(locale: &str) -> Vec<String> {
+    let code = locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('_', "-");
+    if code.is_empty() || code == "C" || code == "POSIX" {
+        return Vec::new();
+    }
+
+    let mut candidates = vec![code.clone()];
+    if let Some((language, _)) = code.split_once('-') {
+        candidates.push(language.to_string());
+    }
+    candidates
+}
+
+/// Replace `{name}` placeholders.
+fn format_placeholders(text: &str, args: &[(&str, &str)]) -> String {
+    args.iter()
+        .fold(text.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
+
+/// The user's display language, e.g. "de-DE".
+#[cfg(windows)]
+fn system_locale() -> Option<String> {
+    use windows_sys::Win32::Globalization::GetUserDefaultLocaleName;
+
+    // LOCALE_NAME_MAX_LENGTH
+    let mut buffer = [0u16; 85];
+    let len = unsafe { GetUserDefaultLocaleName(buffer.as_mut_ptr(), buffer.len() as i32) };
+    if len <= 1 {
+        return None;
+    }
+    // The length includes the terminating null
+    Some(String::from_utf16_lossy(&buffer[..len as usize - 1]))
+}
+
+/// The user's message locale, e.g. "de_DE.UTF-8".
+#[cfg(target_os = "linux")]
+fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_candidates() {
+        assert_eq!(locale_candidates("de_DE.UTF-8"), ["de-DE", "de"]);
+        assert_eq!(locale_candidates("pt-BR"), ["pt-BR", "pt"]);
+        assert_eq!(locale_candidates("fr"), ["fr"]);
+        assert!(locale_candidates("C.UTF-8").is_empty());
+    }
+
+    #[test]
+    fn test_built_in_languages_cover_english_keys() {
+        let english: Strings = serde_json::from_str(BUILT_IN[0].1).unwrap();
+        assert!(!english.is_empty());
+        for (code, json) in BUILT_IN {
+            let strings: Strings = serde_json::from_str(json).unwrap();
+            for key in english.keys() {
+                assert!(strings.contains_key(key), "'{}' is missing {}", code, key);
+            }
+        }
+        assert_eq!(
+            format_placeholders(&english["tray.chain"], &[("name", "Movie night")]),
+            "Movie night (chain)"
+        );
+    }
+}
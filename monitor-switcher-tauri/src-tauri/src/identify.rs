@@ -0,0 +1,82 @@
+//! Identify overlay.
+//!
+//! Shows each active monitor's number and name in a small window in its
+//! top-left corner for a few seconds, like "Identify" in the Windows display
+//! settings. Single responsibility: open and close the overlay windows.
+
+use crate::profile::current_monitors;
+use log::error;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, PhysicalPosition, WebviewUrl, WebviewWindowBuilder, Wry};
+
+/// How long the overlays stay up.
+const OVERLAY_DURATION: Duration = Duration::from_secs(3);
+
+/// Label prefix of the overlay windows.
+const LABEL_PREFIX: &str = "identify-";
+
+/// Flash the number and name of every active monitor.
+pub fn identify_monitors(app: &AppHandle<Wry>) -> Result<(), String> {
+    close_overlays(app);
+
+    let labels: Vec<String> = current_monitors()?
+        .iter()
+        .enumerate()
+        .filter_map(|(i, monitor)| {
+            let number = monitor.display_number.unwrap_or(i as u32 + 1);
+            let label = format!("{}{}", LABEL_PREFIX, i);
+            let url = format!("identify.html?number={}&name={}", number, encode_query(&monitor.name));
+
+            let window = WebviewWindowBuilder::new(app, &label, WebviewUrl::App(url.into()))
+                .title(&monitor.name)
+                .inner_size(240.0, 150.0)
+                .resizable(false)
+                .decorations(false)
+                .always_on_top(true)
+                .skip_taskbar(true)
+                .focused(false)
+                .visible(false)
+                .build()
+                .map_err(|e| error!("Failed to create identify overlay: {}", e))
+                .ok()?;
+
+            // Monitor positions are physical desktop coordinates
+            let margin = 40;
+            let _ = window.set_position(PhysicalPosition::new(monitor.position_x + margin, monitor.position_y + margin));
+            let _ = window.show();
+            Some(label)
+        })
+        .collect();
+
+    let app = app.clone();
+    thread::spawn(move || {
+        thread::sleep(OVERLAY_DURATION);
+        for label in labels {
+            if let Some(window) = app.get_webview_window(&label) {
+                let _ = window.close();
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Close overlays still open from an earlier call.
+fn close_overlays(app: &AppHandle<Wry>) {
+    for (label, window) in app.webview_windows() {
+        if label.starts_with(LABEL_PREFIX) {
+            let _ = window.close();
+        }
+    }
+}
+
+/// Percent-encode a query parameter value.
+fn encode_query(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
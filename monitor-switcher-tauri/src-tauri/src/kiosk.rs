@@ -0,0 +1,126 @@
+//! Kiosk mode for conference-room and signage PCs.
+//!
+//! IT manages it with a machine-wide policy file (`kiosk.json` in the
+//! machine-wide config directory), which users can't change and which decides
+//! on its own; without one, the `kiosk` setting applies. While it's on, only
+//! the whitelisted profiles can be applied and everything that changes
+//! profiles, settings or monitor state is refused, whether it comes from the
+//! window, the tray or a hotkey.
+//! Single responsibility: decide what kiosk mode allows.
+
+use crate::i18n;
+use crate::profile::{find_profile_entry, split_host_variant, ProfileEntry};
+use crate::settings::{get_kiosk_policy_path, load_settings, KioskPolicy, TrayAction};
+use std::fs;
+
+/// The kiosk policy in force, if kiosk mode is on.
+pub fn policy() -> Option<KioskPolicy> {
+    if let Ok(path) = get_kiosk_policy_path() {
+        if path.exists() {
+            return Some(read_policy_file(&path)).filter(|kiosk| kiosk.enabled);
+        }
+    }
+
+    load_settings().ok().map(|s| s.kiosk).filter(|kiosk| kiosk.enabled)
+}
+
+/// Read the machine-wide policy. A damaged file locks everything down rather
+/// than lifting the restrictions.
+fn read_policy_file(path: &std::path::Path) -> KioskPolicy {
+    let parsed = fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str::<KioskPolicy>(&json).map_err(|e| e.to_string()));
+
+    match parsed {
+        Ok(policy) => policy,
+        Err(e) => {
+            log::error!("Failed to read the kiosk policy {}: {}", path.display(), e);
+            KioskPolicy { enabled: true, profiles: Vec::new() }
+        }
+    }
+}
+
+/// Whether kiosk mode is on.
+pub fn is_active() -> bool {
+    policy().is_some()
+}
+
+/// Refuse an operation kiosk mode doesn't allow.
+pub fn ensure_unlocked() -> Result<(), String> {
+    match is_active() {
+        true => Err(i18n::tr("error.kioskLocked")),
+        false => Ok(()),
+    }
+}
+
+/// Refuse to apply a profile kiosk mode doesn't allow. `reference` is a
+/// profile ID or name.
+pub fn ensure_profile_allowed(reference: &str) -> Result<(), String> {
+    let Some(policy) = policy() else {
+        return Ok(());
+    };
+
+    match find_profile_entry(reference)? {
+        Some(entry) if allows(&policy, &entry) => Ok(()),
+        _ => Err(i18n::tr_with("error.kioskProfile", &[("name", reference)])),
+    }
+}
+
+/// Whether a policy lists a profile, by ID or by name in any case. Listing a
+/// profile allows its host variants too.
+pub fn allows(policy: &KioskPolicy, entry: &ProfileEntry) -> bool {
+    let name = entry.name.to_lowercase();
+    let base = split_host_variant(&entry.name).0.to_lowercase();
+    policy
+        .profiles
+        .iter()
+        .any(|p| p.eq_ignore_ascii_case(&entry.id) || p.to_lowercase() == name || p.to_lowercase() == base)
+}
+
+/// Whether a quick action may run in kiosk mode. Profile loads are checked
+/// against the whitelist when they run.
+pub fn allows_action(action: &TrayAction) -> bool {
+    matches!(
+        action,
+        TrayAction::None
+            | TrayAction::OpenWindow
+            | TrayAction::IdentifyMonitors
+            | TrayAction::LoadProfile(_)
+            | TrayAction::LoadLastProfile
+            | TrayAction::ToggleProfiles(_, _)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allows() {
+        let policy = KioskPolicy {
+            enabled: true,
+            profiles: vec!["Presentation".to_string(), "3F2504E0-4F89-11D3-9A0C-0305E82C3301".to_string()],
+        };
+        let entry = |id: &str, name: &str| ProfileEntry { id: id.to_string(), name: name.to_string(), path: Default::default() };
+
+        assert!(allows(&policy, &entry("a", "presentation")));
+        assert!(allows(&policy, &entry("3f2504e0-4f89-11d3-9a0c-0305e82c3301", "Signage")));
+        assert!(!allows(&policy, &entry("b", "Gaming")));
+        assert!(allows(&policy, &entry("c", "Presentation@room-2")));
+    }
+
+    #[test]
+    fn test_read_policy_file_fails_closed() {
+        let dir = std::env::temp_dir().join(format!("kiosk-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("kiosk.json");
+
+        fs::write(&path, r#"{"enabled": true, "profiles": ["Signage"]}"#).unwrap();
+        assert_eq!(read_policy_file(&path), KioskPolicy { enabled: true, profiles: vec!["Signage".to_string()] });
+
+        fs::write(&path, "{ not json").unwrap();
+        assert_eq!(read_policy_file(&path), KioskPolicy { enabled: true, profiles: Vec::new() });
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+//! Layout cleanup for the drag-and-drop editor.
+//!
+//! Monitor rectangles dropped by hand are rarely exact: they sit a few pixels
+//! apart, overlap slightly or carry fractional coordinates. Windows and xrandr
+//! treat such layouts as disconnected or reject them.
+//! Single responsibility: turn rough monitor positions into a clean layout.
+
+use serde::{Deserialize, Serialize};
+
+/// Edges closer than this (in pixels) are snapped together.
+pub const SNAP_DISTANCE: f64 = 16.0;
+
+/// Overlap resolution gives up after this many passes (only reached with
+/// pathological input, e.g. many monitors stacked on one spot).
+const MAX_PASSES: usize = 32;
+
+/// One monitor in the editor, in desktop coordinates.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutRect {
+    /// Identity of the monitor, passed through unchanged
+    pub id: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl LayoutRect {
+    fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+
+    fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    /// Overlap on each axis; both are positive only if the rectangles overlap.
+    fn overlap(&self, other: &LayoutRect) -> (f64, f64) {
+        (
+            self.right().min(other.right()) - self.x.max(other.x),
+            self.bottom().min(other.bottom()) - self.y.max(other.y),
+        )
+    }
+}
+
+/// Snap rough monitor positions into a clean layout.
+///
+/// Positions are rounded to whole pixels, edges within `snap_distance` of another
+/// monitor's edge are aligned with it and overlaps are pushed apart. The first
+/// monitor is the anchor and never moves. Sizes are left alone.
+pub fn snap_layout(mut rects: Vec<LayoutRect>, snap_distance: f64) -> Vec<LayoutRect> {
+    for rect in &mut rects {
+        rect.x = rect.x.round();
+        rect.y = rect.y.round();
+    }
+
+    // Each monitor snaps against the ones before it, which are already clean
+    for i in 1..rects.len() {
+        let (placed, rest) = rects.split_at_mut(i);
+        let rect = &mut rest[0];
+        rect.x += snap_offset(rect.x, rect.right(), placed.iter().flat_map(|r| [r.x, r.right()]), snap_distance);
+        rect.y += snap_offset(rect.y, rect.bottom(), placed.iter().flat_map(|r| [r.y, r.bottom()]), snap_distance);
+    }
+
+    resolve_overlaps(&mut rects);
+    rects
+}
+
+/// The smallest shift that puts the start or end edge onto one of `edges`, if
+/// it is within `snap_distance`. 0 if no edge is close enough.
+fn snap_offset(start: f64, end: f64, edges: impl Iterator<Item = f64>, snap_distance: f64) -> f64 {
+    edges
+        .flat_map(|edge| [edge - start, edge - end])
+        .filter(|offset| offset.abs() <= snap_distance)
+        .min_by(|a, b| a.abs().total_cmp(&b.abs()))
+        .unwrap_or(0.0)
+}
+
+/// Push overlapping monitors apart along the axis that needs the smaller move.
+/// The later monitor of each pair moves, so the anchor stays put.
+fn resolve_overlaps(rects: &mut [LayoutRect]) {
+    for _ in 0..MAX_PASSES {
+        let mut moved = false;
+
+        for i in 1..rects.len() {
+            for j in 0..i {
+                let (overlap_x, overlap_y) = rects[i].overlap(&rects[j]);
+                if overlap_x <= 0.0 || overlap_y <= 0.0 {
+                    continue;
+                }
+
+                // Move away from the other monitor's center
+                let (center_x, center_y) = rects[j].center();
+                let rect = &mut rects[i];
+                if overlap_x <= overlap_y {
+                    rect.x += if rect.center().0 >= center_x { overlap_x } else { -overlap_x };
+                } else {
+                    rect.y += if rect.center().1 >= center_y { overlap_y } else { -overlap_y };
+                }
+                moved = true;
+            }
+        }
+
+        if !moved {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(id: &str, x: f64, y: f64, width: f64, height: f64) -> LayoutRect {
+        LayoutRect { id: id.to_string(), x, y, width, height }
+    }
+
+    fn positions(rects: &[LayoutRect]) -> Vec<(f64, f64)> {
+        rects.iter().map(|r| (r.x, r.y)).collect()
+    }
+
+    #[test]
+    fn test_snaps_small_gaps_and_fractions() {
+        let layout = snap_layout(
+            vec![rect("main", 0.0, 0.0, 2560.0, 1440.0), rect("side", 2566.4, -3.7, 1920.0, 1080.0)],
+            SNAP_DISTANCE,
+        );
+
+        assert_eq!(positions(&layout), [(0.0, 0.0), (2560.0, 0.0)]);
+    }
+
+    #[test]
+    fn test_pushes_overlaps_apart() {
+        let layout = snap_layout(
+            vec![rect("main", 0.0, 0.0, 1920.0, 1080.0), rect("side", 1800.0, 500.0, 1920.0, 1080.0)],
+            SNAP_DISTANCE,
+        );
+
+        // Less overlap horizontally (120 px) than vertically (580 px)
+        assert_eq!(positions(&layout), [(0.0, 0.0), (1920.0, 500.0)]);
+    }
+
+    #[test]
+    fn test_leaves_distant_monitors_and_anchor_alone() {
+        let layout = snap_layout(
+            vec![rect("main", 10.0, 20.0, 1920.0, 1080.0), rect("tv", 5000.0, 3000.0, 3840.0, 2160.0)],
+            SNAP_DISTANCE,
+        );
+
+        assert_eq!(positions(&layout), [(10.0, 20.0), (5000.0, 3000.0)]);
+    }
+}
@@ -4,33 +4,69 @@
 //! - Windows: CCD API (see display/windows/)
 //! - Linux: XRandR (see display/linux/)
 
+mod apply_marker;
+mod apply_metrics;
+mod apply_queue;
+mod apply_report;
+mod automation;
+mod chain;
+mod diagnostics;
 mod display;
+mod elevation;
+mod focus;
+mod history;
+mod hotkeys;
+mod i18n;
+mod identify;
+mod kiosk;
+mod layout;
+mod monitor_feed;
+mod presentation;
 mod profile;
+mod settings;
+mod state;
+mod thumbnail;
+mod transaction;
+mod troubleshoot;
 
 #[cfg(windows)]
-use display::{get_display_settings, set_display_settings, turn_off_monitors as platform_turn_off, match_adapter_ids, get_additional_info_for_modes, set_dpi_scaling, LUID};
+use display::{get_display_settings, set_display_settings, turn_off_monitors as platform_turn_off, match_adapter_ids, find_missing_monitors, find_inactive_targets, get_additional_info_for_modes, fill_gdi_device_names, exclude_specialized_targets, find_non_native_modes, set_dpi_scaling, ColorFilter, LUID};
 
 #[cfg(target_os = "linux")]
-use display::{get_display_settings, set_display_settings, turn_off_monitors as platform_turn_off, match_adapter_ids, get_additional_info_for_modes};
+use display::{get_display_settings, set_display_settings, turn_off_monitors as platform_turn_off, match_adapter_ids, find_missing_monitors, find_inactive_targets, get_additional_info_for_modes, find_non_native_modes};
 
 #[cfg(windows)]
-use profile::{list_profiles as storage_list, save_profile as storage_save, load_profile as storage_load, delete_profile as storage_delete, profile_exists as storage_exists, get_profile_details as storage_get_details, current_monitors, MonitorDetails};
+use profile::{list_profiles as storage_list, save_profile as storage_save, load_profile as storage_load, delete_profile as storage_delete, profile_exists as storage_exists, get_profile_details as storage_get_details, current_monitors, get_profile_summary, MonitorDetails, ProfileEntry};
 
 #[cfg(target_os = "linux")]
-use profile::{list_profiles as storage_list, delete_profile as storage_delete, profile_exists as storage_exists, get_profile_details as storage_get_details, current_monitors, MonitorDetails};
+use profile::{list_profiles as storage_list, delete_profile as storage_delete, profile_exists as storage_exists, get_profile_details as storage_get_details, current_monitors, get_profile_summary, MonitorDetails, ProfileEntry};
 
 #[cfg(windows)]
-use profile::{settings_to_profile, profile_to_settings};
+use profile::{settings_to_profile, profile_to_settings, set_profile_apply_flags as storage_set_apply_flags};
+
+use display::{DisplaySettings, GpuInfo, Template, VirtualDisplay};
+use settings::{load_settings, save_settings, AppSettings, KioskPolicy, TrayAction};
+use state::LastApplied;
+use history::HistoryEntry;
+use layout::LayoutRect;
+use monitor_feed::MonitorFeed;
+use transaction::Transaction;
+use apply_report::ApplyReport;
+use apply_metrics::ApplyMetrics;
+use apply_queue::ApplyQueue;
 
 use serde::Serialize;
 use tauri::{
-    AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder, Wry,
+    AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindow, WebviewWindowBuilder, Wry,
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     menu::{Menu, MenuItem, IconMenuItem, Submenu, PredefinedMenuItem},
     image::Image,
 };
-use std::path::PathBuf;
-use log::{info, error};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use log::{info, error, Level, LevelFilter, Log};
 
 // ============================================================================
 // Types for Frontend
@@ -40,8 +76,25 @@ use log::{info, error};
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProfileDetails {
+    pub id: String,
     pub name: String,
     pub monitors: Vec<MonitorDetails>,
+    /// Saved on another machine sharing the profiles folder, so it won't match
+    /// this machine's displays
+    pub incompatible: bool,
+}
+
+/// EDID of a connected monitor, as the monitor sent it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorEdid {
+    /// `MonitorDetails::monitor_id` of the monitor
+    pub monitor_id: String,
+    pub name: String,
+    /// Base64 of the base block followed by any extension blocks
+    pub edid: String,
+    /// The EDID comes from an override rather than the monitor
+    pub overridden: bool,
 }
 
 // ============================================================================
@@ -55,18 +108,22 @@ async fn list_profiles() -> Result<Vec<String>, String> {
 
 #[tauri::command]
 async fn list_profiles_with_details() -> Result<Vec<ProfileDetails>, String> {
-    let names = storage_list()?;
+    let mut entries = profile::list_profile_entries()?;
+    if let Some(policy) = kiosk::policy() {
+        entries.retain(|entry| kiosk::allows(&policy, entry));
+    }
     let mut profiles = Vec::new();
 
-    for name in names {
-        match storage_get_details(&name) {
+    for ProfileEntry { id, name, .. } in entries {
+        let incompatible = profile::is_foreign_profile(&id);
+        match storage_get_details(&id) {
             Ok(monitors) => {
-                profiles.push(ProfileDetails { name, monitors });
+                profiles.push(ProfileDetails { id, name, monitors, incompatible });
             }
             Err(e) => {
                 log::warn!("Failed to get details for profile '{}': {}", name, e);
                 // Include profile with empty monitors on error
-                profiles.push(ProfileDetails { name, monitors: Vec::new() });
+                profiles.push(ProfileDetails { id, name, monitors: Vec::new(), incompatible });
             }
         }
     }
@@ -76,141 +133,1576 @@ async fn list_profiles_with_details() -> Result<Vec<ProfileDetails>, String> {
 
 #[tauri::command]
 async fn save_profile(app: AppHandle, name: String) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    profile::validate_profile_name(&name)?;
+    // Saving "office" over "Office" overwrites it rather than adding a near-duplicate
+    let name = profile::find_profile(&name)?.unwrap_or(name);
     info!("Saving profile: {}", name);
 
+    if display::is_remote_session() {
+        log::warn!("Saving profile '{}' inside a remote session; it will be tagged as such", name);
+    }
+
+    if display::is_simulating() {
+        log::warn!("Saving profile '{}' from simulated displays", name);
+    }
+
     #[cfg(windows)]
     {
         // Get current display settings
-        let settings = get_display_settings(true)?;
+        let mut settings = get_display_settings(true)?;
 
         // Get additional monitor info
-        let additional_info = get_additional_info_for_modes(&settings.mode_info_array);
+        let mut additional_info = get_additional_info_for_modes(&settings.mode_info_array);
+        fill_gdi_device_names(&settings, &mut additional_info);
+
+        // Keep VR headsets and other specialized displays out of the profile
+        let excluded = exclude_specialized_targets(&mut settings, &mut additional_info);
+        if excluded > 0 {
+            info!("Excluded {} specialized display(s) from profile", excluded);
+        }
 
         // Convert to profile format
-        let profile = settings_to_profile(&settings, &additional_info);
+        let mut profile = settings_to_profile(&settings, &additional_info);
+        profile.internal_panel = display::get_internal_panel_state().unwrap_or_else(|e| {
+            log::warn!("Couldn't tell whether the built-in display is on: {}", e);
+            None
+        });
+
+        // Keep the per-profile options when overwriting an existing profile
+        if let Ok(existing) = storage_load(&name) {
+            profile.apply_flags = existing.apply_flags;
+            profile.color_filter = existing.color_filter;
+            profile.night_light = existing.night_light;
+            profile.virtual_displays = existing.virtual_displays;
+            profile.prevent_sleep = existing.prevent_sleep;
+            profile.connect_wireless_display = existing.connect_wireless_display;
+        }
+
+        // Save to disk
+        storage_save(&name, &profile)?;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // Get current display settings
+        let settings = get_display_settings(true)?;
+
+        // Save Linux profile format
+        profile::save_linux_profile(&name, &settings)?;
+    }
+
+    // Refresh tray menu to show new profile
+    let _ = refresh_tray_menu(&app);
+
+    info!("Profile '{}' saved successfully", name);
+    Ok(())
+}
+
+/// Load a profile. Returns what happened to each of its monitors.
+#[tauri::command]
+async fn load_profile(app: AppHandle, name: String) -> Result<ApplyReport, String> {
+    do_load_profile(&app, &name, "window")
+}
+
+/// Load a profile by ID or name and record the outcome in the history.
+/// `source` says what asked for it. Loads run one at a time; repeated requests
+/// for the same profile share one apply.
+fn do_load_profile(app: &AppHandle, reference: &str, source: &str) -> Result<ApplyReport, String> {
+    // The tray, rules and chains may refer to the profile by ID
+    let name = host_variant_name(reference);
+    kiosk::ensure_profile_allowed(&name)?;
+
+    app.state::<ApplyQueue<ApplyReport>>().run(&name, || {
+        let mut metrics = ApplyMetrics::begin(&name);
+        let result = apply_profile(app, &name, &mut metrics);
+        let outcome = result.as_ref().map(|_| ()).map_err(Clone::clone);
+        apply_metrics::record(metrics, &outcome);
+        if let Err(e) = history::record(&name, source, &outcome) {
+            log::warn!("Failed to record history: {}", e);
+        }
+        result
+    })
+}
+
+#[tauri::command]
+async fn toggle_profiles(app: AppHandle, a: String, b: String) -> Result<ApplyReport, String> {
+    do_toggle_profiles(&app, &a, &b, "window")
+}
+
+/// Name of the profile a reference (ID or name) loads: for a name, this
+/// machine's variant of the profile if there is one.
+fn host_variant_name(reference: &str) -> String {
+    profile::resolve_host_variant(reference)
+        .ok()
+        .flatten()
+        .map(|entry| entry.name)
+        .unwrap_or_else(|| reference.to_string())
+}
+
+/// Apply `b` if `a` matches the connected monitors, otherwise `a`.
+fn do_toggle_profiles(app: &AppHandle, a: &str, b: &str, source: &str) -> Result<ApplyReport, String> {
+    let active = profile::find_active_profile()?;
+    let a_is_active = match (active, profile::resolve_host_variant(a)?) {
+        (Some(active), Some(a)) => active.id == a.id,
+        _ => false,
+    };
+    do_load_profile(app, if a_is_active { b } else { a }, source)
+}
+
+/// Apply only the DPI scaling saved in a profile, leaving the layout alone.
+#[cfg(windows)]
+#[tauri::command]
+async fn load_profile_dpi_only(app: AppHandle, name: String) -> Result<(), String> {
+    let name = host_variant_name(&name);
+    kiosk::ensure_profile_allowed(&name)?;
+    let result = change_layout(|| apply_profile_dpi(&name));
+    let label = i18n::tr_with("history.dpiOnly", &[("name", &name)]);
+    if let Err(e) = history::record(&label, "window", &result) {
+        log::warn!("Failed to record history: {}", e);
+    }
+    result?;
+
+    monitor_feed::publish(&app);
+    let _ = app.emit("profile-changed", ());
+    Ok(())
+}
+
+/// Set the DPI scaling saved in a profile on the monitors it was saved for,
+/// without applying paths or modes. Monitors that aren't active are skipped.
+#[cfg(windows)]
+fn apply_profile_dpi(name: &str) -> Result<(), String> {
+    info!("Applying the DPI scaling of profile: {}", name);
+    let profile = storage_load(name)?;
+    if profile.dpi_scale_info.is_empty() {
+        return Err(i18n::tr_with("error.noDpiSaved", &[("name", name)]));
+    }
+
+    let (mut settings, additional_info) = profile_to_settings(&profile);
+    match_adapter_ids(&mut settings, &additional_info)?;
+    let current = get_display_settings(true)?;
+
+    let mut transaction = Transaction::new();
+    for dpi_info in &profile.dpi_scale_info {
+        // Sources are numbered by the current layout; follow the monitor the scaling was saved for
+        let Some(source) = settings
+            .path_info_array
+            .iter()
+            .filter(|path| path.source_info.id == dpi_info.source_id)
+            .find_map(|path| {
+                current.path_info_array.iter().find(|c| {
+                    c.target_info.adapter_id == path.target_info.adapter_id && c.target_info.id == path.target_info.id
+                })
+            })
+            .map(|path| path.source_info)
+        else {
+            log::warn!("Profile '{}': no active display for source {}, skipping its DPI scaling", name, dpi_info.source_id);
+            continue;
+        };
+
+        let (adapter_id, source_id) = (source.adapter_id, source.id);
+        let previous = display::get_dpi_scaling_info(adapter_id, source_id).map(|info| info.current);
+        transaction.stage(
+            "stage.dpi",
+            move || previous.map_or(Ok(()), |dpi| set_dpi_scaling(adapter_id, source_id, dpi)),
+            || set_dpi_scaling(adapter_id, source_id, dpi_info.dpi_scale),
+        )?;
+        info!("Set DPI scaling to {}% for source {}", dpi_info.dpi_scale, source_id);
+    }
+    Ok(())
+}
+
+/// Apply only the refresh rates saved in a profile, keeping the current
+/// resolutions and positions. Monitors that aren't active are skipped.
+#[tauri::command]
+async fn load_profile_refresh_rates(app: AppHandle, name: String) -> Result<(), String> {
+    let name = host_variant_name(&name);
+    kiosk::ensure_profile_allowed(&name)?;
+    let result = change_layout(|| apply_profile_refresh_rates(&name));
+    let label = i18n::tr_with("history.refreshRatesOnly", &[("name", &name)]);
+    if let Err(e) = history::record(&label, "window", &result) {
+        log::warn!("Failed to record history: {}", e);
+    }
+    result?;
+
+    monitor_feed::publish(&app);
+    let _ = app.emit("profile-changed", ());
+    Ok(())
+}
+
+fn apply_profile_refresh_rates(name: &str) -> Result<(), String> {
+    info!("Applying the refresh rates of profile: {}", name);
+    let saved = display::display_modes(&load_matched_settings(name)?);
+
+    let modes: Vec<display::DisplayMode> = display::get_display_modes()?
+        .into_iter()
+        .filter_map(|mode| {
+            let refresh_rate = saved.iter().find(|s| s.key == mode.key)?.refresh_rate;
+            Some(display::DisplayMode { refresh_rate, ..mode })
+        })
+        .collect();
+    if modes.is_empty() {
+        return Err(i18n::tr_with("error.noActiveMonitors", &[("name", name)]));
+    }
+    display::set_display_modes(&modes)
+}
+
+/// Restore only the monitor positions and primary monitor saved in a profile,
+/// keeping the modes currently active (e.g. after a game changed a resolution).
+#[tauri::command]
+async fn load_profile_positions(app: AppHandle, name: String) -> Result<(), String> {
+    let name = host_variant_name(&name);
+    kiosk::ensure_profile_allowed(&name)?;
+    info!("Applying the monitor positions of profile: {}", name);
+    let result = change_layout(|| load_matched_settings(&name).and_then(|settings| display::set_display_positions(&settings)));
+    let label = i18n::tr_with("history.positionsOnly", &[("name", &name)]);
+    if let Err(e) = history::record(&label, "window", &result) {
+        log::warn!("Failed to record history: {}", e);
+    }
+    result?;
+
+    monitor_feed::publish(&app);
+    let _ = app.emit("profile-changed", ());
+    Ok(())
+}
+
+/// The apply a crash cut short, if the user hasn't restored or dismissed it yet.
+#[tauri::command]
+async fn get_interrupted_apply() -> Option<apply_marker::ApplyMarker> {
+    apply_marker::interrupted()
+}
+
+/// Restore the layout from before an apply that a crash cut short.
+#[tauri::command]
+async fn restore_interrupted_apply(app: AppHandle) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    let marker = apply_marker::interrupted().ok_or_else(|| i18n::tr("error.noInterruptedApply"))?;
+    info!("Restoring the layout from before applying: {}", marker.profile);
+
+    let layout = apply_marker::interrupted_layout()?;
+    let result = change_layout(|| restore_snapshot(&layout));
+    let label = i18n::tr_with("history.interruptedRestored", &[("name", &marker.profile)]);
+    if let Err(e) = history::record(&label, "window", &result) {
+        log::warn!("Failed to record history: {}", e);
+    }
+    result?;
+    apply_marker::dismiss()?;
+
+    monitor_feed::publish(&app);
+    let _ = app.emit("profile-changed", ());
+    Ok(())
+}
+
+/// Forget an interrupted apply without restoring anything.
+#[tauri::command]
+async fn dismiss_interrupted_apply() -> Result<(), String> {
+    apply_marker::dismiss()
+}
+
+/// Apply a layout snapshot file, with adapter IDs matched to the current system.
+fn restore_snapshot(path: &std::path::Path) -> Result<(), String> {
+    #[cfg(windows)]
+    let (mut settings, additional_info) = profile::load_fixture(path)?;
+    // Linux matches outputs against their live info
+    #[cfg(target_os = "linux")]
+    let (mut settings, additional_info) = {
+        let (settings, _) = profile::load_fixture(path)?;
+        let additional_info = get_additional_info_for_modes(&settings.outputs);
+        (settings, additional_info)
+    };
+    match_adapter_ids(&mut settings, &additional_info)?;
+    set_display_settings(&mut settings)
+}
+
+/// Load a profile's display settings, with adapter IDs matched to the current system.
+fn load_matched_settings(name: &str) -> Result<DisplaySettings, String> {
+    #[cfg(windows)]
+    {
+        let profile = storage_load(name)?;
+        let (mut settings, additional_info) = profile_to_settings(&profile);
+        match_adapter_ids(&mut settings, &additional_info)?;
+        Ok(settings)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut settings = profile::load_linux_profile(name)?;
+        let additional_info = get_additional_info_for_modes(&settings.outputs);
+        match_adapter_ids(&mut settings, &additional_info)?;
+        Ok(settings)
+    }
+}
+
+/// Monitors whose orientation profiles leave alone.
+fn rotation_locks() -> Vec<String> {
+    load_settings().map(|s| s.rotation_locks).unwrap_or_default()
+}
+
+/// Re-read the configuration after applying and log what the driver set
+/// differently than requested.
+fn verify_applied(
+    name: &str,
+    settings: &DisplaySettings,
+    additional_info: &[display::MonitorAdditionalInfo],
+) -> Vec<(String, Vec<String>)> {
+    let discrepancies = display::find_discrepancies(settings, additional_info).unwrap_or_else(|e| {
+        log::warn!("Couldn't verify the applied configuration: {}", e);
+        Vec::new()
+    });
+    for (monitor, found) in &discrepancies {
+        log::warn!("Profile '{}': {}: {}", name, monitor, found.join(", "));
+    }
+    discrepancies
+}
+
+/// Core profile loading logic - shared between command, tray menu and automation.
+/// Fills in `metrics` as it goes, also when it fails.
+fn apply_profile(app: &AppHandle, name: &str, metrics: &mut ApplyMetrics) -> Result<ApplyReport, String> {
+    info!("Loading profile: {}", name);
+
+    // Left behind if the app or the driver crashes before applying returns
+    let _marker = apply_marker::begin(name);
+    // Rules mustn't react to the layout changes this causes
+    let _own = automation::own_apply();
+
+    #[cfg(windows)]
+    let report = {
+        // Load profile from disk
+        let profile = storage_load(name)?;
+
+        // Convert to CCD settings
+        let (mut settings, mut additional_info) = profile_to_settings(&profile);
+
+        // Every stage below is rolled back if a later one fails
+        let mut transaction = Transaction::new();
+
+        // The layout can only place virtual displays once they exist
+        stage_virtual_displays(&mut transaction, name, profile.virtual_displays.clone())?;
+
+        metrics.wait_ms = apply_metrics::timed(|| {
+            if profile.connect_wireless_display {
+                connect_wireless_displays(name, &settings, &additional_info);
+            }
+            wait_for_monitors(name, || find_missing_monitors(&settings, &additional_info));
+        })
+        .1;
+        let missing = find_missing_monitors(&settings, &additional_info).unwrap_or_default();
+
+        // Match adapter IDs to current system
+        metrics.match_tier = Some(transaction.check("stage.matching", || match_adapter_ids(&mut settings, &additional_info))?);
+
+        // A wireless display that didn't connect mustn't fail the rest of the layout
+        let absent = display::exclude_absent_wireless_targets(&mut settings, &mut additional_info).unwrap_or_else(|e| {
+            log::warn!("Couldn't check the wireless displays: {}", e);
+            Vec::new()
+        });
+        if !absent.is_empty() {
+            log::warn!("Profile '{}': leaving out {}, not connected", name, absent.join(", "));
+        }
+        display::keep_rotations(&mut settings, &rotation_locks());
+
+        for warning in find_non_native_modes(&settings) {
+            log::warn!("Profile '{}': {}", name, warning);
+        }
+
+        // Per-profile flags win over the global default
+        let apply_flags = profile
+            .apply_flags
+            .or_else(|| load_settings().ok().and_then(|s| s.apply_flags));
+
+        // Apply display settings (resolution, position, etc.), unless they're
+        // set already; automation re-firing would only blank the screens
+        metrics.already_active = is_already_applied(name, &settings);
+        if !metrics.already_active {
+            let snapshot = get_display_settings(true).ok();
+            let (attempts, layout_ms) = apply_metrics::timed(|| {
+                transaction.stage("stage.layout", restore_layout(snapshot.clone()), || {
+                    display::apply_display_settings(&mut settings, apply_flags.unwrap_or_default())
+                })
+            });
+            metrics.layout_ms = layout_ms;
+            let attempts = attempts?;
+            metrics.retries = attempts.attempts - 1;
+            metrics.flags = Some(attempts.flags);
+            // Displays that stay dark are only worth waiting for if the layout can be reverted
+            if snapshot.is_some() {
+                transaction.check("stage.layout", || verify_lit_up(|| find_inactive_targets(&settings, &additional_info)))?;
+            }
+        }
+
+        // Windows may turn the built-in panel back on, e.g. in clamshell mode
+        if profile.internal_panel == Some(false) {
+            let before = get_display_settings(true).ok();
+            let turned_off = transaction.stage("stage.internalPanel", restore_layout(before), display::turn_off_internal_panel)?;
+            if turned_off {
+                info!("Turned the built-in display off, as saved in profile '{}'", name);
+            }
+        }
+
+        let dark = find_inactive_targets(&settings, &additional_info).unwrap_or_default();
+
+        // Custom (CRU-style) timings only round-trip if the driver accepts them
+        let replaced = display::find_replaced_timings(&settings, &additional_info).unwrap_or_else(|e| {
+            log::warn!("Couldn't check the applied timings: {}", e);
+            Vec::new()
+        });
+        for (monitor, warning) in &replaced {
+            log::warn!("Profile '{}': {}: {}", name, monitor, warning);
+        }
+        let discrepancies = verify_applied(name, &settings, &additional_info);
+
+        // Apply DPI scaling for each source
+        // We need to match the saved source IDs to the current system's source IDs
+        // After match_adapter_ids, the settings have updated adapter IDs
+        for dpi_info in &profile.dpi_scale_info {
+            // Find the path with matching source ID in the updated settings
+            if let Some(path) = settings.path_info_array.iter().find(|p| p.source_info.id == dpi_info.source_id) {
+                let adapter_id = LUID {
+                    low_part: path.source_info.adapter_id.low_part,
+                    high_part: path.source_info.adapter_id.high_part,
+                };
+                let source_id = dpi_info.source_id;
+                let previous = display::get_dpi_scaling_info(adapter_id, source_id).map(|info| info.current);
+                transaction.stage(
+                    "stage.dpi",
+                    move || previous.map_or(Ok(()), |dpi| set_dpi_scaling(adapter_id, source_id, dpi)),
+                    || set_dpi_scaling(adapter_id, source_id, dpi_info.dpi_scale),
+                )?;
+                info!("Set DPI scaling to {}% for source {}", dpi_info.dpi_scale, source_id);
+            }
+        }
+
+        if let Some(filter) = profile.color_filter {
+            let previous = display::get_color_filter();
+            transaction.stage(
+                "stage.colorFilter",
+                move || previous.map_or(Ok(()), display::set_color_filter),
+                || display::set_color_filter(filter),
+            )?;
+        }
+
+        if let Some(on) = profile.night_light {
+            let previous = display::get_night_light();
+            transaction.stage(
+                "stage.nightLight",
+                move || previous.map_or(Ok(()), display::set_night_light),
+                || display::set_night_light(on),
+            )?;
+        }
+
+        let monitors = display::profile_monitor_names(&settings, &additional_info);
+        ApplyReport {
+            already_active: metrics.already_active,
+            ..ApplyReport::build(name, monitors, missing, dark, replaced, discrepancies)
+        }
+    };
+
+    #[cfg(target_os = "linux")]
+    let report = {
+        // Load and apply Linux profile
+        let mut settings = profile::load_linux_profile(name)?;
+        let virtual_displays = profile::get_profile_virtual_displays(name)?;
+
+        // Every stage below is rolled back if a later one fails
+        let mut transaction = Transaction::new();
+
+        // The layout can only place virtual displays once they exist
+        stage_virtual_displays(&mut transaction, name, virtual_displays)?;
+
+        metrics.wait_ms = apply_metrics::timed(|| wait_for_monitors(name, || find_missing_monitors(&settings, &[]))).1;
+        let missing = find_missing_monitors(&settings, &[]).unwrap_or_default();
+
+        // Match output names to current system
+        let additional_info = get_additional_info_for_modes(&settings.outputs);
+        metrics.match_tier = Some(transaction.check("stage.matching", || match_adapter_ids(&mut settings, &additional_info))?);
+        display::keep_rotations(&mut settings, &rotation_locks());
+
+        for warning in find_non_native_modes(&settings) {
+            log::warn!("Profile '{}': {}", name, warning);
+        }
+
+        // Apply display settings, unless they're set already; xrandr runs once
+        // per X screen, so a failure can leave some screens switched
+        metrics.already_active = is_already_applied(name, &settings);
+        if !metrics.already_active {
+            let snapshot = get_display_settings(true).ok();
+            let (result, layout_ms) = apply_metrics::timed(|| {
+                transaction.stage("stage.layout", restore_layout(snapshot.clone()), || set_display_settings(&mut settings))
+            });
+            metrics.layout_ms = layout_ms;
+            result?;
+            // Displays that stay dark are only worth waiting for if the layout can be reverted
+            if snapshot.is_some() {
+                transaction.check("stage.layout", || verify_lit_up(|| find_inactive_targets(&settings, &additional_info)))?;
+            }
+        }
+        let dark = find_inactive_targets(&settings, &additional_info).unwrap_or_default();
+        let discrepancies = verify_applied(name, &settings, &additional_info);
+
+        let monitors = display::profile_monitor_names(&settings, &additional_info);
+        ApplyReport {
+            already_active: metrics.already_active,
+            ..ApplyReport::build(name, monitors, missing, dark, Vec::new(), discrepancies)
+        }
+    };
+
+    focus::forget();
+
+    // Presentation profiles keep the screen on until another profile is applied
+    let prevent_sleep = profile::get_profile_prevent_sleep(name).unwrap_or(false);
+    if let Err(e) = presentation::set(prevent_sleep.then_some(name)) {
+        log::warn!("Failed to keep the screen on for '{}': {}", name, e);
+    }
+
+    let id = profile::find_profile_entry(name).ok().flatten().map(|entry| entry.id);
+    if let Err(e) = state::record_applied(name, id.as_deref()) {
+        log::warn!("Failed to remember the applied profile: {}", e);
+    }
+    let _ = refresh_tray_menu(app);
+    monitor_feed::publish(app);
+
+    // Emit event so frontend can refresh active profile state
+    let _ = app.emit("profile-changed", ());
+
+    info!("Profile '{}' loaded successfully", name);
+    Ok(report)
+}
+
+/// Whether the connected displays already have the layout of a profile, so
+/// applying it can be skipped. A failed check applies it anyway.
+fn is_already_applied(name: &str, settings: &DisplaySettings) -> bool {
+    match display::is_already_applied(settings) {
+        Ok(true) => {
+            info!("Profile '{}' is already active, leaving the layout alone", name);
+            true
+        }
+        Ok(false) => false,
+        Err(e) => {
+            log::warn!("Couldn't compare profile '{}' with the current layout: {}", name, e);
+            false
+        }
+    }
+}
+
+/// How long to give the user to pick a wireless display in the flyout.
+#[cfg(windows)]
+const WIRELESS_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Open the wireless display flyout if a wireless display of the profile isn't
+/// connected, and wait for it to connect.
+#[cfg(windows)]
+fn connect_wireless_displays(name: &str, settings: &DisplaySettings, additional_info: &[display::MonitorAdditionalInfo]) {
+    let wireless = display::wireless_display_names(settings, additional_info);
+    let is_waiting = || {
+        find_missing_monitors(settings, additional_info).map(|missing| missing.iter().any(|m| wireless.contains(m)))
+    };
+    if wireless.is_empty() || !is_waiting().unwrap_or(false) {
+        return;
+    }
+
+    info!("Profile '{}': asking to connect {}", name, wireless.join(", "));
+    if let Err(e) = display::open_wireless_connect() {
+        log::warn!("{}", e);
+        return;
+    }
+
+    let deadline = Instant::now() + WIRELESS_CONNECT_TIMEOUT;
+    while Instant::now() < deadline && is_waiting().unwrap_or(false) {
+        thread::sleep(MONITOR_POLL_INTERVAL);
+    }
+}
+
+/// Have the virtual display driver show the displays a profile asks for.
+/// Rolling back restores the number it showed before.
+fn stage_virtual_displays(transaction: &mut Transaction, name: &str, displays: Option<Vec<VirtualDisplay>>) -> Result<(), String> {
+    let Some(displays) = displays else {
+        return Ok(());
+    };
+
+    let previous = display::count_virtual_displays()?;
+    let changed = transaction.stage(
+        "stage.virtualDisplays",
+        move || display::set_virtual_display_count(previous).map(|_| ()),
+        || display::set_virtual_displays(&displays),
+    )?;
+    if changed {
+        info!("Profile '{}': switched from {} to {} virtual display(s)", name, previous, displays.len());
+    }
+    Ok(())
+}
+
+/// Change the layout other than by loading a profile. Waits for profile loads
+/// and other changes in progress, as concurrent changes misbehave, keeps rules
+/// from reacting to the change and ends focus mode, whose saved layout would
+/// undo it.
+pub(crate) fn change_layout<T>(change: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    apply_queue::exclusive(|| {
+        let _own = automation::own_apply();
+        let result = change();
+        if result.is_ok() {
+            focus::forget();
+        }
+        result
+    })
+}
+
+#[tauri::command]
+async fn apply_template(app: AppHandle, template: Template) -> Result<(), String> {
+    do_apply_template(&app, template, "window")
+}
+
+/// Switch to a built-in template layout and record the outcome in the history.
+fn do_apply_template(app: &AppHandle, template: Template, source: &str) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    let label = i18n::tr(&format!("template.{}", template.id()));
+    info!("Applying template: {}", label);
+
+    let result = change_layout(|| display::apply_template(template));
+    if let Err(e) = history::record(&label, source, &result) {
+        log::warn!("Failed to record history: {}", e);
+    }
+    result?;
+
+    let _ = refresh_tray_menu(app);
+    monitor_feed::publish(app);
+    let _ = app.emit("profile-changed", ());
+    Ok(())
+}
+
+/// Turn focus mode on or off. On, every display but the primary one is
+/// switched off; off, the layout from before comes back. Returns whether
+/// focus mode is on.
+#[tauri::command]
+async fn focus_mode(app: AppHandle, enable: bool) -> Result<bool, String> {
+    kiosk::ensure_unlocked()?;
+    let (label, toggle): (_, fn() -> Result<bool, String>) = if enable {
+        (i18n::tr("history.focusMode"), focus::enable)
+    } else {
+        (i18n::tr("history.focusModeEnded"), focus::disable)
+    };
+    // Like change_layout, except that it would end focus mode
+    let result = apply_queue::exclusive(|| {
+        let _own = automation::own_apply();
+        toggle()
+    });
+
+    // Nothing changed, e.g. with a single display
+    if matches!(result, Ok(false)) {
+        return Ok(focus::is_enabled());
+    }
+    if let Err(e) = history::record(&label, "window", &result.clone().map(|_| ())) {
+        log::warn!("Failed to record history: {}", e);
+    }
+    result?;
+
+    info!("Focus mode {}", if enable { "on" } else { "off" });
+    let _ = refresh_tray_menu(&app);
+    monitor_feed::publish(&app);
+    let _ = app.emit("profile-changed", ());
+    Ok(focus::is_enabled())
+}
+
+/// Show only the laptop's built-in display, whatever is connected.
+#[tauri::command]
+async fn internal_display_only(app: AppHandle) -> Result<(), String> {
+    do_apply_template(&app, Template::PcOnly, "window")
+}
+
+/// Show only the external displays, whatever is connected.
+#[tauri::command]
+async fn external_displays_only(app: AppHandle) -> Result<(), String> {
+    do_apply_template(&app, Template::ExternalOnly, "window")
+}
+
+/// Duplicate all displays at the largest resolution they all support.
+#[tauri::command]
+async fn duplicate_at_best_common_mode(app: AppHandle) -> Result<(), String> {
+    do_apply_template(&app, Template::Duplicate, "window")
+}
+
+/// How often `wait_for_monitors` and `verify_lit_up` check the monitors.
+const MONITOR_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Wait until all monitors of a profile are connected, for up to the configured
+/// number of seconds. Applying anyway after the timeout keeps the old behaviour.
+fn wait_for_monitors<F>(name: &str, find_missing: F)
+where
+    F: Fn() -> Result<Vec<String>, String>,
+{
+    let timeout = load_settings().map(|s| s.wait_for_monitors_secs).unwrap_or(0);
+    if timeout == 0 {
+        return;
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(timeout);
+    loop {
+        match find_missing() {
+            Ok(missing) if missing.is_empty() => return,
+            Ok(missing) if Instant::now() >= deadline => {
+                log::warn!(
+                    "Profile '{}': {} still not connected after {}s, applying anyway",
+                    name,
+                    missing.join(", "),
+                    timeout
+                );
+                return;
+            }
+            Ok(missing) => log::debug!("Profile '{}': waiting for {}", name, missing.join(", ")),
+            Err(e) => {
+                log::warn!("Failed to check connected monitors: {}", e);
+                return;
+            }
+        }
+        thread::sleep(MONITOR_POLL_INTERVAL);
+    }
+}
+
+/// Timings and details of the most recent profile applies, newest first.
+#[tauri::command]
+async fn get_apply_metrics() -> Vec<ApplyMetrics> {
+    apply_metrics::recent()
+}
+
+/// Applied configurations, newest first.
+#[tauri::command]
+async fn get_history(limit: Option<usize>) -> Result<Vec<HistoryEntry>, String> {
+    history::read(limit)
+}
+
+/// The profile applied most recently, and when.
+#[tauri::command]
+async fn get_last_applied() -> Option<LastApplied> {
+    state::last_applied()
+}
+
+/// Undo step of the layout stage: re-apply the layout from before.
+fn restore_layout(snapshot: Option<DisplaySettings>) -> impl FnOnce() -> Result<(), String> {
+    move || match snapshot {
+        Some(mut snapshot) => set_display_settings(&mut snapshot),
+        None => Err(i18n::tr("error.previousLayoutUnreadable")),
+    }
+}
+
+/// Wait for every display of an applied layout to become active. Fails naming
+/// the displays still dark after the configured timeout, so the apply is rolled back.
+fn verify_lit_up<F>(find_inactive: F) -> Result<(), String>
+where
+    F: Fn() -> Result<Vec<String>, String>,
+{
+    let timeout = load_settings().map(|s| s.revert_if_dark_secs).unwrap_or(0);
+    if timeout == 0 {
+        return Ok(());
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(timeout);
+    loop {
+        let dark = match find_inactive() {
+            Ok(dark) => dark,
+            Err(e) => {
+                log::warn!("Failed to check active monitors: {}", e);
+                return Ok(());
+            }
+        };
+        if dark.is_empty() {
+            return Ok(());
+        }
+
+        if Instant::now() >= deadline {
+            let dark = dark.join(", ");
+            error!("{} stayed dark after {}s, reverting", dark, timeout);
+            return Err(i18n::tr_with("error.stayedDark", &[("displays", &dark)]));
+        }
+        thread::sleep(MONITOR_POLL_INTERVAL);
+    }
+}
+
+#[tauri::command]
+async fn delete_profile(app: AppHandle, name: String) -> Result<(), String> {
+    do_delete_profile(&app, &name)
+}
+
+/// Copy a profile into the machine-wide profiles directory, so every user of
+/// this computer gets it. Asks for administrator rights.
+#[tauri::command]
+async fn install_profile_machine_wide(app: AppHandle, name: String) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    let entry = profile::find_profile_entry(&name)?
+        .ok_or_else(|| i18n::tr_with("error.profileNotFound", &[("name", &name)]))?;
+    info!("Installing profile machine-wide: {}", entry.name);
+
+    elevation::run_privileged(&elevation::PrivilegedOp::InstallProfile {
+        source: entry.path,
+        file_name: format!("{}.json", entry.id),
+    })?;
+
+    let _ = refresh_tray_menu(&app);
+    let _ = app.emit("profile-changed", ());
+    Ok(())
+}
+
+/// Remove a profile from the machine-wide profiles directory. A user copy of
+/// it stays. Asks for administrator rights.
+#[tauri::command]
+async fn remove_machine_profile(app: AppHandle, name: String) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    let entry = profile::find_profile_entry(&name)?
+        .ok_or_else(|| i18n::tr_with("error.profileNotFound", &[("name", &name)]))?;
+    let file_name = format!("{}.json", entry.id);
+    if !profile::get_machine_profiles_dir()?.join(&file_name).exists() {
+        return Err(i18n::tr_with("error.notMachineWide", &[("name", &entry.name)]));
+    }
+    info!("Removing machine-wide profile: {}", entry.name);
+
+    elevation::run_privileged(&elevation::PrivilegedOp::RemoveMachineProfile { file_name })?;
+
+    let _ = refresh_tray_menu(&app);
+    let _ = app.emit("profile-changed", ());
+    Ok(())
+}
+
+/// Core profile deletion logic - shared between command and tray menu
+fn do_delete_profile(app: &AppHandle, name: &str) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    info!("Deleting profile: {}", name);
+    storage_delete(name)?;
+
+    // Refresh tray menu to remove deleted profile
+    let _ = refresh_tray_menu(app);
+
+    // Emit event so frontend can refresh
+    let _ = app.emit("profile-changed", ());
+
+    info!("Profile '{}' deleted successfully", name);
+    Ok(())
+}
+
+#[tauri::command]
+async fn rename_profile(app: AppHandle, old_name: String, new_name: String) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    profile::rename_profile(&old_name, &new_name)?;
+
+    let _ = refresh_tray_menu(&app);
+    let _ = app.emit("profile-changed", ());
+
+    info!("Profile '{}' renamed to '{}'", old_name, new_name);
+    Ok(())
+}
+
+/// Backups of a profile as timestamps ("20240101T120000123456"), newest first.
+/// Damaged profiles are listed too, so they can be recovered from here.
+#[tauri::command]
+async fn list_profile_backups(name: String) -> Result<Vec<String>, String> {
+    profile::list_profile_backups(&name)
+}
+
+#[tauri::command]
+async fn restore_profile_backup(app: AppHandle, name: String, timestamp: String) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    profile::restore_profile_backup(&name, &timestamp)?;
+
+    let _ = refresh_tray_menu(&app);
+    let _ = app.emit("profile-changed", ());
+
+    info!("Profile '{}' restored from backup {}", name, timestamp);
+    Ok(())
+}
+
+#[tauri::command]
+async fn profile_exists(name: String) -> Result<bool, String> {
+    storage_exists(&name)
+}
+
+#[tauri::command]
+async fn turn_off_monitors() -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    info!("Turning off monitors");
+    platform_turn_off()
+}
+
+/// Check a profile against the connected monitors without applying it.
+/// Returns a list of warnings, e.g. resolutions that aren't the panel's native mode.
+#[tauri::command]
+async fn validate_profile(name: String) -> Result<Vec<String>, String> {
+    #[cfg(windows)]
+    {
+        let profile = storage_load(&name)?;
+        let (mut settings, additional_info) = profile_to_settings(&profile);
+        match_adapter_ids(&mut settings, &additional_info)?;
+        Ok(find_non_native_modes(&settings))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let settings = profile::load_linux_profile(&name)?;
+        Ok(find_non_native_modes(&settings))
+    }
+}
+
+/// Run all checks against a profile without applying it.
+/// Returns the problems found, most severe first, each with a suggested fix.
+#[tauri::command]
+async fn troubleshoot_profile(name: String) -> Vec<troubleshoot::Problem> {
+    troubleshoot::troubleshoot_profile(&name)
+}
+
+/// Run a profile chain, returning once all its steps finished.
+#[tauri::command]
+async fn run_chain(app: AppHandle, name: String) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    tauri::async_runtime::spawn_blocking(move || chain::run_chain(&app, &name))
+        .await
+        .map_err(|e| format!("Chain task failed: {}", e))?
+}
+
+#[tauri::command]
+async fn is_remote_session() -> bool {
+    display::is_remote_session()
+}
+
+#[tauri::command]
+async fn open_save_dialog(app: AppHandle) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    open_save_popup(&app, None);
+    Ok(())
+}
+
+/// Name of the profile closest to the current layout, if any resembles it.
+#[tauri::command]
+async fn find_matching_profile() -> Result<Option<String>, String> {
+    Ok(profile::find_closest_profile()?.map(|entry| entry.name))
+}
+
+/// Overwrite the profile the user confirmed, as `find_matching_profile` named
+/// it, with the current layout. Takes the name or ID and returns the name.
+#[tauri::command]
+async fn update_matching_profile(app: AppHandle, name: String) -> Result<String, String> {
+    let name = profile::find_profile(&name)?
+        .ok_or_else(|| i18n::tr_with("error.profileNotFound", &[("name", &name)]))?;
+    save_profile(app, name.clone()).await?;
+    Ok(name)
+}
+
+#[tauri::command]
+async fn get_current_monitors() -> Result<Vec<MonitorDetails>, String> {
+    current_monitors()
+}
+
+/// Push `monitors-changed` events to the calling window whenever the current
+/// monitors change. Returns the current monitors.
+#[tauri::command]
+async fn subscribe_monitors(window: WebviewWindow, feed: State<'_, MonitorFeed>) -> Result<Vec<MonitorDetails>, String> {
+    feed.subscribe(window.label())
+}
+
+/// Stop the updates started by `subscribe_monitors`.
+#[tauri::command]
+async fn unsubscribe_monitors(window: WebviewWindow, feed: State<'_, MonitorFeed>) -> Result<(), String> {
+    feed.unsubscribe(window.label());
+    Ok(())
+}
+
+/// Render a thumbnail of a profile's monitor arrangement to the cache folder.
+/// Returns the path of the PNG file.
+#[tauri::command]
+async fn get_profile_thumbnail(name: String) -> Result<String, String> {
+    let monitors = profile::get_profile_layout(&name)?;
+    let png = thumbnail::render(&monitors, thumbnail::THUMBNAIL_SIZE).to_png()?;
+
+    let path = profile::get_thumbnail_path(&name)?;
+    std::fs::write(&path, png)
+        .map_err(|e| i18n::tr_with("error.writeFile", &[("path", &path.display().to_string()), ("error", &e.to_string())]))?;
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Clean up monitor positions from the layout editor: snap nearby edges together,
+/// remove sub-pixel gaps and push overlapping monitors apart.
+#[tauri::command]
+async fn snap_layout(monitors: Vec<LayoutRect>, snap_distance: Option<f64>) -> Vec<LayoutRect> {
+    layout::snap_layout(monitors, snap_distance.unwrap_or(layout::SNAP_DISTANCE))
+}
+
+/// Dump the raw display state and profile matching results as pretty-printed JSON.
+#[tauri::command]
+async fn dump_display_state() -> Result<String, String> {
+    let dump = diagnostics::dump_display_state()?;
+    serde_json::to_string_pretty(&dump)
+        .map_err(|e| format!("Failed to serialize display state: {}", e))
+}
+
+/// Read what a monitor supports over DDC/CI (brightness, input switching, ...).
+#[tauri::command]
+async fn get_monitor_capabilities(monitor_id: String) -> Result<display::MonitorCapabilities, String> {
+    display::get_monitor_capabilities(&monitor_id)
+}
+
+/// Read a raw VCP feature over DDC/CI.
+#[tauri::command]
+async fn get_vcp(monitor_id: String, code: u8) -> Result<display::VcpValue, String> {
+    display::get_vcp(&monitor_id, code)
+}
+
+/// Write a raw VCP feature over DDC/CI (OSD presets, KVM switching, ...).
+#[tauri::command]
+async fn set_vcp(monitor_id: String, code: u8, value: u32) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    display::set_vcp(&monitor_id, code, value)
+}
+
+/// Set every monitor to the same brightness (DDC/CI, or the backlight for built-in panels).
+#[tauri::command]
+async fn set_all_brightness(percent: u8) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    display::set_all_brightness(percent)
+}
+
+/// Raw EDID of each connected monitor, for identifying models and parsing
+/// capabilities the app itself doesn't look at.
+#[tauri::command]
+async fn get_edid_raw() -> Result<Vec<MonitorEdid>, String> {
+    use base64::Engine;
+
+    Ok(display::read_raw_edids()?
+        .into_iter()
+        .map(|raw| MonitorEdid {
+            monitor_id: raw.monitor_id,
+            name: raw.name,
+            edid: base64::engine::general_purpose::STANDARD.encode(&raw.edid),
+            overridden: raw.overridden,
+        })
+        .collect())
+}
+
+/// List the display adapters (GPUs) and the monitors connected to each.
+#[tauri::command]
+async fn get_gpu_info() -> Result<Vec<GpuInfo>, String> {
+    display::get_gpu_info()
+}
+
+/// Show each monitor's number and name on the monitor itself for a few seconds.
+#[tauri::command]
+async fn identify_monitors(app: AppHandle) -> Result<(), String> {
+    identify::identify_monitors(&app)
+}
+
+/// The kiosk policy in force, if kiosk mode is on, so the window can hide
+/// what it refuses anyway.
+#[tauri::command]
+async fn get_kiosk_policy() -> Option<KioskPolicy> {
+    kiosk::policy()
+}
+
+#[tauri::command]
+async fn get_settings() -> Result<AppSettings, String> {
+    load_settings()
+}
+
+#[tauri::command]
+async fn update_settings(app: AppHandle, settings: AppSettings) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    save_settings(&settings)?;
+    hotkeys::register(&app);
+    #[cfg(windows)]
+    display::set_match_virtual_adapters(settings.match_virtual_adapters);
+    Ok(())
+}
+
+/// Export the settings (rules, chains, aliases, ...) for another machine.
+/// Writes to `path`, or to the Exports folder. Returns the path of the file.
+#[tauri::command]
+async fn export_settings(path: Option<String>) -> Result<String, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => settings::get_settings_export_path()?,
+    };
+    settings::export_settings(&path)?;
+
+    info!("Exported settings to {}", path.display());
+    Ok(path.display().to_string())
+}
+
+/// Replace the settings with an export from `export_settings`.
+#[tauri::command]
+async fn import_settings(app: AppHandle, path: String) -> Result<AppSettings, String> {
+    kiosk::ensure_unlocked()?;
+    let settings = settings::import_settings(Path::new(&path))?;
+
+    // Chains and aliases show up in the tray and the monitor list
+    let _ = refresh_tray_menu(&app);
+    monitor_feed::publish(&app);
+    hotkeys::register(&app);
+    #[cfg(windows)]
+    display::set_match_virtual_adapters(settings.match_virtual_adapters);
+
+    info!("Imported settings from {}", path);
+    Ok(settings)
+}
+
+/// Encode a profile as one line of text to paste into a forum post or chat.
+#[tauri::command]
+async fn export_profile_string(name: String) -> Result<String, String> {
+    profile::export_profile_string(&name)
+}
+
+/// Save the profile in a string from `export_profile_string`. Returns the
+/// name it was saved under, numbered if a profile had its name already.
+#[tauri::command]
+async fn import_profile_string(app: AppHandle, data: String) -> Result<String, String> {
+    kiosk::ensure_unlocked()?;
+    let name = profile::import_profile_string(&data)?;
+
+    let _ = refresh_tray_menu(&app);
+    let _ = app.emit("profile-changed", ());
+    info!("Imported shared profile '{}'", name);
+    Ok(name)
+}
+
+/// Turn a monitor by 90° steps, clockwise for positive `quarter_turns`. Without
+/// a `monitor_id`, the monitor under the mouse pointer turns.
+#[tauri::command]
+async fn rotate_monitor(app: AppHandle, monitor_id: Option<String>, quarter_turns: i32) -> Result<(), String> {
+    do_rotate_monitor(&app, monitor_id, quarter_turns)
+}
+
+fn do_rotate_monitor(app: &AppHandle, monitor_id: Option<String>, quarter_turns: i32) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    let monitor_id = monitor_id
+        .or_else(display::monitor_at_cursor)
+        .ok_or_else(|| i18n::tr("error.noMonitorAtCursor"))?;
+    change_layout(|| display::rotate_display(&monitor_id, quarter_turns))?;
+
+    monitor_feed::publish(app);
+    let _ = app.emit("profile-changed", ());
+    Ok(())
+}
+
+/// Change the log level right away and keep it for the next start, e.g. debug
+/// while reproducing a matching problem. An empty level goes back to RUST_LOG.
+#[tauri::command]
+async fn set_log_level(level: String) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    let level = level.trim().to_lowercase();
+    let saved = if level.is_empty() {
+        None
+    } else {
+        level
+            .parse::<LevelFilter>()
+            .map_err(|_| i18n::tr_with("error.unknownLogLevel", &[("level", &level)]))?;
+        Some(level)
+    };
+
+    let mut settings = load_settings()?;
+    settings.log_level = saved.clone();
+    save_settings(&settings)?;
+
+    let filter = configured_log_level(saved);
+    log::set_max_level(filter);
+    info!("Log level set to {}", filter);
+    Ok(())
+}
+
+/// Lock or unlock a monitor's orientation against profile loads.
+#[tauri::command]
+async fn set_rotation_lock(monitor_id: String, locked: bool) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    let mut settings = load_settings()?;
+    settings.rotation_locks.retain(|id| *id != monitor_id);
+    if locked {
+        settings.rotation_locks.push(monitor_id);
+    }
+    save_settings(&settings)
+}
+
+/// Set the display name of a monitor. An empty alias restores the reported name.
+#[tauri::command]
+async fn set_monitor_alias(app: AppHandle, monitor_id: String, alias: String) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    let mut settings = load_settings()?;
+    let alias = alias.trim();
+
+    if alias.is_empty() {
+        settings.monitor_aliases.remove(&monitor_id);
+    } else {
+        settings.monitor_aliases.insert(monitor_id, alias.to_string());
+    }
+
+    save_settings(&settings)?;
+    monitor_feed::publish(&app);
+    Ok(())
+}
+
+/// Save a snapshot of the current display state as a matcher test fixture.
+/// Returns the path of the written file.
+#[cfg(windows)]
+#[tauri::command]
+async fn capture_display_fixture(name: String) -> Result<String, String> {
+    let path = profile::save_fixture(&name)?;
+    info!("Captured display fixture: {}", path.display());
+    Ok(path.display().to_string())
+}
+
+/// Export a profile as a standalone `apply.sh` that runs the exact xrandr
+/// invocation, for display managers and autostart scripts.
+/// Returns the path of the written script.
+#[cfg(target_os = "linux")]
+#[tauri::command]
+async fn export_xrandr_script(name: String) -> Result<String, String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let settings = profile::load_linux_profile(&name)?;
+    let script = display::export_xrandr_script(&name, &settings);
+
+    let path = profile::get_export_dir(&name)?.join("apply.sh");
+    std::fs::write(&path, script)
+        .map_err(|e| i18n::tr_with("error.writeFile", &[("path", &path.display().to_string()), ("error", &e.to_string())]))?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+        .map_err(|e| i18n::tr_with("error.makeExecutable", &[("path", &path.display().to_string()), ("error", &e.to_string())]))?;
+
+    info!("Exported profile '{}' to {}", name, path.display());
+    Ok(path.display().to_string())
+}
+
+/// Export a profile as a GNOME `monitors.xml`. Copied to `~/.config/`, GNOME
+/// uses it as the layout for these monitors; copied to GDM's config directory,
+/// it sets up the login screen. Returns the path of the written file.
+#[cfg(target_os = "linux")]
+#[tauri::command]
+async fn export_gnome_monitors(name: String) -> Result<String, String> {
+    let settings = profile::load_linux_profile(&name)?;
+    let xml = display::export_gnome_monitors_xml(&settings);
+
+    let path = profile::get_export_dir(&name)?.join("monitors.xml");
+    std::fs::write(&path, xml)
+        .map_err(|e| i18n::tr_with("error.writeFile", &[("path", &path.display().to_string()), ("error", &e.to_string())]))?;
+
+    info!("Exported profile '{}' to {}", name, path.display());
+    Ok(path.display().to_string())
+}
+
+/// Import the layouts of a GNOME `monitors.xml` as profiles, one per
+/// configuration, named after their monitors and numbered when a profile has
+/// that name already. Reads `~/.config/monitors.xml`
+/// when no path is given. Returns the names of the saved profiles.
+#[cfg(target_os = "linux")]
+#[tauri::command]
+async fn import_gnome_monitors(app: AppHandle, path: Option<String>) -> Result<Vec<String>, String> {
+    kiosk::ensure_unlocked()?;
+
+    let path = match path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => dirs::config_dir()
+            .ok_or_else(|| i18n::tr("error.noConfigDir"))?
+            .join("monitors.xml"),
+    };
+    let xml = std::fs::read_to_string(&path)
+        .map_err(|e| i18n::tr_with("error.readFile", &[("path", &path.display().to_string()), ("error", &e.to_string())]))?;
+
+    let mut names = Vec::new();
+    for settings in display::import_gnome_monitors_xml(&xml)? {
+        let connectors: Vec<&str> = settings.outputs.iter().filter(|o| o.enabled).map(|o| o.name.as_str()).collect();
+        let name = profile::save_imported_linux_profile(&format!("GNOME: {}", connectors.join(" + ")), &settings)?;
+        names.push(name);
+    }
+
+    let _ = refresh_tray_menu(&app);
+    let _ = app.emit("profile-changed", ());
+    info!("Imported {} profile(s) from {}", names.len(), path.display());
+    Ok(names)
+}
+
+/// Import KDE's kscreen configurations as profiles, one per set of monitors,
+/// named after their monitors and numbered when a profile has that name
+/// already. Reads `~/.local/share/kscreen/` when no directory is given.
+/// Returns the names of the saved profiles.
+#[cfg(target_os = "linux")]
+#[tauri::command]
+async fn import_kscreen_configs(app: AppHandle, dir: Option<String>) -> Result<Vec<String>, String> {
+    kiosk::ensure_unlocked()?;
+
+    let dir = match dir {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => dirs::data_dir()
+            .ok_or_else(|| i18n::tr("error.noDataDir"))?
+            .join("kscreen"),
+    };
+    let entries = std::fs::read_dir(&dir)
+        .map_err(|e| i18n::tr_with("error.readFile", &[("path", &dir.display().to_string()), ("error", &e.to_string())]))?;
+
+    let mut names: Vec<String> = Vec::new();
+    for path in entries.flatten().map(|e| e.path()).filter(|p| p.is_file()) {
+        // Besides the layouts the directory holds other kscreen state
+        let settings = match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| display::import_kscreen_config(&json))
+        {
+            Ok(settings) => settings,
+            Err(e) => {
+                log::debug!("Skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
 
-        // Save to disk
-        storage_save(&name, &profile)?;
+        let connectors: Vec<&str> = settings.outputs.iter().filter(|o| o.enabled).map(|o| o.name.as_str()).collect();
+        let name = profile::save_imported_linux_profile(&format!("KDE: {}", connectors.join(" + ")), &settings)?;
+        names.push(name);
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        // Get current display settings
-        let settings = get_display_settings(true)?;
-
-        // Save Linux profile format
-        profile::save_linux_profile(&name, &settings)?;
+    if names.is_empty() {
+        return Err(i18n::tr_with("error.noKscreenConfigs", &[("path", &dir.display().to_string())]));
     }
 
-    // Refresh tray menu to show new profile
     let _ = refresh_tray_menu(&app);
-
-    info!("Profile '{}' saved successfully", name);
-    Ok(())
+    let _ = app.emit("profile-changed", ());
+    info!("Imported {} profile(s) from {}", names.len(), dir.display());
+    Ok(names)
 }
 
+/// Export a profile as an autorandr profile directory with its `config` and
+/// `setup` files. Copied to `~/.config/autorandr/`, autorandr applies it when
+/// these monitors are connected. Returns the path of the written directory.
+#[cfg(target_os = "linux")]
 #[tauri::command]
-async fn load_profile(app: AppHandle, name: String) -> Result<(), String> {
-    do_load_profile(&app, &name)
+async fn export_autorandr_profile(name: String) -> Result<String, String> {
+    let settings = profile::load_linux_profile(&name)?;
+    let (config, setup) = display::export_autorandr_profile(&settings);
+
+    let dir = profile::get_export_dir(&name)?.join("autorandr");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| i18n::tr_with("error.createExportDir", &[("error", &e.to_string())]))?;
+    std::fs::write(dir.join("config"), config)
+        .map_err(|e| i18n::tr_with("error.writeFile", &[("path", &dir.join("config").display().to_string()), ("error", &e.to_string())]))?;
+    std::fs::write(dir.join("setup"), setup)
+        .map_err(|e| i18n::tr_with("error.writeFile", &[("path", &dir.join("setup").display().to_string()), ("error", &e.to_string())]))?;
+
+    info!("Exported profile '{}' to {}", name, dir.display());
+    Ok(dir.display().to_string())
 }
 
-/// Core profile loading logic - shared between command and tray menu
-fn do_load_profile(app: &AppHandle, name: &str) -> Result<(), String> {
-    info!("Loading profile: {}", name);
+/// Import autorandr profiles under their autorandr names, numbered when a
+/// profile has that name already. `dir` is either one profile directory or a
+/// directory of them; `~/.config/autorandr/` when not given. Returns the names
+/// of the saved profiles.
+#[cfg(target_os = "linux")]
+#[tauri::command]
+async fn import_autorandr_profiles(app: AppHandle, dir: Option<String>) -> Result<Vec<String>, String> {
+    kiosk::ensure_unlocked()?;
+
+    let dir = match dir {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => dirs::config_dir()
+            .ok_or_else(|| i18n::tr("error.noConfigDir"))?
+            .join("autorandr"),
+    };
 
-    #[cfg(windows)]
-    {
-        // Load profile from disk
-        let profile = storage_load(name)?;
+    let profile_dirs: Vec<std::path::PathBuf> = if dir.join("config").is_file() {
+        vec![dir.clone()]
+    } else {
+        std::fs::read_dir(&dir)
+            .map_err(|e| i18n::tr_with("error.readFile", &[("path", &dir.display().to_string()), ("error", &e.to_string())]))?
+            .flatten()
+            .map(|e| e.path())
+            // Hook scripts live next to the profiles; hidden directories aren't profiles
+            .filter(|p| p.join("config").is_file() && !p.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')))
+            .collect()
+    };
 
-        // Convert to CCD settings
-        let (mut settings, additional_info) = profile_to_settings(&profile);
+    let mut names = Vec::new();
+    for profile_dir in profile_dirs {
+        let Some(name) = profile_dir.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let config = std::fs::read_to_string(profile_dir.join("config"))
+            .map_err(|e| i18n::tr_with("error.importedProfile", &[("tool", "autorandr"), ("name", &name), ("error", &e.to_string())]))?;
+        let setup = std::fs::read_to_string(profile_dir.join("setup")).unwrap_or_default();
+
+        let settings = display::import_autorandr_profile(&config, &setup)
+            .map_err(|e| i18n::tr_with("error.importedProfile", &[("tool", "autorandr"), ("name", &name), ("error", &e)]))?;
+        names.push(profile::save_imported_linux_profile(&name, &settings)?);
+    }
 
-        // Match adapter IDs to current system
-        match_adapter_ids(&mut settings, &additional_info)?;
+    if names.is_empty() {
+        return Err(i18n::tr_with("error.noAutorandrProfiles", &[("path", &dir.display().to_string())]));
+    }
 
-        // Apply display settings (resolution, position, etc.)
-        set_display_settings(&mut settings)?;
+    let _ = refresh_tray_menu(&app);
+    let _ = app.emit("profile-changed", ());
+    info!("Imported {} profile(s) from {}", names.len(), dir.display());
+    Ok(names)
+}
 
-        // Apply DPI scaling for each source
-        // We need to match the saved source IDs to the current system's source IDs
-        // After match_adapter_ids, the settings have updated adapter IDs
-        for dpi_info in &profile.dpi_scale_info {
-            // Find the path with matching source ID in the updated settings
-            if let Some(path) = settings.path_info_array.iter().find(|p| p.source_info.id == dpi_info.source_id) {
-                let adapter_id = LUID {
-                    low_part: path.source_info.adapter_id.low_part,
-                    high_part: path.source_info.adapter_id.high_part,
-                };
-                if let Err(e) = set_dpi_scaling(adapter_id, dpi_info.source_id, dpi_info.dpi_scale) {
-                    log::warn!("Failed to set DPI scaling for source {}: {}", dpi_info.source_id, e);
-                } else {
-                    info!("Set DPI scaling to {}% for source {}", dpi_info.dpi_scale, dpi_info.source_id);
-                }
-            }
-        }
+/// Import the monitor profiles of a DisplayFusion settings export (`.reg`) as
+/// profiles named "DisplayFusion: <name>", numbered when a profile has that
+/// name already. Each layout is mapped onto the
+/// active displays, so it only covers monitors that are connected now.
+/// Returns the names of the saved profiles.
+#[cfg(windows)]
+#[tauri::command]
+async fn import_displayfusion_profiles(app: AppHandle, path: String) -> Result<Vec<String>, String> {
+    kiosk::ensure_unlocked()?;
+
+    let bytes = std::fs::read(&path)
+        .map_err(|e| i18n::tr_with("error.readFile", &[("path", &path), ("error", &e.to_string())]))?;
+    let layouts = profile::parse_displayfusion_export(&profile::decode_reg_file(&bytes))?;
+
+    let mut names = Vec::new();
+    for layout in layouts {
+        let mut settings = display::settings_from_geometry(&layout.monitors)
+            .map_err(|e| i18n::tr_with("error.importedProfile", &[("tool", "DisplayFusion"), ("name", &layout.name), ("error", &e)]))?;
+        let mut additional_info = get_additional_info_for_modes(&settings.mode_info_array);
+        fill_gdi_device_names(&settings, &mut additional_info);
+        exclude_specialized_targets(&mut settings, &mut additional_info);
+
+        let name = format!("DisplayFusion: {}", layout.name.trim());
+        names.push(profile::save_imported_profile(&name, &settings_to_profile(&settings, &additional_info))?);
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        // Load and apply Linux profile
-        let mut settings = profile::load_linux_profile(name)?;
+    let _ = refresh_tray_menu(&app);
+    let _ = app.emit("profile-changed", ());
+    info!("Imported {} profile(s) from {}", names.len(), path);
+    Ok(names)
+}
 
-        // Match output names to current system
-        let additional_info = get_additional_info_for_modes(&settings.outputs);
-        match_adapter_ids(&mut settings, &additional_info)?;
+/// Export a profile as a standalone `apply.ps1` that applies it with the
+/// command-line MonitorSwitcher.exe, for deployment and imaging workflows.
+/// Returns the path of the written script.
+#[cfg(windows)]
+#[tauri::command]
+async fn export_powershell_script(name: String) -> Result<String, String> {
+    let profile = storage_load(&name)?;
+    let script = profile::build_powershell_script(&name, &profile)?;
 
-        // Apply display settings
-        set_display_settings(&mut settings)?;
-    }
+    let path = profile::get_export_dir(&name)?.join("apply.ps1");
+    std::fs::write(&path, script)
+        .map_err(|e| i18n::tr_with("error.writeFile", &[("path", &path.display().to_string()), ("error", &e.to_string())]))?;
 
-    // Emit event so frontend can refresh active profile state
-    let _ = app.emit("profile-changed", ());
+    info!("Exported profile '{}' to {}", name, path.display());
+    Ok(path.display().to_string())
+}
 
-    info!("Profile '{}' loaded successfully", name);
+#[cfg(windows)]
+#[tauri::command]
+async fn set_profile_apply_flags(name: String, flags: Option<display::ApplyFlags>) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    storage_set_apply_flags(&name, flags)
+}
+
+/// Whether applying a profile saves its configuration to the Windows display
+/// database, from its own apply flags or else the global ones.
+#[cfg(windows)]
+#[tauri::command]
+async fn get_profile_persistence(name: String) -> Result<bool, String> {
+    let profile = storage_load(&name)?;
+    Ok(effective_apply_flags(&profile).save_to_database)
+}
+
+/// Set whether applying a profile saves its configuration to the Windows
+/// display database. The profile's other apply flags stay as they are.
+#[cfg(windows)]
+#[tauri::command]
+async fn set_profile_persistence(name: String, persist: bool) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    let profile = storage_load(&name)?;
+    let flags = display::ApplyFlags {
+        save_to_database: persist,
+        ..effective_apply_flags(&profile)
+    };
+    storage_set_apply_flags(&name, Some(flags))
+}
+
+#[cfg(windows)]
+fn effective_apply_flags(profile: &profile::DisplayProfile) -> display::ApplyFlags {
+    profile
+        .apply_flags
+        .or_else(|| load_settings().ok().and_then(|s| s.apply_flags))
+        .unwrap_or_default()
+}
+
+/// Maintenance for when the settings Windows saved for the connected monitors
+/// fight with profiles: re-apply the current configuration without saving it
+/// to the display database, and with `rewrite`, save it over the stale entry.
+#[cfg(windows)]
+#[tauri::command]
+async fn reset_display_database(rewrite: bool) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    change_layout(|| display::refresh_display_database(rewrite))?;
+    info!("Re-applied the current configuration (database entry rewritten: {})", rewrite);
     Ok(())
 }
 
+/// Whether a profile keeps the screen on and the system awake while applied.
 #[tauri::command]
-async fn delete_profile(app: AppHandle, name: String) -> Result<(), String> {
-    do_delete_profile(&app, &name)
+async fn get_profile_prevent_sleep(name: String) -> Result<bool, String> {
+    profile::get_profile_prevent_sleep(&name)
 }
 
-/// Core profile deletion logic - shared between command and tray menu
-fn do_delete_profile(app: &AppHandle, name: &str) -> Result<(), String> {
-    info!("Deleting profile: {}", name);
-    storage_delete(name)?;
+/// Set whether a profile keeps the screen on and the system awake while it's
+/// the profile applied last. Takes effect the next time it's applied.
+#[tauri::command]
+async fn set_profile_prevent_sleep(name: String, on: bool) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    profile::set_profile_prevent_sleep(&name, on)
+}
 
-    // Refresh tray menu to remove deleted profile
-    let _ = refresh_tray_menu(app);
+/// Set whether a profile opens the wireless display flyout when one of its
+/// wireless (Miracast) displays isn't connected.
+#[cfg(windows)]
+#[tauri::command]
+async fn set_profile_connect_wireless_display(name: String, on: bool) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    profile::set_profile_connect_wireless_display(&name, on)
+}
 
-    // Emit event so frontend can refresh
-    let _ = app.emit("profile-changed", ());
+/// Name of the installed virtual display driver, if any.
+#[tauri::command]
+async fn get_virtual_display_driver() -> Option<String> {
+    display::virtual_display_driver().map(str::to_string)
+}
 
-    info!("Profile '{}' deleted successfully", name);
-    Ok(())
+/// Get the virtual displays a profile has the driver show. None if it leaves them alone.
+#[tauri::command]
+async fn get_profile_virtual_displays(name: String) -> Result<Option<Vec<VirtualDisplay>>, String> {
+    profile::get_profile_virtual_displays(&name)
 }
 
+/// Set or clear the virtual displays a profile has the driver show. An empty
+/// list removes the virtual displays when the profile is applied.
 #[tauri::command]
-async fn profile_exists(name: String) -> Result<bool, String> {
-    storage_exists(&name)
+async fn set_profile_virtual_displays(name: String, displays: Option<Vec<VirtualDisplay>>) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    if displays.as_ref().is_some_and(|d| d.iter().any(|d| d.width == 0 || d.height == 0 || d.refresh_rate == 0)) {
+        return Err(i18n::tr("error.virtualDisplayMode"));
+    }
+    profile::set_profile_virtual_displays(&name, displays)
 }
 
+/// Get the current Windows color filter, for storing it in a profile.
+#[cfg(windows)]
 #[tauri::command]
-async fn turn_off_monitors() -> Result<(), String> {
-    info!("Turning off monitors");
-    platform_turn_off()
+async fn get_color_filter() -> Option<ColorFilter> {
+    display::get_color_filter()
 }
 
+/// Set or clear the color filter a profile switches to.
+#[cfg(windows)]
 #[tauri::command]
-async fn open_save_dialog(app: AppHandle) -> Result<(), String> {
-    open_save_popup(&app);
-    Ok(())
+async fn set_profile_color_filter(name: String, filter: Option<ColorFilter>) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    profile::set_profile_color_filter(&name, filter)
 }
 
+/// Whether Windows Night Light is on right now.
+#[cfg(windows)]
 #[tauri::command]
-async fn get_current_monitors() -> Result<Vec<MonitorDetails>, String> {
-    current_monitors()
+async fn get_night_light() -> Option<bool> {
+    display::get_night_light()
+}
+
+/// Set or clear the Night Light state a profile switches to.
+#[cfg(windows)]
+#[tauri::command]
+async fn set_profile_night_light(name: String, on: Option<bool>) -> Result<(), String> {
+    kiosk::ensure_unlocked()?;
+    profile::set_profile_night_light(&name, on)
 }
 
 // ============================================================================
@@ -226,7 +1718,8 @@ fn calc_popup_height(profile_count: usize) -> f64 {
     (base + header + profile_count as f64 * per_profile).min(350.0)
 }
 
-fn open_save_popup(app: &AppHandle<Wry>) {
+/// Open the "save profile" popup, with `name` filled in if given.
+fn open_save_popup(app: &AppHandle<Wry>, name: Option<&str>) {
     // If popup already exists, just focus it
     if let Some(window) = app.get_webview_window("save-popup") {
         let _ = window.set_focus();
@@ -243,7 +1736,11 @@ fn open_save_popup(app: &AppHandle<Wry>) {
         "save-popup",
         WebviewUrl::App("popup.html".into()),
     )
-    .title("Save Profile")
+    .title(i18n::tr("window.saveProfile"))
+    .initialization_script(format!(
+        "window.prefillProfileName = {};",
+        serde_json::to_string(&name).unwrap_or_else(|_| "null".to_string())
+    ))
     .inner_size(300.0, popup_height)
     .min_inner_size(280.0, 180.0)
     .resizable(true)
@@ -272,6 +1769,9 @@ fn open_save_popup(app: &AppHandle<Wry>) {
 // System Tray
 // ============================================================================
 
+/// Brightness levels offered in the tray.
+const BRIGHTNESS_STEPS: [u8; 5] = [0, 25, 50, 75, 100];
+
 /// Load a menu icon from the icons/menu directory
 fn load_menu_icon(app: &AppHandle<Wry>, name: &str) -> Option<Image<'static>> {
     let resource_path: PathBuf = app
@@ -285,8 +1785,36 @@ fn load_menu_icon(app: &AppHandle<Wry>, name: &str) -> Option<Image<'static>> {
     Image::from_path(&resource_path).ok()
 }
 
+/// Tray line describing one monitor of a profile, e.g. "DELL U2720Q: 3840×2160 at 1920, 0".
+fn tray_monitor_label(monitor: &MonitorDetails) -> String {
+    i18n::tr_with(
+        "tray.monitorDetails",
+        &[
+            ("name", &monitor.name),
+            ("width", &monitor.width.to_string()),
+            ("height", &monitor.height.to_string()),
+            ("x", &monitor.position_x.to_string()),
+            ("y", &monitor.position_y.to_string()),
+        ],
+    )
+}
+
 fn build_tray_menu(app: &AppHandle<Wry>) -> Result<Menu<Wry>, tauri::Error> {
-    let profiles = storage_list().unwrap_or_default();
+    // Menu IDs carry profile IDs, so they stay valid while profiles are renamed
+    let mut profiles = profile::profiles_for_host(profile::list_profile_entries().unwrap_or_default(), &profile::host_name());
+    // Profiles other machines saved to a synced folder can't be applied here
+    profiles.retain(|entry| !profile::is_foreign_profile(&entry.id));
+    let last_applied = state::last_applied().and_then(|last| last.profile_id);
+    let mut chains: Vec<String> = load_settings()
+        .map(|s| s.chains.into_iter().map(|chain| chain.name).collect())
+        .unwrap_or_default();
+
+    // Kiosk mode offers the whitelisted profiles and nothing that changes anything
+    let kiosk = kiosk::policy();
+    if let Some(policy) = &kiosk {
+        profiles.retain(|entry| kiosk::allows(policy, entry));
+        chains.clear();
+    }
 
     // Load icons
     let monitor_icon = load_menu_icon(app, "monitor");
@@ -299,16 +1827,47 @@ fn build_tray_menu(app: &AppHandle<Wry>) -> Result<Menu<Wry>, tauri::Error> {
 
     // Build Load Profile submenu
     let load_submenu = {
-        let submenu = Submenu::with_id_and_items(app, "load_submenu", "Load Profile", true, &[])?;
+        let submenu = Submenu::with_id_and_items(app, "load_submenu", i18n::tr("tray.loadProfile"), true, &[])?;
         submenu.set_icon(monitor_icon.clone())?;
         if profiles.is_empty() {
-            submenu.append(&MenuItem::with_id(app, "no_profiles", "(No profiles)", false, None::<&str>)?)?;
+            submenu.append(&MenuItem::with_id(app, "no_profiles", i18n::tr("tray.noProfiles"), false, None::<&str>)?)?;
         } else {
+            // One submenu per profile listing its monitors, to check the layout before loading it
             for profile in &profiles {
+                let profile_submenu = Submenu::with_id_and_items(
+                    app,
+                    format!("profile_{}", profile.id),
+                    if last_applied.as_ref() == Some(&profile.id) {
+                        format!("✓ {}", tray_profile_label(&profile.name))
+                    } else {
+                        tray_profile_label(&profile.name)
+                    },
+                    true,
+                    &[],
+                )?;
+                profile_submenu.set_icon(tray_profile_icon(&profile.id).or_else(|| monitor_icon.clone()))?;
+                profile_submenu.append(&IconMenuItem::with_id(
+                    app,
+                    format!("load_{}", profile.id),
+                    i18n::tr("tray.load"),
+                    true,
+                    monitor_icon.clone(),
+                    None::<&str>,
+                )?)?;
+                profile_submenu.append(&PredefinedMenuItem::separator(app)?)?;
+                for monitor in storage_get_details(&profile.id).unwrap_or_default() {
+                    profile_submenu.append(&MenuItem::new(app, tray_monitor_label(&monitor), false, None::<&str>)?)?;
+                }
+                submenu.append(&profile_submenu)?;
+            }
+        }
+        if !chains.is_empty() {
+            submenu.append(&PredefinedMenuItem::separator(app)?)?;
+            for chain in &chains {
                 submenu.append(&IconMenuItem::with_id(
                     app,
-                    format!("load_{}", profile),
-                    profile,
+                    format!("chain_{}", chain),
+                    i18n::tr_with("tray.chain", &[("name", chain)]),
                     true,
                     monitor_icon.clone(),
                     None::<&str>,
@@ -318,18 +1877,54 @@ fn build_tray_menu(app: &AppHandle<Wry>) -> Result<Menu<Wry>, tauri::Error> {
         submenu
     };
 
+    // Build Templates submenu
+    let templates_submenu = {
+        let submenu = Submenu::with_id_and_items(app, "templates_submenu", i18n::tr("tray.templates"), true, &[])?;
+        submenu.set_icon(monitor_icon.clone())?;
+        for template in Template::ALL {
+            submenu.append(&IconMenuItem::with_id(
+                app,
+                format!("template_{}", template.id()),
+                i18n::tr(&format!("template.{}", template.id())),
+                true,
+                monitor_icon.clone(),
+                None::<&str>,
+            )?)?;
+        }
+        submenu
+    };
+
+    // Build Brightness submenu
+    let brightness_submenu = {
+        let submenu = Submenu::with_id_and_items(app, "brightness_submenu", i18n::tr("tray.brightness"), true, &[])?;
+        submenu.set_icon(monitor_icon.clone())?;
+        for percent in BRIGHTNESS_STEPS {
+            submenu.append(&MenuItem::with_id(
+                app,
+                format!("brightness_{}", percent),
+                format!("{}%", percent),
+                true,
+                None::<&str>,
+            )?)?;
+        }
+        submenu
+    };
+
     // Build Save Profile submenu
     let save_submenu = {
-        let submenu = Submenu::with_id_and_items(app, "save_submenu", "Save Profile", true, &[])?;
+        let submenu = Submenu::with_id_and_items(app, "save_submenu", i18n::tr("tray.saveProfile"), true, &[])?;
         submenu.set_icon(save_icon.clone())?;
-        submenu.append(&IconMenuItem::with_id(app, "save_new", "New Profile...", true, save_icon.clone(), None::<&str>)?)?;
+        submenu.append(&IconMenuItem::with_id(app, "save_new", i18n::tr("tray.newProfile"), true, save_icon.clone(), None::<&str>)?)?;
+        if !profiles.is_empty() {
+            submenu.append(&IconMenuItem::with_id(app, "update_matching", i18n::tr("tray.updateMatching"), true, save_icon.clone(), None::<&str>)?)?;
+        }
         if !profiles.is_empty() {
             submenu.append(&PredefinedMenuItem::separator(app)?)?;
             for profile in &profiles {
                 submenu.append(&IconMenuItem::with_id(
                     app,
-                    format!("save_{}", profile),
-                    profile,
+                    format!("save_{}", profile.id),
+                    &profile.name,
                     true,
                     monitor_icon.clone(),
                     None::<&str>,
@@ -341,16 +1936,16 @@ fn build_tray_menu(app: &AppHandle<Wry>) -> Result<Menu<Wry>, tauri::Error> {
 
     // Build Delete Profile submenu
     let delete_submenu = {
-        let submenu = Submenu::with_id_and_items(app, "delete_submenu", "Delete Profile", !profiles.is_empty(), &[])?;
+        let submenu = Submenu::with_id_and_items(app, "delete_submenu", i18n::tr("tray.deleteProfile"), !profiles.is_empty(), &[])?;
         submenu.set_icon(delete_icon.clone())?;
         if profiles.is_empty() {
-            submenu.append(&MenuItem::with_id(app, "no_profiles_delete", "(No profiles)", false, None::<&str>)?)?;
+            submenu.append(&MenuItem::with_id(app, "no_profiles_delete", i18n::tr("tray.noProfiles"), false, None::<&str>)?)?;
         } else {
             for profile in &profiles {
                 submenu.append(&IconMenuItem::with_id(
                     app,
-                    format!("delete_{}", profile),
-                    profile,
+                    format!("delete_{}", profile.id),
+                    &profile.name,
                     true,
                     monitor_delete_icon.clone(),
                     None::<&str>,
@@ -363,13 +1958,22 @@ fn build_tray_menu(app: &AppHandle<Wry>) -> Result<Menu<Wry>, tauri::Error> {
     // Build main menu
     let menu = Menu::new(app)?;
     menu.append(&load_submenu)?;
-    menu.append(&save_submenu)?;
-    menu.append(&delete_submenu)?;
-    menu.append(&PredefinedMenuItem::separator(app)?)?;
-    menu.append(&IconMenuItem::with_id(app, "turn_off", "Turn Off All Monitors", true, power_icon, None::<&str>)?)?;
+    if kiosk.is_none() {
+        menu.append(&templates_submenu)?;
+        menu.append(&brightness_submenu)?;
+        menu.append(&save_submenu)?;
+        menu.append(&delete_submenu)?;
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+        // Laptop quick actions, only where there is a built-in panel
+        if let Ok(Some(_)) = display::get_internal_panel_state() {
+            menu.append(&IconMenuItem::with_id(app, "internal_only", i18n::tr("tray.internalOnly"), true, monitor_icon.clone(), None::<&str>)?)?;
+            menu.append(&IconMenuItem::with_id(app, "external_only", i18n::tr("tray.externalOnly"), true, monitor_icon.clone(), None::<&str>)?)?;
+        }
+        menu.append(&IconMenuItem::with_id(app, "turn_off", i18n::tr("tray.turnOff"), true, power_icon, None::<&str>)?)?;
+    }
     menu.append(&PredefinedMenuItem::separator(app)?)?;
-    menu.append(&IconMenuItem::with_id(app, "open_window", "Open Window", true, window_icon, None::<&str>)?)?;
-    menu.append(&IconMenuItem::with_id(app, "quit", "Exit", true, exit_icon, None::<&str>)?)?;
+    menu.append(&IconMenuItem::with_id(app, "open_window", i18n::tr("tray.openWindow"), true, window_icon, None::<&str>)?)?;
+    menu.append(&IconMenuItem::with_id(app, "quit", i18n::tr("tray.exit"), true, exit_icon, None::<&str>)?)?;
 
     Ok(menu)
 }
@@ -378,16 +1982,29 @@ fn setup_tray(app: &AppHandle<Wry>) -> Result<(), Box<dyn std::error::Error>> {
     let menu = build_tray_menu(app)?;
 
     let _tray = TrayIconBuilder::with_id("main")
-        .icon(app.default_window_icon().cloned().unwrap())
+        .icon(tray_icon(app).unwrap_or_else(|| app.default_window_icon().cloned().unwrap()))
         .menu(&menu)
-        .tooltip("Monitor Switcher")
+        .tooltip(tray_tooltip())
         .on_menu_event(move |app, event| {
             let id = event.id().as_ref();
 
             if let Some(name) = id.strip_prefix("load_") {
-                if let Err(e) = do_load_profile(app, name) {
-                    error!("Failed to load profile '{}': {}", name, e);
-                }
+                let name = name.to_string();
+                spawn_apply(app, move |app| {
+                    if let Err(e) = do_load_profile(app, &name, "tray") {
+                        error!("Failed to load profile '{}': {}", name, e);
+                    }
+                });
+            } else if let Some(name) = id.strip_prefix("chain_") {
+                spawn_chain(app, name);
+            } else if let Some(template) = id.strip_prefix("template_").and_then(Template::from_id) {
+                spawn_apply(app, move |app| {
+                    if let Err(e) = do_apply_template(app, template, "tray") {
+                        error!("Failed to apply template '{}': {}", template.id(), e);
+                    }
+                });
+            } else if let Some(percent) = id.strip_prefix("brightness_").and_then(|p| p.parse().ok()) {
+                spawn_set_brightness(percent);
             } else if let Some(name) = id.strip_prefix("save_").filter(|n| *n != "new") {
                 let app_clone = app.clone();
                 let name = name.to_string();
@@ -402,37 +2019,40 @@ fn setup_tray(app: &AppHandle<Wry>) -> Result<(), Box<dyn std::error::Error>> {
                 }
             } else {
                 match id {
-                    "save_new" => open_save_popup(app),
-                    "turn_off" => {
-                        tauri::async_runtime::spawn(async {
-                            if let Err(e) = turn_off_monitors().await {
-                                error!("Failed to turn off monitors: {}", e);
-                            }
-                        });
-                    }
-                    "open_window" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
+                    "save_new" => run_tray_action(app, &TrayAction::SaveNewProfile),
+                    "turn_off" => run_tray_action(app, &TrayAction::TurnOffMonitors),
+                    "update_matching" => run_tray_action(app, &TrayAction::UpdateMatchingProfile),
+                    "internal_only" => run_tray_action(app, &TrayAction::InternalDisplayOnly),
+                    "external_only" => run_tray_action(app, &TrayAction::ExternalDisplaysOnly),
+                    "open_window" => run_tray_action(app, &TrayAction::OpenWindow),
                     "quit" => app.exit(0),
                     _ => {}
                 }
             }
         })
         .on_tray_icon_event(|tray, event| {
-            if let TrayIconEvent::Click {
-                button: MouseButton::Left,
-                button_state: MouseButtonState::Up,
-                ..
-            } = event
-            {
-                let app = tray.app_handle();
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.set_focus();
+            let app = tray.app_handle();
+            match event {
+                TrayIconEvent::Click {
+                    button,
+                    button_state: MouseButtonState::Up,
+                    ..
+                } => match button {
+                    MouseButton::Left => on_tray_left_click(app),
+                    MouseButton::Middle => {
+                        let action = load_settings().unwrap_or_default().middle_click_action;
+                        run_tray_action(app, &action);
+                    }
+                    _ => {}
+                },
+                TrayIconEvent::DoubleClick { button: MouseButton::Left, .. } => {
+                    // Cancels the pending single click and skips the release that follows
+                    TRAY_CLICKS.fetch_add(1, Ordering::SeqCst);
+                    TRAY_DOUBLE_CLICKED.store(true, Ordering::SeqCst);
+                    let action = tray_double_click_action();
+                    run_tray_action(app, &action);
                 }
+                _ => {}
             }
         })
         .build(app)?;
@@ -440,6 +2060,209 @@ fn setup_tray(app: &AppHandle<Wry>) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Left clicks on the tray icon so far, for telling a pending single click
+/// that a second click came in.
+static TRAY_CLICKS: AtomicU64 = AtomicU64::new(0);
+
+/// Set by a double click, whose button release also arrives as a click.
+static TRAY_DOUBLE_CLICKED: AtomicBool = AtomicBool::new(false);
+
+/// Open the window on a left click. With a double-click action (Windows only
+/// reports double clicks), wait out the double-click time first, so a double
+/// click doesn't open the window as well.
+fn on_tray_left_click(app: &AppHandle<Wry>) {
+    if TRAY_DOUBLE_CLICKED.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    if !cfg!(windows) || tray_double_click_action() == TrayAction::None {
+        run_tray_action(app, &TrayAction::OpenWindow);
+        return;
+    }
+
+    let click = TRAY_CLICKS.fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+    thread::spawn(move || {
+        thread::sleep(double_click_time());
+        if TRAY_CLICKS.load(Ordering::SeqCst) == click {
+            run_tray_action(&app, &TrayAction::OpenWindow);
+        }
+    });
+}
+
+fn tray_double_click_action() -> TrayAction {
+    load_settings()
+        .map(|s| s.double_click_action)
+        .unwrap_or(TrayAction::LoadLastProfile)
+}
+
+/// Longest time between the clicks of a double click.
+#[cfg(windows)]
+fn double_click_time() -> Duration {
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::GetDoubleClickTime;
+    Duration::from_millis(unsafe { GetDoubleClickTime() } as u64)
+}
+
+#[cfg(not(windows))]
+fn double_click_time() -> Duration {
+    Duration::from_millis(500)
+}
+
+/// Tray label for a profile, e.g. "Office — 3× (2×1440p + 1×4K vertical)".
+fn tray_profile_label(name: &str) -> String {
+    match get_profile_summary(name) {
+        Ok(summary) if !summary.is_empty() => format!("{} — {}", name, summary),
+        _ => name.to_string(),
+    }
+}
+
+/// Tray icon for a profile: a thumbnail of its arrangement.
+fn tray_profile_icon(name: &str) -> Option<Image<'static>> {
+    let monitors = profile::get_profile_layout(name).ok()?;
+    let image = thumbnail::render(&monitors, thumbnail::THUMBNAIL_SIZE);
+    Some(Image::new_owned(image.rgba, image.width, image.height))
+}
+
+/// App icon with the number of active displays on it, so a dock that didn't
+/// bring up every screen shows at a glance. None if either isn't available.
+fn tray_icon(app: &AppHandle<Wry>) -> Option<Image<'static>> {
+    let icon = app.default_window_icon()?;
+    let count = current_monitors().ok()?.len() as u32;
+    let image = thumbnail::with_badge(icon.rgba(), icon.width(), icon.height(), count);
+    Some(Image::new_owned(image.rgba, image.width, image.height))
+}
+
+/// Tray tooltip, naming the last applied profile.
+fn tray_tooltip() -> String {
+    match state::last_applied() {
+        Some(last) => i18n::tr_with("tray.tooltipApplied", &[("profile", &last.profile)]),
+        None => i18n::tr("tray.tooltip"),
+    }
+}
+
+/// Run a tray quick action.
+fn run_tray_action(app: &AppHandle<Wry>, action: &TrayAction) {
+    if !kiosk::allows_action(action) {
+        log::warn!("Quick action {:?} is not available in kiosk mode", action);
+        return;
+    }
+
+    match action {
+        TrayAction::None => {}
+        TrayAction::TurnOffMonitors => {
+            tauri::async_runtime::spawn(async {
+                if let Err(e) = turn_off_monitors().await {
+                    error!("Failed to turn off monitors: {}", e);
+                }
+            });
+        }
+        TrayAction::TurnOffAndLock => {
+            tauri::async_runtime::spawn(async {
+                // Lock first; the lock screen would otherwise wake the monitors again
+                if let Err(e) = display::lock_session() {
+                    error!("Failed to lock the session: {}", e);
+                }
+                if let Err(e) = turn_off_monitors().await {
+                    error!("Failed to turn off monitors: {}", e);
+                }
+            });
+        }
+        TrayAction::OpenWindow => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+        TrayAction::SaveNewProfile => open_save_popup(app, None),
+        TrayAction::IdentifyMonitors => {
+            if let Err(e) = identify::identify_monitors(app) {
+                error!("Failed to identify monitors: {}", e);
+            }
+        }
+        TrayAction::LoadProfile(name) => {
+            let name = name.clone();
+            spawn_apply(app, move |app| {
+                if let Err(e) = do_load_profile(app, &name, "tray click") {
+                    error!("Failed to load profile '{}': {}", name, e);
+                }
+            });
+        }
+        TrayAction::LoadLastProfile => match state::last_applied() {
+            Some(last) => {
+                // The ID survives renames; the name covers state saved before IDs
+                let reference = last.profile_id.unwrap_or(last.profile);
+                spawn_apply(app, move |app| {
+                    if let Err(e) = do_load_profile(app, &reference, "tray click") {
+                        error!("Failed to load profile '{}': {}", reference, e);
+                    }
+                });
+            }
+            None => log::warn!("No profile has been applied yet"),
+        },
+        TrayAction::RunChain(name) => spawn_chain(app, name),
+        TrayAction::ToggleProfiles(a, b) => {
+            let (a, b) = (a.clone(), b.clone());
+            spawn_apply(app, move |app| {
+                if let Err(e) = do_toggle_profiles(app, &a, &b, "tray click") {
+                    error!("Failed to toggle between '{}' and '{}': {}", a, b, e);
+                }
+            });
+        }
+        TrayAction::SetBrightness(percent) => spawn_set_brightness(*percent),
+        TrayAction::InternalDisplayOnly => spawn_apply(app, |app| {
+            if let Err(e) = do_apply_template(app, Template::PcOnly, "tray click") {
+                error!("Failed to switch to the internal display only: {}", e);
+            }
+        }),
+        TrayAction::ExternalDisplaysOnly => spawn_apply(app, |app| {
+            if let Err(e) = do_apply_template(app, Template::ExternalOnly, "tray click") {
+                error!("Failed to switch to the external displays only: {}", e);
+            }
+        }),
+        TrayAction::RotateClockwise | TrayAction::RotateCounterclockwise => {
+            let quarter_turns = if *action == TrayAction::RotateClockwise { 1 } else { -1 };
+            spawn_apply(app, move |app| {
+                if let Err(e) = do_rotate_monitor(app, None, quarter_turns) {
+                    error!("Failed to rotate the monitor: {}", e);
+                }
+            });
+        }
+        TrayAction::UpdateMatchingProfile => {
+            // The save popup with the profile filled in asks before overwriting it
+            match profile::find_closest_profile() {
+                Ok(closest) => open_save_popup(app, closest.map(|entry| entry.name).as_deref()),
+                Err(e) => error!("Failed to find the matching profile: {}", e),
+            }
+        }
+    }
+}
+
+/// Apply a layout in the background, as the automation dispatcher does. Waits
+/// for monitors and for the layout lock would block the tray or hotkey thread.
+fn spawn_apply(app: &AppHandle<Wry>, apply: impl FnOnce(&AppHandle<Wry>) + Send + 'static) {
+    let app = app.clone();
+    thread::spawn(move || apply(&app));
+}
+
+/// Set the brightness in the background; DDC/CI takes a while per monitor.
+fn spawn_set_brightness(percent: u8) {
+    thread::spawn(move || {
+        if let Err(e) = display::set_all_brightness(percent) {
+            error!("Failed to set the brightness to {}%: {}", percent, e);
+        }
+    });
+}
+
+/// Run a profile chain in the background; its delays would block the caller.
+fn spawn_chain(app: &AppHandle<Wry>, name: &str) {
+    let app = app.clone();
+    let name = name.to_string();
+    thread::spawn(move || {
+        if let Err(e) = chain::run_chain(&app, &name) {
+            error!("{}", e);
+        }
+    });
+}
+
 fn refresh_tray_menu(app: &AppHandle<Wry>) -> Result<(), Box<dyn std::error::Error>> {
     // Rebuild the menu with updated profiles
     let menu = build_tray_menu(app)?;
@@ -447,6 +2270,10 @@ fn refresh_tray_menu(app: &AppHandle<Wry>) -> Result<(), Box<dyn std::error::Err
     // Get the tray icon and update its menu
     if let Some(tray) = app.tray_by_id("main") {
         tray.set_menu(Some(menu))?;
+        tray.set_tooltip(Some(tray_tooltip()))?;
+        if let Some(icon) = tray_icon(app) {
+            tray.set_icon(Some(icon))?;
+        }
     }
 
     Ok(())
@@ -456,12 +2283,75 @@ fn refresh_tray_menu(app: &AppHandle<Wry>) -> Result<(), Box<dyn std::error::Err
 // App Entry Point
 // ============================================================================
 
+/// Get the fixture path passed as `--simulate <fixture.json>`, if any.
+fn simulate_fixture_arg() -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--simulate" {
+            return args.next().map(PathBuf::from);
+        }
+    }
+    None
+}
+
+/// Log target of this crate's records, as RUST_LOG directives name it.
+const LOG_TARGET: &str = env!("CARGO_CRATE_NAME");
+
+/// Set up logging. RUST_LOG directives filter every module as usual, except
+/// that this crate's records are limited by the global max level alone, so
+/// `set_log_level` takes effect without a restart.
+fn init_logging() {
+    let level = configured_log_level(load_settings().ok().and_then(|s| s.log_level));
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("error"))
+        .filter_module(LOG_TARGET, LevelFilter::Trace)
+        .init();
+    log::set_max_level(level);
+}
+
+/// The saved log level, else the level RUST_LOG gives this crate, else errors
+/// only.
+fn configured_log_level(saved: Option<String>) -> LevelFilter {
+    if let Some(level) = saved.and_then(|level| level.trim().parse().ok()) {
+        return level;
+    }
+
+    let from_env = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("error")).build();
+    [Level::Trace, Level::Debug, Level::Info, Level::Warn, Level::Error]
+        .into_iter()
+        .find(|&level| from_env.enabled(&log::Metadata::builder().level(level).target(LOG_TARGET).build()))
+        .map_or(LevelFilter::Off, |level| level.to_level_filter())
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    env_logger::init();
+    // A relaunch for one privileged operation does only that
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).is_some_and(|arg| arg == elevation::WORKER_ARG) {
+        std::process::exit(elevation::run_worker(&args[2..]));
+    }
+
+    init_logging();
+    i18n::init(load_settings().ok().and_then(|s| s.language).as_deref());
+    #[cfg(windows)]
+    display::set_match_virtual_adapters(load_settings().is_ok_and(|s| s.match_virtual_adapters));
+    profile::migrate_legacy_profiles();
+
+    // Development aid: drive the display backend from a fixture instead of real hardware
+    if let Some(path) = simulate_fixture_arg() {
+        match profile::load_fixture(&path) {
+            Ok((settings, additional_info)) => {
+                display::enable_simulation(settings, additional_info);
+                info!("Simulating displays from fixture {}", path.display());
+            }
+            Err(e) => error!("Failed to load simulation fixture {}: {}", path.display(), e),
+        }
+    }
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .manage(MonitorFeed::default())
+        .manage(ApplyQueue::<ApplyReport>::new(apply_queue::DEBOUNCE))
         .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
             // Focus the main window when another instance is launched
             if let Some(window) = app.get_webview_window("main") {
@@ -475,8 +2365,21 @@ pub fn run() {
                 error!("Failed to setup tray: {}", e);
             }
 
+            // Notice layout changes made outside the app (other tools, the DE, hotplug)
+            // and apply profiles automatically on configured triggers
+            automation::start(app.handle());
+
+            hotkeys::register(app.handle());
+
             // Hide window on close instead of quitting
             let window = app.get_webview_window("main").unwrap();
+
+            // A crash during the last apply may have left a broken layout;
+            // the window offers to restore the one from before
+            if let Some(marker) = apply_marker::take_leftover() {
+                log::warn!("Applying '{}' was interrupted at {}", marker.profile, marker.started_at);
+                let _ = window.show();
+            }
             let window_clone = window.clone();
             window.on_window_event(move |event| {
                 if let tauri::WindowEvent::CloseRequested { api, .. } = event {
@@ -492,11 +2395,102 @@ pub fn run() {
             list_profiles_with_details,
             save_profile,
             load_profile,
+            toggle_profiles,
+            apply_template,
+            get_last_applied,
+            identify_monitors,
+            get_history,
+            get_apply_metrics,
             delete_profile,
+            install_profile_machine_wide,
+            remove_machine_profile,
             profile_exists,
+            rename_profile,
+            list_profile_backups,
+            restore_profile_backup,
             turn_off_monitors,
+            validate_profile,
             open_save_dialog,
+            find_matching_profile,
+            update_matching_profile,
             get_current_monitors,
+            subscribe_monitors,
+            unsubscribe_monitors,
+            snap_layout,
+            get_profile_thumbnail,
+            dump_display_state,
+            get_gpu_info,
+            get_edid_raw,
+            get_monitor_capabilities,
+            get_vcp,
+            set_vcp,
+            set_all_brightness,
+            internal_display_only,
+            external_displays_only,
+            focus_mode,
+            duplicate_at_best_common_mode,
+            troubleshoot_profile,
+            run_chain,
+            get_kiosk_policy,
+            get_settings,
+            update_settings,
+            export_settings,
+            import_settings,
+            export_profile_string,
+            import_profile_string,
+            set_monitor_alias,
+            rotate_monitor,
+            set_rotation_lock,
+            set_log_level,
+            get_interrupted_apply,
+            restore_interrupted_apply,
+            dismiss_interrupted_apply,
+            is_remote_session,
+            #[cfg(windows)]
+            set_profile_apply_flags,
+            #[cfg(windows)]
+            get_profile_persistence,
+            #[cfg(windows)]
+            set_profile_persistence,
+            #[cfg(windows)]
+            reset_display_database,
+            #[cfg(windows)]
+            load_profile_dpi_only,
+            load_profile_refresh_rates,
+            load_profile_positions,
+            #[cfg(windows)]
+            get_color_filter,
+            #[cfg(windows)]
+            set_profile_color_filter,
+            #[cfg(windows)]
+            get_night_light,
+            #[cfg(windows)]
+            set_profile_night_light,
+            get_virtual_display_driver,
+            get_profile_virtual_displays,
+            set_profile_virtual_displays,
+            get_profile_prevent_sleep,
+            set_profile_prevent_sleep,
+            #[cfg(windows)]
+            set_profile_connect_wireless_display,
+            #[cfg(windows)]
+            capture_display_fixture,
+            #[cfg(windows)]
+            export_powershell_script,
+            #[cfg(windows)]
+            import_displayfusion_profiles,
+            #[cfg(target_os = "linux")]
+            export_xrandr_script,
+            #[cfg(target_os = "linux")]
+            export_gnome_monitors,
+            #[cfg(target_os = "linux")]
+            import_gnome_monitors,
+            #[cfg(target_os = "linux")]
+            import_kscreen_configs,
+            #[cfg(target_os = "linux")]
+            export_autorandr_profile,
+            #[cfg(target_os = "linux")]
+            import_autorandr_profiles,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
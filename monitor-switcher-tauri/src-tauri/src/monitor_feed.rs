@@ -0,0 +1,65 @@
+//! Live monitor data for the frontend.
+//!
+//! Windows that call `subscribe_monitors` receive a `monitors-changed` event with
+//! the current monitor details whenever they change, instead of polling.
+//! Single responsibility: track subscribers and push changed monitor data.
+
+use crate::profile::{current_monitors, MonitorDetails};
+use std::sync::{Mutex, MutexGuard};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+/// Subscribed windows and the monitor data they were sent last.
+/// Managed by Tauri; reach it with `app.state::<MonitorFeed>()`.
+#[derive(Default)]
+pub struct MonitorFeed {
+    subscribers: Mutex<Vec<String>>,
+    last: Mutex<Option<Vec<MonitorDetails>>>,
+}
+
+impl MonitorFeed {
+    /// Register a window for updates. Returns the current monitors to start from.
+    pub fn subscribe(&self, label: &str) -> Result<Vec<MonitorDetails>, String> {
+        let mut subscribers = lock(&self.subscribers);
+        if !subscribers.iter().any(|s| s == label) {
+            subscribers.push(label.to_string());
+        }
+
+        let monitors = current_monitors()?;
+        *lock(&self.last) = Some(monitors.clone());
+        Ok(monitors)
+    }
+
+    /// Stop sending updates to a window.
+    pub fn unsubscribe(&self, label: &str) {
+        lock(&self.subscribers).retain(|s| s != label);
+    }
+}
+
+/// Re-read the monitors and push them to all subscribers if they changed.
+pub fn publish(app: &AppHandle<Wry>) {
+    let feed = app.state::<MonitorFeed>();
+    let subscribers = lock(&feed.subscribers).clone();
+    if subscribers.is_empty() {
+        return;
+    }
+
+    let Ok(monitors) = current_monitors() else {
+        return;
+    };
+    {
+        let mut last = lock(&feed.last);
+        if last.as_ref() == Some(&monitors) {
+            return;
+        }
+        *last = Some(monitors.clone());
+    }
+
+    for label in &subscribers {
+        let _ = app.emit_to(label.as_str(), "monitors-changed", &monitors);
+    }
+}
+
+/// Lock a mutex, ignoring poisoning; the data stays consistent either way.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -0,0 +1,100 @@
+//! Presentation mode: keep the screen on while a profile asks for it.
+//!
+//! Profiles such as "Projector" can keep the screen saver and sleep away for
+//! as long as they are the profile applied last. Windows ties the request to
+//! a thread (SetThreadExecutionState), so a thread waits until it's released;
+//! on Linux a `systemd-inhibit` process holds it, ending with the app at the
+//! latest.
+//! Single responsibility: take and release the sleep inhibitor.
+
+use std::sync::Mutex;
+
+/// The inhibitor held, with the profile it's held for.
+static HELD: Mutex<Option<(String, Inhibitor)>> = Mutex::new(None);
+
+/// Keep the screen on and the system awake for `profile`, or let them sleep
+/// again with None.
+pub fn set(profile: Option<&str>) -> Result<(), String> {
+    let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+    if held.as_ref().map(|(name, _)| name.as_str()) == profile {
+        return Ok(());
+    }
+
+    // Dropping the inhibitor releases it
+    if let Some((name, _)) = held.take() {
+        log::info!("No longer keeping the screen on for '{}'", name);
+    }
+    if let Some(profile) = profile {
+        *held = Some((profile.to_string(), Inhibitor::take(profile)?));
+        log::info!("Keeping the screen on while '{}' is applied", profile);
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+struct Inhibitor {
+    /// Dropping it wakes the thread holding the request
+    _release: std::sync::mpsc::Sender<()>,
+}
+
+#[cfg(windows)]
+impl Inhibitor {
+    fn take(_profile: &str) -> Result<Self, String> {
+        use std::sync::mpsc;
+        use windows_sys::Win32::System::Power::{
+            SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED,
+        };
+
+        let (release, released) = mpsc::channel::<()>();
+        let (started_tx, started) = mpsc::channel();
+        std::thread::spawn(move || {
+            let previous = unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED | ES_DISPLAY_REQUIRED) };
+            let _ = started_tx.send(previous != 0);
+            if previous == 0 {
+                return;
+            }
+            // Returns once the sender is dropped
+            let _ = released.recv();
+            unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+        });
+
+        match started.recv() {
+            Ok(true) => Ok(Self { _release: release }),
+            _ => Err("Windows refused to keep the screen on".to_string()),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+struct Inhibitor {
+    child: std::process::Child,
+}
+
+#[cfg(target_os = "linux")]
+impl Inhibitor {
+    fn take(profile: &str) -> Result<Self, String> {
+        use std::process::{Command, Stdio};
+
+        // `tail --pid` ends the inhibitor together with the app if it isn't released
+        let child = Command::new("systemd-inhibit")
+            .arg("--what=idle:sleep")
+            .arg("--who=Monitor Switcher")
+            .arg(format!("--why=Profile '{}' is applied", profile))
+            .arg("--mode=block")
+            .args(["tail", &format!("--pid={}", std::process::id()), "-f", "/dev/null"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to run systemd-inhibit: {}", e))?;
+        Ok(Self { child })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for Inhibitor {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
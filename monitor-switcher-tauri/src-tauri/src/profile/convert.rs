@@ -8,8 +8,9 @@ use crate::display::{
     LUID, DisplayConfigRational, DisplayConfig2DRegion, PointL,
     DisplayConfigPathSourceInfo, DisplayConfigPathTargetInfo,
     DisplayConfigVideoSignalInfo,
-    get_dpi_scaling_info,
+    get_dpi_scaling_info, is_remote_session, is_virtual_adapter, current_fingerprint, primary_index,
 };
+use super::storage::machine_fingerprint;
 use super::types::*;
 
 /// Convert CCD DisplaySettings to a DisplayProfile for JSON serialization.
@@ -20,23 +21,37 @@ pub fn settings_to_profile(
     let path_info_array = settings
         .path_info_array
         .iter()
-        .map(|p| path_info_to_json(p))
+        .map(path_info_to_json)
         .collect();
 
     let mode_info_array = settings
         .mode_info_array
         .iter()
-        .map(|m| mode_info_to_json(m))
+        .map(mode_info_to_json)
         .collect();
 
+    // Entries are keyed by the target they describe, so they survive reordering
     let additional = additional_info
         .iter()
-        .map(|a| ProfileMonitorInfo {
+        .enumerate()
+        .map(|(i, a)| (settings.mode_info_array.get(i).filter(|m| m.info_type == MODE_INFO_TYPE_TARGET), a))
+        .map(|(target, a)| ProfileMonitorInfo {
             manufacture_id: a.manufacture_id,
             product_code_id: a.product_code_id,
             valid: a.valid,
             monitor_device_path: a.monitor_device_path.clone(),
             monitor_friendly_device: a.monitor_friendly_device.clone(),
+            specialized: a.specialized,
+            gdi_device_name: a.gdi_device_name.clone(),
+            adapter_name: a.adapter_name.clone(),
+            connector_instance: a.connector_instance,
+            serial_number: a.serial_number.clone(),
+            virtual_adapter: a.virtual_adapter,
+            adapter_id: target.map(|m| AdapterId {
+                low_part: m.adapter_id.low_part,
+                high_part: m.adapter_id.high_part,
+            }),
+            target_id: target.map(|m| m.id),
         })
         .collect();
 
@@ -55,37 +70,77 @@ pub fn settings_to_profile(
 
     DisplayProfile {
         version: 1,
+        id: None,
+        name: None,
         path_info_array,
         mode_info_array,
         additional_info: additional,
         dpi_scale_info,
+        apply_flags: None,
+        saved_in_remote_session: is_remote_session(),
+        hardware_fingerprint: current_fingerprint().ok().filter(|f| !f.is_empty()),
+        machine_fingerprint: machine_fingerprint(),
+        color_filter: None,
+        night_light: None,
+        internal_panel: None,
+        virtual_displays: None,
+        prevent_sleep: false,
+        connect_wireless_display: false,
+        primary_source: primary_source(settings),
     }
 }
 
+/// Source of the primary display, the active source at the desktop origin.
+fn primary_source(settings: &DisplaySettings) -> Option<PrimarySource> {
+    let sources: Vec<(&DisplayConfigPathInfo, (i32, i32))> = settings
+        .path_info_array
+        .iter()
+        .filter_map(|path| {
+            let mode = settings.mode_info_array.get(path.source_info.mode_info_idx as usize)?;
+            if mode.info_type != MODE_INFO_TYPE_SOURCE {
+                return None;
+            }
+            let position = mode.get_source_mode().position;
+            Some((path, (position.x, position.y)))
+        })
+        .collect();
+    let positions: Vec<(i32, i32)> = sources.iter().map(|(_, position)| *position).collect();
+    let (path, _) = sources.get(primary_index(&positions)?)?;
+
+    Some(PrimarySource {
+        adapter_id: AdapterId {
+            low_part: path.source_info.adapter_id.low_part,
+            high_part: path.source_info.adapter_id.high_part,
+        },
+        id: path.source_info.id,
+    })
+}
+
 /// Convert a DisplayProfile back to CCD DisplaySettings.
+/// The additional info comes back aligned with the modes: each target mode's
+/// entry at its index, defaults for source modes.
 pub fn profile_to_settings(profile: &DisplayProfile) -> (DisplaySettings, Vec<MonitorAdditionalInfo>) {
     let path_info_array = profile
         .path_info_array
         .iter()
-        .map(|p| path_info_from_json(p))
+        .map(path_info_from_json)
         .collect();
 
     let mode_info_array = profile
         .mode_info_array
         .iter()
-        .map(|m| mode_info_from_json(m))
+        .map(mode_info_from_json)
         .collect();
 
     let additional_info = profile
-        .additional_info
+        .mode_info_array
         .iter()
-        .map(|a| MonitorAdditionalInfo {
-            manufacture_id: a.manufacture_id,
-            product_code_id: a.product_code_id,
-            valid: a.valid,
-            monitor_device_path: a.monitor_device_path.clone(),
-            monitor_friendly_device: a.monitor_friendly_device.clone(),
+        .enumerate()
+        .map(|(i, mode)| match mode.info_type {
+            MODE_INFO_TYPE_TARGET => profile.monitor_info_for(&mode.adapter_id, mode.id, i as u32),
+            _ => None,
         })
+        .map(|info| info.map(monitor_info_from_json).unwrap_or_default())
         .collect();
 
     (
@@ -97,6 +152,23 @@ pub fn profile_to_settings(profile: &DisplayProfile) -> (DisplaySettings, Vec<Mo
     )
 }
 
+fn monitor_info_from_json(a: &ProfileMonitorInfo) -> MonitorAdditionalInfo {
+    MonitorAdditionalInfo {
+        manufacture_id: a.manufacture_id,
+        product_code_id: a.product_code_id,
+        valid: a.valid,
+        monitor_device_path: a.monitor_device_path.clone(),
+        monitor_friendly_device: a.monitor_friendly_device.clone(),
+        specialized: a.specialized,
+        gdi_device_name: a.gdi_device_name.clone(),
+        adapter_name: a.adapter_name.clone(),
+        connector_instance: a.connector_instance,
+        serial_number: a.serial_number.clone(),
+        virtual_adapter: a.virtual_adapter || is_virtual_adapter(&a.adapter_name, None),
+        edid_override: None,
+    }
+}
+
 fn path_info_to_json(p: &DisplayConfigPathInfo) -> PathInfo {
     PathInfo {
         source_info: PathSourceInfo {
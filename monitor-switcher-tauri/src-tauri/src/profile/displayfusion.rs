@@ -0,0 +1,318 @@
+//! DisplayFusion monitor configuration import.
+//!
+//! DisplayFusion exports its settings as a registry file (`.reg`). Each
+//! monitor profile is a key holding one subkey per monitor with its desktop
+//! bounds, refresh rate and orientation, next to an identifier naming the
+//! monitor's hardware ID. Monitors may be divided into splits, which are
+//! subkeys with bounds of their own; only whole monitors are imported.
+//! Key and value names differ between DisplayFusion versions, so monitors are
+//! recognized by their values rather than by their place in the tree.
+//! Single responsibility: read monitor layouts from a DisplayFusion export.
+
+use crate::display::MonitorGeometry;
+
+// Value names of a monitor's desktop bounds
+const X: &[&str] = &["X", "Left", "PositionX"];
+const Y: &[&str] = &["Y", "Top", "PositionY"];
+const WIDTH: &[&str] = &["Width", "ResolutionWidth", "PelsWidth"];
+const HEIGHT: &[&str] = &["Height", "ResolutionHeight", "PelsHeight"];
+
+/// A monitor profile from a DisplayFusion export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayFusionProfile {
+    pub name: String,
+    pub monitors: Vec<MonitorGeometry>,
+}
+
+/// Decode an exported `.reg` file, which regedit writes as UTF-16 with a BOM
+/// (REGEDIT4 files are ANSI).
+pub fn decode_reg_file(bytes: &[u8]) -> String {
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => {
+            let units: Vec<u16> = rest.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+            String::from_utf16_lossy(&units)
+        }
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).to_string(),
+        _ => String::from_utf8_lossy(bytes).to_string(),
+    }
+}
+
+/// Read the monitor profiles of a DisplayFusion export. Positions are moved
+/// so the primary monitor is at the origin, where Windows puts it.
+pub fn parse_displayfusion_export(text: &str) -> Result<Vec<DisplayFusionProfile>, String> {
+    let keys = parse_reg(text)?;
+
+    // A monitor is a key with bounds that isn't below another one (that'd be a split)
+    let is_monitor = |key: &RegKey| {
+        [X, Y, WIDTH, HEIGHT].iter().all(|names| key.number(names).is_some())
+            && !key.path.iter().any(|segment| segment.eq_ignore_ascii_case("splits"))
+    };
+    let monitor_keys: Vec<&RegKey> = keys.iter().filter(|k| is_monitor(k)).collect();
+    let monitor_keys: Vec<&RegKey> = monitor_keys
+        .iter()
+        .filter(|k| !monitor_keys.iter().any(|other| other.path.len() < k.path.len() && k.path.starts_with(&other.path)))
+        .copied()
+        .collect();
+
+    let mut profiles: Vec<(Vec<String>, DisplayFusionProfile)> = Vec::new();
+    for key in monitor_keys {
+        // Monitors sit directly under their profile, or in a "Monitors" key below it
+        let mut profile_path = &key.path[..key.path.len() - 1];
+        if profile_path.last().is_some_and(|s| s.eq_ignore_ascii_case("monitors")) {
+            profile_path = &profile_path[..profile_path.len() - 1];
+        }
+
+        let monitor = MonitorGeometry {
+            hardware_id: key.string(&["MonitorID", "DeviceID", "HardwareID", "ID"]).unwrap_or_default(),
+            name: key.string(&["MonitorName", "FriendlyName", "Name"]).unwrap_or_default(),
+            x: key.number(X).unwrap_or(0) as i32,
+            y: key.number(Y).unwrap_or(0) as i32,
+            width: key.number(WIDTH).unwrap_or(0) as u32,
+            height: key.number(HEIGHT).unwrap_or(0) as u32,
+            refresh_rate: key.number(&["Frequency", "RefreshRate", "DisplayFrequency"]).filter(|r| *r > 1).map(|r| r as f64),
+            quarter_turns: match key.number(&["Orientation", "Rotation", "DisplayOrientation"]).unwrap_or(0) {
+                // Degrees, or DEVMODE's DMDO_* (clockwise quarter turns)
+                degrees @ (90 | 180 | 270) => (degrees / 90) as u32,
+                dmdo @ 0..=3 => dmdo as u32,
+                _ => 0,
+            },
+            primary: key.flag(&["Primary", "IsPrimary"]),
+        };
+        if monitor.width == 0 || monitor.height == 0 {
+            continue;
+        }
+
+        match profiles.iter_mut().find(|(path, _)| path == profile_path) {
+            Some((_, profile)) => profile.monitors.push(monitor),
+            None => {
+                let name = keys
+                    .iter()
+                    .find(|k| k.path == profile_path)
+                    .and_then(|k| k.string(&["Name", "ProfileName"]))
+                    .or_else(|| profile_path.last().cloned())
+                    .unwrap_or_else(|| "DisplayFusion".to_string());
+                profiles.push((profile_path.to_vec(), DisplayFusionProfile { name, monitors: vec![monitor] }));
+            }
+        }
+    }
+
+    if profiles.is_empty() {
+        return Err("No monitor profiles found in the DisplayFusion export".to_string());
+    }
+
+    Ok(profiles
+        .into_iter()
+        .map(|(_, mut profile)| {
+            move_primary_to_origin(&mut profile.monitors);
+            profile
+        })
+        .collect())
+}
+
+/// Shift all monitors so the primary one (or else the first) is at 0,0.
+fn move_primary_to_origin(monitors: &mut [MonitorGeometry]) {
+    let Some(primary) = monitors.iter().position(|m| m.primary).or((!monitors.is_empty()).then_some(0)) else {
+        return;
+    };
+    let (dx, dy) = (monitors[primary].x, monitors[primary].y);
+    for (i, monitor) in monitors.iter_mut().enumerate() {
+        monitor.x -= dx;
+        monitor.y -= dy;
+        monitor.primary = i == primary;
+    }
+}
+
+// ============================================================================
+// Registry Files
+// ============================================================================
+
+/// A key of a registry file with its values. Value names match in any case.
+#[derive(Debug, Default)]
+struct RegKey {
+    path: Vec<String>,
+    values: Vec<(String, RegValue)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum RegValue {
+    String(String),
+    Dword(u32),
+    Other,
+}
+
+impl RegKey {
+    fn value(&self, names: &[&str]) -> Option<&RegValue> {
+        names
+            .iter()
+            .find_map(|name| self.values.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v))
+    }
+
+    fn string(&self, names: &[&str]) -> Option<String> {
+        match self.value(names)? {
+            RegValue::String(s) if !s.is_empty() => Some(s.clone()),
+            _ => None,
+        }
+    }
+
+    /// A number stored as a DWORD (signed, for negative positions) or as text.
+    fn number(&self, names: &[&str]) -> Option<i64> {
+        match self.value(names)? {
+            RegValue::Dword(d) => Some(*d as i32 as i64),
+            RegValue::String(s) => s.trim().parse::<f64>().ok().map(|n| n.round() as i64),
+            RegValue::Other => None,
+        }
+    }
+
+    fn flag(&self, names: &[&str]) -> bool {
+        match self.value(names) {
+            Some(RegValue::Dword(d)) => *d != 0,
+            Some(RegValue::String(s)) => matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"),
+            _ => false,
+        }
+    }
+}
+
+/// Parse the keys and values of a registry file.
+fn parse_reg(text: &str) -> Result<Vec<RegKey>, String> {
+    let header = text.lines().next().unwrap_or_default().trim_start_matches('\u{feff}').trim();
+    if header != "Windows Registry Editor Version 5.00" && header != "REGEDIT4" {
+        return Err("Not a registry export".to_string());
+    }
+
+    let mut keys: Vec<RegKey> = Vec::new();
+    let mut lines = text.lines().skip(1);
+    while let Some(line) = lines.next() {
+        // Binary values continue on the next lines after a trailing backslash
+        let mut line = line.trim().to_string();
+        while line.ends_with('\\') && !line.starts_with('[') && !line.ends_with("\\\"") {
+            line.pop();
+            line.push_str(lines.next().unwrap_or_default().trim());
+        }
+
+        if let Some(path) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            keys.push(RegKey {
+                path: path.split('\\').map(str::to_string).collect(),
+                values: Vec::new(),
+            });
+        } else if let (Some(key), Some((name, value))) = (keys.last_mut(), parse_value_line(&line)) {
+            key.values.push((name, value));
+        }
+    }
+    Ok(keys)
+}
+
+/// Parse a `"name"=value` line.
+fn parse_value_line(line: &str) -> Option<(String, RegValue)> {
+    let (name, rest) = if let Some(rest) = line.strip_prefix('@') {
+        (String::new(), rest)
+    } else {
+        let (name, len) = parse_quoted(line)?;
+        (name, &line[len..])
+    };
+    let data = rest.trim_start().strip_prefix('=')?.trim();
+
+    let value = if data.starts_with('"') {
+        RegValue::String(parse_quoted(data)?.0)
+    } else if let Some(hex) = data.strip_prefix("dword:") {
+        RegValue::Dword(u32::from_str_radix(hex.trim(), 16).ok()?)
+    } else {
+        RegValue::Other
+    };
+    Some((name, value))
+}
+
+/// Parse a quoted string at the start of `text`, with `\\` and `\"` escapes.
+/// Returns the string and the length of its quoted form.
+fn parse_quoted(text: &str) -> Option<(String, usize)> {
+    let mut chars = text.char_indices();
+    if chars.next()?.1 != '"' {
+        return None;
+    }
+    let mut value = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next()?.1),
+            '"' => return Some((value, i + 1)),
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"Windows Registry Editor Version 5.00
+
+[HKEY_CURRENT_USER\Software\Binary Fortress Software\DisplayFusion\MonitorConfig\Profiles\{5B0E}]
+"Name"="Desk \"A\""
+
+[HKEY_CURRENT_USER\Software\Binary Fortress Software\DisplayFusion\MonitorConfig\Profiles\{5B0E}\Monitors\0]
+"MonitorID"="MONITOR\\DEL40F4\\{4d36e96e}\\0001"
+"X"=dword:fffff100
+"Y"=dword:00000000
+"Width"=dword:00000f00
+"Height"=dword:00000870
+"Frequency"=dword:0000003c
+"Orientation"=dword:00000000
+"EDID"=hex:00,ff,ff,ff,\
+  ff,ff,ff,00
+
+[HKEY_CURRENT_USER\Software\Binary Fortress Software\DisplayFusion\MonitorConfig\Profiles\{5B0E}\Monitors\0\Splits\0]
+"X"=dword:fffff100
+"Y"=dword:00000000
+"Width"=dword:00000780
+"Height"=dword:00000870
+
+[HKEY_CURRENT_USER\Software\Binary Fortress Software\DisplayFusion\MonitorConfig\Profiles\{5B0E}\Monitors\1]
+"MonitorID"="MONITOR\\SAM0F9B"
+"X"="0"
+"Y"="0"
+"Width"="1080"
+"Height"="1920"
+"Orientation"="90"
+"Primary"="True"
+"#;
+
+    #[test]
+    fn test_parse_displayfusion_export() {
+        let profiles = parse_displayfusion_export(EXPORT).unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].name, "Desk \"A\"");
+
+        let monitors = &profiles[0].monitors;
+        assert_eq!(monitors.len(), 2);
+        assert_eq!(monitors[0].hardware_id, r"MONITOR\DEL40F4\{4d36e96e}\0001");
+        assert_eq!((monitors[0].x, monitors[0].width, monitors[0].height), (-3840, 3840, 2160));
+        assert_eq!(monitors[0].refresh_rate, Some(60.0));
+        assert!(!monitors[0].primary);
+
+        assert_eq!((monitors[1].width, monitors[1].height, monitors[1].quarter_turns), (1080, 1920, 1));
+        assert!(monitors[1].primary);
+    }
+
+    #[test]
+    fn test_primary_moves_to_origin() {
+        let export = EXPORT.replace("\"X\"=\"0\"", "\"X\"=\"100\"");
+        let monitors = &parse_displayfusion_export(&export).unwrap()[0].monitors;
+        assert_eq!((monitors[0].x, monitors[1].x), (-3940, 0));
+    }
+
+    #[test]
+    fn test_decode_reg_file() {
+        let utf16: Vec<u8> = [0xFF, 0xFE]
+            .into_iter()
+            .chain("REGEDIT4".encode_utf16().flat_map(|u| u.to_le_bytes()))
+            .collect();
+        assert_eq!(decode_reg_file(&utf16), "REGEDIT4");
+        assert_eq!(decode_reg_file(b"REGEDIT4"), "REGEDIT4");
+
+        assert!(parse_displayfusion_export("{\"monitors\": []}").is_err());
+        assert!(parse_displayfusion_export("REGEDIT4\n\n[HKEY_CURRENT_USER\\Software]\n").is_err());
+    }
+}
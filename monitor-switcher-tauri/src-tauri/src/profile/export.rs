@@ -0,0 +1,66 @@
+//! PowerShell export of Windows profiles.
+//!
+//! The script embeds the profile JSON and applies it with the command-line
+//! `MonitorSwitcher.exe -load:`, which reads the same format, so layouts can be
+//! baked into deployment and imaging workflows without the tray app.
+//! Single responsibility: turn a profile into a standalone script.
+
+#![cfg(windows)]
+
+use super::types::DisplayProfile;
+
+/// Build a standalone `apply.ps1` for a profile.
+pub fn build_powershell_script(profile_name: &str, profile: &DisplayProfile) -> Result<String, String> {
+    let json = serde_json::to_string_pretty(profile)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+
+    // A single-quoted here-string ends at a line starting with '@, which JSON never has
+    let mut script = String::new();
+    script.push_str(&format!(
+        "# Applies the monitor profile \"{}\".\r\n",
+        profile_name.replace(['\r', '\n'], " ")
+    ));
+    script.push_str("# Exported by Monitor Switcher. Needs MonitorSwitcher.exe, the command-line tool,\r\n");
+    script.push_str("# next to this script, on the PATH, or passed with -MonitorSwitcher.\r\n");
+    script.push_str("# DPI scaling stored in the profile is not applied by the command-line tool.\r\n");
+    script.push_str("param(\r\n");
+    script.push_str("    [string]$MonitorSwitcher = (Join-Path $PSScriptRoot 'MonitorSwitcher.exe')\r\n");
+    script.push_str(")\r\n");
+    script.push_str("\r\n");
+    script.push_str("$ErrorActionPreference = 'Stop'\r\n");
+    script.push_str("\r\n");
+    script.push_str("if (-not (Test-Path $MonitorSwitcher)) {\r\n");
+    script.push_str("    $MonitorSwitcher = (Get-Command 'MonitorSwitcher.exe').Source\r\n");
+    script.push_str("}\r\n");
+    script.push_str("\r\n");
+    script.push_str("$profileJson = @'\r\n");
+    script.push_str(&json.replace('\n', "\r\n"));
+    script.push_str("\r\n'@\r\n");
+    script.push_str("\r\n");
+    script.push_str("$profilePath = Join-Path ([IO.Path]::GetTempPath()) ([IO.Path]::GetRandomFileName() + '.json')\r\n");
+    script.push_str("Set-Content -Path $profilePath -Value $profileJson -Encoding UTF8\r\n");
+    script.push_str("try {\r\n");
+    script.push_str("    & $MonitorSwitcher \"-load:$profilePath\"\r\n");
+    script.push_str("    exit $LASTEXITCODE\r\n");
+    script.push_str("} finally {\r\n");
+    script.push_str("    Remove-Item $profilePath -ErrorAction SilentlyContinue\r\n");
+    script.push_str("}\r\n");
+
+    Ok(script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_embeds_profile() {
+        let profile = DisplayProfile::default();
+        let script = build_powershell_script("Desk\r\nrm -rf", &profile).unwrap();
+
+        assert!(script.starts_with("# Applies the monitor profile \"Desk  rm -rf\".\r\n"));
+        assert!(script.contains("$profileJson = @'\r\n{\r\n  \"Version\": 1,"));
+        assert!(script.contains("\r\n}\r\n'@\r\n"));
+        assert!(!script.replace("\r\n", "").contains('\n'));
+    }
+}
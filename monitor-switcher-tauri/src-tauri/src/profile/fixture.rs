@@ -0,0 +1,249 @@
+//! Display state fixtures for reproducing matching problems (Windows only).
+//!
+//! A fixture is a snapshot of the live system in the regular profile format, so
+//! it can be replayed as "the current system" against any saved profile.
+//! Single responsibility: capture snapshots and replay the matcher against them.
+
+use super::convert::{profile_to_settings, settings_to_profile};
+use super::types::DisplayProfile;
+use crate::display::{
+    get_display_settings, get_additional_info_for_modes, fill_gdi_device_names,
+    match_adapter_ids_against, DisplaySettings, MonitorAdditionalInfo,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Get the fixtures directory path.
+fn get_fixtures_dir() -> Result<PathBuf, String> {
+    let app_data = dirs::config_dir()
+        .ok_or("Could not find config directory")?;
+
+    let fixtures_dir = app_data.join("MonitorSwitcher").join("Fixtures");
+
+    // Create directory if it doesn't exist
+    if !fixtures_dir.exists() {
+        fs::create_dir_all(&fixtures_dir)
+            .map_err(|e| format!("Failed to create fixtures directory: {}", e))?;
+    }
+
+    Ok(fixtures_dir)
+}
+
+/// Capture the current display settings and additional info as a snapshot.
+pub fn capture_snapshot() -> Result<DisplayProfile, String> {
+    let settings = get_display_settings(true)?;
+    let mut additional_info = get_additional_info_for_modes(&settings.mode_info_array);
+    fill_gdi_device_names(&settings, &mut additional_info);
+
+    Ok(settings_to_profile(&settings, &additional_info))
+}
+
+/// Capture a snapshot and save it to the fixtures directory.
+/// Returns the path of the written file.
+pub fn save_fixture(name: &str) -> Result<PathBuf, String> {
+    let path = get_fixtures_dir()?.join(format!("{}.json", name));
+    save_snapshot(&path)?;
+    Ok(path)
+}
+
+/// Capture a snapshot and save it to `path`; `load_fixture` reads it back.
+pub fn save_snapshot(path: &Path) -> Result<(), String> {
+    let snapshot = capture_snapshot()?;
+
+    let json = serde_json::to_string_pretty(&snapshot)
+        .map_err(|e| format!("Failed to serialize fixture: {}", e))?;
+
+    fs::write(path, json)
+        .map_err(|e| format!("Failed to write fixture file: {}", e))
+}
+
+/// Load a fixture for the simulated display backend.
+/// Captured fixtures and saved profiles use the same format.
+pub fn load_fixture(path: &Path) -> Result<(DisplaySettings, Vec<MonitorAdditionalInfo>), String> {
+    let json = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read fixture file: {}", e))?;
+
+    let snapshot: DisplayProfile = serde_json::from_str(&json)
+        .map_err(|e| format!("Failed to parse fixture: {}", e))?;
+
+    Ok(profile_to_settings(&snapshot))
+}
+
+/// Match a profile against a recorded snapshot instead of the live system.
+/// Returns the settings as they would be passed to SetDisplayConfig.
+pub fn replay_match(profile: &DisplayProfile, snapshot: &DisplayProfile) -> DisplaySettings {
+    let (mut settings, additional_info) = profile_to_settings(profile);
+    let (current, current_additional_info) = profile_to_settings(snapshot);
+
+    match_adapter_ids_against(&mut settings, &additional_info, &current, &current_additional_info);
+    settings
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::types::AdapterId;
+    use crate::display::{LUID, MODE_INFO_TYPE_TARGET, MatchTier};
+
+    const PROFILE_TWO_MONITORS: &str =
+        include_str!("../../tests/fixtures/windows/profile_two_monitors.json");
+    const SNAPSHOT_AFTER_REBOOT: &str =
+        include_str!("../../tests/fixtures/windows/snapshot_after_reboot.json");
+    const SNAPSHOT_NEW_TARGET_IDS: &str =
+        include_str!("../../tests/fixtures/windows/snapshot_new_target_ids.json");
+    const SNAPSHOT_GENERIC_NAMES: &str =
+        include_str!("../../tests/fixtures/windows/snapshot_generic_names.json");
+
+    const NEW_ADAPTER: LUID = LUID { low_part: 0x2222, high_part: 0 };
+
+    fn load(json: &str) -> DisplayProfile {
+        serde_json::from_str(json).expect("fixture should parse")
+    }
+
+    /// The snapshot with a Parsec display in front of the monitors, using the
+    /// source and target IDs of the first one.
+    fn with_parsec_display(mut snapshot: DisplayProfile) -> DisplayProfile {
+        let adapter = AdapterId { low_part: 0x3333, high_part: 0 };
+        let mut path = snapshot.path_info_array[0].clone();
+        let mut target_mode = snapshot.mode_info_array[path.target_info.mode_info_idx as usize].clone();
+        let mut source_mode = snapshot.mode_info_array[path.source_info.mode_info_idx as usize].clone();
+        let mut target_info = snapshot.additional_info[path.target_info.mode_info_idx as usize].clone();
+        let source_info = snapshot.additional_info[path.source_info.mode_info_idx as usize].clone();
+
+        target_mode.adapter_id = adapter.clone();
+        source_mode.adapter_id = adapter.clone();
+        path.target_info.adapter_id = adapter.clone();
+        path.source_info.adapter_id = adapter;
+        path.target_info.mode_info_idx = snapshot.mode_info_array.len() as u32;
+        path.source_info.mode_info_idx = path.target_info.mode_info_idx + 1;
+        target_info.monitor_friendly_device = "Parsec Virtual Display".to_string();
+        target_info.adapter_name = r"\\?\ROOT#DISPLAY#0000#{5b45201d-f2f2-4f3b-85bb-30ff1f953599}".to_string();
+
+        snapshot.mode_info_array.extend([target_mode, source_mode]);
+        snapshot.additional_info.extend([target_info, source_info]);
+        snapshot.path_info_array.insert(0, path);
+        snapshot
+    }
+
+    /// The profile with two identical daisy-chained monitors, told apart by
+    /// the given serial numbers in target order.
+    fn with_daisy_chain(mut profile: DisplayProfile, serials: [&str; 2]) -> DisplayProfile {
+        let monitors = profile.additional_info.iter_mut().filter(|info| info.valid);
+        for (info, serial) in monitors.zip(serials) {
+            info.manufacture_id = 4268;
+            info.product_code_id = 41203;
+            info.monitor_friendly_device = "DELL U2720Q".to_string();
+            info.serial_number = serial.to_string();
+        }
+        profile
+    }
+
+    #[test]
+    fn test_replay_after_reboot_updates_adapter_ids() {
+        let settings = replay_match(&load(PROFILE_TWO_MONITORS), &load(SNAPSHOT_AFTER_REBOOT));
+
+        for path in &settings.path_info_array {
+            assert_eq!(path.source_info.adapter_id, NEW_ADAPTER);
+            assert_eq!(path.target_info.adapter_id, NEW_ADAPTER);
+        }
+        for mode in &settings.mode_info_array {
+            assert_eq!(mode.adapter_id, NEW_ADAPTER);
+        }
+    }
+
+    #[test]
+    fn test_replay_passes_over_virtual_adapters() {
+        let snapshot = with_parsec_display(load(SNAPSHOT_AFTER_REBOOT));
+        let settings = replay_match(&load(PROFILE_TWO_MONITORS), &snapshot);
+
+        for path in &settings.path_info_array {
+            assert_eq!(path.source_info.adapter_id, NEW_ADAPTER);
+            assert_eq!(path.target_info.adapter_id, NEW_ADAPTER);
+        }
+    }
+
+    #[test]
+    fn test_replay_new_target_ids_matches_by_friendly_name() {
+        let settings = replay_match(&load(PROFILE_TWO_MONITORS), &load(SNAPSHOT_NEW_TARGET_IDS));
+
+        let target_ids: Vec<u32> = settings
+            .mode_info_array
+            .iter()
+            .filter(|m| m.info_type == MODE_INFO_TYPE_TARGET)
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(target_ids, vec![0x2200, 0x2201]);
+
+        for path in &settings.path_info_array {
+            assert_eq!(path.source_info.adapter_id, NEW_ADAPTER);
+        }
+    }
+
+    #[test]
+    fn test_generic_names_match_by_gdi_name() {
+        let (mut settings, additional_info) = profile_to_settings(&load(PROFILE_TWO_MONITORS));
+        let (current, current_additional_info) = profile_to_settings(&load(SNAPSHOT_GENERIC_NAMES));
+
+        let tier = match_adapter_ids_against(&mut settings, &additional_info, &current, &current_additional_info);
+        assert_eq!(tier, MatchTier::GdiName);
+
+        let target_ids: Vec<u32> = settings.path_info_array.iter().map(|path| path.target_info.id).collect();
+        assert_eq!(target_ids, vec![0x2200, 0x2201]);
+        for mode in &settings.mode_info_array {
+            assert_eq!(mode.adapter_id, NEW_ADAPTER);
+            if mode.info_type == MODE_INFO_TYPE_TARGET {
+                assert!(target_ids.contains(&mode.id));
+            }
+        }
+    }
+
+    #[test]
+    fn test_gdi_name_needs_every_active_path() {
+        let mut snapshot = load(SNAPSHOT_GENERIC_NAMES);
+        for info in snapshot.additional_info.iter_mut().skip(2) {
+            info.gdi_device_name.clear();
+        }
+        let (mut settings, additional_info) = profile_to_settings(&load(PROFILE_TWO_MONITORS));
+        let (current, current_additional_info) = profile_to_settings(&snapshot);
+
+        let tier = match_adapter_ids_against(&mut settings, &additional_info, &current, &current_additional_info);
+        assert_ne!(tier, MatchTier::GdiName);
+    }
+
+    #[test]
+    fn test_replay_daisy_chain_follows_serial_numbers() {
+        let profile = with_daisy_chain(load(PROFILE_TWO_MONITORS), ["7RM2Q53", "9KX1P21"]);
+        // After a power cycle the chain came back with the target IDs swapped
+        let snapshot = with_daisy_chain(load(PROFILE_TWO_MONITORS), ["9KX1P21", "7RM2Q53"]);
+        let settings = replay_match(&profile, &snapshot);
+
+        let target_ids: Vec<u32> = settings.path_info_array.iter().map(|path| path.target_info.id).collect();
+        assert_eq!(target_ids, vec![4353, 4352]);
+    }
+
+    #[test]
+    fn test_keyed_monitor_info_survives_reordering() {
+        let mut profile = load(PROFILE_TWO_MONITORS);
+        profile.key_monitor_info();
+        profile.additional_info.reverse();
+
+        let names: Vec<&str> = profile
+            .path_info_array
+            .iter()
+            .filter_map(|path| profile.monitor_info(path))
+            .map(|info| info.monitor_friendly_device.as_str())
+            .collect();
+        assert_eq!(names, vec!["DELL U2720Q", "LG ULTRAGEAR"]);
+
+        let (settings, additional_info) = profile_to_settings(&profile);
+        for path in &settings.path_info_array {
+            let info = &additional_info[path.target_info.mode_info_idx as usize];
+            assert!(!info.monitor_friendly_device.is_empty());
+        }
+        assert_eq!(additional_info[2].monitor_friendly_device, "LG ULTRAGEAR");
+    }
+}
@@ -2,10 +2,12 @@
 //!
 //! Uses a simplified profile format optimized for XRandR.
 
-use crate::display::{DisplaySettings, OutputConfig, Rotation};
-use super::storage::get_profile_path;
+use crate::display::{current_fingerprint, is_remote_session, DisplaySettings, MonitorAdditionalInfo, OutputConfig, Rotation, VirtualDisplay};
+use crate::i18n;
+use super::storage::{get_profile_path, lock_profiles, machine_fingerprint, profile_slot, read_profile_json, unused_profile_name, write_profile_file};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::Path;
 
 /// Linux display profile format.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,8 +16,31 @@ pub struct LinuxDisplayProfile {
     pub version: u32,
     /// Platform identifier
     pub platform: String,
+    /// Stable identity; the file is named after it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Display name of the profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     /// Output configurations
     pub outputs: Vec<LinuxOutputConfig>,
+    /// Saved inside a remote desktop session, so it describes a virtual layout
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub saved_in_remote_session: bool,
+    /// Fingerprint of the outputs connected when the profile was saved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hardware_fingerprint: Option<String>,
+    /// Machine the profile was saved on. Missing in older profiles.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine_fingerprint: Option<String>,
+    /// evdi devices to provide before the layout is applied. None leaves them
+    /// alone; empty removes them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub virtual_displays: Option<Vec<VirtualDisplay>>,
+    /// Keep the screen on and the system awake while this is the profile
+    /// applied last
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub prevent_sleep: bool,
 }
 
 /// Serializable output configuration.
@@ -31,6 +56,18 @@ pub struct LinuxOutputConfig {
     pub pos_y: i32,
     pub rotation: String,
     pub scale: f32,
+    /// Software brightness. Missing in profiles saved before it was captured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<f32>,
+    /// Gamma correction (red, green, blue). Missing in older profiles.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gamma: Option<[f32; 3]>,
+    /// X screen number. Older profiles only covered screen 0.
+    #[serde(default)]
+    pub screen: u32,
+    /// Custom mode, created on apply when the X server doesn't have it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modeline: Option<String>,
 }
 
 impl From<&OutputConfig> for LinuxOutputConfig {
@@ -46,6 +83,10 @@ impl From<&OutputConfig> for LinuxOutputConfig {
             pos_y: output.pos_y,
             rotation: output.rotation.to_xrandr_arg().to_string(),
             scale: output.scale,
+            brightness: output.brightness,
+            gamma: output.gamma,
+            screen: output.screen,
+            modeline: output.modeline.clone(),
         }
     }
 }
@@ -63,39 +104,220 @@ impl From<&LinuxOutputConfig> for OutputConfig {
             pos_y: config.pos_y,
             rotation: Rotation::from_xrandr(&config.rotation),
             scale: config.scale,
+            brightness: config.brightness,
+            gamma: config.gamma,
+            screen: config.screen,
+            modeline: config.modeline.clone(),
+            non_desktop: false,
+            preferred: None,
+            resolutions: Vec::new(),
         }
     }
 }
 
-/// Save a Linux display profile.
+/// Save a Linux display profile. Overwrites the profile with that name, if any.
 pub fn save_linux_profile(name: &str, settings: &DisplaySettings) -> Result<(), String> {
+    // Two instances saving the same new name must not create two profiles
+    let _lock = lock_profiles()?;
+    let (id, path) = profile_slot(name)?;
+    // Keep the per-profile options when overwriting an existing profile
+    let existing = read_linux_profile(&path).ok();
     let profile = LinuxDisplayProfile {
         version: 1,
         platform: "linux".to_string(),
-        outputs: settings.outputs.iter().map(LinuxOutputConfig::from).collect(),
+        id: Some(id),
+        name: Some(name.to_string()),
+        outputs: settings
+            .outputs
+            .iter()
+            .filter(|o| !o.non_desktop)
+            .map(LinuxOutputConfig::from)
+            .collect(),
+        saved_in_remote_session: is_remote_session(),
+        hardware_fingerprint: current_fingerprint().ok().filter(|f| !f.is_empty()),
+        machine_fingerprint: machine_fingerprint(),
+        prevent_sleep: existing.as_ref().is_some_and(|p| p.prevent_sleep),
+        virtual_displays: existing.and_then(|p| p.virtual_displays),
     };
 
-    let path = get_profile_path(name)?;
     let json = serde_json::to_string_pretty(&profile)
         .map_err(|e| format!("Failed to serialize profile: {}", e))?;
 
-    fs::write(&path, json)
-        .map_err(|e| format!("Failed to write profile file: {}", e))?;
-
-    Ok(())
+    write_profile_file(&path, &json)
 }
 
 /// Load a Linux display profile.
 pub fn load_linux_profile(name: &str) -> Result<DisplaySettings, String> {
     let path = get_profile_path(name)?;
+    load_linux_profile_from(&path)
+}
+
+/// Save the active outputs to `path` in the profile format; `load_fixture`
+/// reads them back.
+pub fn save_snapshot(path: &Path) -> Result<(), String> {
+    let settings = crate::display::get_display_settings(true)?;
+    let profile = LinuxDisplayProfile {
+        version: 1,
+        platform: "linux".to_string(),
+        id: None,
+        name: None,
+        outputs: settings
+            .outputs
+            .iter()
+            .filter(|o| !o.non_desktop)
+            .map(LinuxOutputConfig::from)
+            .collect(),
+        saved_in_remote_session: is_remote_session(),
+        hardware_fingerprint: None,
+        machine_fingerprint: None,
+        virtual_displays: None,
+        prevent_sleep: false,
+    };
+
+    let json = serde_json::to_string_pretty(&profile)
+        .map_err(|e| format!("Failed to serialize snapshot: {}", e))?;
+
+    fs::write(path, json)
+        .map_err(|e| format!("Failed to write snapshot: {}", e))
+}
+
+/// Load a simulation fixture. Any saved Linux profile can be used as one.
+pub fn load_fixture(path: &Path) -> Result<(DisplaySettings, Vec<MonitorAdditionalInfo>), String> {
+    let settings = load_linux_profile_from(path)?;
+    let additional_info = vec![MonitorAdditionalInfo { valid: true }; settings.outputs.len()];
+    Ok((settings, additional_info))
+}
+
+/// Get the hardware fingerprint a Linux profile was saved with.
+pub fn load_linux_fingerprint(name: &str) -> Result<Option<String>, String> {
+    let path = get_profile_path(name)?;
+    Ok(read_linux_profile(&path)?.hardware_fingerprint)
+}
+
+/// Get the machine fingerprint a Linux profile was saved with.
+pub fn load_linux_machine_fingerprint(name: &str) -> Result<Option<String>, String> {
+    let path = get_profile_path(name)?;
+    Ok(read_linux_profile(&path)?.machine_fingerprint)
+}
+
+/// Get the virtual displays a Linux profile provides.
+pub fn load_linux_virtual_displays(name: &str) -> Result<Option<Vec<VirtualDisplay>>, String> {
+    let path = get_profile_path(name)?;
+    Ok(read_linux_profile(&path)?.virtual_displays)
+}
+
+/// Set or clear the virtual displays a Linux profile provides.
+pub fn set_linux_virtual_displays(reference: &str, displays: Option<Vec<VirtualDisplay>>) -> Result<(), String> {
+    let _lock = lock_profiles()?;
+    let path = get_profile_path(reference)?;
+    let mut profile = read_linux_profile(&path)?;
+    profile.virtual_displays = displays;
+
+    let json = serde_json::to_string_pretty(&profile)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+
+    write_profile_file(&path, &json)
+}
+
+/// Whether a Linux profile keeps the system awake while applied.
+pub fn load_linux_prevent_sleep(name: &str) -> Result<bool, String> {
+    let path = get_profile_path(name)?;
+    Ok(read_linux_profile(&path)?.prevent_sleep)
+}
+
+/// Set whether a Linux profile keeps the system awake while applied.
+pub fn set_linux_prevent_sleep(reference: &str, on: bool) -> Result<(), String> {
+    let _lock = lock_profiles()?;
+    let path = get_profile_path(reference)?;
+    let mut profile = read_linux_profile(&path)?;
+    profile.prevent_sleep = on;
+
+    let json = serde_json::to_string_pretty(&profile)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+
+    write_profile_file(&path, &json)
+}
+
+/// A Linux profile as JSON to share, without its ID and the hardware and
+/// machine fingerprints.
+pub fn linux_profile_share_json(name: &str) -> Result<String, String> {
+    let profile = LinuxDisplayProfile {
+        id: None,
+        hardware_fingerprint: None,
+        machine_fingerprint: None,
+        ..read_linux_profile(&get_profile_path(name)?)?
+    };
+    serde_json::to_string(&profile)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))
+}
+
+/// Save a shared Linux profile under its shared name, numbered when a profile
+/// has that name already. Returns the name it was saved under.
+pub fn save_shared_linux_profile(json: &str) -> Result<String, String> {
+    let shared: LinuxDisplayProfile = serde_json::from_str(json)
+        .map_err(|e| i18n::tr_with("error.parseSharedProfile", &[("error", &e.to_string())]))?;
+
+    // Another import could take the name between picking and saving it
+    let _lock = lock_profiles()?;
+    let name = unused_profile_name(shared.name.as_deref());
+    let (id, path) = profile_slot(&name)?;
+    let profile = LinuxDisplayProfile {
+        id: Some(id),
+        name: Some(name.clone()),
+        hardware_fingerprint: None,
+        machine_fingerprint: None,
+        ..shared
+    };
 
-    let json = fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read profile file: {}", e))?;
+    let json = serde_json::to_string_pretty(&profile)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+    write_profile_file(&path, &json)?;
+    Ok(name)
+}
 
-    let profile: LinuxDisplayProfile = serde_json::from_str(&json)
-        .map_err(|e| format!("Failed to parse profile: {}", e))?;
+/// Save a layout imported from another tool under `name`, numbered when a
+/// profile has that name already. It may describe other hardware, so like a
+/// shared profile it's saved without the hardware and machine fingerprints.
+/// Returns the name it was saved under.
+pub fn save_imported_linux_profile(name: &str, settings: &DisplaySettings) -> Result<String, String> {
+    // Another import could take the name between picking and saving it
+    let _lock = lock_profiles()?;
+    let name = unused_profile_name(Some(name));
+    let (id, path) = profile_slot(&name)?;
+    let profile = LinuxDisplayProfile {
+        version: 1,
+        platform: "linux".to_string(),
+        id: Some(id),
+        name: Some(name.clone()),
+        outputs: settings
+            .outputs
+            .iter()
+            .filter(|o| !o.non_desktop)
+            .map(LinuxOutputConfig::from)
+            .collect(),
+        saved_in_remote_session: false,
+        hardware_fingerprint: None,
+        machine_fingerprint: None,
+        prevent_sleep: false,
+        virtual_displays: None,
+    };
+
+    let json = serde_json::to_string_pretty(&profile)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+    write_profile_file(&path, &json)?;
+    Ok(name)
+}
+
+/// Load a Linux display profile from a file path.
+fn load_linux_profile_from(path: &Path) -> Result<DisplaySettings, String> {
+    let profile = read_linux_profile(path)?;
 
     let outputs = profile.outputs.iter().map(OutputConfig::from).collect();
 
     Ok(DisplaySettings { outputs })
 }
+
+/// Read and parse a Linux profile file.
+fn read_linux_profile(path: &Path) -> Result<LinuxDisplayProfile, String> {
+    read_profile_json(path)
+}
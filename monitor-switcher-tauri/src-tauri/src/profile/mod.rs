@@ -5,25 +5,52 @@
 
 mod types;
 mod storage;
+mod share;
 
 #[cfg(windows)]
 mod convert;
 
+#[cfg(windows)]
+mod fixture;
+
+#[cfg(windows)]
+mod export;
+
+#[cfg_attr(not(windows), allow(dead_code))]
+mod displayfusion;
+
 #[cfg(windows)]
 pub use convert::*;
 
+#[cfg(windows)]
+pub use fixture::{save_fixture, save_snapshot, load_fixture, capture_snapshot, replay_match};
+
+#[cfg(windows)]
+pub use export::build_powershell_script;
+
+#[cfg(windows)]
+pub use displayfusion::{decode_reg_file, parse_displayfusion_export};
+
 pub use storage::{
-    list_profiles, profile_exists, delete_profile,
-    get_profile_details, current_monitors, MonitorDetails,
+    list_profiles, list_profile_entries, find_profile_entry, migrate_legacy_profiles, ProfileEntry,
+    profile_exists, find_profile, rename_profile, list_profile_backups, restore_profile_backup, validate_profile_name, delete_profile,
+    get_profile_details, get_profile_summary, get_profile_layout, get_thumbnail_path, get_export_dir, get_machine_profiles_dir,
+    get_profile_virtual_displays, set_profile_virtual_displays, get_profile_prevent_sleep, set_profile_prevent_sleep,
+    host_name, split_host_variant, is_foreign_profile, resolve_host_variant, profiles_for_host,
+    current_monitors, find_profile_by_fingerprint, profile_has_fingerprint, find_active_profile, find_closest_profile, MonitorDetails,
+    export_profile_string, import_profile_string,
 };
 
 // Windows uses the original DisplayProfile format
 #[cfg(windows)]
-pub use storage::{save_profile, load_profile};
+pub use storage::{save_profile, save_imported_profile, load_profile, set_profile_apply_flags, set_profile_color_filter, set_profile_night_light, set_profile_connect_wireless_display};
+
+#[cfg(windows)]
+pub use types::DisplayProfile;
 
 // Linux uses its own profile format
 #[cfg(target_os = "linux")]
 mod linux;
 
 #[cfg(target_os = "linux")]
-pub use linux::{save_linux_profile, load_linux_profile};
+pub use linux::{save_linux_profile, save_imported_linux_profile, load_linux_profile, save_snapshot, load_fixture};
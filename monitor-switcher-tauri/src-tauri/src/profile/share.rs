@@ -0,0 +1,122 @@
+//! Profiles shared as text.
+//!
+//! A share string is one line to paste into a forum post or a chat: the
+//! profile's JSON, deflate-compressed and base64 encoded (URL-safe, without
+//! padding), behind a tag naming the format version and the platform, e.g.
+//! `ms1-win:eJy...`. Profile formats differ per platform, so a string only
+//! imports on the platform it was made on.
+//! Single responsibility: turn profile JSON into share strings and back.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+/// Version of the share string format.
+const VERSION: &str = "ms1";
+
+/// Platform whose profile format the strings carry.
+#[cfg(windows)]
+const PLATFORM: &str = "win";
+#[cfg(not(windows))]
+const PLATFORM: &str = "linux";
+
+/// Largest profile JSON accepted, so a crafted string can't inflate without end.
+const MAX_JSON_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Encode profile JSON as a share string.
+pub fn encode_share_string(json: &str) -> Result<String, String> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder
+        .write_all(json.as_bytes())
+        .and_then(|_| encoder.finish())
+        .map(|compressed| format!("{}-{}:{}", VERSION, PLATFORM, URL_SAFE_NO_PAD.encode(compressed)))
+        .map_err(|e| format!("Failed to compress profile: {}", e))
+}
+
+/// Decode a share string back into profile JSON. Whitespace is ignored, as
+/// forums and mail clients wrap long lines.
+pub fn decode_share_string(data: &str) -> Result<String, String> {
+    let data: String = data.chars().filter(|c| !c.is_whitespace()).collect();
+    let (tag, payload) = data
+        .split_once(':')
+        .ok_or("Not a Monitor Switcher profile string")?;
+    let (version, platform) = tag
+        .split_once('-')
+        .ok_or("Not a Monitor Switcher profile string")?;
+
+    if version != VERSION {
+        return Err(format!("Unsupported profile string version '{}'; it may be from a newer Monitor Switcher", version));
+    }
+    if platform != PLATFORM {
+        return Err(format!(
+            "This profile was shared from {}, it can't be imported on {}",
+            platform_label(platform),
+            platform_label(PLATFORM)
+        ));
+    }
+
+    let compressed = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|e| format!("Profile string is damaged: {}", e))?;
+
+    let mut json = String::new();
+    ZlibDecoder::new(compressed.as_slice())
+        .take(MAX_JSON_SIZE + 1)
+        .read_to_string(&mut json)
+        .map_err(|e| format!("Profile string is damaged: {}", e))?;
+    if json.len() as u64 > MAX_JSON_SIZE {
+        return Err("Profile string is too large".to_string());
+    }
+
+    Ok(json)
+}
+
+fn platform_label(platform: &str) -> &str {
+    match platform {
+        "win" => "Windows",
+        "linux" => "Linux",
+        other => other,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &str = r#"{"version":1,"platform":"linux","name":"Desk","outputs":[]}"#;
+
+    #[test]
+    fn test_round_trip() {
+        let shared = encode_share_string(JSON).unwrap();
+        assert!(shared.starts_with(&format!("{}-{}:", VERSION, PLATFORM)));
+        assert!(!shared.contains(char::is_whitespace));
+        assert_eq!(decode_share_string(&shared).unwrap(), JSON);
+
+        // Wrapped by a forum post
+        let wrapped: String = shared
+            .chars()
+            .enumerate()
+            .flat_map(|(i, c)| if i % 20 == 19 { vec![c, '\n'] } else { vec![c] })
+            .collect();
+        assert_eq!(decode_share_string(&format!("  {}  ", wrapped)).unwrap(), JSON);
+    }
+
+    #[test]
+    fn test_rejects_other_strings() {
+        let shared = encode_share_string(JSON).unwrap();
+        let payload = shared.split_once(':').unwrap().1;
+
+        assert!(decode_share_string("hello").is_err());
+        assert!(decode_share_string(&format!("ms9-{}:{}", PLATFORM, payload)).unwrap_err().contains("newer"));
+        assert!(decode_share_string(&format!("ms1-amiga:{}", payload)).unwrap_err().contains("amiga"));
+        assert!(decode_share_string(&format!("ms1-{}:not base64!", PLATFORM)).is_err());
+        assert!(decode_share_string(&format!("ms1-{}:{}", PLATFORM, &payload[..payload.len() / 2])).is_err());
+    }
+}
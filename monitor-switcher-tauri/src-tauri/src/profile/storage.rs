@@ -2,17 +2,34 @@
 
 #[cfg(windows)]
 use super::types::DisplayProfile;
+#[cfg(windows)]
+use crate::display::{ApplyFlags, ColorFilter};
+use crate::i18n;
+use crate::display::VirtualDisplay;
+use super::share::{decode_share_string, encode_share_string};
+use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::cell::Cell;
 use std::fs;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
 
 /// Details about a single monitor extracted from a profile.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MonitorDetails {
     pub name: String,
+    /// Size on the desktop, with the rotation applied: portrait monitors are
+    /// taller than wide.
     pub width: u32,
     pub height: u32,
+    /// Resolution of the display mode the monitor receives, before rotation.
+    pub mode_width: u32,
+    pub mode_height: u32,
     pub refresh_rate: f64,
     pub position_x: i32,
     pub position_y: i32,
@@ -21,6 +38,152 @@ pub struct MonitorDetails {
     /// DPI scaling percentage (100, 125, 150, etc.). None if not available.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dpi_scale: Option<u32>,
+    /// GDI device name (e.g. `\\.\DISPLAY1`). Windows only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gdi_device_name: Option<String>,
+    /// Display number as shown in Windows Settings. Windows only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_number: Option<u32>,
+    /// Device path of the adapter (GPU) driving this monitor. Windows only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adapter_name: Option<String>,
+    /// Native resolution of the connected monitor, in the same orientation as
+    /// width/height. None if unknown or the monitor isn't connected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_height: Option<u32>,
+    /// Stable identity of the monitor, used as the alias key: the monitor device
+    /// path on Windows, the output name on Linux. None if unknown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monitor_id: Option<String>,
+    /// Name reported by the monitor, when `name` was replaced by a user alias.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edid_name: Option<String>,
+    /// Connector the monitor is plugged into, e.g. "DP-1" or "HDMI-2".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connector: Option<String>,
+    /// An EDID override changes the identity the monitor reports. Windows only.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub edid_override: bool,
+}
+
+/// Replace monitor names with the user's aliases, keeping the reported name in `edid_name`.
+fn apply_aliases(monitors: &mut [MonitorDetails]) {
+    let aliases = crate::settings::load_settings()
+        .map(|s| s.monitor_aliases)
+        .unwrap_or_default();
+
+    for monitor in monitors {
+        let alias = monitor.monitor_id.as_ref().and_then(|id| aliases.get(id));
+        if let Some(alias) = alias {
+            monitor.edid_name = Some(std::mem::replace(&mut monitor.name, alias.clone()));
+        }
+    }
+}
+
+/// Append the connector to names shared by several monitors, e.g. "LG 27GL850 (DP-1)".
+fn disambiguate_names(monitors: &mut [MonitorDetails]) {
+    let names: Vec<String> = monitors.iter().map(|m| m.name.clone()).collect();
+
+    for monitor in monitors.iter_mut() {
+        let shared = names.iter().filter(|n| **n == monitor.name).count() > 1;
+        // On Linux the name is usually the connector already
+        let connector = monitor.connector.as_ref().filter(|c| **c != monitor.name);
+        if let (true, Some(connector)) = (shared, connector) {
+            monitor.name = format!("{} ({})", monitor.name, connector);
+        }
+    }
+}
+
+/// Summarize a layout as e.g. "3× (2×1440p + 1×4K vertical)".
+fn layout_summary(monitors: &[MonitorDetails]) -> String {
+    // Group identical monitors, keeping the order they first appear in
+    let mut groups: Vec<(String, usize)> = Vec::new();
+    for monitor in monitors {
+        let label = monitor_label(monitor);
+        match groups.iter_mut().find(|(l, _)| *l == label) {
+            Some((_, count)) => *count += 1,
+            None => groups.push((label, 1)),
+        }
+    }
+
+    match groups.as_slice() {
+        [] => String::new(),
+        [(label, 1)] => label.clone(),
+        [(label, count)] => format!("{}× {}", count, label),
+        _ => {
+            let parts: Vec<String> = groups
+                .iter()
+                .map(|(label, count)| format!("{}×{}", count, label))
+                .collect();
+            format!("{}× ({})", monitors.len(), parts.join(" + "))
+        }
+    }
+}
+
+/// Whether two monitor arrangements are the same layout: resolution, position,
+/// rotation, refresh rate (within 1 Hz) and DPI scale of every monitor.
+fn monitors_match(a: &[MonitorDetails], b: &[MonitorDetails]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let sorted = |monitors: &[MonitorDetails]| {
+        let mut monitors: Vec<MonitorDetails> = monitors.to_vec();
+        monitors.sort_by_key(|m| (m.position_x, m.position_y));
+        monitors
+    };
+
+    sorted(a).iter().zip(sorted(b).iter()).all(|(ma, mb)| {
+        ma.width == mb.width
+            && ma.height == mb.height
+            && ma.position_x == mb.position_x
+            && ma.position_y == mb.position_y
+            && ma.rotation == mb.rotation
+            && (ma.refresh_rate - mb.refresh_rate).abs() <= 1.0
+            && ma.dpi_scale.unwrap_or(100) == mb.dpi_scale.unwrap_or(100)
+    })
+}
+
+/// How close two monitor arrangements are. Each monitor of `a` scores against
+/// its best counterpart in `b`: 1 for the same name, 2 for the same resolution
+/// and rotation, 1 for the same position. Every monitor one side has more than
+/// the other costs 2.
+fn layout_similarity(a: &[MonitorDetails], b: &[MonitorDetails]) -> i64 {
+    let score = |ma: &MonitorDetails, mb: &MonitorDetails| {
+        i64::from(ma.name == mb.name)
+            + 2 * i64::from(ma.width == mb.width && ma.height == mb.height && ma.rotation == mb.rotation)
+            + i64::from(ma.position_x == mb.position_x && ma.position_y == mb.position_y)
+    };
+
+    let matched: i64 = a
+        .iter()
+        .map(|ma| b.iter().map(|mb| score(ma, mb)).max().unwrap_or(0))
+        .sum();
+    matched - 2 * (a.len() as i64 - b.len() as i64).abs()
+}
+
+/// Compact label for one monitor, e.g. "1440p" or "4K vertical".
+fn monitor_label(monitor: &MonitorDetails) -> String {
+    // Rotation: 1=0°, 2=90°, 3=180°, 4=270°
+    let vertical = monitor.rotation == 2 || monitor.rotation == 4;
+    let long = monitor.width.max(monitor.height);
+    let short = monitor.width.min(monitor.height);
+
+    let resolution = match (long, short) {
+        (3840, 2160) => "4K".to_string(),
+        (2560, 1440) => "1440p".to_string(),
+        (1920, 1080) => "1080p".to_string(),
+        (1280, 720) => "720p".to_string(),
+        _ => format!("{}x{}", long, short),
+    };
+
+    if vertical {
+        format!("{} vertical", resolution)
+    } else {
+        resolution
+    }
 }
 
 /// Get the profiles directory path.
@@ -33,95 +196,1009 @@ pub fn get_profiles_dir() -> Result<PathBuf, String> {
     // Create directory if it doesn't exist
     if !profiles_dir.exists() {
         fs::create_dir_all(&profiles_dir)
-            .map_err(|e| format!("Failed to create profiles directory: {}", e))?;
+            .map_err(|e| i18n::tr_with("error.createProfilesDir", &[("error", &e.to_string())]))?;
     }
 
     Ok(profiles_dir)
 }
 
-/// Get the path for a specific profile.
-pub fn get_profile_path(name: &str) -> Result<PathBuf, String> {
-    let dir = get_profiles_dir()?;
-    Ok(dir.join(format!("{}.json", sanitize_filename(name))))
+/// Get the machine-wide profiles directory, shared by all users. Only an
+/// administrator can write to it, so it isn't created here.
+pub fn get_machine_profiles_dir() -> Result<PathBuf, String> {
+    Ok(crate::settings::get_machine_dir()?.join("Profiles"))
 }
 
-/// List all saved profiles.
-pub fn list_profiles() -> Result<Vec<String>, String> {
+/// Get the file path of a saved profile, by ID or name.
+pub fn get_profile_path(reference: &str) -> Result<PathBuf, String> {
+    find_profile_entry(reference)?
+        .map(|entry| entry.path)
+        .ok_or_else(|| i18n::tr_with("error.profileNotFound", &[("name", reference)]))
+}
+
+/// Get the ID and file path to save a profile named `name` under: those of the
+/// existing profile with that name, or a new ID. Saving over a machine-wide
+/// profile creates a user copy with its ID, which takes its place.
+pub fn profile_slot(name: &str) -> Result<(String, PathBuf), String> {
     let dir = get_profiles_dir()?;
 
-    let mut profiles = Vec::new();
+    if let Some(entry) = find_profile_entry(name)? {
+        let path = match entry.path.starts_with(&dir) {
+            true => entry.path,
+            false => dir.join(format!("{}.json", entry.id)),
+        };
+        return Ok((entry.id, path));
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let path = dir.join(format!("{}.json", id));
+    Ok((id, path))
+}
 
-    let entries = fs::read_dir(&dir)
-        .map_err(|e| format!("Failed to read profiles directory: {}", e))?;
+/// Get the cached layout thumbnail path for a profile, by ID or name. Thumbnails
+/// live in the cache directory since they can be re-rendered at any time.
+pub fn get_thumbnail_path(reference: &str) -> Result<PathBuf, String> {
+    let id = find_profile_entry(reference)?
+        .map(|entry| entry.id)
+        .ok_or_else(|| i18n::tr_with("error.profileNotFound", &[("name", reference)]))?;
+
+    let cache = dirs::cache_dir()
+        .ok_or("Could not find cache directory")?;
+
+    let thumbnails_dir = cache.join("MonitorSwitcher").join("Thumbnails");
+    if !thumbnails_dir.exists() {
+        fs::create_dir_all(&thumbnails_dir)
+            .map_err(|e| format!("Failed to create thumbnails directory: {}", e))?;
+    }
+
+    Ok(thumbnails_dir.join(format!("{}.png", id)))
+}
+
+/// Get the folder a profile is exported to as standalone scripts, creating it
+/// if needed.
+pub fn get_export_dir(name: &str) -> Result<PathBuf, String> {
+    let app_data = dirs::config_dir()
+        .ok_or("Could not find config directory")?;
+
+    let export_dir = app_data.join("MonitorSwitcher").join("Exports").join(encode_filename(name));
+    if !export_dir.exists() {
+        fs::create_dir_all(&export_dir)
+            .map_err(|e| format!("Failed to create export directory: {}", e))?;
+    }
+
+    Ok(export_dir)
+}
+
+/// Identity of a saved profile. Profiles are stored as `<id>.json` with the
+/// name inside, so renaming one doesn't break references to its ID.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileEntry {
+    pub id: String,
+    pub name: String,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+/// JSON keys of the ID and name in a profile file. Windows profiles keep the
+/// PascalCase of the original format.
+#[cfg(windows)]
+const ID_KEY: &str = "Id";
+#[cfg(windows)]
+const NAME_KEY: &str = "Name";
+#[cfg(target_os = "linux")]
+const ID_KEY: &str = "id";
+#[cfg(target_os = "linux")]
+const NAME_KEY: &str = "name";
+
+/// List all saved profiles with their IDs, sorted by name: the user's own and
+/// the machine-wide ones, where a user profile hides a machine-wide one with
+/// the same ID. Listing never writes; see `migrate_legacy_profiles`.
+pub fn list_profile_entries() -> Result<Vec<ProfileEntry>, String> {
+    let entries = fs::read_dir(get_profiles_dir()?)
+        .map_err(|e| i18n::tr_with("error.readProfilesDir", &[("error", &e.to_string())]))?;
+    let mut profiles = read_profile_entries(entries);
+
+    // Usually missing; there's nothing to list then
+    if let Ok(entries) = get_machine_profiles_dir().and_then(|dir| fs::read_dir(dir).map_err(|e| e.to_string())) {
+        for entry in read_profile_entries(entries) {
+            if !profiles.iter().any(|p| p.id == entry.id) {
+                profiles.push(entry);
+            }
+        }
+    }
+
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(profiles)
+}
+
+/// A profile entry as read from its file, with the file's modification time
+/// and size at that point.
+struct CachedEntry {
+    modified: SystemTime,
+    len: u64,
+    entry: ProfileEntry,
+}
+
+/// Entries of the profile files read so far. Lookups list the profiles over
+/// and over, so only files that changed since are parsed again.
+static ENTRY_CACHE: Mutex<Option<HashMap<PathBuf, CachedEntry>>> = Mutex::new(None);
+
+/// Read the profile files of a directory listing, skipping unreadable ones.
+fn read_profile_entries(entries: fs::ReadDir) -> Vec<ProfileEntry> {
+    let mut profiles = Vec::new();
+    let mut cache = ENTRY_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let cache = cache.get_or_insert_with(HashMap::new);
 
     for entry in entries.flatten() {
         let path = entry.path();
-        if path.extension().map_or(false, |ext| ext == "json") {
-            if let Some(stem) = path.file_stem() {
-                if let Some(name) = stem.to_str() {
-                    profiles.push(name.to_string());
-                }
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let Ok((modified, len)) = entry.metadata().and_then(|m| Ok((m.modified()?, m.len()))) else {
+            continue;
+        };
+        if let Some(cached) = cache.get(&path).filter(|c| c.modified == modified && c.len == len) {
+            profiles.push(cached.entry.clone());
+            continue;
+        }
+        match read_profile_entry(&path) {
+            Ok(entry) => {
+                cache.insert(path, CachedEntry { modified, len, entry: entry.clone() });
+                profiles.push(entry);
             }
+            Err(e) => log::warn!("Skipping profile {}: {}", path.display(), e),
         }
     }
 
-    profiles.sort();
-    Ok(profiles)
+    profiles
 }
 
-/// Check if a profile exists.
-pub fn profile_exists(name: &str) -> Result<bool, String> {
-    let path = get_profile_path(name)?;
-    Ok(path.exists())
+/// List the names of all saved profiles.
+pub fn list_profiles() -> Result<Vec<String>, String> {
+    Ok(list_profile_entries()?.into_iter().map(|entry| entry.name).collect())
+}
+
+/// Read the ID and name of a profile file. A profile that predates IDs, and
+/// wasn't migrated, is listed under its file name as both.
+fn read_profile_entry(path: &Path) -> Result<ProfileEntry, String> {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+
+    let value = match read_json(path) {
+        Ok(value) => value,
+        // A damaged profile stays listed under its ID so it can be restored from a backup
+        Err(e) if Uuid::parse_str(stem).is_ok() => {
+            log::warn!("Profile {} is damaged: {}", path.display(), e);
+            let name = backup_paths(path)
+                .iter()
+                .find_map(|backup| read_json(backup).ok()?.get(NAME_KEY)?.as_str().map(str::to_string))
+                .unwrap_or_else(|| stem.to_string());
+            return Ok(ProfileEntry { id: stem.to_string(), name, path: path.to_path_buf() });
+        }
+        Err(e) => return Err(e),
+    };
+
+    let text = |value: &serde_json::Value, key: &str| value.get(key).and_then(|v| v.as_str()).map(str::to_string);
+
+    let id = text(&value, ID_KEY).unwrap_or_else(|| stem.to_string());
+    let name = text(&value, NAME_KEY).unwrap_or_else(|| decode_filename(stem));
+    Ok(ProfileEntry { id, name, path: path.to_path_buf() })
+}
+
+/// Give the user's profiles saved before IDs existed an ID, moving each to
+/// `<id>.json`. Runs once at startup; machine-wide profiles are left alone,
+/// since only an administrator can write them.
+pub fn migrate_legacy_profiles() {
+    let Ok(dir) = get_profiles_dir() else {
+        return;
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return;
+    };
+    let _lock = match lock_profiles() {
+        Ok(lock) => lock,
+        Err(e) => {
+            log::warn!("Not migrating profiles: {}", e);
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        if let Err(e) = migrate_profile(&path) {
+            log::warn!("Failed to migrate profile {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Give a profile file without an ID one, moving it to `<id>.json`. The name
+/// of such a profile is its file name. Call with the profiles locked.
+fn migrate_profile(path: &Path) -> Result<Option<ProfileEntry>, String> {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+    let Ok(mut value) = read_json(path) else {
+        return Ok(None);
+    };
+    if value.get(ID_KEY).is_some() {
+        return Ok(None);
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let name = decode_filename(stem);
+    let object = value.as_object_mut().ok_or("Profile is not a JSON object")?;
+    object.insert(ID_KEY.to_string(), id.clone().into());
+    object.insert(NAME_KEY.to_string(), name.clone().into());
+
+    let new_path = path.with_file_name(format!("{}.json", id));
+    let json = serde_json::to_string_pretty(&value)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+    write_atomic(&new_path, &json)?;
+    fs::remove_file(path)
+        .map_err(|e| format!("Failed to remove migrated profile file: {}", e))?;
+
+    log::info!("Gave profile '{}' the ID {}", name, id);
+    Ok(Some(ProfileEntry { id, name, path: new_path }))
+}
+
+/// Read a profile or backup file as untyped JSON.
+fn read_json(path: &Path) -> Result<serde_json::Value, String> {
+    read_profile_json(path)
+}
+
+/// Find a saved profile by ID or name. Names differing only in case count as
+/// the same profile, since they can't coexist on case-insensitive file systems.
+pub fn find_profile_entry(reference: &str) -> Result<Option<ProfileEntry>, String> {
+    let profiles = list_profile_entries()?;
+    let lower = reference.to_lowercase();
+
+    let by_id = profiles.iter().position(|p| p.id.eq_ignore_ascii_case(reference));
+    let by_name = || profiles.iter().position(|p| p.name == reference);
+    let by_lowercase_name = || profiles.iter().position(|p| p.name.to_lowercase() == lower);
+
+    Ok(by_id
+        .or_else(by_name)
+        .or_else(by_lowercase_name)
+        .map(|i| profiles[i].clone()))
+}
+
+/// Check if a profile exists, by ID or name.
+pub fn profile_exists(reference: &str) -> Result<bool, String> {
+    Ok(find_profile_entry(reference)?.is_some())
+}
+
+/// Find a saved profile by ID or name. Returns its name as saved.
+pub fn find_profile(reference: &str) -> Result<Option<String>, String> {
+    Ok(find_profile_entry(reference)?.map(|entry| entry.name))
+}
+
+// ============================================================================
+// Host Variants
+// ============================================================================
+
+// When profiles are synced between machines, `Office@desktop` and
+// `Office@laptop` are variants of `Office` for the machines of those names.
+// Loading `Office` picks the variant for this machine, or `Office` itself
+// when it has none.
+
+/// Name of this machine, as host variants spell it.
+pub fn host_name() -> String {
+    #[cfg(windows)]
+    let name = std::env::var("COMPUTERNAME").unwrap_or_default();
+    #[cfg(target_os = "linux")]
+    let name = fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_default();
+    name.trim().to_string()
+}
+
+/// Split a profile name into its base name and the host it's a variant for.
+pub fn split_host_variant(name: &str) -> (&str, Option<&str>) {
+    match name.rsplit_once('@') {
+        Some((base, host)) if !base.is_empty() && !host.is_empty() => (base, Some(host)),
+        _ => (name, None),
+    }
+}
+
+/// Find the profile to load for a reference: the variant for this machine if
+/// the reference names a base profile that has one, otherwise the profile
+/// itself. IDs always mean that exact profile.
+pub fn resolve_host_variant(reference: &str) -> Result<Option<ProfileEntry>, String> {
+    let profiles = list_profile_entries()?;
+    Ok(pick_host_variant(&profiles, reference, &host_name()))
+}
+
+fn pick_host_variant(profiles: &[ProfileEntry], reference: &str, host: &str) -> Option<ProfileEntry> {
+    if let Some(entry) = profiles.iter().find(|p| p.id.eq_ignore_ascii_case(reference)) {
+        return Some(entry.clone());
+    }
+
+    let variant = profiles.iter().find(|p| match split_host_variant(&p.name) {
+        (base, Some(h)) => base.eq_ignore_ascii_case(reference) && h.eq_ignore_ascii_case(host),
+        _ => false,
+    });
+    let lower = reference.to_lowercase();
+    variant
+        .or_else(|| profiles.iter().find(|p| p.name == reference))
+        .or_else(|| profiles.iter().find(|p| p.name.to_lowercase() == lower))
+        .cloned()
+}
+
+/// The profiles to offer on this machine: variants for other hosts are left
+/// out, and the variant for this one takes the place of its base profile,
+/// under the base name.
+pub fn profiles_for_host(profiles: Vec<ProfileEntry>, host: &str) -> Vec<ProfileEntry> {
+    let has_variant = |base: &str| {
+        profiles.iter().any(|p| match split_host_variant(&p.name) {
+            (b, Some(h)) => b.eq_ignore_ascii_case(base) && h.eq_ignore_ascii_case(host),
+            _ => false,
+        })
+    };
+
+    profiles
+        .iter()
+        .filter_map(|p| match split_host_variant(&p.name) {
+            (base, Some(h)) if h.eq_ignore_ascii_case(host) => Some(ProfileEntry { name: base.to_string(), ..p.clone() }),
+            (_, Some(_)) => None,
+            (base, None) if has_variant(base) => None,
+            _ => Some(p.clone()),
+        })
+        .collect()
+}
+
+/// Rename a profile, keeping its ID. Changing only the case of the name is allowed.
+pub fn rename_profile(reference: &str, new_name: &str) -> Result<(), String> {
+    validate_profile_name(new_name)?;
+    let _lock = lock_profiles()?;
+
+    let entry = find_profile_entry(reference)?
+        .ok_or_else(|| i18n::tr_with("error.profileNotFound", &[("name", reference)]))?;
+    if let Some(existing) = find_profile_entry(new_name)?.filter(|p| p.id != entry.id) {
+        return Err(i18n::tr_with("error.profileExists", &[("name", &existing.name)]));
+    }
+
+    let mut value = read_json(&entry.path)?;
+    value
+        .as_object_mut()
+        .ok_or("Profile is not a JSON object")?
+        .insert(NAME_KEY.to_string(), new_name.into());
+
+    let json = serde_json::to_string_pretty(&value)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+    write_atomic(&entry.path, &json)
 }
 
-/// Save a profile to disk (Windows).
+/// Save a profile to disk (Windows). Overwrites the profile with that name, if any.
 #[cfg(windows)]
 pub fn save_profile(name: &str, profile: &DisplayProfile) -> Result<(), String> {
-    let path = get_profile_path(name)?;
+    // Two instances saving the same new name must not create two profiles
+    let _lock = lock_profiles()?;
+    let (id, path) = profile_slot(name)?;
+    let profile = DisplayProfile {
+        id: Some(id),
+        name: Some(name.to_string()),
+        ..profile.clone()
+    };
 
-    let json = serde_json::to_string_pretty(profile)
+    let json = serde_json::to_string_pretty(&profile)
         .map_err(|e| format!("Failed to serialize profile: {}", e))?;
 
-    fs::write(&path, json)
-        .map_err(|e| format!("Failed to write profile file: {}", e))?;
-
-    Ok(())
+    write_profile_file(&path, &json)
 }
 
 /// Load a profile from disk (Windows).
 #[cfg(windows)]
 pub fn load_profile(name: &str) -> Result<DisplayProfile, String> {
     let path = get_profile_path(name)?;
+    let mut profile: DisplayProfile = read_profile_json(&path)?;
 
-    let json = fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read profile file: {}", e))?;
-
-    let profile: DisplayProfile = serde_json::from_str(&json)
-        .map_err(|e| format!("Failed to parse profile: {}", e))?;
+    // Older profiles hold unkeyed entries; the keys are written on the next save
+    profile.key_monitor_info();
 
     Ok(profile)
 }
 
-/// Delete a profile from disk.
-pub fn delete_profile(name: &str) -> Result<(), String> {
-    let path = get_profile_path(name)?;
+/// Set or clear the SetDisplayConfig flag override of a profile (Windows).
+#[cfg(windows)]
+pub fn set_profile_apply_flags(reference: &str, flags: Option<ApplyFlags>) -> Result<(), String> {
+    let _lock = lock_profiles()?;
+    let mut profile = load_profile(reference)?;
+    profile.apply_flags = flags;
+    let name = profile.name.clone().unwrap_or_else(|| reference.to_string());
+    save_profile(&name, &profile)
+}
+
+/// Set or clear the color filter a profile switches to (Windows).
+#[cfg(windows)]
+pub fn set_profile_color_filter(reference: &str, filter: Option<ColorFilter>) -> Result<(), String> {
+    let _lock = lock_profiles()?;
+    let mut profile = load_profile(reference)?;
+    profile.color_filter = filter;
+    let name = profile.name.clone().unwrap_or_else(|| reference.to_string());
+    save_profile(&name, &profile)
+}
+
+/// Set or clear the Night Light state a profile switches to (Windows).
+#[cfg(windows)]
+pub fn set_profile_night_light(reference: &str, on: Option<bool>) -> Result<(), String> {
+    let _lock = lock_profiles()?;
+    let mut profile = load_profile(reference)?;
+    profile.night_light = on;
+    let name = profile.name.clone().unwrap_or_else(|| reference.to_string());
+    save_profile(&name, &profile)
+}
+
+/// Get the virtual displays a profile has the driver show (Windows). None
+/// if it leaves them alone.
+#[cfg(windows)]
+pub fn get_profile_virtual_displays(reference: &str) -> Result<Option<Vec<VirtualDisplay>>, String> {
+    Ok(load_profile(reference)?.virtual_displays)
+}
+
+/// Get the virtual displays a profile has the driver show (Linux).
+#[cfg(target_os = "linux")]
+pub fn get_profile_virtual_displays(reference: &str) -> Result<Option<Vec<VirtualDisplay>>, String> {
+    super::linux::load_linux_virtual_displays(reference)
+}
+
+/// Set or clear the virtual displays a profile has the driver show (Windows).
+#[cfg(windows)]
+pub fn set_profile_virtual_displays(reference: &str, displays: Option<Vec<VirtualDisplay>>) -> Result<(), String> {
+    let _lock = lock_profiles()?;
+    let mut profile = load_profile(reference)?;
+    profile.virtual_displays = displays;
+    let name = profile.name.clone().unwrap_or_else(|| reference.to_string());
+    save_profile(&name, &profile)
+}
+
+/// Set or clear the virtual displays a profile has the driver show (Linux).
+#[cfg(target_os = "linux")]
+pub fn set_profile_virtual_displays(reference: &str, displays: Option<Vec<VirtualDisplay>>) -> Result<(), String> {
+    super::linux::set_linux_virtual_displays(reference, displays)
+}
+
+/// Whether a profile keeps the system awake while applied (Windows).
+#[cfg(windows)]
+pub fn get_profile_prevent_sleep(reference: &str) -> Result<bool, String> {
+    Ok(load_profile(reference)?.prevent_sleep)
+}
+
+/// Whether a profile keeps the system awake while applied (Linux).
+#[cfg(target_os = "linux")]
+pub fn get_profile_prevent_sleep(reference: &str) -> Result<bool, String> {
+    super::linux::load_linux_prevent_sleep(reference)
+}
+
+/// Set whether a profile keeps the system awake while applied (Windows).
+#[cfg(windows)]
+pub fn set_profile_prevent_sleep(reference: &str, on: bool) -> Result<(), String> {
+    let _lock = lock_profiles()?;
+    let mut profile = load_profile(reference)?;
+    profile.prevent_sleep = on;
+    let name = profile.name.clone().unwrap_or_else(|| reference.to_string());
+    save_profile(&name, &profile)
+}
+
+/// Set whether a profile keeps the system awake while applied (Linux).
+#[cfg(target_os = "linux")]
+pub fn set_profile_prevent_sleep(reference: &str, on: bool) -> Result<(), String> {
+    super::linux::set_linux_prevent_sleep(reference, on)
+}
+
+/// Set whether a profile asks to connect its wireless displays (Windows).
+#[cfg(windows)]
+pub fn set_profile_connect_wireless_display(reference: &str, on: bool) -> Result<(), String> {
+    let _lock = lock_profiles()?;
+    let mut profile = load_profile(reference)?;
+    profile.connect_wireless_display = on;
+    let name = profile.name.clone().unwrap_or_else(|| reference.to_string());
+    save_profile(&name, &profile)
+}
+
+/// Find the one profile saved with the given hardware fingerprint.
+/// Returns None if no profile or more than one profile matches.
+pub fn find_profile_by_fingerprint(fingerprint: &str) -> Result<Option<String>, String> {
+    let mut matches = list_profiles()?
+        .into_iter()
+        .filter(|name| load_fingerprint(name).ok().flatten().as_deref() == Some(fingerprint));
+
+    let first = matches.next();
+    Ok(if matches.next().is_some() { None } else { first })
+}
+
+/// Whether a profile was saved with the given hardware fingerprint.
+pub fn profile_has_fingerprint(reference: &str, fingerprint: &str) -> bool {
+    load_fingerprint(reference).ok().flatten().as_deref() == Some(fingerprint)
+}
+
+/// Find the first profile whose layout matches the connected monitors, the
+/// same way the window marks the active profile.
+pub fn find_active_profile() -> Result<Option<ProfileEntry>, String> {
+    let current = current_monitors()?;
+    Ok(list_profile_entries()?.into_iter().find(|entry| {
+        get_profile_layout(&entry.id).is_ok_and(|layout| monitors_match(&layout, &current))
+    }))
+}
+
+/// Find the profile the connected monitors are closest to, for updating it
+/// after tweaking the layout. Profiles saved with the same monitors connected
+/// come first, then the most similar layout; ties go to the first by name.
+/// None if no profile resembles the current layout at all.
+pub fn find_closest_profile() -> Result<Option<ProfileEntry>, String> {
+    let current = current_monitors()?;
+    let fingerprint = crate::display::current_fingerprint().unwrap_or_default();
+
+    let mut best: Option<((bool, i64), ProfileEntry)> = None;
+    for entry in list_profile_entries()? {
+        let Ok(layout) = get_profile_layout(&entry.id) else {
+            continue;
+        };
+        let similarity = layout_similarity(&current, &layout);
+        if similarity <= 0 {
+            continue;
+        }
+        let rank = (profile_has_fingerprint(&entry.id, &fingerprint), similarity);
+        if best.as_ref().is_none_or(|(best_rank, _)| rank > *best_rank) {
+            best = Some((rank, entry));
+        }
+    }
+    Ok(best.map(|(_, entry)| entry))
+}
+
+/// Get the hardware fingerprint a profile was saved with (Windows).
+#[cfg(windows)]
+fn load_fingerprint(name: &str) -> Result<Option<String>, String> {
+    Ok(load_profile(name)?.hardware_fingerprint)
+}
+
+/// Get the hardware fingerprint a profile was saved with (Linux).
+#[cfg(target_os = "linux")]
+fn load_fingerprint(name: &str) -> Result<Option<String>, String> {
+    super::linux::load_linux_fingerprint(name)
+}
+
+// ============================================================================
+// Sharing
+// ============================================================================
+
+/// A profile as a share string, without what ties it to this machine: its ID
+/// and the hardware and machine fingerprints (Windows).
+#[cfg(windows)]
+pub fn export_profile_string(reference: &str) -> Result<String, String> {
+    let profile = DisplayProfile {
+        id: None,
+        hardware_fingerprint: None,
+        machine_fingerprint: None,
+        ..load_profile(reference)?
+    };
+    let json = serde_json::to_string(&profile)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+    encode_share_string(&json)
+}
+
+/// A profile as a share string, without what ties it to this machine (Linux).
+#[cfg(target_os = "linux")]
+pub fn export_profile_string(reference: &str) -> Result<String, String> {
+    encode_share_string(&super::linux::linux_profile_share_json(reference)?)
+}
+
+/// Save the profile in a share string under its shared name, numbered when a
+/// profile has that name already. Returns the name it was saved under (Windows).
+#[cfg(windows)]
+pub fn import_profile_string(data: &str) -> Result<String, String> {
+    let shared: DisplayProfile = serde_json::from_str(&decode_share_string(data)?)
+        .map_err(|e| i18n::tr_with("error.parseSharedProfile", &[("error", &e.to_string())]))?;
+
+    // Another import could take the name between picking and saving it
+    let _lock = lock_profiles()?;
+    let name = unused_profile_name(shared.name.as_deref());
+    save_profile(&name, &DisplayProfile { hardware_fingerprint: None, machine_fingerprint: None, ..shared })?;
+    Ok(name)
+}
+
+/// Save a layout imported from another tool under `name`, numbered when a
+/// profile has that name already. Returns the name it was saved under
+/// (Windows). It's saved without the hardware fingerprint, so it doesn't make
+/// the fingerprint of the profile saved for these monitors ambiguous.
+#[cfg(windows)]
+pub fn save_imported_profile(name: &str, profile: &DisplayProfile) -> Result<String, String> {
+    // Another import could take the name between picking and saving it
+    let _lock = lock_profiles()?;
+    let name = unused_profile_name(Some(name));
+    save_profile(&name, &DisplayProfile { hardware_fingerprint: None, ..profile.clone() })?;
+    Ok(name)
+}
+
+/// Save the profile in a share string under its shared name, numbered when a
+/// profile has that name already. Returns the name it was saved under (Linux).
+#[cfg(target_os = "linux")]
+pub fn import_profile_string(data: &str) -> Result<String, String> {
+    super::linux::save_shared_linux_profile(&decode_share_string(data)?)
+}
+
+/// `name`, or "Shared profile" without a usable one, numbered like
+/// "Office (2)" when a profile has that name already. Call with the profiles
+/// locked, until the profile is saved under the name.
+pub(super) fn unused_profile_name(name: Option<&str>) -> String {
+    let base = name
+        .filter(|name| validate_profile_name(name).is_ok())
+        .unwrap_or("Shared profile");
+    (1..)
+        .map(|n| if n == 1 { base.to_string() } else { format!("{} ({})", base, n) })
+        .find(|name| !matches!(profile_exists(name), Ok(true)))
+        .unwrap_or_else(|| base.to_string())
+}
+
+// ============================================================================
+// Machine Identity
+// ============================================================================
+
+/// Identity of this machine, tagged onto the profiles saved on it so that a
+/// profiles folder synced between machines can tell them apart. Derived from
+/// the OS machine ID (MachineGuid on Windows, machine-id on Linux) without
+/// giving it away. None if the OS has none.
+pub fn machine_fingerprint() -> Option<String> {
+    static FINGERPRINT: OnceLock<Option<String>> = OnceLock::new();
+    FINGERPRINT
+        .get_or_init(|| read_machine_id().map(|id| hash_machine_id(&id)))
+        .clone()
+}
+
+/// Whether a profile was saved on another machine. Profiles saved before
+/// machines were recorded count as this machine's.
+pub fn is_foreign_profile(reference: &str) -> bool {
+    match (load_machine_fingerprint(reference).ok().flatten(), machine_fingerprint()) {
+        (Some(saved), Some(current)) => saved != current,
+        _ => false,
+    }
+}
+
+/// 64-bit FNV-1a of the machine ID, salted so it doesn't match other apps' hashes.
+fn hash_machine_id(id: &str) -> String {
+    let hash = format!("monitor-switcher:{}", id.trim().to_lowercase())
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3));
+    format!("{:016x}", hash)
+}
+
+#[cfg(windows)]
+fn read_machine_id() -> Option<String> {
+    use windows_sys::Win32::System::Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ, RRF_SUBKEY_WOW6464KEY};
+
+    let sub_key: Vec<u16> = r"SOFTWARE\Microsoft\Cryptography".encode_utf16().chain(Some(0)).collect();
+    let value: Vec<u16> = "MachineGuid".encode_utf16().chain(Some(0)).collect();
+    let mut buffer = [0u16; 64];
+    let mut size = std::mem::size_of_val(&buffer) as u32;
+
+    let result = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            sub_key.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_SZ | RRF_SUBKEY_WOW6464KEY,
+            std::ptr::null_mut(),
+            buffer.as_mut_ptr() as *mut _,
+            &mut size,
+        )
+    };
+    if result != 0 {
+        return None;
+    }
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    Some(String::from_utf16_lossy(&buffer[..len])).filter(|id| !id.is_empty())
+}
+
+#[cfg(target_os = "linux")]
+fn read_machine_id() -> Option<String> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+}
+
+/// Get the machine fingerprint a profile was saved with (Windows).
+#[cfg(windows)]
+fn load_machine_fingerprint(name: &str) -> Result<Option<String>, String> {
+    Ok(load_profile(name)?.machine_fingerprint)
+}
+
+/// Get the machine fingerprint a profile was saved with (Linux).
+#[cfg(target_os = "linux")]
+fn load_machine_fingerprint(name: &str) -> Result<Option<String>, String> {
+    super::linux::load_linux_machine_fingerprint(name)
+}
+
+/// Directory in the profiles directory that deleted profiles are moved to,
+/// together with their backups, so they can still be recovered by hand.
+const DELETED_DIR: &str = "deleted";
 
-    if !path.exists() {
-        return Err(format!("Profile '{}' does not exist", name));
+/// Delete a profile, by ID or name. The file and its backups are moved to the
+/// `deleted` directory rather than removed.
+pub fn delete_profile(reference: &str) -> Result<(), String> {
+    let _lock = lock_profiles()?;
+
+    // The thumbnail is only a cache; it may never have been rendered
+    if let Ok(thumbnail) = get_thumbnail_path(reference) {
+        let _ = fs::remove_file(thumbnail);
     }
 
-    fs::remove_file(&path)
-        .map_err(|e| format!("Failed to delete profile: {}", e))?;
+    let path = get_profile_path(reference)?;
+    let deleted = path.with_file_name(DELETED_DIR);
+    fs::create_dir_all(&deleted)
+        .map_err(|e| i18n::tr_with("error.deleteProfile", &[("error", &e.to_string())]))?;
+    for backup in backup_paths(&path) {
+        if let Some(file_name) = backup.file_name() {
+            let _ = fs::rename(&backup, deleted.join(file_name));
+        }
+    }
+    fs::rename(&path, deleted.join(path.file_name().unwrap_or_default()))
+        .map_err(|e| i18n::tr_with("error.deleteProfile", &[("error", &e.to_string())]))?;
 
     Ok(())
 }
 
-/// Get detailed monitor information from a profile.
+// ============================================================================
+// Concurrent Access
+// ============================================================================
+
+/// Name of the lock file in a profiles directory.
+const LOCK_FILE: &str = ".lock";
+
+/// How often reading a profile is tried before giving up, and the pause
+/// between tries.
+const READ_ATTEMPTS: u32 = 3;
+const READ_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+thread_local! {
+    /// Profile locks held by this thread, so locked operations can nest.
+    static LOCK_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Held while profile files are changed. Other instances, such as the GUI and
+/// the command line, wait for it. On the holding thread it's reentrant.
+pub(super) struct ProfilesLock {
+    /// The locked file; closing it releases the lock. None when nested.
+    _file: Option<fs::File>,
+}
+
+impl Drop for ProfilesLock {
+    fn drop(&mut self) {
+        LOCK_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Lock the profiles directory for a read-modify-write of profile files.
+pub(super) fn lock_profiles() -> Result<ProfilesLock, String> {
+    lock_dir(&get_profiles_dir()?)
+}
+
+/// Take the advisory lock of a profiles directory, waiting for other holders.
+fn lock_dir(dir: &Path) -> Result<ProfilesLock, String> {
+    let file = match LOCK_DEPTH.with(Cell::get) {
+        0 => {
+            let file = fs::OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(dir.join(LOCK_FILE))
+                .map_err(|e| i18n::tr_with("error.lockProfiles", &[("error", &e.to_string())]))?;
+            file.lock()
+                .map_err(|e| i18n::tr_with("error.lockProfiles", &[("error", &e.to_string())]))?;
+            Some(file)
+        }
+        _ => None,
+    };
+
+    LOCK_DEPTH.with(|depth| depth.set(depth.get() + 1));
+    Ok(ProfilesLock { _file: file })
+}
+
+/// Replace a file through a temporary file next to it, so readers see either
+/// the old or the new contents and never a partial write.
+fn write_atomic(path: &Path, json: &str) -> Result<(), String> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let temp = path.with_file_name(format!("{}.tmp-{}", file_name, std::process::id()));
+
+    fs::write(&temp, json)
+        .map_err(|e| i18n::tr_with("error.writeProfile", &[("error", &e.to_string())]))?;
+    fs::rename(&temp, path).map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        i18n::tr_with("error.writeProfile", &[("error", &e.to_string())])
+    })
+}
+
+/// Read and parse a profile file, trying again briefly if that fails: the
+/// file may be held open by a writer on Windows, or be written in place by an
+/// older version.
+pub(super) fn read_profile_json<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let mut attempt = 1;
+    loop {
+        let result = fs::read(path)
+            .map_err(|e| i18n::tr_with("error.readProfile", &[("error", &e.to_string())]))
+            .and_then(|json| serde_json::from_slice(&json).map_err(|e| i18n::tr_with("error.parseProfile", &[("error", &e.to_string())])));
+
+        match result {
+            Err(_) if attempt < READ_ATTEMPTS && path.exists() => {
+                attempt += 1;
+                thread::sleep(READ_RETRY_DELAY);
+            }
+            result => return result,
+        }
+    }
+}
+
+// ============================================================================
+// Backups
+// ============================================================================
+
+/// Backups kept per profile; older ones are deleted.
+const MAX_BACKUPS: usize = 5;
+
+/// Backups are stored next to the profile as `<id>.json.bak-<timestamp>`.
+const BACKUP_SUFFIX: &str = ".bak-";
+
+/// Timestamp format of backup file names, down to the microsecond, e.g.
+/// "20240101T120000123456".
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%6f";
+
+/// Timestamp format of the backups taken before microseconds were added.
+const OLD_BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+/// JSON keys of the layout in a profile file. Saves that only change other
+/// keys, like the apply flags, aren't backed up.
 #[cfg(windows)]
+const LAYOUT_KEYS: &[&str] = &["PathInfoArray", "ModeInfoArray", "AdditionalInfo", "DpiScaleInfo", "PrimarySource"];
+#[cfg(target_os = "linux")]
+const LAYOUT_KEYS: &[&str] = &["outputs"];
+
+/// Write a profile file, first backing up the version it replaces if the
+/// layout changes.
+pub(super) fn write_profile_file(path: &Path, json: &str) -> Result<(), String> {
+    let _lock = lock_dir(path.parent().unwrap_or(Path::new(".")))?;
+
+    if path.exists() && changes_layout(path, json) {
+        if let Err(e) = backup_profile_file(path) {
+            log::warn!("Failed to back up {}: {}", path.display(), e);
+        }
+    }
+
+    write_atomic(path, json)
+}
+
+/// Whether writing `json` over a profile file changes its layout. A file that
+/// can't be compared counts as changed.
+fn changes_layout(path: &Path, json: &str) -> bool {
+    let (Ok(old), Ok(new)) = (read_json(path), serde_json::from_str::<serde_json::Value>(json)) else {
+        return true;
+    };
+    LAYOUT_KEYS.iter().any(|key| old.get(key) != new.get(key))
+}
+
+/// Copy a profile file to a timestamped backup and prune old backups.
+fn backup_profile_file(path: &Path) -> Result<(), String> {
+    fs::copy(path, new_backup_path(path))
+        .map_err(|e| format!("Failed to copy profile file: {}", e))?;
+
+    for old in backup_paths(path).into_iter().skip(MAX_BACKUPS) {
+        let _ = fs::remove_file(old);
+    }
+    Ok(())
+}
+
+/// Path for a new backup of a profile file, named after the current time.
+fn new_backup_path(path: &Path) -> PathBuf {
+    let mut time = chrono::Local::now();
+    // Two backups within a microsecond would share the name otherwise
+    loop {
+        let backup = backup_path(path, &time.format(BACKUP_TIMESTAMP_FORMAT).to_string());
+        if !backup.exists() {
+            return backup;
+        }
+        time += chrono::Duration::microseconds(1);
+    }
+}
+
+/// Path of the backup of a profile file taken at `timestamp`.
+fn backup_path(path: &Path, timestamp: &str) -> PathBuf {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    path.with_file_name(format!("{}{}{}", file_name, BACKUP_SUFFIX, timestamp))
+}
+
+/// Backups of a profile file, newest first.
+fn backup_paths(path: &Path) -> Vec<PathBuf> {
+    let mut backups: Vec<PathBuf> = backup_timestamps(path)
+        .iter()
+        .map(|timestamp| backup_path(path, timestamp))
+        .collect();
+    backups.reverse();
+    backups
+}
+
+/// Timestamps of the backups of a profile file, oldest first.
+fn backup_timestamps(path: &Path) -> Vec<String> {
+    let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}{}", file_name, BACKUP_SUFFIX);
+    let Ok(entries) = path.parent().map(fs::read_dir).transpose() else {
+        return Vec::new();
+    };
+
+    let mut timestamps: Vec<String> = entries
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.strip_prefix(&prefix).map(str::to_string))
+        .filter(|timestamp| is_backup_timestamp(timestamp))
+        .collect();
+    // Both formats sort chronologically, also among each other
+    timestamps.sort();
+    timestamps
+}
+
+/// Whether `timestamp` is a backup timestamp, and safe to build a path from.
+fn is_backup_timestamp(timestamp: &str) -> bool {
+    [BACKUP_TIMESTAMP_FORMAT, OLD_BACKUP_TIMESTAMP_FORMAT]
+        .iter()
+        .any(|format| chrono::NaiveDateTime::parse_from_str(timestamp, format).is_ok())
+}
+
+/// List the backups of a profile as timestamps ("20240101T120000123456"),
+/// newest first.
+pub fn list_profile_backups(reference: &str) -> Result<Vec<String>, String> {
+    let path = get_profile_path(reference)?;
+    let mut timestamps = backup_timestamps(&path);
+    timestamps.reverse();
+    Ok(timestamps)
+}
+
+/// Replace a profile with one of its backups. The replaced version is backed
+/// up in turn, so a restore can be undone. The profile keeps its current name.
+pub fn restore_profile_backup(reference: &str, timestamp: &str) -> Result<(), String> {
+    let _lock = lock_profiles()?;
+    let entry = find_profile_entry(reference)?
+        .ok_or_else(|| i18n::tr_with("error.profileNotFound", &[("name", reference)]))?;
+
+    let backup = backup_path(&entry.path, timestamp);
+    if !is_backup_timestamp(timestamp) || !backup.exists() {
+        return Err(i18n::tr_with("error.noBackup", &[("name", &entry.name), ("timestamp", timestamp)]));
+    }
+
+    let mut value = read_json(&backup)?;
+    let object = value.as_object_mut().ok_or("Backup is not a JSON object")?;
+    object.insert(ID_KEY.to_string(), entry.id.clone().into());
+    object.insert(NAME_KEY.to_string(), entry.name.clone().into());
+
+    let json = serde_json::to_string_pretty(&value)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+    write_profile_file(&entry.path, &json)
+}
+
+/// Get detailed monitor information from a profile.
 pub fn get_profile_details(name: &str) -> Result<Vec<MonitorDetails>, String> {
+    profile_details(name, true)
+}
+
+/// Get the monitor arrangement of a profile without querying the connected monitors.
+pub fn get_profile_layout(name: &str) -> Result<Vec<MonitorDetails>, String> {
+    profile_details(name, false)
+}
+
+/// Get a short layout summary of a profile without querying the connected monitors.
+pub fn get_profile_summary(name: &str) -> Result<String, String> {
+    profile_details(name, false).map(|monitors| layout_summary(&monitors))
+}
+
+/// Get monitor details from a profile, optionally comparing against the connected monitors (Windows).
+#[cfg(windows)]
+fn profile_details(name: &str, with_native: bool) -> Result<Vec<MonitorDetails>, String> {
+    use crate::display::{connector_label, settings_display_number};
+
     let profile = load_profile(name)?;
+    let connected = if with_native { connected_native_modes() } else { Vec::new() };
     let mut monitors = Vec::new();
+    let mut sources = Vec::new();
 
     // Each path in path_info_array represents an active display connection
     for (path_idx, path) in profile.path_info_array.iter().enumerate() {
@@ -144,14 +1221,22 @@ pub fn get_profile_details(name: &str) -> Result<Vec<MonitorDetails>, String> {
                 .and_then(|m| m.target_mode.as_ref());
 
             if let Some(tgt) = target_mode {
-                (tgt.target_video_signal_info.active_size.cx,
-                 tgt.target_video_signal_info.active_size.cy,
-                 0, 0)
+                let active_size = &tgt.target_video_signal_info.active_size;
+                let (width, height) = orient((active_size.cx, active_size.cy), path.target_info.rotation);
+                (width, height, 0, 0)
             } else {
                 continue; // Skip if no mode info found
             }
         };
 
+        // The target mode is what the monitor receives, in panel orientation
+        let (mode_width, mode_height) = profile
+            .mode_info_array
+            .get(path.target_info.mode_info_idx as usize)
+            .and_then(|m| m.target_mode.as_ref())
+            .map(|tgt| (tgt.target_video_signal_info.active_size.cx, tgt.target_video_signal_info.active_size.cy))
+            .unwrap_or_else(|| orient((width, height), path.target_info.rotation));
+
         // Get refresh rate from target info
         let refresh_rate = if path.target_info.refresh_rate.denominator > 0 {
             path.target_info.refresh_rate.numerator as f64
@@ -160,20 +1245,17 @@ pub fn get_profile_details(name: &str) -> Result<Vec<MonitorDetails>, String> {
             0.0
         };
 
-        // Get monitor name from additional_info
-        // The additional_info array has 2 entries per path (one for source, one for target)
-        // We look for the first valid entry for this path
-        let name = profile
-            .additional_info
-            .iter()
-            .skip(path_idx * 2) // Each path has 2 additional_info entries
-            .take(2)
-            .find(|info| info.valid && !info.monitor_friendly_device.is_empty())
-            .map(|info| info.monitor_friendly_device.clone())
-            .unwrap_or_else(|| format!("Display {}", path_idx + 1));
+        // Additional info is stored per target, keyed by adapter and target ID
+        let target_info = profile.monitor_info(path);
 
-        // Determine if this is the primary monitor (position 0,0)
-        let is_primary = position_x == 0 && position_y == 0;
+        // Use the number Windows Settings shows when naming unnamed monitors
+        let display_number = target_info
+            .and_then(|info| settings_display_number(&info.monitor_device_path));
+
+        let name = target_info
+            .filter(|info| info.valid && !info.monitor_friendly_device.is_empty())
+            .map(|info| info.monitor_friendly_device.clone())
+            .unwrap_or_else(|| i18n::tr_with("monitor.unnamed", &[("number", &display_number.unwrap_or(path_idx as u32 + 1).to_string())]));
 
         // Get DPI scale for this source
         let source_id = path.source_info.id;
@@ -183,52 +1265,153 @@ pub fn get_profile_details(name: &str) -> Result<Vec<MonitorDetails>, String> {
             .find(|info| info.source_id == source_id)
             .map(|info| info.dpi_scale);
 
+        let gdi_device_name = target_info
+            .map(|info| info.gdi_device_name.clone())
+            .filter(|name| !name.is_empty());
+        let adapter_name = target_info
+            .map(|info| info.adapter_name.clone())
+            .filter(|name| !name.is_empty());
+
+        // Compare against the monitor as it is connected now, not as it was when saved
+        let native = target_info
+            .filter(|info| !info.monitor_device_path.is_empty())
+            .and_then(|info| connected.iter().find(|(path, _)| *path == info.monitor_device_path))
+            .map(|(_, native)| orient(*native, path.target_info.rotation));
+        let monitor_id = target_info
+            .map(|info| info.monitor_device_path.clone())
+            .filter(|path| !path.is_empty());
+        let connector = target_info
+            .filter(|info| info.valid)
+            .map(|info| connector_label(path.target_info.output_technology, info.connector_instance));
+
         monitors.push(MonitorDetails {
             name,
             width,
             height,
+            mode_width,
+            mode_height,
             refresh_rate,
             position_x,
             position_y,
             rotation: path.target_info.rotation,
-            is_primary,
+            is_primary: false,
             dpi_scale,
+            gdi_device_name,
+            display_number,
+            adapter_name,
+            native_width: native.map(|n| n.0),
+            native_height: native.map(|n| n.1),
+            monitor_id,
+            edid_name: None,
+            edid_override: false,
+            connector,
         });
+        sources.push(&path.source_info);
     }
 
+    // The primary recorded when saving wins; older profiles have none recorded
+    let recorded = profile.primary_source.as_ref().and_then(|primary| {
+        sources
+            .iter()
+            .position(|source| source.adapter_id == primary.adapter_id && source.id == primary.id)
+    });
+    mark_primary(&mut monitors, recorded);
+
+    sort_monitors(&mut monitors);
+    apply_aliases(&mut monitors);
+    disambiguate_names(&mut monitors);
     Ok(monitors)
 }
 
-/// Get detailed monitor information from a profile (Linux).
+/// Flag the monitor at `primary` as the primary one, or without it the monitor
+/// at the desktop origin.
+fn mark_primary(monitors: &mut [MonitorDetails], primary: Option<usize>) {
+    let positions: Vec<(i32, i32)> = monitors.iter().map(|m| (m.position_x, m.position_y)).collect();
+    let primary = primary.or_else(|| crate::display::primary_index(&positions));
+    for (i, monitor) in monitors.iter_mut().enumerate() {
+        monitor.is_primary = Some(i) == primary;
+    }
+}
+
+/// Order monitors left to right, then top to bottom, then by monitor ID, so
+/// they keep their place whatever order the driver enumerates them in.
+fn sort_monitors(monitors: &mut [MonitorDetails]) {
+    monitors.sort_by(|a, b| {
+        (a.position_x, a.position_y, &a.monitor_id).cmp(&(b.position_x, b.position_y, &b.monitor_id))
+    });
+}
+
+/// Get monitor details from a profile, optionally comparing against the connected outputs (Linux).
 #[cfg(target_os = "linux")]
-pub fn get_profile_details(name: &str) -> Result<Vec<MonitorDetails>, String> {
+fn profile_details(name: &str, with_native: bool) -> Result<Vec<MonitorDetails>, String> {
     let settings = super::linux::load_linux_profile(name)?;
 
-    let monitors = settings.outputs
+    // Compare against the outputs as they are connected now
+    let connected = if with_native {
+        crate::display::get_display_settings(false)
+            .map(|current| current.outputs)
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    let mut monitors: Vec<MonitorDetails> = settings.outputs
         .iter()
-        .map(|output| MonitorDetails {
-            name: output.name.clone(),
-            width: output.width,
-            height: output.height,
-            refresh_rate: output.refresh_rate as f64,
-            position_x: output.pos_x,
-            position_y: output.pos_y,
-            rotation: output.rotation.to_u32(),
-            is_primary: output.primary,
-            dpi_scale: None, // Linux doesn't track per-monitor DPI in the same way
+        .map(|output| {
+            let rotation = output.rotation.to_u32();
+            let native = connected
+                .iter()
+                .find(|c| c.name == output.name)
+                .and_then(|c| c.preferred)
+                .map(|native| orient(native, rotation));
+            // xrandr modes are in panel orientation
+            let (width, height) = orient((output.width, output.height), rotation);
+
+            MonitorDetails {
+                name: output.name.clone(),
+                width,
+                height,
+                mode_width: output.width,
+                mode_height: output.height,
+                refresh_rate: output.refresh_rate as f64,
+                position_x: output.pos_x,
+                position_y: output.pos_y,
+                rotation,
+                is_primary: output.primary,
+                dpi_scale: None, // Linux doesn't track per-monitor DPI in the same way
+                gdi_device_name: None,
+                display_number: None,
+                adapter_name: None,
+                native_width: native.map(|n| n.0),
+                native_height: native.map(|n| n.1),
+                monitor_id: Some(output.name.clone()),
+                edid_name: None,
+                edid_override: false,
+                connector: Some(output.name.clone()),
+            }
         })
         .collect();
 
+    // X may have no primary output, then the one at the origin acts as it
+    let flagged = monitors.iter().position(|m| m.is_primary);
+    mark_primary(&mut monitors, flagged);
+
+    sort_monitors(&mut monitors);
+    apply_aliases(&mut monitors);
+    disambiguate_names(&mut monitors);
     Ok(monitors)
 }
 
 /// Get current monitor configuration from the system (Windows).
 #[cfg(windows)]
 pub fn current_monitors() -> Result<Vec<MonitorDetails>, String> {
-    use crate::display::{get_display_settings, get_additional_info_for_modes, get_dpi_scaling_info, MODE_INFO_TYPE_SOURCE};
+    use crate::display::{get_display_settings, get_additional_info_for_modes, get_dpi_scaling_info, get_source_gdi_device_name, settings_display_number, connector_label, get_adapter_name, get_preferred_mode, exclude_specialized_targets, MODE_INFO_TYPE_SOURCE, MODE_INFO_TYPE_TARGET};
 
-    let settings = get_display_settings(true)?;
-    let additional_info = get_additional_info_for_modes(&settings.mode_info_array);
+    let mut settings = get_display_settings(true)?;
+    let mut additional_info = get_additional_info_for_modes(&settings.mode_info_array);
+
+    // Specialized displays are never saved, so leave them out here as well
+    exclude_specialized_targets(&mut settings, &mut additional_info);
 
     let mut monitors = Vec::new();
 
@@ -246,10 +1429,9 @@ pub fn current_monitors() -> Result<Vec<MonitorDetails>, String> {
                 // Fallback to target mode active size
                 let target_mode_idx = path.target_info.mode_info_idx as usize;
                 if let Some(tm) = settings.mode_info_array.get(target_mode_idx) {
-                    let tgt = tm.get_target_mode();
-                    (tgt.target_video_signal_info.active_size.cx,
-                     tgt.target_video_signal_info.active_size.cy,
-                     0, 0)
+                    let active_size = tm.get_target_mode().target_video_signal_info.active_size;
+                    let (width, height) = orient((active_size.cx, active_size.cy), path.target_info.rotation);
+                    (width, height, 0, 0)
                 } else {
                     continue;
                 }
@@ -258,6 +1440,15 @@ pub fn current_monitors() -> Result<Vec<MonitorDetails>, String> {
             continue;
         };
 
+        // The target mode is what the monitor receives, in panel orientation
+        let (mode_width, mode_height) = settings
+            .mode_info_array
+            .get(path.target_info.mode_info_idx as usize)
+            .filter(|m| m.info_type == MODE_INFO_TYPE_TARGET)
+            .map(|m| m.get_target_mode().target_video_signal_info.active_size)
+            .map(|active_size| (active_size.cx, active_size.cy))
+            .unwrap_or_else(|| orient((width, height), path.target_info.rotation));
+
         // Get refresh rate
         let refresh_rate = if path.target_info.refresh_rate.denominator > 0 {
             path.target_info.refresh_rate.numerator as f64
@@ -266,37 +1457,96 @@ pub fn current_monitors() -> Result<Vec<MonitorDetails>, String> {
             0.0
         };
 
-        // Get monitor name from additional_info
-        let name = additional_info
-            .iter()
-            .skip(path_idx * 2)
-            .take(2)
-            .find(|info| info.valid && !info.monitor_friendly_device.is_empty())
-            .map(|info| info.monitor_friendly_device.clone())
-            .unwrap_or_else(|| format!("Display {}", path_idx + 1));
+        let gdi_device_name = get_source_gdi_device_name(path.source_info.adapter_id, path.source_info.id);
 
-        let is_primary = position_x == 0 && position_y == 0;
+        // Additional info is aligned with the modes; the target's entry holds the name
+        let target_info = additional_info.get(path.target_info.mode_info_idx as usize);
+        let display_number = target_info.and_then(|info| settings_display_number(&info.monitor_device_path));
+        let name = target_info
+            .filter(|info| info.valid && !info.monitor_friendly_device.is_empty())
+            .map(|info| info.monitor_friendly_device.clone())
+            .unwrap_or_else(|| i18n::tr_with("monitor.unnamed", &[("number", &display_number.unwrap_or(path_idx as u32 + 1).to_string())]));
 
         // Get DPI scaling for this source
         let dpi_scale = get_dpi_scaling_info(path.source_info.adapter_id, path.source_info.id)
             .map(|info| info.current);
 
+        let adapter_name = get_adapter_name(path.target_info.adapter_id);
+        let native = get_preferred_mode(path.target_info.adapter_id, path.target_info.id)
+            .map(|native| orient(native, path.target_info.rotation));
+        let monitor_id = target_info
+            .map(|info| info.monitor_device_path.clone())
+            .filter(|path| !path.is_empty());
+        let connector = target_info
+            .filter(|info| info.valid)
+            .map(|info| connector_label(path.target_info.output_technology, info.connector_instance));
+        let edid_override = target_info.is_some_and(|info| info.edid_override.is_some());
+
         monitors.push(MonitorDetails {
             name,
             width,
             height,
+            mode_width,
+            mode_height,
             refresh_rate,
             position_x,
             position_y,
             rotation: path.target_info.rotation,
-            is_primary,
+            is_primary: false,
             dpi_scale,
+            gdi_device_name,
+            display_number,
+            adapter_name,
+            native_width: native.map(|n| n.0),
+            native_height: native.map(|n| n.1),
+            monitor_id,
+            edid_name: None,
+            edid_override,
+            connector,
         });
     }
 
+    // Windows keeps the current primary source at the desktop origin
+    mark_primary(&mut monitors, None);
+
+    sort_monitors(&mut monitors);
+    apply_aliases(&mut monitors);
+    disambiguate_names(&mut monitors);
     Ok(monitors)
 }
 
+/// Native resolutions of the connected monitors, keyed by monitor device path (Windows).
+#[cfg(windows)]
+fn connected_native_modes() -> Vec<(String, (u32, u32))> {
+    use crate::display::{get_display_settings, get_additional_info_for_modes, get_preferred_mode};
+
+    let Ok(settings) = get_display_settings(true) else {
+        return Vec::new();
+    };
+    let additional_info = get_additional_info_for_modes(&settings.mode_info_array);
+
+    settings
+        .path_info_array
+        .iter()
+        .filter_map(|path| {
+            let info = additional_info.get(path.target_info.mode_info_idx as usize)?;
+            let native = get_preferred_mode(path.target_info.adapter_id, path.target_info.id)?;
+            Some((info.monitor_device_path.clone(), native))
+        })
+        .collect()
+}
+
+/// Rotate a resolution in panel orientation into desktop orientation. Swapping
+/// is its own inverse, so this also turns desktop sizes back into panel sizes.
+fn orient((width, height): (u32, u32), rotation: u32) -> (u32, u32) {
+    // DISPLAYCONFIG_ROTATION_ROTATE90 / ROTATE270
+    if rotation == 2 || rotation == 4 {
+        (height, width)
+    } else {
+        (width, height)
+    }
+}
+
 /// Get current monitor configuration from the system (Linux).
 #[cfg(target_os = "linux")]
 pub fn current_monitors() -> Result<Vec<MonitorDetails>, String> {
@@ -304,50 +1554,428 @@ pub fn current_monitors() -> Result<Vec<MonitorDetails>, String> {
 
     let settings = get_display_settings(true)?;
 
-    let monitors = settings.outputs
+    // Non-desktop outputs (VR headsets) are never saved, so leave them out here as well
+    let mut monitors: Vec<MonitorDetails> = settings.outputs
         .iter()
-        .map(|output| MonitorDetails {
-            name: output.name.clone(),
-            width: output.width,
-            height: output.height,
-            refresh_rate: output.refresh_rate as f64,
-            position_x: output.pos_x,
-            position_y: output.pos_y,
-            rotation: output.rotation.to_u32(),
-            is_primary: output.primary,
-            dpi_scale: None,
+        .filter(|output| !output.non_desktop)
+        .map(|output| {
+            let rotation = output.rotation.to_u32();
+            let native = output.preferred.map(|native| orient(native, rotation));
+            // xrandr modes are in panel orientation
+            let (width, height) = orient((output.width, output.height), rotation);
+
+            MonitorDetails {
+                name: output.name.clone(),
+                width,
+                height,
+                mode_width: output.width,
+                mode_height: output.height,
+                refresh_rate: output.refresh_rate as f64,
+                position_x: output.pos_x,
+                position_y: output.pos_y,
+                rotation,
+                is_primary: output.primary,
+                dpi_scale: None,
+                gdi_device_name: None,
+                display_number: None,
+                adapter_name: None,
+                native_width: native.map(|n| n.0),
+                native_height: native.map(|n| n.1),
+                monitor_id: Some(output.name.clone()),
+                edid_name: None,
+                edid_override: false,
+                connector: Some(output.name.clone()),
+            }
         })
         .collect();
 
+    // X may have no primary output, then the one at the origin acts as it
+    let flagged = monitors.iter().position(|m| m.is_primary);
+    mark_primary(&mut monitors, flagged);
+
+    sort_monitors(&mut monitors);
+    apply_aliases(&mut monitors);
+    disambiguate_names(&mut monitors);
     Ok(monitors)
 }
 
-/// Sanitize a filename by removing invalid characters.
-fn sanitize_filename(name: &str) -> String {
-    let invalid_chars = ['\\', '/', ':', '*', '?', '"', '<', '>', '|'];
-    let reserved_names = [
-        "CON", "PRN", "AUX", "NUL",
-        "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
-        "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
-    ];
+/// Characters that can't appear in file names on Windows (or Linux, for '/').
+const INVALID_FILENAME_CHARS: [char; 9] = ['\\', '/', ':', '*', '?', '"', '<', '>', '|'];
 
-    let mut sanitized: String = name
-        .chars()
-        .filter(|c| !invalid_chars.contains(c))
-        .collect();
+/// Device names Windows reserves regardless of extension.
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Map a profile name to a file name. Characters that aren't allowed in file
+/// names are escaped as `%XX` instead of dropped, so "Büro?" and "Büro" get
+/// different files and the name can be recovered with `decode_filename`.
+/// Names without such characters map to themselves, as they always did.
+fn encode_filename(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut encoded = String::with_capacity(name.len());
+
+    for (i, &c) in chars.iter().enumerate() {
+        // A literal '%' only needs escaping where it would read as an escape
+        let looks_escaped = c == '%'
+            && chars.get(i + 1).is_some_and(|c| c.is_ascii_hexdigit())
+            && chars.get(i + 2).is_some_and(|c| c.is_ascii_hexdigit());
+        // Windows drops trailing dots and spaces from file names
+        let trailing = (c == '.' || c == ' ') && chars[i..].iter().all(|&c| c == '.' || c == ' ');
+
+        if INVALID_FILENAME_CHARS.contains(&c) || c.is_control() || looks_escaped || trailing {
+            let mut buf = [0u8; 4];
+            for byte in c.encode_utf8(&mut buf).bytes() {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        } else {
+            encoded.push(c);
+        }
+    }
+
+    // Escaping the first letter keeps the name readable and off the reserved list
+    // ("CON.old" is reserved too)
+    let base = encoded.split('.').next().unwrap_or_default();
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(base)) {
+        encoded = format!("%{:02X}{}", encoded.as_bytes()[0], &encoded[1..]);
+    }
+
+    encoded
+}
+
+/// Recover a profile name from a file name written by `encode_filename`.
+fn decode_filename(stem: &str) -> String {
+    let bytes = stem.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| stem.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
 
-    // Trim whitespace
-    sanitized = sanitized.trim().to_string();
+    String::from_utf8(decoded).unwrap_or_else(|_| stem.to_string())
+}
 
-    // Check for reserved names (case-insensitive)
-    if reserved_names.iter().any(|r| r.eq_ignore_ascii_case(&sanitized)) {
-        sanitized = format!("_{}", sanitized);
+/// Check that a profile name can be stored.
+pub fn validate_profile_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err(i18n::tr("error.emptyName"));
     }
+    if name.trim() != name {
+        return Err(i18n::tr("error.nameSpaces"));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
 
-    // Ensure non-empty
-    if sanitized.is_empty() {
-        sanitized = "profile".to_string();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(width: u32, height: u32, rotation: u32) -> MonitorDetails {
+        let (mode_width, mode_height) = orient((width, height), rotation);
+        MonitorDetails {
+            width,
+            height,
+            mode_width,
+            mode_height,
+            refresh_rate: 60.0,
+            rotation,
+            ..Default::default()
+        }
     }
 
-    sanitized
+    #[test]
+    fn test_monitors_match() {
+        let mut left = monitor(2560, 1440, 1);
+        let mut right = monitor(1920, 1080, 1);
+        right.position_x = 2560;
+        let docked = [left.clone(), right.clone()];
+
+        // Order doesn't matter, small refresh rate differences don't either
+        right.refresh_rate = 59.94;
+        assert!(monitors_match(&docked, &[right.clone(), left.clone()]));
+
+        left.dpi_scale = Some(125);
+        assert!(!monitors_match(&docked, &[left.clone(), right.clone()]));
+        assert!(!monitors_match(&docked, &docked[..1]));
+    }
+
+    #[test]
+    fn test_layout_similarity() {
+        let named = |name: &str, width: u32, x: i32| MonitorDetails {
+            name: name.to_string(),
+            position_x: x,
+            ..monitor(width, 1440, 1)
+        };
+        let current = [named("DELL U2720Q", 2560, 0), named("LG 27GL850", 2560, 2560)];
+
+        // Moving one monitor scores lower than the same layout, but higher than a laptop-only profile
+        let same = layout_similarity(&current, &current);
+        let moved = layout_similarity(&current, &[named("DELL U2720Q", 2560, 2560), named("LG 27GL850", 2560, 0)]);
+        let laptop = layout_similarity(&current, &[named("Built-in Display", 1920, 0)]);
+        assert_eq!(same, 8);
+        assert!(same > moved && moved > laptop);
+        assert!(layout_similarity(&current, &[]) <= 0);
+    }
+
+    #[test]
+    fn test_sort_monitors() {
+        let placed = |id: &str, x: i32, y: i32| MonitorDetails {
+            monitor_id: Some(id.to_string()),
+            position_x: x,
+            position_y: y,
+            ..monitor(1920, 1080, 1)
+        };
+        let mut monitors = [
+            placed("DP-2", 1920, 0),
+            placed("HDMI-1", 0, 1080),
+            placed("DP-3", 0, 0),
+            placed("DP-1", 0, 0),
+        ];
+
+        sort_monitors(&mut monitors);
+        let ids: Vec<&str> = monitors.iter().filter_map(|m| m.monitor_id.as_deref()).collect();
+        assert_eq!(ids, ["DP-1", "DP-3", "HDMI-1", "DP-2"]);
+    }
+
+    #[test]
+    fn test_orient() {
+        assert_eq!(orient((3840, 2160), 1), (3840, 2160));
+        assert_eq!(orient((3840, 2160), 2), (2160, 3840));
+        assert_eq!(orient((3840, 2160), 3), (3840, 2160));
+        assert_eq!(orient(orient((3840, 2160), 4), 4), (3840, 2160));
+    }
+
+    #[test]
+    fn test_layout_summary() {
+        assert_eq!(layout_summary(&[]), "");
+        assert_eq!(layout_summary(&[monitor(3840, 2160, 1)]), "4K");
+        assert_eq!(
+            layout_summary(&[monitor(2560, 1440, 1), monitor(2560, 1440, 1)]),
+            "2× 1440p"
+        );
+        assert_eq!(
+            layout_summary(&[monitor(2560, 1440, 1), monitor(2160, 3840, 2), monitor(2560, 1440, 1)]),
+            "3× (2×1440p + 1×4K vertical)"
+        );
+        assert_eq!(layout_summary(&[monitor(1680, 1050, 1)]), "1680x1050");
+    }
+
+    #[test]
+    fn test_disambiguate_names() {
+        let named = |name: &str, connector: &str| MonitorDetails {
+            name: name.to_string(),
+            connector: Some(connector.to_string()),
+            ..monitor(2560, 1440, 1)
+        };
+        let mut monitors = [
+            named("LG 27GL850", "DP-1"),
+            named("LG 27GL850", "HDMI-2"),
+            named("DELL U2720Q", "DP-2"),
+            named("HDMI-1", "HDMI-1"),
+        ];
+
+        disambiguate_names(&mut monitors);
+        let names: Vec<&str> = monitors.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["LG 27GL850 (DP-1)", "LG 27GL850 (HDMI-2)", "DELL U2720Q", "HDMI-1"]);
+    }
+
+    #[test]
+    fn test_filename_encoding() {
+        assert_eq!(encode_filename("Büro"), "Büro");
+        assert_eq!(encode_filename("Büro?"), "Büro%3F");
+        assert_eq!(encode_filename("a/b: c"), "a%2Fb%3A c");
+        assert_eq!(encode_filename("100% scale"), "100% scale");
+        assert_eq!(encode_filename("%41"), "%2541");
+        assert_eq!(encode_filename("Office..."), "Office%2E%2E%2E");
+        assert_eq!(encode_filename("con"), "%63on");
+        assert_eq!(encode_filename("Nul.old"), "%4Eul.old");
+
+        for name in ["Büro?", "a/b: c", "100% scale", "%41", "Office...", "con", "Nul.old", "日本語 *"] {
+            assert_eq!(decode_filename(&encode_filename(name)), name);
+        }
+    }
+
+    #[test]
+    fn test_hash_machine_id() {
+        let hash = hash_machine_id("0123456789abcdef0123456789abcdef");
+        assert_eq!(hash.len(), 16);
+        assert_eq!(hash, hash_machine_id(" 0123456789ABCDEF0123456789ABCDEF\n"));
+        assert_ne!(hash, hash_machine_id("fedcba9876543210fedcba9876543210"));
+    }
+
+    #[test]
+    fn test_host_variants() {
+        let entry = |id: &str, name: &str| ProfileEntry { id: id.to_string(), name: name.to_string(), path: PathBuf::new() };
+        let profiles = vec![
+            entry("1", "Office"),
+            entry("2", "Office@desktop"),
+            entry("3", "Office@LAPTOP"),
+            entry("4", "Gaming"),
+            entry("5", "me@home"),
+        ];
+
+        let pick = |reference: &str, host: &str| pick_host_variant(&profiles, reference, host).map(|e| e.id);
+        assert_eq!(pick("office", "laptop").as_deref(), Some("3"));
+        assert_eq!(pick("Office", "server").as_deref(), Some("1"));
+        assert_eq!(pick("Office@desktop", "laptop").as_deref(), Some("2"));
+        assert_eq!(pick("1", "laptop").as_deref(), Some("1"));
+        assert_eq!(pick("Gaming", "laptop").as_deref(), Some("4"));
+        assert_eq!(pick("Missing", "laptop"), None);
+
+        let offered: Vec<(String, String)> = profiles_for_host(profiles.clone(), "Desktop")
+            .into_iter()
+            .map(|e| (e.id, e.name))
+            .collect();
+        assert_eq!(
+            offered,
+            vec![("2".to_string(), "Office".to_string()), ("4".to_string(), "Gaming".to_string())]
+        );
+
+        assert_eq!(split_host_variant("@desktop"), ("@desktop", None));
+        assert_eq!(split_host_variant("a@b@c"), ("a@b", Some("c")));
+    }
+
+    #[test]
+    fn test_legacy_profile_is_given_an_id() {
+        let dir = std::env::temp_dir().join(format!("monitor-switcher-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let legacy = dir.join("Büro%3F.json");
+        fs::write(&legacy, r#"{"version": 1}"#).unwrap();
+
+        // Listing doesn't write
+        let listed = read_profile_entry(&legacy).unwrap();
+        assert_eq!(listed.name, "Büro?");
+        assert_eq!(listed.path, legacy);
+
+        let entry = migrate_profile(&legacy).unwrap().unwrap();
+        assert_eq!(entry.name, "Büro?");
+        assert_eq!(entry.path, dir.join(format!("{}.json", entry.id)));
+        assert!(!legacy.exists());
+        assert_eq!(migrate_profile(&entry.path).unwrap(), None);
+
+        // Reading it again keeps the ID
+        assert_eq!(read_profile_entry(&entry.path).unwrap(), entry);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_listing_rereads_changed_profiles() {
+        let dir = std::env::temp_dir().join(format!("monitor-switcher-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let profile = dir.join("office.json");
+        let names = |dir: &Path| -> Vec<String> {
+            read_profile_entries(fs::read_dir(dir).unwrap()).into_iter().map(|entry| entry.name).collect()
+        };
+
+        fs::write(&profile, format!(r#"{{"{}": "1", "{}": "Office"}}"#, ID_KEY, NAME_KEY)).unwrap();
+        assert_eq!(names(&dir), ["Office"]);
+        write_profile_file(&profile, &format!(r#"{{"{}": "1", "{}": "Office 2"}}"#, ID_KEY, NAME_KEY)).unwrap();
+        assert_eq!(names(&dir), ["Office 2"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_damaged_profile_can_be_restored() {
+        let dir = std::env::temp_dir().join(format!("monitor-switcher-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let id = Uuid::new_v4().to_string();
+        let profile = dir.join(format!("{}.json", id));
+        let backup = format!(r#"{{"{}": "{}", "{}": "Office", "version": 1}}"#, ID_KEY, id, NAME_KEY);
+        fs::write(backup_path(&profile, "20240101T120000"), &backup).unwrap();
+        fs::write(&profile, "{\"version\": 1, \"outp").unwrap();
+
+        let entry = read_profile_entry(&profile).unwrap();
+        assert_eq!((entry.id.as_str(), entry.name.as_str()), (id.as_str(), "Office"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_backups_are_pruned() {
+        let dir = std::env::temp_dir().join(format!("monitor-switcher-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let profile = dir.join("office.json");
+        fs::write(&profile, "{}").unwrap();
+        for day in 1..=6 {
+            fs::write(backup_path(&profile, &format!("2024010{}T120000", day)), "{}").unwrap();
+        }
+        fs::write(dir.join("office.json.bak-notes"), "").unwrap();
+
+        let new = format!(r#"{{"{}": [1]}}"#, LAYOUT_KEYS[0]);
+        write_profile_file(&profile, &new).unwrap();
+
+        let timestamps = backup_timestamps(&profile);
+        assert_eq!(timestamps.len(), MAX_BACKUPS);
+        assert_eq!(timestamps[0], "20240103T120000");
+        assert!(timestamps[MAX_BACKUPS - 1].as_str() > "2025");
+        assert!(dir.join("office.json.bak-notes").exists());
+        assert_eq!(fs::read_to_string(&profile).unwrap(), new);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_only_layout_changes_are_backed_up() {
+        let dir = std::env::temp_dir().join(format!("monitor-switcher-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let profile = dir.join("office.json");
+        let layout = |n: u32| format!(r#"{{"{}": [{}], "{}": "Office"}}"#, LAYOUT_KEYS[0], n, NAME_KEY);
+        fs::write(&profile, layout(1)).unwrap();
+
+        // Other options changing keep the backups
+        write_profile_file(&profile, &format!(r#"{{"{}": [1], "{}": "Office 2"}}"#, LAYOUT_KEYS[0], NAME_KEY)).unwrap();
+        assert!(backup_timestamps(&profile).is_empty());
+
+        // Saves in quick succession each get a backup of their own
+        write_profile_file(&profile, &layout(2)).unwrap();
+        write_profile_file(&profile, &layout(3)).unwrap();
+        let backups = backup_paths(&profile);
+        assert_eq!(backups.len(), 2);
+        assert_eq!(fs::read_to_string(&backups[0]).unwrap(), layout(2));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_profile_lock_nests_and_excludes_others() {
+        let dir = std::env::temp_dir().join(format!("monitor-switcher-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let outer = lock_dir(&dir).unwrap();
+        // A locked operation calling another doesn't wait for itself
+        write_profile_file(&dir.join("office.json"), "{}").unwrap();
+
+        let lock_file = dir.join(LOCK_FILE);
+        let other = thread::spawn(move || fs::File::open(lock_file).unwrap().try_lock().is_err());
+        assert!(other.join().unwrap());
+
+        drop(outer);
+        assert!(fs::File::open(dir.join(LOCK_FILE)).unwrap().try_lock().is_ok());
+        // Only the profile and the lock file remain; no temporary files
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
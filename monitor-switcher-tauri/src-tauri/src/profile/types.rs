@@ -4,6 +4,7 @@
 
 #![cfg(windows)]
 
+use crate::display::{ApplyFlags, ColorFilter, VirtualDisplay, MODE_INFO_TYPE_TARGET};
 use serde::{Deserialize, Serialize};
 
 /// Root object for display profile JSON serialization.
@@ -11,26 +12,126 @@ use serde::{Deserialize, Serialize};
 #[serde(rename_all = "PascalCase")]
 pub struct DisplayProfile {
     pub version: i32,
+    /// Stable identity; the file is named after it. Missing in files the
+    /// command-line tool wrote, which are given one when first listed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Display name of the profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     pub path_info_array: Vec<PathInfo>,
     pub mode_info_array: Vec<ModeInfo>,
     pub additional_info: Vec<ProfileMonitorInfo>,
     /// DPI scaling settings per source. Added in version 2.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub dpi_scale_info: Vec<DpiScaleInfo>,
+    /// SetDisplayConfig flag overrides. None uses the global default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub apply_flags: Option<ApplyFlags>,
+    /// Saved inside a Remote Desktop session, so it describes a virtual layout.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub saved_in_remote_session: bool,
+    /// Fingerprint of the monitors connected when the profile was saved, for
+    /// picking the profile automatically when that hardware is plugged in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hardware_fingerprint: Option<String>,
+    /// Machine the profile was saved on, for telling profiles of other machines
+    /// apart in a synced profiles folder. Missing in older profiles.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine_fingerprint: Option<String>,
+    /// Color filter to switch to when the profile is applied. None leaves it alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_filter: Option<ColorFilter>,
+    /// Night Light state to switch to when the profile is applied. None leaves it alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub night_light: Option<bool>,
+    /// Whether the built-in panel was on when saved. None on machines without one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub internal_panel: Option<bool>,
+    /// Virtual displays to have the virtual display driver show before the
+    /// layout is applied. None leaves them alone; empty removes them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub virtual_displays: Option<Vec<VirtualDisplay>>,
+    /// Keep the screen on and the system awake while this is the profile
+    /// applied last, e.g. for presenting on a projector.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub prevent_sleep: bool,
+    /// Open the wireless display flyout before applying when a wireless
+    /// (Miracast) display of the profile isn't connected.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub connect_wireless_display: bool,
+    /// Source shown on the primary display. None in profiles saved before it was
+    /// recorded, where the source at the desktop origin is taken.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_source: Option<PrimarySource>,
 }
 
 impl Default for DisplayProfile {
     fn default() -> Self {
         Self {
             version: 1,
+            id: None,
+            name: None,
             path_info_array: Vec::new(),
             mode_info_array: Vec::new(),
             additional_info: Vec::new(),
             dpi_scale_info: Vec::new(),
+            apply_flags: None,
+            saved_in_remote_session: false,
+            hardware_fingerprint: None,
+            machine_fingerprint: None,
+            color_filter: None,
+            night_light: None,
+            internal_panel: None,
+            virtual_displays: None,
+            prevent_sleep: false,
+            connect_wireless_display: false,
+            primary_source: None,
         }
     }
 }
 
+impl DisplayProfile {
+    /// Additional info of the monitor a path drives, looked up by the path's
+    /// target. Entries not keyed yet are found at the target's mode index.
+    pub fn monitor_info(&self, path: &PathInfo) -> Option<&ProfileMonitorInfo> {
+        let target = &path.target_info;
+        self.monitor_info_for(&target.adapter_id, target.id, target.mode_info_idx)
+    }
+
+    /// Additional info of the target with the given identity and mode index.
+    pub fn monitor_info_for(&self, adapter_id: &AdapterId, target_id: u32, mode_idx: u32) -> Option<&ProfileMonitorInfo> {
+        self.additional_info
+            .iter()
+            .find(|info| info.target_id == Some(target_id) && info.adapter_id.as_ref() == Some(adapter_id))
+            .or_else(|| {
+                self.additional_info
+                    .get(mode_idx as usize)
+                    .filter(|info| info.target_id.is_none())
+            })
+    }
+
+    /// Key additional info entries of profiles saved before entries were keyed.
+    /// Those were written one per mode, so each belongs to the target mode at
+    /// its own index; entries of source modes stay unkeyed.
+    pub fn key_monitor_info(&mut self) {
+        for (info, mode) in self.additional_info.iter_mut().zip(&self.mode_info_array) {
+            if info.target_id.is_none() && mode.info_type == MODE_INFO_TYPE_TARGET {
+                info.adapter_id = Some(mode.adapter_id.clone());
+                info.target_id = Some(mode.id);
+            }
+        }
+    }
+}
+
+/// Source identity of the primary display.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PrimarySource {
+    pub adapter_id: AdapterId,
+    pub id: u32,
+}
+
 /// Display path information.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -67,7 +168,7 @@ pub struct PathTargetInfo {
 }
 
 /// Adapter identifier.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct AdapterId {
     pub low_part: u32,
@@ -154,6 +255,31 @@ pub struct ProfileMonitorInfo {
     pub monitor_device_path: String,
     #[serde(default, deserialize_with = "deserialize_null_string")]
     pub monitor_friendly_device: String,
+    /// Specialized display such as a VR headset.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub specialized: bool,
+    /// GDI device name of the source, e.g. `\\.\DISPLAY1`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub gdi_device_name: String,
+    /// Device path of the adapter (GPU) driving the monitor.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub adapter_name: String,
+    /// Connector instance among connectors of the same type on the adapter.
+    #[serde(default)]
+    pub connector_instance: u32,
+    /// EDID serial number; tells daisy-chained identical monitors apart.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub serial_number: String,
+    /// Virtual or remote-streaming adapter. Missing in older profiles, where
+    /// it's told from the adapter name.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub virtual_adapter: bool,
+    /// Adapter and ID of the target the entry describes, as saved. None for
+    /// source entries and in profiles saved before entries were keyed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adapter_id: Option<AdapterId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<u32>,
 }
 
 /// DPI scaling information for a display source.
@@ -0,0 +1,457 @@
+//! Application settings.
+//!
+//! App-wide preferences are stored as `settings.json` next to the profiles directory.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::i18n;
+#[cfg(windows)]
+use crate::display::ApplyFlags;
+
+/// App-wide settings. Missing fields fall back to their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    /// Default SetDisplayConfig flags for profiles without their own override.
+    #[cfg(windows)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apply_flags: Option<ApplyFlags>,
+    /// Before applying a profile, wait up to this many seconds for all its
+    /// monitors to be connected (docks can enumerate displays slowly). 0 disables waiting.
+    pub wait_for_monitors_secs: u64,
+    /// After applying, give displays this many seconds to light up; if one stays
+    /// dark, the previous layout is restored. 0 disables the check.
+    pub revert_if_dark_secs: u64,
+    /// When the connected monitors change, apply the one profile saved with
+    /// exactly the new set of monitors.
+    pub auto_select_profile: bool,
+    /// Re-apply the last applied profile after resuming from sleep or unlocking
+    /// the session, when monitors tend to come back in a different layout.
+    pub reapply_on_resume: bool,
+    /// After resume or a hotplug, wait this many seconds before automation
+    /// applies a profile (some docks need 10–20 seconds to bring links up).
+    pub settle_delay_secs: u64,
+    /// After the settle delay, keep waiting up to this many seconds for the
+    /// connected monitors to stop changing. 0 applies right after the delay.
+    pub settle_max_wait_secs: u64,
+    /// Seconds an automation rule rests after it applied a profile, for rules
+    /// without a cooldown of their own. 0 disables the cooldown.
+    pub automation_cooldown_secs: u64,
+    /// Language code for the tray and messages, e.g. "de". None follows the
+    /// system locale. Takes effect after a restart.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Log level of the app's own records: "off", "error", "warn", "info",
+    /// "debug" or "trace". None follows RUST_LOG. Changes through `set_log_level` apply right away.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+    /// Action run when the tray icon is middle-clicked.
+    pub middle_click_action: TrayAction,
+    /// Action run when the tray icon is double-clicked (Windows); applies the
+    /// last applied profile by default. Set to none, a single click opens the
+    /// window without waiting for a second one.
+    pub double_click_action: TrayAction,
+    /// Profiles to apply when processes start or exit.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub process_rules: Vec<ProcessRule>,
+    /// Profiles to apply when networks connect.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub network_rules: Vec<NetworkRule>,
+    /// Refresh rate limiting while running on battery.
+    pub battery_saver: BatterySaver,
+    /// User-chosen monitor names, keyed by `MonitorDetails::monitor_id`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub monitor_aliases: BTreeMap<String, String>,
+    /// Monitors (by `MonitorDetails::monitor_id`) whose orientation loading a
+    /// profile leaves alone, for tablets and pivoting stands.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rotation_locks: Vec<String>,
+    /// Named sequences of profiles and actions.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub chains: Vec<ProfileChain>,
+    /// Global keyboard shortcuts for quick actions.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hotkeys: Vec<HotkeyBinding>,
+    /// Locked-down mode for shared PCs. A machine-wide policy file takes
+    /// precedence (see `kiosk.rs`).
+    pub kiosk: KioskPolicy,
+    /// Match profile monitors to displays of virtual adapters (Parsec,
+    /// spacedesk, virtual display drivers) like to physical ones (Windows).
+    /// Off keeps them from taking the place of real monitors.
+    pub match_virtual_adapters: bool,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            #[cfg(windows)]
+            apply_flags: None,
+            wait_for_monitors_secs: 0,
+            revert_if_dark_secs: 0,
+            auto_select_profile: false,
+            reapply_on_resume: false,
+            settle_delay_secs: 0,
+            settle_max_wait_secs: 0,
+            automation_cooldown_secs: 0,
+            language: None,
+            log_level: None,
+            middle_click_action: TrayAction::default(),
+            double_click_action: TrayAction::LoadLastProfile,
+            process_rules: Vec::new(),
+            network_rules: Vec::new(),
+            battery_saver: BatterySaver::default(),
+            monitor_aliases: BTreeMap::new(),
+            rotation_locks: Vec::new(),
+            chains: Vec::new(),
+            hotkeys: Vec::new(),
+            kiosk: KioskPolicy::default(),
+            match_virtual_adapters: false,
+        }
+    }
+}
+
+/// Restrict the app to applying a fixed set of profiles, for conference-room
+/// and signage PCs. Saving, deleting, turning monitors off and changing
+/// settings are refused while it's enabled.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KioskPolicy {
+    pub enabled: bool,
+    /// Profiles that may be applied, by ID or name.
+    pub profiles: Vec<String>,
+}
+
+/// A named sequence of steps, for hardware that needs staged switching
+/// (e.g. an AV receiver that must lock onto a signal before the full layout).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProfileChain {
+    pub name: String,
+    pub steps: Vec<ChainStep>,
+}
+
+/// One step of a profile chain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "step")]
+pub enum ChainStep {
+    /// Load the named profile.
+    #[serde(rename_all = "camelCase")]
+    LoadProfile { profile: String },
+    /// Wait before the next step.
+    #[serde(rename_all = "camelCase")]
+    Wait { ms: u64 },
+    /// Run a program and wait for it to exit.
+    #[serde(rename_all = "camelCase")]
+    RunCommand {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Turn off all monitors.
+    TurnOffMonitors,
+}
+
+/// Cap refresh rates (and optionally the laptop panel resolution) while on battery.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BatterySaver {
+    pub enabled: bool,
+    /// Highest refresh rate allowed on battery, in Hz.
+    pub max_refresh_rate: u32,
+    /// Resolution for the built-in panel on battery, as `[width, height]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub internal_resolution: Option<[u32; 2]>,
+}
+
+impl Default for BatterySaver {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_refresh_rate: 60,
+            internal_resolution: None,
+        }
+    }
+}
+
+/// Apply a profile when a process starts and/or exits.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProcessRule {
+    /// Executable name, e.g. `iRacingSim64DX11.exe`. Case-insensitive, `.exe` optional.
+    pub process: String,
+    /// Profile to apply when the process starts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_start: Option<String>,
+    /// Profile to apply when the process exits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_exit: Option<String>,
+    /// Seconds to ignore the process starting or exiting again after it
+    /// applied a profile. None uses `automation_cooldown_secs`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cooldown_secs: Option<u64>,
+}
+
+/// A global keyboard shortcut and the quick action it runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyBinding {
+    /// Shortcut such as "Ctrl+Alt+M" or "Super+Shift+F12".
+    pub shortcut: String,
+    pub action: TrayAction,
+}
+
+/// Apply a profile when a network connects, e.g. the office Wi-Fi.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NetworkRule {
+    /// Wi-Fi SSID, or NetworkManager connection name on Linux. Case-insensitive.
+    pub network: String,
+    /// Profile to apply.
+    pub profile: String,
+    /// Seconds to ignore the network connecting again after it applied a
+    /// profile. None uses `automation_cooldown_secs`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cooldown_secs: Option<u64>,
+}
+
+/// A quick action that can be bound to a tray icon click or a hotkey.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "action", content = "profile")]
+pub enum TrayAction {
+    /// Do nothing.
+    None,
+    /// Turn off all monitors.
+    #[default]
+    TurnOffMonitors,
+    /// Turn off all monitors and lock the session.
+    TurnOffAndLock,
+    /// Show the main window.
+    OpenWindow,
+    /// Open the "save new profile" popup.
+    SaveNewProfile,
+    /// Flash the number and name of every monitor.
+    IdentifyMonitors,
+    /// Load the named profile.
+    LoadProfile(String),
+    /// Load the profile applied most recently again.
+    LoadLastProfile,
+    /// Run the named profile chain.
+    RunChain(String),
+    /// Apply whichever of the two profiles isn't active.
+    ToggleProfiles(String, String),
+    /// Set every monitor to this brightness (percent).
+    SetBrightness(u8),
+    /// Show the built-in display only.
+    InternalDisplayOnly,
+    /// Show the external displays only.
+    ExternalDisplaysOnly,
+    /// Offer to overwrite the profile closest to the current layout.
+    UpdateMatchingProfile,
+    /// Turn the monitor under the mouse pointer 90° clockwise.
+    RotateClockwise,
+    /// Turn the monitor under the mouse pointer 90° counter-clockwise.
+    RotateCounterclockwise,
+}
+
+/// Get the app's config directory, creating it if needed.
+fn get_app_dir() -> Result<PathBuf, String> {
+    let app_data = dirs::config_dir()
+        .ok_or("Could not find config directory")?;
+
+    let app_dir = app_data.join("MonitorSwitcher");
+
+    // Create directory if it doesn't exist
+    if !app_dir.exists() {
+        fs::create_dir_all(&app_dir)
+            .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    }
+
+    Ok(app_dir)
+}
+
+/// Get the machine-wide config directory, shared by all users and managed by
+/// an administrator. It isn't created here, since only an administrator can.
+pub fn get_machine_dir() -> Result<PathBuf, String> {
+    #[cfg(windows)]
+    let dir = std::env::var_os("ProgramData")
+        .map(PathBuf::from)
+        .ok_or("Could not find the ProgramData directory")?
+        .join("MonitorSwitcher");
+    #[cfg(target_os = "linux")]
+    let dir = PathBuf::from("/etc/monitor-switcher");
+
+    Ok(dir)
+}
+
+/// Get the machine-wide kiosk policy path (see `kiosk.rs`).
+pub fn get_kiosk_policy_path() -> Result<PathBuf, String> {
+    Ok(get_machine_dir()?.join("kiosk.json"))
+}
+
+/// Get the settings file path.
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(get_app_dir()?.join("settings.json"))
+}
+
+/// Get the automation script path. The script is optional and only run if it exists.
+pub fn get_script_path() -> Result<PathBuf, String> {
+    Ok(get_app_dir()?.join("automation.rhai"))
+}
+
+/// Get the folder of user language files (see `i18n.rs`).
+pub fn get_languages_dir() -> Result<PathBuf, String> {
+    Ok(get_app_dir()?.join("Languages"))
+}
+
+/// Get the runtime state file path (see `state.rs`).
+pub fn get_state_path() -> Result<PathBuf, String> {
+    Ok(get_app_dir()?.join("state.json"))
+}
+
+/// Get the path of the marker written while a profile is applied (see `apply_marker.rs`).
+pub fn get_apply_marker_path() -> Result<PathBuf, String> {
+    Ok(get_app_dir()?.join("apply-in-progress.json"))
+}
+
+/// Get the applied-configuration history path (see `history.rs`).
+pub fn get_history_path() -> Result<PathBuf, String> {
+    Ok(get_app_dir()?.join("history.jsonl"))
+}
+
+/// Get the default path settings are exported to.
+pub fn get_settings_export_path() -> Result<PathBuf, String> {
+    let dir = get_app_dir()?.join("Exports");
+    if !dir.exists() {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create export directory: {}", e))?;
+    }
+    Ok(dir.join("settings.json"))
+}
+
+/// Load settings from disk, returning defaults if none have been saved yet.
+pub fn load_settings() -> Result<AppSettings, String> {
+    let path = get_settings_path()?;
+
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+
+    let json = fs::read_to_string(&path)
+        .map_err(|e| i18n::tr_with("error.readSettings", &[("error", &e.to_string())]))?;
+
+    serde_json::from_str(&json)
+        .map_err(|e| i18n::tr_with("error.parseSettings", &[("error", &e.to_string())]))
+}
+
+/// Save settings to disk.
+pub fn save_settings(settings: &AppSettings) -> Result<(), String> {
+    let path = get_settings_path()?;
+
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    fs::write(&path, json)
+        .map_err(|e| i18n::tr_with("error.writeSettings", &[("error", &e.to_string())]))?;
+
+    Ok(())
+}
+
+// ============================================================================
+// Import / Export
+// ============================================================================
+
+/// Marks a file as exported settings, so a profile isn't imported by mistake.
+const EXPORT_FORMAT: &str = "monitor-switcher-settings";
+
+/// Exported settings, for moving a setup to another machine. Profiles are
+/// exported separately.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SettingsExport {
+    format: String,
+    version: u32,
+    /// Local time in RFC 3339 format
+    exported_at: String,
+    settings: AppSettings,
+}
+
+/// Write the current settings to `path`.
+pub fn export_settings(path: &Path) -> Result<(), String> {
+    let export = SettingsExport {
+        format: EXPORT_FORMAT.to_string(),
+        version: 1,
+        exported_at: chrono::Local::now().to_rfc3339(),
+        settings: load_settings()?,
+    };
+
+    let json = serde_json::to_string_pretty(&export)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    fs::write(path, json)
+        .map_err(|e| i18n::tr_with("error.writeSettingsExport", &[("error", &e.to_string())]))
+}
+
+/// Replace the current settings with those exported to `path`.
+/// Settings of the other platform in the file are ignored.
+pub fn import_settings(path: &Path) -> Result<AppSettings, String> {
+    let json = fs::read_to_string(path)
+        .map_err(|e| i18n::tr_with("error.readSettingsExport", &[("error", &e.to_string())]))?;
+
+    let settings = parse_settings_export(&json)?;
+    save_settings(&settings)?;
+    Ok(settings)
+}
+
+/// Parse exported settings, rejecting files that aren't a settings export.
+fn parse_settings_export(json: &str) -> Result<AppSettings, String> {
+    let value: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| i18n::tr_with("error.parseSettingsExport", &[("error", &e.to_string())]))?;
+    if value.get("format").and_then(|f| f.as_str()) != Some(EXPORT_FORMAT) {
+        return Err(i18n::tr("error.notSettingsExport"));
+    }
+
+    let export: SettingsExport = serde_json::from_value(value)
+        .map_err(|e| i18n::tr_with("error.parseSettingsExport", &[("error", &e.to_string())]))?;
+    Ok(export.settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chain_steps() {
+        let json = r#"{"chains": [{"name": "Movie", "steps": [
+            {"step": "loadProfile", "profile": "TV only"},
+            {"step": "wait", "ms": 3000},
+            {"step": "runCommand", "program": "avr", "args": ["input", "hdmi2"]},
+            {"step": "turnOffMonitors"}
+        ]}]}"#;
+        let settings: AppSettings = serde_json::from_str(json).unwrap();
+
+        assert_eq!(settings.chains[0].steps, [
+            ChainStep::LoadProfile { profile: "TV only".to_string() },
+            ChainStep::Wait { ms: 3000 },
+            ChainStep::RunCommand { program: "avr".to_string(), args: vec!["input".to_string(), "hdmi2".to_string()] },
+            ChainStep::TurnOffMonitors,
+        ]);
+    }
+
+    #[test]
+    fn test_parse_settings_export() {
+        let json = r#"{
+            "format": "monitor-switcher-settings",
+            "version": 1,
+            "exportedAt": "2024-01-01T12:00:00+01:00",
+            "settings": {"autoSelectProfile": true, "monitorAliases": {"DP-1": "Left"}, "applyFlags": null}
+        }"#;
+        let settings = parse_settings_export(json).unwrap();
+        assert!(settings.auto_select_profile);
+        assert_eq!(settings.monitor_aliases["DP-1"], "Left");
+
+        assert!(parse_settings_export(r#"{"version": 1, "platform": "linux", "outputs": []}"#).is_err());
+    }
+}
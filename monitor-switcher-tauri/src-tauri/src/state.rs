@@ -0,0 +1,73 @@
+//! Runtime state remembered across restarts.
+//!
+//! Unlike settings, nothing here is edited by the user; it records what the app
+//! did. Stored as `state.json` next to `settings.json`.
+//! Single responsibility: persist the last applied profile.
+
+use crate::settings::get_state_path;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// Everything the app remembers between runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppState {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_applied: Option<LastApplied>,
+}
+
+/// The profile applied most recently, by the user or by automation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LastApplied {
+    /// Name of the profile when it was applied
+    pub profile: String,
+    /// ID of the profile. Missing in state saved before profiles had IDs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile_id: Option<String>,
+    /// Local time in RFC 3339 format
+    pub applied_at: String,
+}
+
+/// Load the state, returning defaults if none has been saved yet.
+pub fn load_state() -> Result<AppState, String> {
+    let path = get_state_path()?;
+
+    if !path.exists() {
+        return Ok(AppState::default());
+    }
+
+    let json = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read state file: {}", e))?;
+
+    serde_json::from_str(&json)
+        .map_err(|e| format!("Failed to parse state: {}", e))
+}
+
+/// Save the state to disk.
+fn save_state(state: &AppState) -> Result<(), String> {
+    let path = get_state_path()?;
+
+    let json = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize state: {}", e))?;
+
+    fs::write(&path, json)
+        .map_err(|e| format!("Failed to write state file: {}", e))
+}
+
+/// The profile applied most recently, if any.
+pub fn last_applied() -> Option<LastApplied> {
+    load_state().ok()?.last_applied
+}
+
+/// Remember that a profile was just applied.
+pub fn record_applied(profile: &str, profile_id: Option<&str>) -> Result<(), String> {
+    let mut state = load_state().unwrap_or_default();
+    state.last_applied = Some(LastApplied {
+        profile: profile.to_string(),
+        profile_id: profile_id.map(str::to_string),
+        applied_at: Local::now().to_rfc3339(),
+    });
+    save_state(&state)
+}
@@ -0,0 +1,241 @@
+//! Profile layout thumbnails.
+//!
+//! Draws a profile's monitor arrangement as a small image for the tray menu and
+//! the main window: one rectangle per monitor with its number, the primary
+//! monitor highlighted and a bar along the panel's native top edge, so rotated
+//! monitors are recognisable. Text beyond the number doesn't fit at this size.
+//! The same digits make up the badge with the active display count on the
+//! tray icon.
+//! Single responsibility: render and encode thumbnails.
+
+use crate::profile::MonitorDetails;
+
+/// Edge length of the square thumbnail, in pixels.
+pub const THUMBNAIL_SIZE: u32 = 64;
+
+const MARGIN: f64 = 2.0;
+const FILL: [u8; 4] = [0x4b, 0x55, 0x63, 0xff];
+const PRIMARY_FILL: [u8; 4] = [0x25, 0x63, 0xeb, 0xff];
+const BORDER: [u8; 4] = [0x11, 0x18, 0x27, 0xff];
+const TOP_EDGE: [u8; 4] = [0xe5, 0xe7, 0xeb, 0xff];
+const DIGIT: [u8; 4] = [0xff, 0xff, 0xff, 0xff];
+const BADGE_FILL: [u8; 4] = [0x16, 0xa3, 0x4a, 0xff];
+
+/// Largest digit dot in a thumbnail, in pixels.
+const MAX_THUMBNAIL_DOT: u32 = 3;
+
+/// 3x5 bitmaps of the digits 0-9, one row per element, high bit on the left.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// An RGBA image, row by row.
+pub struct Thumbnail {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl Thumbnail {
+    fn new(width: u32, height: u32) -> Self {
+        Self { width, height, rgba: vec![0; (width * height * 4) as usize] }
+    }
+
+    /// Fill the rectangle [x0, x1) x [y0, y1), clipped to the image.
+    fn fill(&mut self, x0: u32, y0: u32, x1: u32, y1: u32, color: [u8; 4]) {
+        for y in y0..y1.min(self.height) {
+            for x in x0..x1.min(self.width) {
+                let i = ((y * self.width + x) * 4) as usize;
+                self.rgba[i..i + 4].copy_from_slice(&color);
+            }
+        }
+    }
+
+    /// Encode as PNG.
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        let mut png_data = Vec::new();
+
+        let mut encoder = png::Encoder::new(&mut png_data, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| format!("Failed to write PNG header: {}", e))?;
+        writer
+            .write_image_data(&self.rgba)
+            .map_err(|e| format!("Failed to write PNG data: {}", e))?;
+        writer
+            .finish()
+            .map_err(|e| format!("Failed to finish PNG: {}", e))?;
+
+        Ok(png_data)
+    }
+}
+
+/// Draw the monitor arrangement into a transparent square of `size` pixels.
+pub fn render(monitors: &[MonitorDetails], size: u32) -> Thumbnail {
+    let mut image = Thumbnail::new(size, size);
+    if monitors.is_empty() {
+        return image;
+    }
+
+    // Bounding box of the desktop
+    let left = monitors.iter().map(|m| m.position_x).min().unwrap_or(0) as f64;
+    let top = monitors.iter().map(|m| m.position_y).min().unwrap_or(0) as f64;
+    let right = monitors.iter().map(|m| m.position_x + m.width as i32).max().unwrap_or(0) as f64;
+    let bottom = monitors.iter().map(|m| m.position_y + m.height as i32).max().unwrap_or(0) as f64;
+
+    let available = size as f64 - 2.0 * MARGIN;
+    let scale = available / (right - left).max(bottom - top).max(1.0);
+    // Center the arrangement
+    let offset_x = MARGIN + (available - (right - left) * scale) / 2.0;
+    let offset_y = MARGIN + (available - (bottom - top) * scale) / 2.0;
+
+    for (i, monitor) in monitors.iter().enumerate() {
+        let x0 = (offset_x + (monitor.position_x as f64 - left) * scale).round() as u32;
+        let y0 = (offset_y + (monitor.position_y as f64 - top) * scale).round() as u32;
+        // Adjacent monitors share an edge; keep one pixel between them
+        let x1 = ((offset_x + (monitor.position_x as f64 + monitor.width as f64 - left) * scale).round() as u32).max(x0 + 2) - 1;
+        let y1 = ((offset_y + (monitor.position_y as f64 + monitor.height as f64 - top) * scale).round() as u32).max(y0 + 2) - 1;
+
+        image.fill(x0, y0, x1, y1, BORDER);
+        if x1 - x0 > 2 && y1 - y0 > 2 {
+            let fill = if monitor.is_primary { PRIMARY_FILL } else { FILL };
+            image.fill(x0 + 1, y0 + 1, x1 - 1, y1 - 1, fill);
+            draw_top_edge(&mut image, (x0 + 1, y0 + 1, x1 - 1, y1 - 1), monitor.rotation);
+        }
+
+        let number = monitor.display_number.unwrap_or(i as u32 + 1);
+        draw_number(&mut image, (x0, y0, x1, y1), number, MAX_THUMBNAIL_DOT);
+    }
+
+    image
+}
+
+/// An RGBA icon of `width` x `height` pixels with a badge showing `count` in
+/// its bottom right quarter.
+pub fn with_badge(rgba: &[u8], width: u32, height: u32, count: u32) -> Thumbnail {
+    let mut image = Thumbnail { width, height, rgba: rgba.to_vec() };
+    let size = width.min(height) / 2 + 2;
+    if size < 8 || image.rgba.len() != (width * height * 4) as usize {
+        return image;
+    }
+
+    let (x0, y0) = (width - size, height - size);
+    image.fill(x0, y0, width, height, BORDER);
+    image.fill(x0 + 1, y0 + 1, width - 1, height - 1, BADGE_FILL);
+    // The icon is scaled down to the tray size, so the digits grow with it
+    draw_number(&mut image, (x0, y0, width, height), count, u32::MAX);
+    image
+}
+
+/// Mark the side the panel's native top edge is on (DISPLAYCONFIG_ROTATION values).
+fn draw_top_edge(image: &mut Thumbnail, (x0, y0, x1, y1): (u32, u32, u32, u32), rotation: u32) {
+    match rotation {
+        2 => image.fill(x1 - 1, y0, x1, y1, TOP_EDGE),
+        3 => image.fill(x0, y1 - 1, x1, y1, TOP_EDGE),
+        4 => image.fill(x0, y0, x0 + 1, y1, TOP_EDGE),
+        _ => image.fill(x0, y0, x1, y0 + 1, TOP_EDGE),
+    }
+}
+
+/// Draw a number centered in a rectangle, as large as fits with dots of at
+/// most `max_dot` pixels. Skipped if even the smallest digits don't fit.
+fn draw_number(image: &mut Thumbnail, (x0, y0, x1, y1): (u32, u32, u32, u32), number: u32, max_dot: u32) {
+    let digits: Vec<usize> = number.to_string().bytes().map(|b| (b - b'0') as usize).collect();
+    // Each digit is 3 columns wide plus one column of spacing
+    let columns = digits.len() as u32 * 4 - 1;
+
+    let width = x1 - x0;
+    let height = y1 - y0;
+    let dot = ((width.saturating_sub(4)) / columns).min(height.saturating_sub(4) / 5).min(max_dot);
+    if dot == 0 {
+        return;
+    }
+
+    let start_x = x0 + (width - columns * dot) / 2;
+    let start_y = y0 + (height - 5 * dot) / 2;
+    for (n, &digit) in digits.iter().enumerate() {
+        let digit_x = start_x + n as u32 * 4 * dot;
+        for (row, bits) in DIGITS[digit].iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) != 0 {
+                    let x = digit_x + column * dot;
+                    let y = start_y + row as u32 * dot;
+                    image.fill(x, y, x + dot, y + dot, DIGIT);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl Thumbnail {
+        fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+            let i = ((y * self.width + x) * 4) as usize;
+            [self.rgba[i], self.rgba[i + 1], self.rgba[i + 2], self.rgba[i + 3]]
+        }
+    }
+
+    fn monitor(x: i32, y: i32, width: u32, height: u32, is_primary: bool) -> MonitorDetails {
+        MonitorDetails {
+            width,
+            height,
+            mode_width: width,
+            mode_height: height,
+            refresh_rate: 60.0,
+            position_x: x,
+            position_y: y,
+            rotation: 1,
+            is_primary,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_side_by_side() {
+        let image = render(&[monitor(0, 0, 1920, 1080, true), monitor(1920, 0, 1920, 1080, false)], 64);
+
+        // Two 16:9 monitors side by side fill the width and are centered vertically
+        assert_eq!(image.pixel(0, 0), [0; 4]);
+        assert_eq!(image.pixel(5, 24), BORDER);
+        assert_eq!(image.pixel(5, 30), PRIMARY_FILL);
+        assert_eq!(image.pixel(58, 30), FILL);
+        // Top edge marker of an unrotated monitor
+        assert_eq!(image.pixel(5, 25), TOP_EDGE);
+    }
+
+    #[test]
+    fn test_badge_in_bottom_right() {
+        let icon = vec![0u8; 32 * 32 * 4];
+        let image = with_badge(&icon, 32, 32, 3);
+
+        assert_eq!(image.pixel(0, 0), [0, 0, 0, 0]);
+        assert_eq!(image.pixel(31, 31), BORDER);
+        assert_eq!(image.pixel(17, 17), BADGE_FILL);
+        assert!((14..32).any(|y| (14..32).any(|x| image.pixel(x, y) == DIGIT)));
+    }
+
+    #[test]
+    fn test_png_roundtrip_size() {
+        let png = render(&[monitor(0, 0, 1920, 1080, true)], THUMBNAIL_SIZE).to_png().unwrap();
+
+        assert_eq!(&png[1..4], b"PNG");
+        let decoder = png::Decoder::new(png.as_slice());
+        let reader = decoder.read_info().unwrap();
+        assert_eq!(reader.info().width, THUMBNAIL_SIZE);
+    }
+}
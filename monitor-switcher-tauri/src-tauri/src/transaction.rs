@@ -0,0 +1,126 @@
+//! Transactional profile apply.
+//!
+//! Applying a profile takes several stages (layout, DPI scaling, color filter,
+//! ...), and any of them can fail after the earlier ones changed the system.
+//! Each stage registers how to restore what it touches before it runs; when a
+//! stage fails, everything registered so far is undone, newest first.
+//! Single responsibility: run apply stages and roll them back on failure.
+
+use crate::i18n;
+use log::{error, info};
+
+type Undo<'a> = Box<dyn FnOnce() -> Result<(), String> + 'a>;
+
+/// The stages of an apply that ran (or started) so far.
+#[derive(Default)]
+pub struct Transaction<'a> {
+    undo: Vec<(&'static str, Undo<'a>)>,
+}
+
+impl<'a> Transaction<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run a stage. `undo` restores the state from before the stage and is kept
+    /// even if the stage fails, since a failing stage may have half-applied.
+    /// On failure, every stage is rolled back and the error names the stage
+    /// (a translation key such as "stage.layout").
+    pub fn stage<T, U, F>(&mut self, stage: &'static str, undo: U, run: F) -> Result<T, String>
+    where
+        U: FnOnce() -> Result<(), String> + 'a,
+        F: FnOnce() -> Result<T, String>,
+    {
+        self.undo.push((stage, Box::new(undo)));
+        run().map_err(|e| self.fail(stage, &e))
+    }
+
+    /// Run a step that changes nothing itself, such as checking what the
+    /// stages before it did. On failure, every stage is rolled back.
+    pub fn check<T, F>(&mut self, stage: &'static str, run: F) -> Result<T, String>
+    where
+        F: FnOnce() -> Result<T, String>,
+    {
+        run().map_err(|e| self.fail(stage, &e))
+    }
+
+    /// Roll back after `stage` failed, and describe what happened.
+    fn fail(&mut self, stage: &str, cause: &str) -> String {
+        let stage_name = i18n::tr(stage);
+        error!("Applying failed at {}: {}, rolling back", stage_name, cause);
+
+        let rollback_errors = self.rollback();
+        if rollback_errors.is_empty() {
+            i18n::tr_with("error.applyStageFailed", &[("stage", &stage_name), ("error", cause)])
+        } else {
+            i18n::tr_with(
+                "error.rollbackFailed",
+                &[("stage", &stage_name), ("error", cause), ("rollbackError", &rollback_errors.join("; "))],
+            )
+        }
+    }
+
+    /// Undo every registered stage, newest first. Returns the failures.
+    fn rollback(&mut self) -> Vec<String> {
+        let mut errors = Vec::new();
+        while let Some((stage, undo)) = self.undo.pop() {
+            match undo() {
+                Ok(()) => info!("Rolled back {}", stage),
+                Err(e) => errors.push(format!("{}: {}", i18n::tr(stage), e)),
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_rolls_back_newest_first() {
+        let log = RefCell::new(Vec::new());
+        let mut transaction = Transaction::new();
+
+        transaction
+            .stage("stage.layout", || { log.borrow_mut().push("undo layout"); Ok(()) }, || Ok(()))
+            .unwrap();
+        let result: Result<(), String> = transaction.stage(
+            "stage.dpi",
+            || { log.borrow_mut().push("undo dpi"); Ok(()) },
+            || Err("access denied".to_string()),
+        );
+
+        assert!(result.unwrap_err().contains("access denied"));
+        assert_eq!(*log.borrow(), ["undo dpi", "undo layout"]);
+    }
+
+    #[test]
+    fn test_reports_failed_rollback() {
+        let mut transaction = Transaction::new();
+        transaction.stage("stage.layout", || Err("driver gone".to_string()), || Ok(())).unwrap();
+        let error = transaction
+            .stage("stage.nightLight", || Ok(()), || Err::<(), _>("no blob".to_string()))
+            .unwrap_err();
+
+        assert!(error.contains("no blob"));
+        assert!(error.contains("driver gone"));
+    }
+
+    #[test]
+    fn test_failed_check_rolls_back() {
+        let log = RefCell::new(Vec::new());
+        let mut transaction = Transaction::new();
+
+        transaction
+            .stage("stage.virtualDisplays", || { log.borrow_mut().push("undo virtual displays"); Ok(()) }, || Ok(()))
+            .unwrap();
+        assert_eq!(transaction.check("stage.matching", || Ok(3)), Ok(3));
+        assert!(log.borrow().is_empty());
+
+        let result: Result<(), String> = transaction.check("stage.matching", || Err("no match".to_string()));
+        assert!(result.unwrap_err().contains("no match"));
+        assert_eq!(*log.borrow(), ["undo virtual displays"]);
+    }
+}
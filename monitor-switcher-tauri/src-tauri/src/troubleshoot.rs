@@ -0,0 +1,221 @@
+//! Troubleshooting assistant for profiles that fail to apply.
+//!
+//! Single responsibility: run every check we know of against a profile without
+//! applying it, and explain each problem found together with a suggested fix.
+
+use serde::Serialize;
+
+use crate::display::{find_non_native_modes, is_remote_session, is_simulating, validate_display_settings};
+
+#[cfg(windows)]
+use crate::display::{get_display_settings, get_dpi_scaling_info, match_adapter_ids, DPI_VALUES};
+#[cfg(windows)]
+use crate::profile::{load_profile, profile_to_settings};
+#[cfg(windows)]
+use crate::settings::load_settings;
+
+#[cfg(target_os = "linux")]
+use crate::display::find_missing_monitors;
+#[cfg(target_os = "linux")]
+use crate::profile::load_linux_profile;
+
+/// How serious a problem is. Problems are reported most severe first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    /// Loading the profile will fail.
+    Error,
+    /// Loading works, but the result probably isn't what the user wants.
+    Warning,
+    /// Worth knowing, usually harmless.
+    Info,
+}
+
+/// One problem found with a profile.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Problem {
+    pub severity: Severity,
+    pub message: String,
+    pub suggestion: String,
+}
+
+impl Problem {
+    fn new(severity: Severity, message: impl Into<String>, suggestion: &str) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            suggestion: suggestion.to_string(),
+        }
+    }
+}
+
+const RESAVE: &str = "Arrange the displays as wanted and save the profile again.";
+const NON_NATIVE: &str =
+    "Non-native resolutions look blurry on LCD panels. If this isn't intended, save the profile again at the native resolution.";
+
+/// Check a profile against the current system (Windows).
+#[cfg(windows)]
+pub fn troubleshoot_profile(name: &str) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    let profile = match load_profile(name) {
+        Ok(profile) => profile,
+        Err(e) => {
+            problems.push(Problem::new(Severity::Error, format!("The profile can't be read: {}", e), RESAVE));
+            return problems;
+        }
+    };
+
+    check_environment(&mut problems, profile.saved_in_remote_session);
+
+    let (mut settings, additional_info) = profile_to_settings(&profile);
+    if let Err(e) = match_adapter_ids(&mut settings, &additional_info) {
+        problems.push(Problem::new(
+            Severity::Error,
+            format!("The monitors could not be matched to the current system: {}", e),
+            "Connect the monitors the profile was saved with, or save the profile again.",
+        ));
+        return problems;
+    }
+
+    // Paths whose target didn't match anything connected keep their stale adapter IDs
+    let connected = get_display_settings(false).map(|s| s.path_info_array).unwrap_or_default();
+    for (i, path) in settings.path_info_array.iter().enumerate() {
+        let is_connected = connected.iter().any(|c| {
+            c.target_info.adapter_id == path.target_info.adapter_id && c.target_info.id == path.target_info.id
+        });
+        if !is_connected {
+            let monitor = additional_info
+                .get(path.target_info.mode_info_idx as usize)
+                .map(|info| info.monitor_friendly_device.clone())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| format!("Display {}", i + 1));
+            problems.push(Problem::new(
+                Severity::Error,
+                format!("{} is not connected", monitor),
+                "Connect the monitor, or save the profile again without it.",
+            ));
+        }
+    }
+
+    for warning in find_non_native_modes(&settings) {
+        problems.push(Problem::new(Severity::Warning, warning, NON_NATIVE));
+    }
+
+    for dpi in &profile.dpi_scale_info {
+        if !DPI_VALUES.contains(&dpi.dpi_scale) {
+            problems.push(Problem::new(
+                Severity::Warning,
+                format!("Scaling {}% for source {} is not a Windows scaling step", dpi.dpi_scale, dpi.source_id),
+                "Pick a scale in Windows display settings and save the profile again.",
+            ));
+        }
+
+        let supported = settings
+            .path_info_array
+            .iter()
+            .find(|p| p.source_info.id == dpi.source_id)
+            .and_then(|p| get_dpi_scaling_info(p.source_info.adapter_id, p.source_info.id));
+        if let Some(info) = supported.filter(|info| !(info.minimum..=info.maximum).contains(&dpi.dpi_scale)) {
+            problems.push(Problem::new(
+                Severity::Warning,
+                format!(
+                    "Scaling {}% for source {} is outside the supported {}–{}% and will be clamped",
+                    dpi.dpi_scale, dpi.source_id, info.minimum, info.maximum
+                ),
+                "Choose a supported scale in Windows display settings and save the profile again.",
+            ));
+        }
+    }
+
+    // Dry run with the flags the profile will actually be applied with
+    let retry_with_allow_changes = profile
+        .apply_flags
+        .or_else(|| load_settings().ok().and_then(|s| s.apply_flags))
+        .map(|flags| flags.retry_with_allow_changes)
+        .unwrap_or(true);
+
+    if let Err(e) = validate_display_settings(&mut settings, false) {
+        if retry_with_allow_changes && validate_display_settings(&mut settings, true).is_ok() {
+            problems.push(Problem::new(
+                Severity::Info,
+                format!("Windows will adjust some modes when applying (exact configuration rejected: {})", e),
+                "Usually harmless. Save the profile again after loading it to store the adjusted modes.",
+            ));
+        } else {
+            problems.push(Problem::new(
+                Severity::Error,
+                format!("Windows rejects the configuration: {}", e),
+                "Update the display driver, reconnect the monitors, or save the profile again.",
+            ));
+        }
+    }
+
+    problems.sort_by_key(|p| p.severity);
+    problems
+}
+
+/// Check a profile against the current system (Linux).
+#[cfg(target_os = "linux")]
+pub fn troubleshoot_profile(name: &str) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    let settings = match load_linux_profile(name) {
+        Ok(settings) => settings,
+        Err(e) => {
+            problems.push(Problem::new(Severity::Error, format!("The profile can't be read: {}", e), RESAVE));
+            return problems;
+        }
+    };
+
+    check_environment(&mut problems, false);
+
+    for output in find_missing_monitors(&settings, &[]).unwrap_or_default() {
+        problems.push(Problem::new(
+            Severity::Error,
+            format!("{} is not connected", output),
+            "Connect the monitor, or save the profile again without it.",
+        ));
+    }
+
+    for warning in find_non_native_modes(&settings) {
+        problems.push(Problem::new(Severity::Warning, warning, NON_NATIVE));
+    }
+
+    if let Err(e) = validate_display_settings(&settings) {
+        problems.push(Problem::new(
+            Severity::Error,
+            format!("xrandr rejects the configuration: {}", e.trim()),
+            "Check that the saved modes are still offered by the monitors (xrandr --query), or save the profile again.",
+        ));
+    }
+
+    problems.sort_by_key(|p| p.severity);
+    problems
+}
+
+/// Report problems caused by the environment the app runs in.
+fn check_environment(problems: &mut Vec<Problem>, saved_in_remote_session: bool) {
+    if is_simulating() {
+        problems.push(Problem::new(
+            Severity::Info,
+            "Displays are simulated; checks run against the loaded fixture",
+            "Restart without --simulate to check the real displays.",
+        ));
+    }
+
+    if is_remote_session() {
+        problems.push(Problem::new(
+            Severity::Warning,
+            "This is a remote desktop session; its display layout is virtual",
+            "Load the profile from the local console instead.",
+        ));
+    } else if saved_in_remote_session {
+        problems.push(Problem::new(
+            Severity::Warning,
+            "The profile was saved in a remote desktop session and describes its virtual layout",
+            RESAVE,
+        ));
+    }
+}
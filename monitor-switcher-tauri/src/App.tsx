@@ -3,6 +3,7 @@ import { invoke } from '@tauri-apps/api/core';
 import { getCurrentWindow, LogicalSize } from '@tauri-apps/api/window';
 import { ProfileList } from './components/ProfileList';
 import { useProfiles } from './hooks/useProfiles';
+import type { InterruptedApply, KioskPolicy, MonitorDetails } from './types';
 import './App.css';
 
 const WINDOW_STATE_KEY = 'monitor-switcher-window-state';
@@ -20,7 +21,10 @@ function App() {
     error,
     loadProfile,
     deleteProfile,
+    listProfileBackups,
+    restoreProfileBackup,
     turnOffMonitors,
+    setMonitorAlias,
     refresh,
   } = useProfiles();
 
@@ -83,6 +87,37 @@ function App() {
   }, [refresh]);
 
   const [notification, setNotification] = useState<{ type: 'success' | 'error'; message: string } | null>(null);
+  const [interrupted, setInterrupted] = useState<InterruptedApply | null>(null);
+  const [kiosk, setKiosk] = useState<KioskPolicy | null>(null);
+
+  // Kiosk mode only lets whitelisted profiles be applied; hide everything else
+  useEffect(() => {
+    invoke<KioskPolicy | null>('get_kiosk_policy')
+      .then(setKiosk)
+      .catch(console.error);
+  }, []);
+
+  // Offer to undo an apply that a crash cut short
+  useEffect(() => {
+    invoke<InterruptedApply | null>('get_interrupted_apply')
+      .then(setInterrupted)
+      .catch(console.error);
+  }, []);
+
+  const handleRestoreInterrupted = async () => {
+    try {
+      await invoke('restore_interrupted_apply');
+      setInterrupted(null);
+      showNotification('success', 'Restored the previous layout');
+    } catch (err) {
+      showNotification('error', err instanceof Error ? err.message : String(err));
+    }
+  };
+
+  const handleDismissInterrupted = async () => {
+    await invoke('dismiss_interrupted_apply').catch(console.error);
+    setInterrupted(null);
+  };
 
   const showNotification = (type: 'success' | 'error', message: string) => {
     setNotification({ type, message });
@@ -91,8 +126,18 @@ function App() {
 
   const handleLoad = async (name: string) => {
     try {
-      await loadProfile(name);
-      showNotification('success', `Loaded "${name}"`);
+      const report = await loadProfile(name);
+      const problems = report.monitors.filter(m => m.status !== 'applied' || m.discrepancies);
+      if (report.alreadyActive && problems.length === 0) {
+        showNotification('success', `"${name}" is already active`);
+      } else if (problems.length === 0) {
+        showNotification('success', `Loaded "${name}"`);
+      } else {
+        const details = problems
+          .map(m => `${m.monitor}: ${m.reason ?? m.discrepancies?.join(', ') ?? m.status}`)
+          .join('; ');
+        showNotification('error', `Loaded "${name}", but ${details}`);
+      }
     } catch (err) {
       showNotification('error', err instanceof Error ? err.message : String(err));
     }
@@ -107,6 +152,49 @@ function App() {
     }
   };
 
+  // Backups are named by timestamp, e.g. "20240101T120000123456"; older ones lack the microseconds
+  const formatBackup = (timestamp: string) =>
+    timestamp.replace(/^(\d{4})(\d{2})(\d{2})T(\d{2})(\d{2})(\d{2})\d*$/, '$1-$2-$3 $4:$5:$6');
+
+  const handleRestore = async (name: string) => {
+    try {
+      const backups = await listProfileBackups(name);
+      if (backups.length === 0) {
+        showNotification('error', `"${name}" has no backups`);
+        return;
+      }
+      const choice = window.prompt(
+        `Restore "${name}" from which backup?\n${backups.map((b, i) => `${i + 1}: ${formatBackup(b)}`).join('\n')}`,
+        '1',
+      );
+      if (choice === null) return;
+      const timestamp = backups[Number(choice) - 1];
+      if (!timestamp) {
+        showNotification('error', `No backup ${choice}`);
+        return;
+      }
+      await restoreProfileBackup(name, timestamp);
+      showNotification('success', `Restored "${name}" from ${formatBackup(timestamp)}`);
+    } catch (err) {
+      showNotification('error', err instanceof Error ? err.message : String(err));
+    }
+  };
+
+  // Right-click on a monitor in any diagram to give it a friendly name
+  const handleRenameMonitor = async (monitor: MonitorDetails) => {
+    if (!monitor.monitorId) return;
+    const alias = window.prompt(
+      `Name for ${monitor.edidName ?? monitor.name} (leave empty to reset):`,
+      monitor.edidName ? monitor.name : '',
+    );
+    if (alias === null) return;
+    try {
+      await setMonitorAlias(monitor.monitorId, alias);
+    } catch (err) {
+      showNotification('error', err instanceof Error ? err.message : String(err));
+    }
+  };
+
   const handleOpenSaveDialog = async () => {
     try {
       await invoke('open_save_dialog');
@@ -171,28 +259,51 @@ function App() {
       {/* Main content */}
       <main className="flex-1 p-3 flex flex-col gap-3 overflow-hidden">
         {/* Quick Actions - smaller, inline */}
-        <div className="flex gap-2">
-          <button
-            onClick={handleOpenSaveDialog}
-            className="flex items-center gap-1.5 px-3 py-1.5 bg-emerald-600 hover:bg-emerald-500 text-white text-xs font-medium rounded-md transition-colors"
-          >
-            <svg className="w-3.5 h-3.5" fill="none" viewBox="0 0 24 24" stroke="currentColor" strokeWidth={2.5}>
-              <path strokeLinecap="round" strokeLinejoin="round" d="M12 4v16m8-8H4" />
-            </svg>
-            Save Current
-          </button>
+        {!kiosk && (
+          <div className="flex gap-2">
+            <button
+              onClick={handleOpenSaveDialog}
+              className="flex items-center gap-1.5 px-3 py-1.5 bg-emerald-600 hover:bg-emerald-500 text-white text-xs font-medium rounded-md transition-colors"
+            >
+              <svg className="w-3.5 h-3.5" fill="none" viewBox="0 0 24 24" stroke="currentColor" strokeWidth={2.5}>
+                <path strokeLinecap="round" strokeLinejoin="round" d="M12 4v16m8-8H4" />
+              </svg>
+              Save Current
+            </button>
 
-          <button
-            onClick={handleTurnOff}
-            className="flex items-center gap-1.5 px-3 py-1.5 bg-slate-700/80 hover:bg-slate-600/80 text-slate-300 hover:text-white text-xs font-medium rounded-md border border-slate-600/50 transition-colors"
-          >
-            <svg className="w-3.5 h-3.5" fill="none" viewBox="0 0 24 24" stroke="currentColor" strokeWidth={1.5}>
-              <path strokeLinecap="round" strokeLinejoin="round" d="M9 17.25v1.007a3 3 0 01-.879 2.122L7.5 21h9l-.621-.621A3 3 0 0115 18.257V17.25m6-12V15a2.25 2.25 0 01-2.25 2.25H5.25A2.25 2.25 0 013 15V5.25A2.25 2.25 0 015.25 3h13.5A2.25 2.25 0 0121 5.25z" />
-              <path strokeLinecap="round" strokeLinejoin="round" d="M3.5 3.5l17 17" strokeWidth={2} />
-            </svg>
-            Turn Off
-          </button>
-        </div>
+            <button
+              onClick={handleTurnOff}
+              className="flex items-center gap-1.5 px-3 py-1.5 bg-slate-700/80 hover:bg-slate-600/80 text-slate-300 hover:text-white text-xs font-medium rounded-md border border-slate-600/50 transition-colors"
+            >
+              <svg className="w-3.5 h-3.5" fill="none" viewBox="0 0 24 24" stroke="currentColor" strokeWidth={1.5}>
+                <path strokeLinecap="round" strokeLinejoin="round" d="M9 17.25v1.007a3 3 0 01-.879 2.122L7.5 21h9l-.621-.621A3 3 0 0115 18.257V17.25m6-12V15a2.25 2.25 0 01-2.25 2.25H5.25A2.25 2.25 0 013 15V5.25A2.25 2.25 0 015.25 3h13.5A2.25 2.25 0 0121 5.25z" />
+                <path strokeLinecap="round" strokeLinejoin="round" d="M3.5 3.5l17 17" strokeWidth={2} />
+              </svg>
+              Turn Off
+            </button>
+          </div>
+        )}
+
+        {/* Interrupted apply */}
+        {interrupted && !kiosk && (
+          <div className="px-2.5 py-2 bg-amber-500/10 border border-amber-500/30 rounded-md flex items-center gap-2">
+            <span className="text-amber-200 text-xs flex-1">
+              Applying "{interrupted.profile}" didn't finish. Restore the layout from before?
+            </span>
+            <button
+              onClick={handleRestoreInterrupted}
+              className="px-2 py-1 bg-amber-600 hover:bg-amber-500 text-white text-xs font-medium rounded-md transition-colors"
+            >
+              Restore
+            </button>
+            <button
+              onClick={handleDismissInterrupted}
+              className="px-2 py-1 text-slate-300 hover:text-white text-xs font-medium rounded-md transition-colors"
+            >
+              Dismiss
+            </button>
+          </div>
+        )}
 
         {/* Error display */}
         {error && (
@@ -212,7 +323,9 @@ function App() {
               profiles={profiles}
               activeProfile={activeProfile}
               onLoad={handleLoad}
-              onDelete={handleDelete}
+              onDelete={kiosk ? undefined : handleDelete}
+              onRestore={kiosk ? undefined : handleRestore}
+              onRenameMonitor={kiosk ? undefined : handleRenameMonitor}
               isLoading={isLoading}
             />
           </div>
@@ -0,0 +1,15 @@
+// Number and name of one monitor, shown by the identify_monitors command
+function IdentifyOverlay() {
+  const params = new URLSearchParams(window.location.search);
+  const number = params.get('number') ?? '';
+  const name = params.get('name') ?? '';
+
+  return (
+    <div className="h-full flex flex-col items-center justify-center select-none bg-slate-900 border-2 border-blue-500 rounded-lg text-white">
+      <div className="text-6xl font-bold">{number}</div>
+      <div className="mt-2 px-3 text-sm text-slate-300 truncate max-w-full">{name}</div>
+    </div>
+  );
+}
+
+export default IdentifyOverlay;
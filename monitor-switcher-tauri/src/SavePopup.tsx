@@ -3,10 +3,11 @@ import { invoke } from '@tauri-apps/api/core';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 
 function SavePopup() {
-  const [name, setName] = useState('');
+  const [name, setName] = useState(window.prefillProfileName ?? '');
   const [isSaving, setIsSaving] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const [existingProfiles, setExistingProfiles] = useState<string[]>([]);
+  const [isRemoteSession, setIsRemoteSession] = useState(false);
   const inputRef = useRef<HTMLInputElement>(null);
 
   useEffect(() => {
@@ -17,6 +18,11 @@ function SavePopup() {
     invoke<string[]>('list_profiles')
       .then(setExistingProfiles)
       .catch(console.error);
+
+    // Warn when saving a remote desktop's virtual layout
+    invoke<boolean>('is_remote_session')
+      .then(setIsRemoteSession)
+      .catch(console.error);
   }, []);
 
   const handleSubmit = async (e: React.FormEvent) => {
@@ -114,6 +120,14 @@ function SavePopup() {
               {error}
             </p>
           )}
+          {isRemoteSession && (
+            <p className="mt-1.5 text-[11px] text-amber-400 flex items-center gap-1">
+              <svg className="w-3 h-3 shrink-0" fill="none" viewBox="0 0 24 24" stroke="currentColor" strokeWidth={2}>
+                <path strokeLinecap="round" strokeLinejoin="round" d="M12 9v2m0 4h.01M10.29 3.86L1.82 18a2 2 0 001.71 3h16.94a2 2 0 001.71-3L13.71 3.86a2 2 0 00-3.42 0z" />
+              </svg>
+              Remote session: this saves the virtual remote layout, not your monitors
+            </p>
+          )}
         </div>
 
         {/* Existing profiles list */}
@@ -4,6 +4,7 @@ import type { MonitorDetails } from '../types';
 interface MonitorDiagramProps {
   monitors: MonitorDetails[];
   maxHeight?: number;
+  onRenameMonitor?: (monitor: MonitorDetails) => void;
 }
 
 // Format resolution as compact string
@@ -21,24 +22,24 @@ function formatRefreshRate(rate: number): string {
   return `${rounded}Hz`;
 }
 
+// Whether the monitor runs below/above its native resolution (looks blurry on LCDs)
+function isNonNative(m: MonitorDetails): boolean {
+  if (m.nativeWidth === undefined || m.nativeHeight === undefined) return false;
+  return m.width !== m.nativeWidth || m.height !== m.nativeHeight;
+}
+
 // Truncate name to fit
 function truncateName(name: string, maxLen: number): string {
   if (name.length <= maxLen) return name;
   return name.substring(0, maxLen - 1) + '…';
 }
 
-export function MonitorDiagram({ monitors, maxHeight = 120 }: MonitorDiagramProps) {
+export function MonitorDiagram({ monitors, maxHeight = 120, onRenameMonitor }: MonitorDiagramProps) {
   const layout = useMemo(() => {
     if (monitors.length === 0) return null;
 
-    // Calculate actual dimensions considering rotation
-    const monitorsWithDims = monitors.map((m) => {
-      // Rotation: 1=0°, 2=90°, 3=180°, 4=270°
-      const isRotated = m.rotation === 2 || m.rotation === 4;
-      const actualWidth = isRotated ? m.height : m.width;
-      const actualHeight = isRotated ? m.width : m.height;
-      return { ...m, actualWidth, actualHeight };
-    });
+    // Width and height come in desktop orientation, rotation already applied
+    const monitorsWithDims = monitors.map((m) => ({ ...m, actualWidth: m.width, actualHeight: m.height }));
 
     // Find bounding box
     let minX = Infinity, minY = Infinity, maxX = -Infinity, maxY = -Infinity;
@@ -106,7 +107,13 @@ export function MonitorDiagram({ monitors, maxHeight = 120 }: MonitorDiagramProp
                 width: m.displayWidth,
                 height: m.displayHeight,
               }}
-              title={`${m.name}\n${m.width}×${m.height} @ ${formatRefreshRate(m.refreshRate)}${m.dpiScale ? `\nScale: ${m.dpiScale}%` : ''}${m.isPrimary ? '\n(Primary)' : ''}`}
+              onContextMenu={(e) => {
+                if (!onRenameMonitor || !m.monitorId) return;
+                e.preventDefault();
+                e.stopPropagation();
+                onRenameMonitor(m);
+              }}
+              title={`${m.name}${m.edidName ? ` (${m.edidName})` : ''}\n${m.width}×${m.height} @ ${formatRefreshRate(m.refreshRate)}${m.modeWidth !== m.width ? ` (mode ${m.modeWidth}×${m.modeHeight})` : ''}${m.dpiScale ? `\nScale: ${m.dpiScale}%` : ''}${m.displayNumber ? `\nDisplay ${m.displayNumber}${m.gdiDeviceName ? ` (${m.gdiDeviceName})` : ''}` : ''}${m.adapterName ? `\nGPU: ${m.adapterName}` : ''}${isNonNative(m) ? `\nNot native resolution (${m.nativeWidth}×${m.nativeHeight})` : ''}${m.isPrimary ? '\n(Primary)' : ''}`}
             >
               {!isTiny && (
                 <>
@@ -122,7 +129,7 @@ export function MonitorDiagram({ monitors, maxHeight = 120 }: MonitorDiagramProp
                   {/* Resolution and refresh rate */}
                   {isHorizontal ? (
                     <span className={`leading-tight ${isSmall ? 'text-[7px]' : 'text-[9px]'}`}>
-                      <span className="text-slate-300">{formatResolution(m.width, m.height)}</span>
+                      <span className={isNonNative(m) ? 'text-amber-400' : 'text-slate-300'}>{formatResolution(m.width, m.height)}</span>
                       <span className="text-slate-500 mx-0.5">@</span>
                       <span className="text-emerald-400">{formatRefreshRate(m.refreshRate)}</span>
                       {m.dpiScale && m.dpiScale !== 100 && (
@@ -132,7 +139,7 @@ export function MonitorDiagram({ monitors, maxHeight = 120 }: MonitorDiagramProp
                   ) : (
                     <>
                       <span
-                        className={`text-slate-300 leading-tight ${isSmall ? 'text-[7px]' : 'text-[9px]'}`}
+                        className={`${isNonNative(m) ? 'text-amber-400' : 'text-slate-300'} leading-tight ${isSmall ? 'text-[7px]' : 'text-[9px]'}`}
                       >
                         {formatResolution(m.width, m.height)}
                       </span>
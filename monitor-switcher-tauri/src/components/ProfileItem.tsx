@@ -1,12 +1,14 @@
 import { useState } from 'react';
-import type { ProfileDetails } from '../types';
+import type { MonitorDetails, ProfileDetails } from '../types';
 import { MonitorDiagram } from './MonitorDiagram';
 
 interface ProfileItemProps {
   profile: ProfileDetails;
   isActive: boolean;
   onLoad: (name: string) => Promise<void>;
-  onDelete: (name: string) => Promise<void>;
+  onDelete?: (name: string) => Promise<void>;
+  onRestore?: (name: string) => Promise<void>;
+  onRenameMonitor?: (monitor: MonitorDetails) => void;
 }
 
 // Get short names for summary
@@ -16,6 +18,8 @@ function getMonitorSummary(profile: ProfileDetails): string {
 
   const names = profile.monitors
     .map(m => {
+      // User aliases are already short and chosen to be told apart
+      if (m.edidName) return m.name;
       // Extract brand/short name from full name
       const parts = m.name.split(' ');
       return parts[0]; // First word is usually the brand
@@ -25,7 +29,7 @@ function getMonitorSummary(profile: ProfileDetails): string {
   return `${count} monitor${count > 1 ? 's' : ''} · ${names}`;
 }
 
-export function ProfileItem({ profile, isActive, onLoad, onDelete }: ProfileItemProps) {
+export function ProfileItem({ profile, isActive, onLoad, onDelete, onRestore, onRenameMonitor }: ProfileItemProps) {
   const [isLoading, setIsLoading] = useState(false);
   const [isDeleting, setIsDeleting] = useState(false);
 
@@ -43,12 +47,17 @@ export function ProfileItem({ profile, isActive, onLoad, onDelete }: ProfileItem
     e.stopPropagation();
     setIsDeleting(true);
     try {
-      await onDelete(profile.name);
+      await onDelete?.(profile.name);
     } finally {
       setIsDeleting(false);
     }
   };
 
+  const handleRestore = async (e: React.MouseEvent) => {
+    e.stopPropagation();
+    await onRestore?.(profile.name);
+  };
+
   return (
     <div
       className={`group rounded-lg transition-all duration-200 cursor-pointer overflow-hidden border min-w-[200px] ${
@@ -63,7 +72,7 @@ export function ProfileItem({ profile, isActive, onLoad, onDelete }: ProfileItem
       {/* Monitor diagram on top */}
       {profile.monitors.length > 0 && (
         <div className="px-3 pt-3 pb-2">
-          <MonitorDiagram monitors={profile.monitors} maxHeight={80} />
+          <MonitorDiagram monitors={profile.monitors} maxHeight={80} onRenameMonitor={onRenameMonitor} />
         </div>
       )}
 
@@ -83,9 +92,27 @@ export function ProfileItem({ profile, isActive, onLoad, onDelete }: ProfileItem
         <div className="text-[10px] text-slate-500 truncate max-w-full">
           {getMonitorSummary(profile)}
         </div>
+        {profile.incompatible && (
+          <div className="text-[10px] text-amber-400/80 truncate max-w-full" title="Saved on another machine; its displays won't match this one">
+            From another machine
+          </div>
+        )}
+
+        {/* Restore button */}
+        {!isLoading && onRestore && (
+          <button
+            onClick={handleRestore}
+            className="absolute left-2 top-1/2 -translate-y-1/2 w-6 h-6 flex items-center justify-center text-slate-500 hover:text-sky-400 hover:bg-sky-500/10 rounded transition-colors opacity-0 group-hover:opacity-100"
+            title="Restore backup"
+          >
+            <svg className="w-3 h-3" fill="none" viewBox="0 0 24 24" stroke="currentColor" strokeWidth={2}>
+              <path strokeLinecap="round" strokeLinejoin="round" d="M3 12a9 9 0 109-9 9.75 9.75 0 00-6.74 2.74L3 8m0-5v5h5m4-1v5l4 2" />
+            </svg>
+          </button>
+        )}
 
         {/* Delete button */}
-        {!isLoading && (
+        {!isLoading && onDelete && (
           <button
             onClick={handleDelete}
             disabled={isDeleting}
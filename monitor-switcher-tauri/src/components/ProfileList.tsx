@@ -1,15 +1,17 @@
 import { ProfileItem } from './ProfileItem';
-import type { ProfileDetails } from '../types';
+import type { MonitorDetails, ProfileDetails } from '../types';
 
 interface ProfileListProps {
   profiles: ProfileDetails[];
   activeProfile: string | null;
   onLoad: (name: string) => Promise<void>;
-  onDelete: (name: string) => Promise<void>;
+  onDelete?: (name: string) => Promise<void>;
+  onRestore?: (name: string) => Promise<void>;
+  onRenameMonitor?: (monitor: MonitorDetails) => void;
   isLoading: boolean;
 }
 
-export function ProfileList({ profiles, activeProfile, onLoad, onDelete, isLoading }: ProfileListProps) {
+export function ProfileList({ profiles, activeProfile, onLoad, onDelete, onRestore, onRenameMonitor, isLoading }: ProfileListProps) {
   if (isLoading) {
     return (
       <div className="flex flex-col items-center justify-center py-8 text-slate-400">
@@ -42,6 +44,8 @@ export function ProfileList({ profiles, activeProfile, onLoad, onDelete, isLoadi
           isActive={profile.name === activeProfile}
           onLoad={onLoad}
           onDelete={onDelete}
+          onRestore={onRestore}
+          onRenameMonitor={onRenameMonitor}
         />
       ))}
     </div>
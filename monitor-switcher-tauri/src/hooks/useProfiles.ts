@@ -1,7 +1,7 @@
-import { useState, useEffect, useCallback } from 'react';
+import { useState, useEffect, useCallback, useMemo } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import type { ProfileDetails, MonitorDetails } from '../types';
+import type { ProfileDetails, MonitorDetails, LastApplied, ApplyReport } from '../types';
 
 // Compare two monitor configurations to see if they match
 function monitorsMatch(a: MonitorDetails[], b: MonitorDetails[]): boolean {
@@ -42,30 +42,25 @@ function monitorsMatch(a: MonitorDetails[], b: MonitorDetails[]): boolean {
 
 export function useProfiles() {
   const [profiles, setProfiles] = useState<ProfileDetails[]>([]);
-  const [activeProfile, setActiveProfile] = useState<string | null>(null);
+  const [currentMonitors, setCurrentMonitors] = useState<MonitorDetails[] | null>(null);
+  const [lastApplied, setLastApplied] = useState<LastApplied | null>(null);
   const [isLoading, setIsLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
 
   const refresh = useCallback(async () => {
     try {
-      const [profileList, currentMonitors] = await Promise.all([
+      const [profileList, last] = await Promise.all([
         invoke<ProfileDetails[]>('list_profiles_with_details'),
-        invoke<MonitorDetails[]>('get_current_monitors'),
+        invoke<LastApplied | null>('get_last_applied'),
       ]);
 
       setProfiles(profileList || []);
-
-      // Find matching profile
-      const matchingProfile = (profileList || []).find(
-        (p) => monitorsMatch(p.monitors, currentMonitors)
-      );
-      setActiveProfile(matchingProfile?.name || null);
+      setLastApplied(last);
 
       setError(null);
     } catch (err) {
       setError(err instanceof Error ? err.message : String(err));
       setProfiles([]);
-      setActiveProfile(null);
     } finally {
       setIsLoading(false);
     }
@@ -80,20 +75,74 @@ export function useProfiles() {
       setTimeout(() => refresh(), 500);
     });
 
+    // Listen for layout changes made outside the app (other tools, the DE, hotplug)
+    const unlistenDisplay = listen('display-changed', () => {
+      refresh();
+    });
+
     return () => {
       unlisten.then((fn) => fn());
+      unlistenDisplay.then((fn) => fn());
     };
   }, [refresh]);
 
+  // The backend pushes the current monitors whenever they change
+  useEffect(() => {
+    invoke<MonitorDetails[]>('subscribe_monitors')
+      .then(setCurrentMonitors)
+      .catch((err) => setError(err instanceof Error ? err.message : String(err)));
+
+    const unlistenMonitors = listen<MonitorDetails[]>('monitors-changed', (event) => {
+      setCurrentMonitors(event.payload);
+    });
+
+    return () => {
+      unlistenMonitors.then((fn) => fn());
+      invoke('unsubscribe_monitors');
+    };
+  }, []);
+
+  // Find matching profile
+  const activeProfile = useMemo(() => {
+    if (!currentMonitors) return null;
+    const matchingProfile = profiles.find((p) => monitorsMatch(p.monitors, currentMonitors));
+    return matchingProfile?.name || null;
+  }, [profiles, currentMonitors]);
+
   const saveProfile = useCallback(async (name: string) => {
     await invoke('save_profile', { name });
     await refresh();
   }, [refresh]);
 
-  const loadProfile = useCallback(async (name: string) => {
-    await invoke('load_profile', { name });
+  const loadProfile = useCallback(async (name: string): Promise<ApplyReport> => {
+    const report = await invoke<ApplyReport>('load_profile', { name });
     // Small delay to let Windows apply display changes, then refresh to update active state
     setTimeout(() => refresh(), 500);
+    return report;
+  }, [refresh]);
+
+  // Windows only: applies the profile's DPI scaling without touching the layout
+  const loadProfileDpiOnly = useCallback(async (name: string) => {
+    await invoke('load_profile_dpi_only', { name });
+    setTimeout(() => refresh(), 500);
+  }, [refresh]);
+
+  // Only the refresh rates; resolutions and positions stay as they are
+  const loadProfileRefreshRates = useCallback(async (name: string) => {
+    await invoke('load_profile_refresh_rates', { name });
+    setTimeout(() => refresh(), 500);
+  }, [refresh]);
+
+  // Only positions and the primary monitor; the current modes stay
+  const loadProfilePositions = useCallback(async (name: string) => {
+    await invoke('load_profile_positions', { name });
+    setTimeout(() => refresh(), 500);
+  }, [refresh]);
+
+  const toggleProfiles = useCallback(async (a: string, b: string): Promise<ApplyReport> => {
+    const report = await invoke<ApplyReport>('toggle_profiles', { a, b });
+    setTimeout(() => refresh(), 500);
+    return report;
   }, [refresh]);
 
   const deleteProfile = useCallback(async (name: string) => {
@@ -101,10 +150,87 @@ export function useProfiles() {
     await refresh();
   }, [refresh]);
 
+  const installProfileMachineWide = useCallback(async (name: string) => {
+    await invoke('install_profile_machine_wide', { name });
+    await refresh();
+  }, [refresh]);
+
+  const removeMachineProfile = useCallback(async (name: string) => {
+    await invoke('remove_machine_profile', { name });
+    await refresh();
+  }, [refresh]);
+
+  const renameProfile = useCallback(async (oldName: string, newName: string) => {
+    await invoke('rename_profile', { oldName, newName });
+    await refresh();
+  }, [refresh]);
+
+  const listProfileBackups = useCallback(async (name: string): Promise<string[]> => {
+    return await invoke('list_profile_backups', { name });
+  }, []);
+
+  const restoreProfileBackup = useCallback(async (name: string, timestamp: string) => {
+    await invoke('restore_profile_backup', { name, timestamp });
+    await refresh();
+  }, [refresh]);
+
   const turnOffMonitors = useCallback(async () => {
     await invoke('turn_off_monitors');
   }, []);
 
+  const setMonitorAlias = useCallback(async (monitorId: string, alias: string) => {
+    await invoke('set_monitor_alias', { monitorId, alias });
+    await refresh();
+  }, [refresh]);
+
+  // Without a monitorId, the monitor under the mouse pointer turns
+  const rotateMonitor = useCallback(async (quarterTurns: number, monitorId?: string) => {
+    await invoke('rotate_monitor', { monitorId: monitorId ?? null, quarterTurns });
+  }, []);
+
+  const setRotationLock = useCallback(async (monitorId: string, locked: boolean) => {
+    await invoke('set_rotation_lock', { monitorId, locked });
+  }, []);
+
+  // 'debug' while reproducing a problem, '' to go back to RUST_LOG
+  const setLogLevel = useCallback(async (level: string) => {
+    await invoke('set_log_level', { level });
+  }, []);
+
+  const identifyMonitors = useCallback(async () => {
+    await invoke('identify_monitors');
+  }, []);
+
+  const setAllBrightness = useCallback(async (percent: number) => {
+    await invoke('set_all_brightness', { percent });
+  }, []);
+
+  const internalDisplayOnly = useCallback(async () => {
+    await invoke('internal_display_only');
+    setTimeout(() => refresh(), 500);
+  }, [refresh]);
+
+  const externalDisplaysOnly = useCallback(async () => {
+    await invoke('external_displays_only');
+    setTimeout(() => refresh(), 500);
+  }, [refresh]);
+
+  const duplicateAtBestCommonMode = useCallback(async () => {
+    await invoke('duplicate_at_best_common_mode');
+    setTimeout(() => refresh(), 500);
+  }, [refresh]);
+
+  const findMatchingProfile = useCallback(async (): Promise<string | null> => {
+    return await invoke('find_matching_profile');
+  }, []);
+
+  // `name` is the profile findMatchingProfile returned, once the user confirmed it
+  const updateMatchingProfile = useCallback(async (name: string): Promise<string> => {
+    const updated = await invoke<string>('update_matching_profile', { name });
+    await refresh();
+    return updated;
+  }, [refresh]);
+
   const profileExists = useCallback(async (name: string): Promise<boolean> => {
     return await invoke('profile_exists', { name });
   }, []);
@@ -112,13 +238,34 @@ export function useProfiles() {
   return {
     profiles,
     activeProfile,
+    lastApplied,
     isLoading,
     error,
     refresh,
     saveProfile,
     loadProfile,
+    loadProfileDpiOnly,
+    loadProfileRefreshRates,
+    loadProfilePositions,
+    toggleProfiles,
     deleteProfile,
+    installProfileMachineWide,
+    removeMachineProfile,
+    renameProfile,
+    listProfileBackups,
+    restoreProfileBackup,
     turnOffMonitors,
+    identifyMonitors,
+    rotateMonitor,
+    setRotationLock,
+    setLogLevel,
+    setAllBrightness,
+    internalDisplayOnly,
+    externalDisplaysOnly,
+    duplicateAtBestCommonMode,
+    findMatchingProfile,
+    updateMatchingProfile,
     profileExists,
+    setMonitorAlias,
   };
 }
@@ -0,0 +1,10 @@
+import React from "react";
+import ReactDOM from "react-dom/client";
+import IdentifyOverlay from "./IdentifyOverlay";
+import "./App.css";
+
+ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
+  <React.StrictMode>
+    <IdentifyOverlay />
+  </React.StrictMode>,
+);
@@ -1,16 +1,113 @@
 export interface MonitorDetails {
   name: string;
-  width: number;
+  width: number; // Size on the desktop, rotation applied
   height: number;
+  modeWidth: number; // Display mode the monitor receives, before rotation
+  modeHeight: number;
   refreshRate: number;
   positionX: number;
   positionY: number;
   rotation: number; // 1=0°, 2=90°, 3=180°, 4=270°
   isPrimary: boolean;
   dpiScale?: number; // DPI scaling percentage (100, 125, 150, etc.)
+  gdiDeviceName?: string; // GDI device name (\\.\DISPLAY1), Windows only
+  displayNumber?: number; // Number shown in Windows Settings, Windows only
+  adapterName?: string; // Device path of the GPU driving this monitor, Windows only
+  nativeWidth?: number; // Native resolution of the connected monitor, if known
+  nativeHeight?: number;
+  monitorId?: string; // Stable monitor identity, the key for user aliases
+  edidName?: string; // Name reported by the monitor, when `name` is a user alias
+  connector?: string; // Connector the monitor is plugged into ("DP-1", "HDMI-2")
+  edidOverride?: boolean; // An EDID override changes the reported identity (Windows)
+}
+
+export interface GpuInfo {
+  name: string;
+  id: string; // LUID ("HIGH:LOW") on Windows, RandR provider id on Linux
+  devicePath?: string; // Windows only
+  driverVersion?: string;
+  monitors: string[]; // MonitorDetails.monitorId of each connected monitor
+}
+
+export interface MonitorEdid {
+  monitorId: string; // MonitorDetails.monitorId
+  name: string;
+  edid: string; // Base64 of the raw EDID, extension blocks included
+  overridden: boolean; // EDID comes from an override, not the monitor
+}
+
+export interface TroubleshootProblem {
+  severity: 'error' | 'warning' | 'info';
+  message: string;
+  suggestion: string;
+}
+
+export interface LastApplied {
+  profile: string;
+  appliedAt: string; // RFC 3339 local time
+}
+
+export interface HistoryEntry {
+  timestamp: string; // RFC 3339 local time
+  profile: string;
+  source: string; // What asked for the load: "window", "tray", "hotplug", ...
+  success: boolean;
+  error?: string;
+}
+
+export type MatchTier =
+  | 'idPairs' | 'gdiName' | 'friendlyName' | 'bulkReplacement' | 'unmatched' // Windows
+  | 'outputName'; // Linux
+
+export interface ApplyMetrics {
+  profile: string;
+  startedAt: string; // RFC 3339 local time
+  durationMs: number;
+  waitMs: number; // Waiting for the profile's monitors to connect
+  layoutMs: number; // Applying the layout; 0 if it was active already
+  retries: number; // 1 = retried with SDC_ALLOW_CHANGES, 2 = GDI fallback (Windows)
+  matchTier?: MatchTier;
+  flags?: number; // SetDisplayConfig flags of the accepted attempt (Windows)
+  alreadyActive: boolean;
+  success: boolean;
+  error?: string;
+}
+
+export interface MonitorResult {
+  monitor: string; // Friendly name, or output name on Linux
+  status: 'applied' | 'skipped' | 'failed';
+  reason?: string;
+  discrepancies?: string[]; // Settings applied differently than requested
+}
+
+// An apply the app or driver crashed during, found at startup
+export interface KioskPolicy {
+  enabled: boolean;
+  profiles: string[]; // IDs or names
+}
+
+export interface InterruptedApply {
+  profile: string;
+  startedAt: string; // RFC 3339
+}
+
+export interface ApplyReport {
+  profile: string;
+  monitors: MonitorResult[];
+  alreadyActive?: boolean; // The layout was in place already, so nothing was re-applied
+}
+
+export interface LayoutRect {
+  id: string; // Passed through unchanged
+  x: number;
+  y: number;
+  width: number;
+  height: number;
 }
 
 export interface ProfileDetails {
+  id: string; // Stable profile identity, survives renames
   name: string;
   monitors: MonitorDetails[];
+  incompatible: boolean; // Saved on another machine sharing the profiles folder
 }
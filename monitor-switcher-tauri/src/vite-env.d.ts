@@ -1 +1,6 @@
 /// <reference types="vite/client" />
+
+interface Window {
+  /** Profile name the save popup opens with, set by the backend */
+  prefillProfileName?: string | null;
+}